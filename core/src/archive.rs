@@ -0,0 +1,339 @@
+//! Passphrase-protected backup archives: every entry, with its attachments
+//! and history, plus a little metadata about the vault, in a single file a
+//! fresh install can recreate the vault from.
+//!
+//! The archive is a standard age file encrypted to a passphrase (scrypt,
+//! ChaCha20-Poly1305), so `age -d` can open it without Lilypad. Inside is a
+//! gzip-compressed JSON document with a format tag and version like the
+//! plaintext export. The passphrase is chosen per archive and is unrelated
+//! to the master password; the restored vault gets a new master password.
+
+use crate::clock;
+use crate::entry::Entry;
+use crate::secret::Zeroizing;
+use crate::vault::{MasterKey, Vault, VaultData, VaultError};
+use age::secrecy::SecretString;
+use age::{scrypt, DecryptError, Decryptor, Encryptor};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::iter;
+use std::path::Path;
+use zeroize::Zeroize;
+
+/// Value of the `format` field inside an archive.
+pub const ARCHIVE_FORMAT: &str = "lilypad-archive";
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// The file is not an age file encrypted to a passphrase, or decrypts to
+    /// something other than a Lilypad archive.
+    NotAnArchive,
+    WrongPassphrase,
+    /// The archive's scrypt cost would take this machine minutes to check.
+    TooExpensive,
+    /// The file was cut short or altered after it was written.
+    Damaged(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnArchive => write!(f, "file is not a Lilypad backup archive"),
+            Self::WrongPassphrase => write!(f, "incorrect archive passphrase"),
+            Self::TooExpensive => write!(
+                f,
+                "the archive's passphrase is stretched too far for this machine to check"
+            ),
+            Self::Damaged(error) => write!(f, "archive is damaged: {error}"),
+            Self::Json(error) => write!(f, "archive contents are not valid: {error}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "archive version {version} is newer than this build supports"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// What an archive says about the vault it was taken from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveMetadata {
+    /// Unix timestamp (seconds, UTC) of the backup.
+    pub archived_at: u64,
+    /// Version of the Lilypad build that wrote the archive.
+    pub app_version: String,
+    /// When the vault itself was created; a restored vault keeps it.
+    pub vault_created_at: u64,
+    pub entry_count: usize,
+    pub attachment_count: usize,
+}
+
+impl ArchiveMetadata {
+    /// Describes `vault` as it is now.
+    pub fn of(vault: &Vault) -> Self {
+        let entries = vault.entries();
+        Self {
+            archived_at: clock::now(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            vault_created_at: vault.data().created_at,
+            entry_count: entries.len(),
+            attachment_count: entries.iter().map(|entry| entry.attachments.len()).sum(),
+        }
+    }
+}
+
+/// The decrypted contents of an archive.
+#[derive(Debug, Deserialize)]
+pub struct Archive {
+    pub metadata: ArchiveMetadata,
+    pub entries: Vec<Entry>,
+}
+
+impl Drop for Archive {
+    fn drop(&mut self) {
+        self.entries.zeroize();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchiveHeader {
+    format: String,
+    version: u32,
+}
+
+#[derive(Serialize)]
+struct ArchiveFileRef<'a> {
+    format: &'a str,
+    version: u32,
+    metadata: &'a ArchiveMetadata,
+    entries: &'a [Entry],
+}
+
+/// Encrypts `entries` and `metadata` to `passphrase`. The scrypt cost is
+/// picked by age to take about a second on this machine.
+pub fn write(
+    metadata: &ArchiveMetadata,
+    entries: &[Entry],
+    passphrase: &str,
+) -> Result<Vec<u8>, ArchiveError> {
+    write_with_work_factor(metadata, entries, passphrase, None)
+}
+
+fn write_with_work_factor(
+    metadata: &ArchiveMetadata,
+    entries: &[Entry],
+    passphrase: &str,
+    work_factor: Option<u8>,
+) -> Result<Vec<u8>, ArchiveError> {
+    let json = Zeroizing::new(serde_json::to_vec(&ArchiveFileRef {
+        format: ARCHIVE_FORMAT,
+        version: ARCHIVE_VERSION,
+        metadata,
+        entries,
+    })?);
+
+    let mut recipient = scrypt::Recipient::new(SecretString::from(passphrase.to_string()));
+    if let Some(log_n) = work_factor {
+        recipient.set_work_factor(log_n);
+    }
+    let encryptor = Encryptor::with_recipients(iter::once(&recipient as _))
+        .expect("a single passphrase recipient is always accepted");
+
+    // Writing into a `Vec` cannot fail.
+    let mut output = Vec::new();
+    let mut writer = encryptor
+        .wrap_output(&mut output)
+        .expect("writing to memory");
+    let mut gzip = GzEncoder::new(&mut writer, Compression::default());
+    gzip.write_all(&json).expect("writing to memory");
+    gzip.finish().expect("writing to memory");
+    writer.finish().expect("writing to memory");
+    Ok(output)
+}
+
+/// Checks that `data` is an age file encrypted to a passphrase before the
+/// user is asked for one.
+pub fn check(data: &[u8]) -> Result<(), ArchiveError> {
+    match Decryptor::new_buffered(data) {
+        Ok(decryptor) if decryptor.is_scrypt() => Ok(()),
+        _ => Err(ArchiveError::NotAnArchive),
+    }
+}
+
+/// Decrypts an archive written by [`write`]. Takes about as long as writing
+/// it did, so frontends run it off the UI thread.
+pub fn read(data: &[u8], passphrase: &str) -> Result<Archive, ArchiveError> {
+    let decryptor = Decryptor::new_buffered(data).map_err(|_| ArchiveError::NotAnArchive)?;
+    if !decryptor.is_scrypt() {
+        return Err(ArchiveError::NotAnArchive);
+    }
+    let identity = scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+    let reader = decryptor
+        .decrypt(iter::once(&identity as _))
+        .map_err(|error| match error {
+            DecryptError::ExcessiveWork { .. } => ArchiveError::TooExpensive,
+            DecryptError::Io(error) => ArchiveError::Damaged(error),
+            DecryptError::InvalidMac | DecryptError::InvalidHeader => ArchiveError::Damaged(
+                io::Error::new(io::ErrorKind::InvalidData, "the header does not verify"),
+            ),
+            _ => ArchiveError::WrongPassphrase,
+        })?;
+
+    // The age stream authenticates each chunk as it is read, so truncation
+    // or tampering surfaces here as an I/O error.
+    let mut json = Zeroizing::new(Vec::new());
+    GzDecoder::new(reader)
+        .read_to_end(&mut json)
+        .map_err(ArchiveError::Damaged)?;
+
+    let header: ArchiveHeader =
+        serde_json::from_slice(&json).map_err(|_| ArchiveError::NotAnArchive)?;
+    if header.format != ARCHIVE_FORMAT {
+        return Err(ArchiveError::NotAnArchive);
+    }
+    if header.version > ARCHIVE_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(header.version));
+    }
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Creates a vault at `path` holding the archive's entries, keyed by
+/// `master_key`. The file is written once, with everything in it.
+pub fn restore(
+    path: &Path,
+    master_key: MasterKey,
+    mut archive: Archive,
+) -> Result<Vault, VaultError> {
+    let data = VaultData {
+        created_at: archive.metadata.vault_created_at,
+        modified_at: clock::now(),
+        entries: std::mem::take(&mut archive.entries),
+    };
+    Vault::create_with_data(path, master_key, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KdfParams;
+    use crate::entry::{Attachment, EntryKind, Login};
+
+    const PASSPHRASE: &str = "pond lily frog";
+    /// Far below age's default so the tests stay fast.
+    const WORK_FACTOR: Option<u8> = Some(4);
+
+    fn sample_entries() -> Vec<Entry> {
+        let mut login = Entry::new(
+            "Mail",
+            EntryKind::Login(Login {
+                username: "ada".to_string(),
+                password: "hunter2".to_string(),
+                ..Login::default()
+            }),
+        );
+        login.attachments.push(Attachment {
+            name: "recovery.txt".to_string(),
+            data: b"codes".to_vec(),
+        });
+        let mut note = Entry::new("Router", EntryKind::SecureNote);
+        note.notes = "admin / admin".to_string();
+        vec![login, note]
+    }
+
+    fn sample_metadata(entries: &[Entry]) -> ArchiveMetadata {
+        ArchiveMetadata {
+            archived_at: 1_700_000_100,
+            app_version: "0.1.0".to_string(),
+            vault_created_at: 1_700_000_000,
+            entry_count: entries.len(),
+            attachment_count: 1,
+        }
+    }
+
+    #[test]
+    fn round_trips_entries_and_metadata() {
+        let entries = sample_entries();
+        let metadata = sample_metadata(&entries);
+        let data = write_with_work_factor(&metadata, &entries, PASSPHRASE, WORK_FACTOR).unwrap();
+        assert!(data.starts_with(b"age-encryption.org/v1\n"));
+        assert!(!data.windows(7).any(|window| window == b"hunter2"));
+        check(&data).unwrap();
+
+        let archive = read(&data, PASSPHRASE).unwrap();
+        assert_eq!(archive.metadata, metadata);
+        assert_eq!(archive.entries, entries);
+    }
+
+    #[test]
+    fn wrong_passphrase_and_damage_are_reported() {
+        let entries = sample_entries();
+        let metadata = sample_metadata(&entries);
+        let mut data =
+            write_with_work_factor(&metadata, &entries, PASSPHRASE, WORK_FACTOR).unwrap();
+        assert!(matches!(
+            read(&data, "pond lily toad"),
+            Err(ArchiveError::WrongPassphrase)
+        ));
+
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(matches!(
+            read(&data, PASSPHRASE),
+            Err(ArchiveError::Damaged(_))
+        ));
+        data.truncate(data.len() - 20);
+        assert!(matches!(
+            read(&data, PASSPHRASE),
+            Err(ArchiveError::Damaged(_))
+        ));
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        assert!(matches!(
+            check(b"{\"format\": \"lilypad-export\"}"),
+            Err(ArchiveError::NotAnArchive)
+        ));
+        assert!(matches!(
+            read(b"LILYPAD\0", PASSPHRASE),
+            Err(ArchiveError::NotAnArchive)
+        ));
+    }
+
+    #[test]
+    fn restores_into_a_new_vault() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let entries = sample_entries();
+        let metadata = sample_metadata(&entries);
+        let data = write_with_work_factor(&metadata, &entries, PASSPHRASE, WORK_FACTOR).unwrap();
+
+        let kdf = KdfParams {
+            memory_kib: 16 * 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let key = MasterKey::derive("new master", kdf).unwrap();
+        let vault = restore(&path, key, read(&data, PASSPHRASE).unwrap()).unwrap();
+        assert_eq!(vault.data().created_at, 1_700_000_000);
+        drop(vault);
+
+        let vault = Vault::open(&path, "new master").unwrap();
+        assert_eq!(vault.entries(), entries.as_slice());
+        assert_eq!(ArchiveMetadata::of(&vault).attachment_count, 1);
+    }
+}
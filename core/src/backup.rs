@@ -0,0 +1,504 @@
+//! Rotating snapshots of the vault file, so a bad write or a deleted vault
+//! does not lose everything.
+//!
+//! After every save the file just written is copied, byte for byte, into
+//! the backup folder as `<vault name>-<path id>-<YYYYMMDDTHHMMSSZ>.lilypad`,
+//! where the path id is a short hash of the vault's full path, so vaults
+//! with the same file name in different folders keep separate snapshots
+//! (and a moved vault starts a new series). The copy
+//! stays encrypted under the master password the vault had at the time. A
+//! `.sha256` file next to it, in the format `sha256sum -c` reads, lets the
+//! snapshot be checked without that password. Only the newest snapshots are
+//! kept.
+
+use crate::clock;
+use crate::vault::{self, write_atomic, Vault, VaultError};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extension of snapshot files, the same as the vault's own.
+const SNAPSHOT_EXTENSION: &str = "lilypad";
+/// Extension appended to a snapshot's file name for its checksum file.
+const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// The backup folder and how many snapshots of each vault it keeps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backups {
+    dir: PathBuf,
+    keep: usize,
+}
+
+/// A snapshot found in the backup folder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    /// Unix timestamp (seconds, UTC) of the save it was taken from.
+    pub taken_at: u64,
+    /// Size of the file in bytes.
+    pub size: u64,
+    pub integrity: Integrity,
+}
+
+/// What checking a snapshot against its checksum found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Integrity {
+    Intact,
+    /// The checksum file is missing or unreadable. Decrypting the snapshot
+    /// still detects any damage, so it can be restored.
+    Unverified,
+    /// The contents do not match the checksum, or are not a vault file.
+    Damaged,
+}
+
+/// A snapshot checked and decrypted, ready to replace the vault file.
+pub struct OpenedSnapshot {
+    backups: Backups,
+    vault: Vault,
+    contents: Vec<u8>,
+}
+
+impl Integrity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Intact => "intact",
+            Self::Unverified => "no checksum",
+            Self::Damaged => "damaged",
+        }
+    }
+}
+
+impl Backups {
+    /// Snapshots in `dir`, keeping the newest `keep` of each vault; at least
+    /// one is always kept.
+    pub fn new(dir: PathBuf, keep: usize) -> Self {
+        Self {
+            dir,
+            keep: keep.max(1),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn keep(&self) -> usize {
+        self.keep
+    }
+
+    /// Stores `contents`, the file just saved at `vault_path`, as the
+    /// snapshot taken at `taken_at`, reads it back to check it, and removes
+    /// the oldest snapshots beyond the limit. A second snapshot in the same
+    /// second replaces the first.
+    pub fn snapshot(
+        &self,
+        vault_path: &Path,
+        contents: &[u8],
+        taken_at: u64,
+    ) -> io::Result<PathBuf> {
+        let path = self.write_snapshot(vault_path, contents, taken_at)?;
+        if check(&path) != Integrity::Intact {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the snapshot did not read back intact",
+            ));
+        }
+        self.prune(vault_path)?;
+        Ok(path)
+    }
+
+    /// The snapshots of the vault at `vault_path`, newest first, each
+    /// checked against its checksum.
+    pub fn list(&self, vault_path: &Path) -> io::Result<Vec<Snapshot>> {
+        self.snapshot_files(vault_path)?
+            .into_iter()
+            .map(|(taken_at, path)| {
+                Ok(Snapshot {
+                    size: fs::metadata(&path)?.len(),
+                    integrity: check(&path),
+                    path,
+                    taken_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Checks `snapshot` and opens it with `password`, the master password
+    /// the vault had when the snapshot was taken, as the vault at
+    /// `vault_path`. Nothing is written until `OpenedSnapshot::replace`.
+    /// Takes as long as unlocking, so frontends run it off the UI thread.
+    pub fn open(
+        &self,
+        snapshot: &Path,
+        vault_path: &Path,
+        password: &str,
+    ) -> Result<OpenedSnapshot, VaultError> {
+        let contents = fs::read(snapshot)?;
+        if matches!(verify(snapshot, &contents), Some(false)) {
+            return Err(VaultError::InvalidFormat(
+                "the snapshot does not match its checksum",
+            ));
+        }
+        let vault = Vault::from_bytes(vault_path, &contents, password)?;
+        Ok(OpenedSnapshot {
+            backups: self.clone(),
+            vault,
+            contents,
+        })
+    }
+
+    /// Opens `snapshot` and replaces the vault at `vault_path` with it in
+    /// one step; see `open` and `OpenedSnapshot::replace`.
+    pub fn restore(
+        &self,
+        snapshot: &Path,
+        vault_path: &Path,
+        password: &str,
+    ) -> Result<Vault, VaultError> {
+        self.open(snapshot, vault_path, password)?.replace()
+    }
+
+    fn write_snapshot(
+        &self,
+        vault_path: &Path,
+        contents: &[u8],
+        taken_at: u64,
+    ) -> io::Result<PathBuf> {
+        let stamp = clock::format_compact(taken_at).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the clock is out of range")
+        })?;
+        let name = format!("{}-{stamp}.{SNAPSHOT_EXTENSION}", vault_prefix(vault_path));
+        let path = self.dir.join(&name);
+        write_atomic(&path, contents)?;
+        write_atomic(
+            &checksum_path(&path),
+            format!("{}  {name}\n", sha256_hex(contents)).as_bytes(),
+        )?;
+        Ok(path)
+    }
+
+    /// Removes all but the newest `keep` snapshots of the vault.
+    fn prune(&self, vault_path: &Path) -> io::Result<()> {
+        for (_, path) in self.snapshot_files(vault_path)?.into_iter().skip(self.keep) {
+            fs::remove_file(&path)?;
+            match fs::remove_file(checksum_path(&path)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Snapshot files of the vault with their timestamps, newest first.
+    fn snapshot_files(&self, vault_path: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let prefix = format!("{}-", vault_prefix(vault_path));
+        let suffix = format!(".{SNAPSHOT_EXTENSION}");
+        let mut files = Vec::new();
+        for item in read_dir {
+            let path = item?.path();
+            let taken_at = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix)?.strip_suffix(&suffix))
+                .and_then(clock::parse_compact);
+            if let Some(taken_at) = taken_at {
+                files.push((taken_at, path));
+            }
+        }
+        files.sort_by(|a, b| b.cmp(a));
+        Ok(files)
+    }
+}
+
+impl OpenedSnapshot {
+    /// Replaces the vault file with the snapshot and returns the vault,
+    /// saving into the same backups. The file being replaced is first kept
+    /// as a snapshot of its own, so the restore can be undone. Only writes
+    /// files, so frontends can run it on the UI thread, where locking the
+    /// app cannot leave it half done.
+    pub fn replace(self) -> Result<Vault, VaultError> {
+        let Self {
+            backups,
+            mut vault,
+            contents,
+        } = self;
+        let vault_path = vault.path().to_path_buf();
+        match fs::read(&vault_path) {
+            Ok(current) if current != contents => {
+                backups.write_snapshot(&vault_path, &current, clock::now())?;
+            }
+            Ok(_) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+        backups.prune(&vault_path)?;
+        write_atomic(&vault_path, &contents)?;
+        vault.set_backups(Some(backups));
+        Ok(vault)
+    }
+}
+
+/// Checks the snapshot at `path` against its checksum file and that it
+/// still starts with a vault header.
+pub fn check(path: &Path) -> Integrity {
+    let Ok(contents) = fs::read(path) else {
+        return Integrity::Damaged;
+    };
+    if vault::check_header(&contents).is_err() {
+        return Integrity::Damaged;
+    }
+    match verify(path, &contents) {
+        Some(true) => Integrity::Intact,
+        Some(false) => Integrity::Damaged,
+        None => Integrity::Unverified,
+    }
+}
+
+/// Whether `contents` match the checksum stored for the snapshot at
+/// `path`; `None` without a readable checksum file.
+fn verify(path: &Path, contents: &[u8]) -> Option<bool> {
+    let recorded = fs::read_to_string(checksum_path(path)).ok()?;
+    let recorded = recorded.split_whitespace().next()?;
+    Some(recorded.eq_ignore_ascii_case(&sha256_hex(contents)))
+}
+
+/// The start of the vault's snapshot names: its file stem and the first
+/// eight hex digits of the SHA-256 of its canonical path.
+fn vault_prefix(vault_path: &Path) -> String {
+    let stem = vault_path
+        .file_stem()
+        .map_or_else(|| "vault".into(), |stem| stem.to_string_lossy());
+    // The folder always exists once the vault is saved; the file may not.
+    let canonical = vault_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .and_then(|parent| parent.canonicalize().ok())
+        .zip(vault_path.file_name())
+        .map(|(parent, name)| parent.join(name))
+        .or_else(|| std::path::absolute(vault_path).ok())
+        .unwrap_or_else(|| vault_path.to_path_buf());
+    let id = sha256_hex(canonical.as_os_str().as_encoded_bytes());
+    format!("{stem}-{}", &id[..8])
+}
+
+fn checksum_path(snapshot: &Path) -> PathBuf {
+    let mut path = snapshot.as_os_str().to_owned();
+    path.push(".");
+    path.push(CHECKSUM_EXTENSION);
+    PathBuf::from(path)
+}
+
+fn sha256_hex(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KdfParams;
+    use crate::entry::{Entry, EntryKind};
+
+    const PASSWORD: &str = "pond lily frog";
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 16 * 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn new_vault(dir: &Path) -> Vault {
+        Vault::create_with_params(&dir.join("vault.lilypad"), PASSWORD, TEST_KDF).unwrap()
+    }
+
+    #[test]
+    fn saves_keep_the_newest_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path().join("backups"), 3);
+        let mut vault = new_vault(dir.path());
+        vault.set_backups(Some(backups.clone()));
+        vault.add_entry(Entry::new("Mail", EntryKind::SecureNote));
+        vault.save().unwrap();
+        assert!(vault.backup_error().is_none());
+        let snapshots = backups.list(vault.path()).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].taken_at, vault.data().modified_at);
+        assert_eq!(snapshots[0].integrity, Integrity::Intact);
+        assert_eq!(
+            fs::read(&snapshots[0].path).unwrap(),
+            fs::read(vault.path()).unwrap()
+        );
+
+        let contents = fs::read(vault.path()).unwrap();
+        for taken_at in 1_700_000_000..1_700_000_005 {
+            backups.snapshot(vault.path(), &contents, taken_at).unwrap();
+        }
+        let taken: Vec<u64> = backups
+            .list(vault.path())
+            .unwrap()
+            .iter()
+            .map(|snapshot| snapshot.taken_at)
+            .collect();
+        assert_eq!(
+            taken,
+            [vault.data().modified_at, 1_700_000_004, 1_700_000_003]
+        );
+        // Three snapshots and their checksums.
+        assert_eq!(fs::read_dir(backups.dir()).unwrap().count(), 6);
+    }
+
+    #[test]
+    fn damaged_snapshots_are_reported_and_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path().join("backups"), 5);
+        let mut vault = new_vault(dir.path());
+        vault.set_backups(Some(backups.clone()));
+        vault.save().unwrap();
+        let snapshot = backups.list(vault.path()).unwrap().remove(0).path;
+
+        let mut contents = fs::read(&snapshot).unwrap();
+        let last = contents.len() - 1;
+        contents[last] ^= 1;
+        fs::write(&snapshot, &contents).unwrap();
+        assert_eq!(check(&snapshot), Integrity::Damaged);
+        assert!(matches!(
+            backups.restore(&snapshot, vault.path(), PASSWORD),
+            Err(VaultError::InvalidFormat(_))
+        ));
+
+        // Without a checksum only decryption can tell.
+        fs::remove_file(checksum_path(&snapshot)).unwrap();
+        assert_eq!(check(&snapshot), Integrity::Unverified);
+        assert!(matches!(
+            backups.restore(&snapshot, vault.path(), PASSWORD),
+            Err(VaultError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn restore_replaces_the_vault_and_keeps_the_current_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path().join("backups"), 5);
+        let mut vault = new_vault(dir.path());
+        vault.set_backups(Some(backups.clone()));
+        vault.add_entry(Entry::new("Mail", EntryKind::SecureNote));
+        vault.save().unwrap();
+        let snapshot = backups.list(vault.path()).unwrap().remove(0).path;
+
+        let vault_path = vault.path().to_path_buf();
+        drop(vault);
+        fs::write(&vault_path, b"garbage").unwrap();
+
+        assert!(matches!(
+            backups.restore(&snapshot, &vault_path, "pond lily toad"),
+            Err(VaultError::WrongPassword)
+        ));
+        // Opening alone leaves the file and the snapshots as they were.
+        let opened = backups.open(&snapshot, &vault_path, PASSWORD).unwrap();
+        drop(opened);
+        assert_eq!(fs::read(&vault_path).unwrap(), b"garbage");
+        assert_eq!(backups.list(&vault_path).unwrap().len(), 1);
+
+        let vault = backups.restore(&snapshot, &vault_path, PASSWORD).unwrap();
+        assert_eq!(vault.entries()[0].title, "Mail");
+        assert_eq!(vault.backups(), Some(&backups));
+        drop(vault);
+        assert_eq!(
+            Vault::open(&vault_path, PASSWORD).unwrap().entries().len(),
+            1
+        );
+
+        let kept = backups
+            .list(&vault_path)
+            .unwrap()
+            .into_iter()
+            .any(|snapshot| fs::read(&snapshot.path).unwrap() == b"garbage");
+        assert!(kept);
+    }
+
+    #[test]
+    fn restore_keeps_the_snapshot_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path().join("backups"), 2);
+        let mut vault = new_vault(dir.path());
+        let contents = fs::read(vault.path()).unwrap();
+        for taken_at in [1_700_000_000, 1_700_000_001] {
+            backups.snapshot(vault.path(), &contents, taken_at).unwrap();
+        }
+        vault.add_entry(Entry::new("Mail", EntryKind::SecureNote));
+        vault.save().unwrap();
+
+        let oldest = backups.list(vault.path()).unwrap().remove(1).path;
+        let vault_path = vault.path().to_path_buf();
+        drop(vault);
+        backups.restore(&oldest, &vault_path, PASSWORD).unwrap();
+        let snapshots = backups.list(&vault_path).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_ne!(snapshots[0].taken_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn vaults_with_the_same_name_keep_separate_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path().join("backups"), 5);
+        let home = dir.path().join("home");
+        let work = dir.path().join("work");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&work).unwrap();
+        let home_vault = new_vault(&home).path().to_path_buf();
+        let work_vault = new_vault(&work).path().to_path_buf();
+        let home_contents = fs::read(&home_vault).unwrap();
+        backups
+            .snapshot(&home_vault, &home_contents, 1_700_000_000)
+            .unwrap();
+        backups
+            .snapshot(&work_vault, &fs::read(&work_vault).unwrap(), 1_700_000_001)
+            .unwrap();
+
+        let listed = backups.list(&home_vault).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(fs::read(&listed[0].path).unwrap(), home_contents);
+        // The same folder reached through another path is the same vault.
+        let dotted = home.join(".").join("vault.lilypad");
+        assert_eq!(backups.list(&dotted).unwrap(), listed);
+    }
+
+    #[test]
+    fn other_vaults_and_files_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path().to_path_buf(), 5);
+        let work = vault_prefix(&dir.path().join("work.lilypad"));
+        let vault = vault_prefix(&dir.path().join("vault.lilypad"));
+        for name in [
+            format!("{work}-20231114T221320Z.lilypad"),
+            format!("{vault}-20231114T221320Z.lilypad.tmp"),
+            format!("{vault}-latest.lilypad"),
+            "vault-20231114T221320Z.lilypad".to_string(),
+            "notes.txt".to_string(),
+        ] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        assert!(backups
+            .list(&dir.path().join("vault.lilypad"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            backups.list(&dir.path().join("work.lilypad")).unwrap()[0].taken_at,
+            1_700_000_000
+        );
+        assert!(Backups::new(dir.path().join("missing"), 5)
+            .list(&dir.path().join("vault.lilypad"))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,644 @@
+//! Reader for Bitwarden's JSON exports: the plain kind and the
+//! password-protected encrypted kind.
+//!
+//! A password-protected export stretches the export password with
+//! PBKDF2-SHA256 or Argon2id (whichever the account uses), expands the result
+//! with HKDF into an encryption key and a MAC key, and stores the plain
+//! export as a single AES-256-CBC + HMAC-SHA256 "EncString". Exports
+//! encrypted with the account key can only be read by Bitwarden itself.
+//!
+//! Items keep their Bitwarden id, so importing the same export twice adds
+//! nothing the second time. Password history and favorites have no Lilypad
+//! counterpart and are dropped.
+
+use crate::clock;
+use crate::entry::{Card, CustomField, Entry, EntryKind, Identity, Login};
+use crate::secret::Zeroizing;
+use aes::Aes256;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+const KDF_PBKDF2: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+
+/// Upper bounds Bitwarden itself enforces, so a crafted file cannot make the
+/// import run for hours or allocate gigabytes.
+const MAX_PBKDF2_ITERATIONS: u32 = 2_000_000;
+const MAX_ARGON2_ITERATIONS: u32 = 10;
+const MAX_ARGON2_MEMORY_MIB: u32 = 1024;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+/// Encryption type prefix of an AES-256-CBC + HMAC-SHA256 EncString.
+const AES_CBC_HMAC: &str = "2";
+
+const ITEM_LOGIN: u8 = 1;
+const ITEM_SECURE_NOTE: u8 = 2;
+const ITEM_CARD: u8 = 3;
+const ITEM_IDENTITY: u8 = 4;
+const ITEM_SSH_KEY: u8 = 5;
+
+const FIELD_HIDDEN: u8 = 1;
+/// A field that only points at a built-in field; it holds no value.
+const FIELD_LINKED: u8 = 3;
+
+#[derive(Debug)]
+pub enum BitwardenError {
+    Json(serde_json::Error),
+    /// The file is JSON but not a Bitwarden export.
+    NotBitwarden,
+    /// Encrypted with the Bitwarden account key rather than a password.
+    AccountEncrypted,
+    PasswordRequired,
+    WrongPassword,
+    UnsupportedKdf(u8),
+    InvalidKdfParams,
+    /// The named part of an encrypted export cannot be decoded.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for BitwardenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "export file is not valid JSON: {error}"),
+            Self::NotBitwarden => write!(f, "file is not a Bitwarden JSON export"),
+            Self::AccountEncrypted => write!(
+                f,
+                "export is encrypted with a Bitwarden account key; export again choosing \"Password protected\""
+            ),
+            Self::PasswordRequired => write!(f, "export is password protected"),
+            Self::WrongPassword => write!(f, "export password is incorrect"),
+            Self::UnsupportedKdf(kind) => write!(f, "unsupported key derivation type {kind}"),
+            Self::InvalidKdfParams => write!(f, "export has invalid key derivation settings"),
+            Self::Malformed(part) => write!(f, "export {part} is damaged"),
+        }
+    }
+}
+
+impl std::error::Error for BitwardenError {}
+
+impl From<serde_json::Error> for BitwardenError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// What [`read`] found in an export.
+#[derive(Debug, Default)]
+pub struct BitwardenImport {
+    pub entries: Vec<Entry>,
+    /// Names of items whose type Lilypad cannot store.
+    pub skipped: Vec<String>,
+}
+
+#[derive(Deserialize, Zeroize)]
+#[serde(rename_all = "camelCase")]
+struct ExportFile {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    password_protected: bool,
+    salt: Option<String>,
+    kdf_type: Option<u8>,
+    kdf_iterations: Option<u32>,
+    kdf_memory: Option<u32>,
+    kdf_parallelism: Option<u32>,
+    #[serde(rename = "encKeyValidation_DO_NOT_EDIT")]
+    key_validation: Option<String>,
+    data: Option<String>,
+    folders: Option<Vec<Folder>>,
+    /// Organization exports list collections instead of folders.
+    collections: Option<Vec<Folder>>,
+    items: Option<Vec<Item>>,
+}
+
+impl Drop for ExportFile {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[derive(Deserialize, Zeroize)]
+struct Folder {
+    id: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Zeroize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    id: Option<String>,
+    #[serde(rename = "type")]
+    kind: u8,
+    name: Option<String>,
+    notes: Option<String>,
+    folder_id: Option<String>,
+    collection_ids: Option<Vec<String>>,
+    fields: Option<Vec<Field>>,
+    login: Option<ItemLogin>,
+    card: Option<ItemCard>,
+    identity: Option<ItemIdentity>,
+    ssh_key: Option<ItemSshKey>,
+    creation_date: Option<String>,
+    revision_date: Option<String>,
+}
+
+#[derive(Deserialize, Zeroize)]
+struct Field {
+    name: Option<String>,
+    value: Option<String>,
+    #[serde(rename = "type", default)]
+    kind: u8,
+}
+
+#[derive(Default, Deserialize, Zeroize)]
+struct ItemLogin {
+    uris: Option<Vec<Uri>>,
+    username: Option<String>,
+    password: Option<String>,
+    totp: Option<String>,
+}
+
+#[derive(Deserialize, Zeroize)]
+struct Uri {
+    uri: Option<String>,
+}
+
+#[derive(Default, Deserialize, Zeroize)]
+#[serde(rename_all = "camelCase")]
+struct ItemCard {
+    cardholder_name: Option<String>,
+    brand: Option<String>,
+    number: Option<String>,
+    exp_month: Option<String>,
+    exp_year: Option<String>,
+    code: Option<String>,
+}
+
+#[derive(Default, Deserialize, Zeroize)]
+#[serde(rename_all = "camelCase")]
+struct ItemIdentity {
+    title: Option<String>,
+    first_name: Option<String>,
+    middle_name: Option<String>,
+    last_name: Option<String>,
+    address1: Option<String>,
+    address2: Option<String>,
+    address3: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    postal_code: Option<String>,
+    country: Option<String>,
+    company: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+    ssn: Option<String>,
+    username: Option<String>,
+    passport_number: Option<String>,
+    license_number: Option<String>,
+}
+
+#[derive(Default, Deserialize, Zeroize)]
+#[serde(rename_all = "camelCase")]
+struct ItemSshKey {
+    private_key: Option<String>,
+    public_key: Option<String>,
+    key_fingerprint: Option<String>,
+}
+
+/// The keys stretched from the export password.
+struct ExportKeys {
+    encryption: Zeroizing<[u8; 32]>,
+    mac: Zeroizing<[u8; 32]>,
+}
+
+/// Whether `text` is a Bitwarden export that needs its password to be read.
+pub fn is_password_protected(text: &str) -> Result<bool, BitwardenError> {
+    Ok(parse(text)?.password_protected)
+}
+
+/// Reads the entries of a Bitwarden export. `password` is only used, and
+/// only required, for password-protected exports; the key derivation then
+/// takes as long as it does in Bitwarden.
+pub fn read(text: &str, password: Option<&str>) -> Result<BitwardenImport, BitwardenError> {
+    let file = parse(text)?;
+    if !file.password_protected {
+        return Ok(convert(&file));
+    }
+
+    let password = password.ok_or(BitwardenError::PasswordRequired)?;
+    let keys = derive_keys(&file, password)?;
+    let validation = file
+        .key_validation
+        .as_deref()
+        .ok_or(BitwardenError::Malformed("key check"))?;
+    decrypt(&keys, validation)?;
+
+    let data = file
+        .data
+        .as_deref()
+        .ok_or(BitwardenError::Malformed("data"))?;
+    let plaintext = decrypt(&keys, data).map_err(|error| match error {
+        // The password was right, so a bad MAC means the data was altered.
+        BitwardenError::WrongPassword => BitwardenError::Malformed("data"),
+        error => error,
+    })?;
+    let text = std::str::from_utf8(&plaintext).map_err(|_| BitwardenError::Malformed("data"))?;
+    let inner = parse(text)?;
+    if inner.password_protected {
+        return Err(BitwardenError::Malformed("data"));
+    }
+    Ok(convert(&inner))
+}
+
+fn parse(text: &str) -> Result<ExportFile, BitwardenError> {
+    let file: ExportFile = serde_json::from_str(text).map_err(|error| {
+        if error.is_data() {
+            BitwardenError::NotBitwarden
+        } else {
+            BitwardenError::Json(error)
+        }
+    })?;
+    if file.password_protected {
+        Ok(file)
+    } else if file.encrypted {
+        Err(BitwardenError::AccountEncrypted)
+    } else if file.items.is_none() {
+        Err(BitwardenError::NotBitwarden)
+    } else {
+        Ok(file)
+    }
+}
+
+fn derive_keys(file: &ExportFile, password: &str) -> Result<ExportKeys, BitwardenError> {
+    let salt = file
+        .salt
+        .as_deref()
+        .ok_or(BitwardenError::Malformed("salt"))?;
+    let iterations = file
+        .kdf_iterations
+        .filter(|&iterations| iterations > 0)
+        .ok_or(BitwardenError::InvalidKdfParams)?;
+
+    let mut master = Zeroizing::new([0u8; 32]);
+    match file.kdf_type.unwrap_or(KDF_PBKDF2) {
+        KDF_PBKDF2 => {
+            if iterations > MAX_PBKDF2_ITERATIONS {
+                return Err(BitwardenError::InvalidKdfParams);
+            }
+            pbkdf2::pbkdf2_hmac::<Sha256>(
+                password.as_bytes(),
+                salt.as_bytes(),
+                iterations,
+                master.as_mut(),
+            );
+        }
+        KDF_ARGON2ID => {
+            let memory_mib = file
+                .kdf_memory
+                .filter(|memory| (1..=MAX_ARGON2_MEMORY_MIB).contains(memory))
+                .ok_or(BitwardenError::InvalidKdfParams)?;
+            let parallelism = file
+                .kdf_parallelism
+                .filter(|lanes| (1..=MAX_ARGON2_PARALLELISM).contains(lanes))
+                .ok_or(BitwardenError::InvalidKdfParams)?;
+            if iterations > MAX_ARGON2_ITERATIONS {
+                return Err(BitwardenError::InvalidKdfParams);
+            }
+            let params = Params::new(memory_mib * 1024, iterations, parallelism, Some(32))
+                .map_err(|_| BitwardenError::InvalidKdfParams)?;
+            // Bitwarden salts Argon2id with the hash of the salt string.
+            let salt = Sha256::digest(salt.as_bytes());
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(password.as_bytes(), &salt, master.as_mut())
+                .map_err(|_| BitwardenError::InvalidKdfParams)?;
+        }
+        other => return Err(BitwardenError::UnsupportedKdf(other)),
+    }
+
+    let expand =
+        Hkdf::<Sha256>::from_prk(master.as_ref()).map_err(|_| BitwardenError::InvalidKdfParams)?;
+    let mut keys = ExportKeys {
+        encryption: Zeroizing::new([0; 32]),
+        mac: Zeroizing::new([0; 32]),
+    };
+    expand
+        .expand(b"enc", keys.encryption.as_mut())
+        .and_then(|()| expand.expand(b"mac", keys.mac.as_mut()))
+        .map_err(|_| BitwardenError::InvalidKdfParams)?;
+    Ok(keys)
+}
+
+/// Checks and decrypts a `2.iv|ciphertext|mac` EncString. A MAC mismatch is
+/// reported as [`BitwardenError::WrongPassword`].
+fn decrypt(keys: &ExportKeys, enc_string: &str) -> Result<Zeroizing<Vec<u8>>, BitwardenError> {
+    let malformed = || BitwardenError::Malformed("encrypted data");
+    let (kind, parts) = enc_string.split_once('.').ok_or_else(malformed)?;
+    if kind != AES_CBC_HMAC {
+        return Err(malformed());
+    }
+    let mut parts = parts.split('|');
+    let (Some(iv), Some(ciphertext), Some(mac), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(malformed());
+    };
+    let decode = |part: &str| BASE64.decode(part).map_err(|_| malformed());
+    let iv: [u8; 16] = decode(iv)?.try_into().map_err(|_| malformed())?;
+    let mut buffer = Zeroizing::new(decode(ciphertext)?);
+    let mac = decode(mac)?;
+
+    let mut check = <Hmac<Sha256> as Mac>::new_from_slice(keys.mac.as_ref())
+        .map_err(|_| BitwardenError::InvalidKdfParams)?;
+    check.update(&iv);
+    check.update(&buffer);
+    check
+        .verify_slice(&mac)
+        .map_err(|_| BitwardenError::WrongPassword)?;
+
+    let length = cbc::Decryptor::<Aes256>::new((&*keys.encryption).into(), &iv.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+        .map_err(|_| malformed())?
+        .len();
+    buffer.truncate(length);
+    Ok(buffer)
+}
+
+fn convert(file: &ExportFile) -> BitwardenImport {
+    let names = |list: &Option<Vec<Folder>>| -> HashMap<String, String> {
+        list.iter()
+            .flatten()
+            .filter_map(|folder| Some((folder.id.clone()?, folder.name.clone()?)))
+            .collect()
+    };
+    let folders = names(&file.folders);
+    let collections = names(&file.collections);
+
+    let mut import = BitwardenImport::default();
+    for item in file.items.iter().flatten() {
+        match convert_item(item) {
+            Some(mut entry) => {
+                if let Some(folder) = item.folder_id.as_ref().and_then(|id| folders.get(id)) {
+                    entry.folder = folder.clone();
+                }
+                entry.tags = item
+                    .collection_ids
+                    .iter()
+                    .flatten()
+                    .filter_map(|id| collections.get(id).cloned())
+                    .collect();
+                import.entries.push(entry);
+            }
+            None => import.skipped.push(title_of(item)),
+        }
+    }
+    import
+}
+
+/// Maps one item onto an entry; `None` for item types Lilypad lacks.
+fn convert_item(item: &Item) -> Option<Entry> {
+    let mut extra_fields = Vec::new();
+    let mut extra = |name: &str, value: &Option<String>, hidden: bool| {
+        push_field(&mut extra_fields, name, text(value), hidden);
+    };
+
+    let kind = match item.kind {
+        ITEM_LOGIN => {
+            let default = ItemLogin::default();
+            let login = item.login.as_ref().unwrap_or(&default);
+            EntryKind::Login(Login {
+                username: text(&login.username),
+                password: text(&login.password),
+                urls: login
+                    .uris
+                    .iter()
+                    .flatten()
+                    .filter_map(|uri| uri.uri.clone())
+                    .filter(|uri| !uri.trim().is_empty())
+                    .collect(),
+                password_rules: String::new(),
+                totp: text(&login.totp),
+            })
+        }
+        ITEM_SECURE_NOTE => EntryKind::SecureNote,
+        ITEM_CARD => {
+            let default = ItemCard::default();
+            let card = item.card.as_ref().unwrap_or(&default);
+            EntryKind::Card(Card {
+                cardholder_name: text(&card.cardholder_name),
+                brand: text(&card.brand),
+                number: text(&card.number),
+                expiry_month: text(&card.exp_month),
+                expiry_year: text(&card.exp_year),
+                security_code: text(&card.code),
+            })
+        }
+        ITEM_IDENTITY => {
+            let default = ItemIdentity::default();
+            let identity = item.identity.as_ref().unwrap_or(&default);
+            extra("Title", &identity.title, false);
+            extra("Middle name", &identity.middle_name, false);
+            extra("Username", &identity.username, false);
+            extra("Social security number", &identity.ssn, true);
+            extra("Passport number", &identity.passport_number, true);
+            extra("License number", &identity.license_number, true);
+            let address: Vec<String> = [&identity.address1, &identity.address2, &identity.address3]
+                .into_iter()
+                .map(text)
+                .filter(|line| !line.is_empty())
+                .collect();
+            EntryKind::Identity(Identity {
+                first_name: text(&identity.first_name),
+                last_name: text(&identity.last_name),
+                email: text(&identity.email),
+                phone: text(&identity.phone),
+                company: text(&identity.company),
+                address: address.join(", "),
+                city: text(&identity.city),
+                state: text(&identity.state),
+                postal_code: text(&identity.postal_code),
+                country: text(&identity.country),
+            })
+        }
+        ITEM_SSH_KEY => {
+            let default = ItemSshKey::default();
+            let key = item.ssh_key.as_ref().unwrap_or(&default);
+            extra("Public key", &key.public_key, false);
+            extra("Fingerprint", &key.key_fingerprint, false);
+            extra("Private key", &key.private_key, true);
+            EntryKind::SecureNote
+        }
+        _ => return None,
+    };
+
+    let mut entry = Entry::new(title_of(item), kind);
+    if let Some(id) = item.id.as_deref().and_then(|id| Uuid::parse_str(id).ok()) {
+        entry.id = id;
+    }
+    entry.notes = text(&item.notes);
+    for field in item.fields.iter().flatten() {
+        if field.kind != FIELD_LINKED {
+            let name = field.name.as_deref().unwrap_or_default();
+            let name = if name.trim().is_empty() {
+                "Field"
+            } else {
+                name
+            };
+            push_field(
+                &mut entry.custom_fields,
+                name,
+                text(&field.value),
+                field.kind == FIELD_HIDDEN,
+            );
+        }
+    }
+    entry.custom_fields.append(&mut extra_fields);
+
+    let date = |value: &Option<String>| value.as_deref().and_then(clock::parse_rfc3339);
+    if let Some(modified) = date(&item.revision_date) {
+        entry.modified_at = modified;
+        entry.created_at = date(&item.creation_date).unwrap_or(modified);
+    }
+    Some(entry)
+}
+
+fn title_of(item: &Item) -> String {
+    match item.name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => "Untitled".to_string(),
+    }
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn push_field(fields: &mut Vec<CustomField>, name: &str, value: String, hidden: bool) {
+    if !value.is_empty() {
+        fields.push(CustomField {
+            name: name.to_string(),
+            value,
+            hidden,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: &str = include_str!("../fixtures/bitwarden-plain.json");
+    const PBKDF2: &str = include_str!("../fixtures/bitwarden-pbkdf2.json");
+    const ARGON2ID: &str = include_str!("../fixtures/bitwarden-argon2id.json");
+    const PASSWORD: &str = "lilypad-fixture";
+
+    fn field<'a>(entry: &'a Entry, name: &str) -> Option<&'a CustomField> {
+        entry.custom_fields.iter().find(|field| field.name == name)
+    }
+
+    #[test]
+    fn maps_every_item_type() {
+        assert!(!is_password_protected(PLAIN).unwrap());
+        let import = read(PLAIN, None).unwrap();
+        assert!(import.skipped.is_empty());
+        let [login, note, card, identity, ssh_key] = import.entries.as_slice() else {
+            panic!("expected five entries, got {}", import.entries.len());
+        };
+
+        assert_eq!(
+            login.id,
+            Uuid::parse_str("b6f1c0a2-3d4e-4f5a-8b6c-7d8e9f0a1b2c").unwrap()
+        );
+        assert_eq!(login.folder, "Work");
+        assert_eq!(login.created_at, 1_700_000_000);
+        let EntryKind::Login(account) = &login.kind else {
+            panic!("expected a login");
+        };
+        assert_eq!(account.username, "alice@example.com");
+        assert_eq!(account.password, "correct-horse-battery");
+        assert_eq!(account.urls.len(), 2);
+        assert!(account.totp.starts_with("otpauth://totp/"));
+        let names: Vec<_> = login.custom_fields.iter().map(|f| &f.name).collect();
+        assert_eq!(names, ["Recovery PIN", "Department", "Admin"]);
+        assert!(login.custom_fields[0].hidden);
+
+        assert_eq!(note.kind, EntryKind::SecureNote);
+        assert_eq!(note.notes, "1111-2222\n3333-4444");
+        assert!(note.folder.is_empty());
+
+        let EntryKind::Card(visa) = &card.kind else {
+            panic!("expected a card");
+        };
+        assert_eq!(visa.expiry_month, "7");
+        assert_eq!(visa.security_code, "123");
+        assert!(card.validate().is_ok());
+
+        let EntryKind::Identity(person) = &identity.kind else {
+            panic!("expected an identity");
+        };
+        assert_eq!(person.address, "1 Main Street, Apartment 2");
+        assert!(field(identity, "Social security number").unwrap().hidden);
+        assert_eq!(field(identity, "Middle name").unwrap().value, "B");
+        assert!(field(identity, "License number").is_none());
+
+        assert_eq!(ssh_key.kind, EntryKind::SecureNote);
+        assert_eq!(ssh_key.folder, "Work/Servers");
+        assert!(field(ssh_key, "Private key").unwrap().hidden);
+    }
+
+    #[test]
+    fn decrypts_password_protected_exports() {
+        for export in [PBKDF2, ARGON2ID] {
+            assert!(is_password_protected(export).unwrap());
+            assert!(matches!(
+                read(export, None),
+                Err(BitwardenError::PasswordRequired)
+            ));
+            assert!(matches!(
+                read(export, Some("wrong password")),
+                Err(BitwardenError::WrongPassword)
+            ));
+
+            let import = read(export, Some(PASSWORD)).unwrap();
+            assert_eq!(import.entries.len(), 1);
+            let entry = &import.entries[0];
+            assert_eq!(entry.title, "Company mail");
+            assert_eq!(entry.folder, "Work");
+            assert_eq!(entry.primary_secret(), Some("correct-horse-battery"));
+        }
+    }
+
+    #[test]
+    fn rejects_other_documents_and_skips_unknown_items() {
+        assert!(matches!(
+            read(r#"{"format": "lilypad-export", "entries": []}"#, None),
+            Err(BitwardenError::NotBitwarden)
+        ));
+        assert!(matches!(
+            read(r#"{"encrypted": true, "items": []}"#, None),
+            Err(BitwardenError::AccountEncrypted)
+        ));
+        assert!(matches!(
+            read("not json", None),
+            Err(BitwardenError::Json(_))
+        ));
+
+        let import = read(
+            r#"{"encrypted": false, "items": [{"type": 9, "name": "Passkey"}]}"#,
+            None,
+        )
+        .unwrap();
+        assert!(import.entries.is_empty());
+        assert_eq!(import.skipped, ["Passkey"]);
+    }
+}
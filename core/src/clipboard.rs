@@ -0,0 +1,157 @@
+//! Timing for clearing copied secrets from the system clipboard.
+//!
+//! Frontends own the actual clipboard; this type only remembers what was
+//! copied and when it is due to be cleared, so the desktop app, the TUI, and
+//! any future frontend share the same rules: clear after the configured
+//! timeout, on lock, and on exit, but only if the clipboard still holds the
+//! secret Lilypad put there.
+
+use crate::secret::{ExposeSecret, SecretString};
+use std::time::{Duration, Instant};
+
+/// How long a copied secret stays on the clipboard before it is wiped: 30
+/// seconds, enough to switch windows and paste it.
+pub const DEFAULT_CLEAR_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct Pending {
+    secret: SecretString,
+    deadline: Option<Instant>,
+}
+
+/// Tracks the last secret copied to the clipboard.
+#[derive(Debug)]
+pub struct ClipboardClear {
+    clear_after: Option<Duration>,
+    pending: Option<Pending>,
+}
+
+impl Default for ClipboardClear {
+    fn default() -> Self {
+        Self::new(Some(DEFAULT_CLEAR_AFTER))
+    }
+}
+
+impl ClipboardClear {
+    /// `clear_after` of `None` keeps copied secrets until lock or exit.
+    pub fn new(clear_after: Option<Duration>) -> Self {
+        Self {
+            clear_after,
+            pending: None,
+        }
+    }
+
+    pub fn clear_after(&self) -> Option<Duration> {
+        self.clear_after
+    }
+
+    /// Changes the timeout; a pending secret keeps its original deadline.
+    pub fn set_clear_after(&mut self, clear_after: Option<Duration>) {
+        self.clear_after = clear_after;
+    }
+
+    /// Records that `secret` was just placed on the clipboard, replacing any
+    /// earlier secret (the clipboard no longer holds it anyway).
+    pub fn copied(&mut self, secret: &str, now: Instant) {
+        self.pending = Some(Pending {
+            secret: SecretString::from(secret),
+            deadline: self.clear_after.map(|after| now + after),
+        });
+    }
+
+    /// Time left before the pending secret is cleared, if one is scheduled.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        let deadline = self.pending.as_ref()?.deadline?;
+        Some(deadline.saturating_duration_since(now))
+    }
+
+    /// Hands back the pending secret once its deadline has passed, so the
+    /// caller can compare it with the clipboard and clear on a match.
+    pub fn take_due(&mut self, now: Instant) -> Option<SecretString> {
+        let due = self
+            .pending
+            .as_ref()?
+            .deadline
+            .is_some_and(|deadline| now >= deadline);
+        if due {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    /// Hands back the pending secret regardless of its deadline; used on
+    /// lock and exit.
+    pub fn take(&mut self) -> Option<SecretString> {
+        self.pending.take().map(|pending| pending.secret)
+    }
+}
+
+/// Whether the clipboard should be cleared: only when it still holds exactly
+/// the secret Lilypad copied, never something the user copied since.
+pub fn still_holds(clipboard_text: Option<&str>, secret: &SecretString) -> bool {
+    clipboard_text == Some(secret.expose_secret())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clears_only_after_the_timeout() {
+        let start = Instant::now();
+        let mut clear = ClipboardClear::new(Some(Duration::from_secs(20)));
+        clear.copied("hunter2", start);
+
+        assert_eq!(clear.remaining(start), Some(Duration::from_secs(20)));
+        assert!(clear.take_due(start + Duration::from_secs(19)).is_none());
+        assert_eq!(
+            clear
+                .take_due(start + Duration::from_secs(20))
+                .as_ref()
+                .map(ExposeSecret::expose_secret),
+            Some("hunter2")
+        );
+        assert_eq!(clear.remaining(start), None);
+        assert!(clear.take().is_none());
+    }
+
+    #[test]
+    fn newer_copy_replaces_the_pending_secret() {
+        let start = Instant::now();
+        let mut clear = ClipboardClear::default();
+        clear.copied("first", start);
+        clear.copied("second", start + Duration::from_secs(10));
+
+        assert_eq!(
+            clear.remaining(start + Duration::from_secs(10)),
+            Some(DEFAULT_CLEAR_AFTER)
+        );
+        assert_eq!(
+            clear.take().as_ref().map(ExposeSecret::expose_secret),
+            Some("second")
+        );
+    }
+
+    #[test]
+    fn without_timeout_secret_waits_for_lock_or_exit() {
+        let start = Instant::now();
+        let mut clear = ClipboardClear::new(None);
+        clear.copied("hunter2", start);
+
+        assert_eq!(clear.remaining(start), None);
+        assert!(clear.take_due(start + Duration::from_secs(3600)).is_none());
+        assert_eq!(
+            clear.take().as_ref().map(ExposeSecret::expose_secret),
+            Some("hunter2")
+        );
+    }
+
+    #[test]
+    fn leaves_other_clipboard_contents_alone() {
+        let secret = SecretString::from("hunter2");
+        assert!(still_holds(Some("hunter2"), &secret));
+        assert!(!still_holds(Some("grocery list"), &secret));
+        assert!(!still_holds(None, &secret));
+    }
+}
@@ -0,0 +1,109 @@
+//! Wall-clock helpers. Timestamps are stored as Unix seconds (UTC).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, PrimitiveDateTime};
+
+/// Current time as Unix seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM UTC` for display.
+pub fn format_utc(timestamp: u64) -> String {
+    let Ok(datetime) = i64::try_from(timestamp)
+        .map_err(|_| ())
+        .and_then(|seconds| OffsetDateTime::from_unix_timestamp(seconds).map_err(|_| ()))
+    else {
+        return "unknown".to_string();
+    };
+
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        datetime.year(),
+        u8::from(datetime.month()),
+        datetime.day(),
+        datetime.hour(),
+        datetime.minute()
+    )
+}
+
+/// Formats a Unix timestamp as `YYYYMMDDTHHMMSSZ`, which sorts by time and
+/// is safe in file names. `None` past the year 9999.
+pub fn format_compact(timestamp: u64) -> Option<String> {
+    let datetime = OffsetDateTime::from_unix_timestamp(i64::try_from(timestamp).ok()?).ok()?;
+    if datetime.year() > 9999 {
+        return None;
+    }
+    Some(format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        datetime.year(),
+        u8::from(datetime.month()),
+        datetime.day(),
+        datetime.hour(),
+        datetime.minute(),
+        datetime.second()
+    ))
+}
+
+/// Reads a timestamp written by [`format_compact`].
+pub fn parse_compact(text: &str) -> Option<u64> {
+    let format =
+        time::format_description::parse_borrowed::<2>("[year][month][day]T[hour][minute][second]Z")
+            .ok()?;
+    let datetime = PrimitiveDateTime::parse(text, &format).ok()?;
+    u64::try_from(datetime.assume_utc().unix_timestamp()).ok()
+}
+
+/// Parses an RFC 3339 date such as `2024-03-05T12:30:00.000Z`, as found in
+/// other managers' exports, into Unix seconds. `None` if malformed or before
+/// 1970.
+pub fn parse_rfc3339(text: &str) -> Option<u64> {
+    let datetime = OffsetDateTime::parse(text.trim(), &Rfc3339).ok()?;
+    u64::try_from(datetime.unix_timestamp()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_known_timestamp() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13 UTC");
+    }
+
+    #[test]
+    fn parses_rfc3339_dates() {
+        assert_eq!(
+            parse_rfc3339("2023-11-14T22:13:20.000Z"),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            parse_rfc3339("2023-11-15T00:13:20+02:00"),
+            Some(1_700_000_000)
+        );
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), None);
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+
+    #[test]
+    fn compact_timestamps_round_trip() {
+        assert_eq!(
+            format_compact(1_700_000_000).as_deref(),
+            Some("20231114T221320Z")
+        );
+        assert_eq!(parse_compact("20231114T221320Z"), Some(1_700_000_000));
+        assert_eq!(format_compact(u64::MAX), None);
+        assert_eq!(parse_compact("2023-11-14"), None);
+        assert_eq!(parse_compact("20231114T221320Z.tmp"), None);
+    }
+
+    #[test]
+    fn out_of_range_timestamp_is_unknown() {
+        assert_eq!(format_utc(u64::MAX), "unknown");
+    }
+}
@@ -0,0 +1,600 @@
+//! User preferences stored as a versioned TOML file (`config.toml` in the
+//! platform config directory).
+//!
+//! Every section and field has a default, so a partial or empty file loads
+//! fine and new fields can be added without bumping [`CONFIG_VERSION`]. Bump
+//! it only when a field changes meaning, and teach [`Config::load`] to
+//! migrate the older layout. Durations are whole seconds where `0` means
+//! "never", since TOML has no null.
+
+use crate::crypto::KdfParams;
+use crate::generator::{GeneratorMode, GeneratorSettings};
+use crate::keymap::{Keymap, KeymapError};
+use crate::vault;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const CONFIG_VERSION: u32 = 1;
+pub const CONFIG_FILE: &str = "config.toml";
+/// The single-line file earlier versions used to remember the welcome
+/// dialog; migrated into [`Config::welcome_acknowledged`] and then removed.
+pub const LEGACY_WELCOME_ACK_FILE: &str = "welcome_ack";
+
+/// Generator length limits, matching what the generator panels offer.
+pub const LENGTH_RANGE: (usize, usize) = (4, 128);
+pub const WORD_COUNT_RANGE: (usize, usize) = (3, 12);
+pub const FONT_SCALE_RANGE: (f32, f32) = (0.75, 2.0);
+/// Argon2id limits offered in settings: enough to stay meaningful, low
+/// enough that a typo cannot make the vault take minutes to open.
+pub const KDF_MEMORY_MIB_RANGE: (u32, u32) = (16, 4096);
+pub const KDF_ITERATIONS_RANGE: (u32, u32) = (1, 20);
+pub const KDF_PARALLELISM_RANGE: (u32, u32) = (1, 16);
+/// How many vault snapshots may be kept; `0` turns snapshots off.
+pub const BACKUP_KEEP_RANGE: (usize, usize) = (0, 100);
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    UnsupportedVersion(u32),
+    Invalid(&'static str),
+    Keymap(KeymapError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "config file error: {error}"),
+            Self::Parse(error) => write!(f, "config file is not valid TOML: {error}"),
+            Self::Serialize(error) => write!(f, "unable to write config: {error}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "config file version {version} is newer than this build supports"
+            ),
+            Self::Invalid(reason) => write!(f, "invalid config: {reason}"),
+            Self::Keymap(error) => write!(f, "invalid config: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl From<toml::ser::Error> for ConfigError {
+    fn from(error: toml::ser::Error) -> Self {
+        Self::Serialize(error)
+    }
+}
+
+impl From<KeymapError> for ConfigError {
+    fn from(error: KeymapError) -> Self {
+        Self::Keymap(error)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    pub welcome_acknowledged: bool,
+    pub generator: GeneratorConfig,
+    pub security: SecurityConfig,
+    pub appearance: AppearanceConfig,
+    pub vault: VaultConfig,
+    pub backup: BackupConfig,
+    pub unlock: UnlockState,
+    /// Shortcut overrides keyed by [`crate::keymap::Action::name`], such as
+    /// `lock = "Ctrl+Alt+L"`; an empty value unbinds the action.
+    pub keymap: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            welcome_acknowledged: false,
+            generator: GeneratorConfig::default(),
+            security: SecurityConfig::default(),
+            appearance: AppearanceConfig::default(),
+            vault: VaultConfig::default(),
+            backup: BackupConfig::default(),
+            unlock: UnlockState::default(),
+            keymap: BTreeMap::new(),
+        }
+    }
+}
+
+/// Defaults the generator starts from; per-session tweaks are not saved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    pub mode: GeneratorMode,
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    pub exclude_ambiguous: bool,
+    pub word_count: usize,
+    pub separator: String,
+    pub capitalize: bool,
+    pub append_digit: bool,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self::from_settings(&GeneratorSettings::default())
+    }
+}
+
+impl GeneratorConfig {
+    pub fn from_settings(settings: &GeneratorSettings) -> Self {
+        let password = &settings.password;
+        let passphrase = &settings.passphrase;
+        Self {
+            mode: settings.mode,
+            length: password.length,
+            lowercase: password.lowercase,
+            uppercase: password.uppercase,
+            digits: password.digits,
+            symbols: password.symbols,
+            exclude_ambiguous: password.exclude_ambiguous,
+            word_count: passphrase.word_count,
+            separator: passphrase.separator.clone(),
+            capitalize: passphrase.capitalize,
+            append_digit: passphrase.append_digit,
+        }
+    }
+
+    pub fn to_settings(&self) -> GeneratorSettings {
+        let mut settings = GeneratorSettings::default();
+        self.apply_to(&mut settings);
+        settings
+    }
+
+    /// Overwrites the fields these defaults cover, keeping per-session
+    /// constraints such as site rules or minimum counts.
+    pub fn apply_to(&self, settings: &mut GeneratorSettings) {
+        settings.mode = self.mode;
+        let password = &mut settings.password;
+        password.length = self.length;
+        password.lowercase = self.lowercase;
+        password.uppercase = self.uppercase;
+        password.digits = self.digits;
+        password.symbols = self.symbols;
+        password.exclude_ambiguous = self.exclude_ambiguous;
+        let passphrase = &mut settings.passphrase;
+        passphrase.word_count = self.word_count;
+        passphrase.separator = self.separator.clone();
+        passphrase.capitalize = self.capitalize;
+        passphrase.append_digit = self.append_digit;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Seconds of inactivity before the vault locks; `0` never locks.
+    pub idle_timeout_secs: u64,
+    pub lock_on_minimize: bool,
+    pub lock_on_focus_loss: bool,
+    /// Seconds before a copied secret is cleared; `0` waits for lock or exit.
+    pub clipboard_clear_secs: u64,
+    /// Key derivation cost for new vaults and master-password changes.
+    pub kdf: KdfConfig,
+}
+
+/// Argon2id cost, with memory in MiB rather than the KiB the vault header
+/// stores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KdfConfig {
+    pub memory_mib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfConfig {
+    fn default() -> Self {
+        Self::from_params(KdfParams::default())
+    }
+}
+
+impl KdfConfig {
+    pub fn from_params(params: KdfParams) -> Self {
+        Self {
+            memory_mib: params.memory_kib / 1024,
+            iterations: params.iterations,
+            parallelism: params.parallelism,
+        }
+    }
+
+    /// Whether every cost is within the ranges settings offer.
+    pub fn is_supported(&self) -> bool {
+        (KDF_MEMORY_MIB_RANGE.0..=KDF_MEMORY_MIB_RANGE.1).contains(&self.memory_mib)
+            && (KDF_ITERATIONS_RANGE.0..=KDF_ITERATIONS_RANGE.1).contains(&self.iterations)
+            && (KDF_PARALLELISM_RANGE.0..=KDF_PARALLELISM_RANGE.1).contains(&self.parallelism)
+    }
+
+    pub fn to_params(self) -> KdfParams {
+        KdfParams {
+            memory_kib: self.memory_mib.saturating_mul(1024),
+            iterations: self.iterations,
+            parallelism: self.parallelism,
+        }
+    }
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_secs: crate::session::DEFAULT_IDLE_TIMEOUT.as_secs(),
+            lock_on_minimize: true,
+            lock_on_focus_loss: false,
+            clipboard_clear_secs: crate::clipboard::DEFAULT_CLEAR_AFTER.as_secs(),
+            kdf: KdfConfig::default(),
+        }
+    }
+}
+
+impl SecurityConfig {
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        seconds_or_never(self.idle_timeout_secs)
+    }
+
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout_secs = timeout.map_or(0, |timeout| timeout.as_secs());
+    }
+
+    pub fn clipboard_clear_after(&self) -> Option<Duration> {
+        seconds_or_never(self.clipboard_clear_secs)
+    }
+
+    pub fn set_clipboard_clear_after(&mut self, after: Option<Duration>) {
+        self.clipboard_clear_secs = after.map_or(0, |after| after.as_secs());
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceConfig {
+    /// A built-in theme, a user theme's name, or
+    /// [`theme::FOLLOW_SYSTEM`](crate::theme::FOLLOW_SYSTEM).
+    pub theme: String,
+    /// Multiplier applied to all text and widgets.
+    pub font_scale: f32,
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            theme: crate::theme::DARK.to_string(),
+            font_scale: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// Where the vault file lives; unset uses the platform data directory.
+    pub path: Option<PathBuf>,
+}
+
+/// Snapshots of the vault file taken on every save; see [`crate::backup`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Number of snapshots to keep, oldest removed first; `0` takes none.
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self { keep: 10 }
+    }
+}
+
+/// Failed unlock attempts since the last success, kept so the back-off of
+/// [`crate::session::unlock_backoff`] survives a restart.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnlockState {
+    pub failed_attempts: u32,
+    /// Unix timestamp (seconds, UTC) of the latest failure.
+    pub last_failure_at: u64,
+}
+
+impl UnlockState {
+    /// Counts a wrong password entered at `now`.
+    pub fn record_failure(&mut self, now: u64) {
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        self.last_failure_at = now;
+    }
+}
+
+impl Config {
+    /// Loads `config.toml` from `config_dir`.
+    ///
+    /// With no config file, settings are migrated from the legacy
+    /// `welcome_ack` file when present (which is then replaced by a fresh
+    /// `config.toml`), and defaults are used otherwise.
+    pub fn load(config_dir: &Path) -> Result<Self, ConfigError> {
+        let path = config_dir.join(CONFIG_FILE);
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Self::migrate_legacy(config_dir)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Parses and validates the contents of a config file.
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(contents)?;
+        if config.version > CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion(config.version));
+        }
+        // Version 1 is the first TOML layout; older versions would be
+        // migrated here before validation.
+        let config = Self {
+            version: CONFIG_VERSION,
+            ..config
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks values that deserialize fine but make no sense.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let generator = &self.generator;
+        if !(LENGTH_RANGE.0..=LENGTH_RANGE.1).contains(&generator.length) {
+            return Err(ConfigError::Invalid(
+                "generator.length must be between 4 and 128",
+            ));
+        }
+        if !(WORD_COUNT_RANGE.0..=WORD_COUNT_RANGE.1).contains(&generator.word_count) {
+            return Err(ConfigError::Invalid(
+                "generator.word_count must be between 3 and 12",
+            ));
+        }
+        if !(generator.lowercase || generator.uppercase || generator.digits || generator.symbols) {
+            return Err(ConfigError::Invalid(
+                "generator must enable at least one character class",
+            ));
+        }
+        if !self.security.kdf.is_supported() {
+            return Err(ConfigError::Invalid(
+                "security.kdf is outside the supported range",
+            ));
+        }
+        if !(FONT_SCALE_RANGE.0..=FONT_SCALE_RANGE.1).contains(&self.appearance.font_scale) {
+            return Err(ConfigError::Invalid(
+                "appearance.font_scale must be between 0.75 and 2.0",
+            ));
+        }
+        if self.appearance.theme.trim().is_empty() {
+            return Err(ConfigError::Invalid("appearance.theme must not be empty"));
+        }
+        if self
+            .vault
+            .path
+            .as_ref()
+            .is_some_and(|path| path.as_os_str().is_empty() || path.is_relative())
+        {
+            return Err(ConfigError::Invalid("vault.path must be an absolute path"));
+        }
+        if !(BACKUP_KEEP_RANGE.0..=BACKUP_KEEP_RANGE.1).contains(&self.backup.keep) {
+            return Err(ConfigError::Invalid(
+                "backup.keep must be between 0 and 100",
+            ));
+        }
+        Keymap::from_overrides(&self.keymap)?;
+        Ok(())
+    }
+
+    /// The default shortcuts with the configured overrides applied. Loading
+    /// validates the overrides, so this only falls back to the defaults for a
+    /// config that was edited in memory without calling [`Config::validate`].
+    pub fn keymap(&self) -> Keymap {
+        Keymap::from_overrides(&self.keymap).unwrap_or_default()
+    }
+
+    /// Validates and atomically writes `config.toml` into `config_dir`.
+    pub fn save(&self, config_dir: &Path) -> Result<(), ConfigError> {
+        self.validate()?;
+        let contents = toml::to_string_pretty(self)?;
+        vault::write_atomic(&config_dir.join(CONFIG_FILE), contents.as_bytes())?;
+        Ok(())
+    }
+
+    fn migrate_legacy(config_dir: &Path) -> Result<Self, ConfigError> {
+        let legacy_path = config_dir.join(LEGACY_WELCOME_ACK_FILE);
+        let contents = match fs::read_to_string(&legacy_path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error.into()),
+        };
+
+        let config = Self {
+            welcome_acknowledged: contents.trim() == "acknowledged=true",
+            ..Self::default()
+        };
+        config.save(config_dir)?;
+        fs::remove_file(legacy_path)?;
+        Ok(config)
+    }
+}
+
+fn seconds_or_never(seconds: u64) -> Option<Duration> {
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::Action;
+
+    #[test]
+    fn missing_file_gives_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Config::load(dir.path()).unwrap(), Config::default());
+        assert!(!dir.path().join(CONFIG_FILE).exists());
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            welcome_acknowledged: true,
+            ..Config::default()
+        };
+        config.generator.mode = GeneratorMode::Passphrase;
+        config.generator.word_count = 8;
+        config.security.set_idle_timeout(None);
+        config
+            .security
+            .set_clipboard_clear_after(Some(Duration::from_secs(45)));
+        config.vault.path = Some(std::env::temp_dir().join("vault.lilypad"));
+        config.backup.keep = 3;
+        config.unlock.record_failure(1_700_000_000);
+
+        config.save(dir.path()).unwrap();
+        let loaded = Config::load(dir.path()).unwrap();
+        assert_eq!(loaded, config);
+        assert_eq!(loaded.security.idle_timeout(), None);
+        assert_eq!(
+            loaded.security.clipboard_clear_after(),
+            Some(Duration::from_secs(45))
+        );
+    }
+
+    #[test]
+    fn partial_files_fill_in_defaults() {
+        let config =
+            Config::parse("welcome_acknowledged = true\n[security]\nlock_on_focus_loss = true\n")
+                .unwrap();
+        assert!(config.welcome_acknowledged);
+        assert!(config.security.lock_on_focus_loss);
+        assert!(config.security.lock_on_minimize);
+        assert_eq!(config.generator, GeneratorConfig::default());
+        assert_eq!(config.version, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn migrates_the_welcome_ack_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(LEGACY_WELCOME_ACK_FILE),
+            "acknowledged=true\n",
+        )
+        .unwrap();
+
+        let config = Config::load(dir.path()).unwrap();
+        assert!(config.welcome_acknowledged);
+        assert!(!dir.path().join(LEGACY_WELCOME_ACK_FILE).exists());
+        assert_eq!(Config::load(dir.path()).unwrap(), config);
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_invalid_values() {
+        assert!(matches!(
+            Config::parse("version = 2\n"),
+            Err(ConfigError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            Config::parse("[generator]\nlength = 2\n"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            Config::parse("[security.kdf]\nmemory_mib = 1\n"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            Config::parse("[appearance]\nfont_scale = 5.0\n"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            Config::parse("[vault]\npath = \"relative/vault.lilypad\"\n"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            Config::parse("[backup]\nkeep = 500\n"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            Config::parse("welcome_acknowledged = \"yes\"\n"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn keymap_overrides_apply_and_are_validated() {
+        let config = Config::parse("[keymap]\nlock = \"Ctrl+Alt+L\"\nnew-entry = \"\"\n").unwrap();
+        let keymap = config.keymap();
+        assert_eq!(
+            keymap
+                .shortcut(Action::Lock)
+                .map(|shortcut| shortcut.to_string()),
+            Some("Ctrl+Alt+L".to_string())
+        );
+        assert_eq!(keymap.shortcut(Action::NewEntry), None);
+
+        assert!(matches!(
+            Config::parse("[keymap]\nlock = \"Ctrl+K\"\n"),
+            Err(ConfigError::Keymap(KeymapError::Conflict { .. }))
+        ));
+        assert!(matches!(
+            Config::parse("[keymap]\nlaunch = \"Ctrl+J\"\n"),
+            Err(ConfigError::Keymap(KeymapError::UnknownAction(_)))
+        ));
+    }
+
+    #[test]
+    fn generator_settings_round_trip() {
+        let mut settings = GeneratorSettings::default();
+        settings.password.length = 24;
+        settings.password.symbols = false;
+        settings.passphrase.separator = ".".to_string();
+        assert_eq!(
+            GeneratorConfig::from_settings(&settings).to_settings(),
+            settings
+        );
+    }
+
+    #[test]
+    fn applying_generator_defaults_keeps_session_constraints() {
+        let mut settings = GeneratorSettings::default();
+        settings.password.min_digits = 3;
+        let defaults = GeneratorConfig {
+            length: 30,
+            ..GeneratorConfig::default()
+        };
+        defaults.apply_to(&mut settings);
+        assert_eq!(settings.password.length, 30);
+        assert_eq!(settings.password.min_digits, 3);
+    }
+
+    #[test]
+    fn kdf_config_matches_vault_defaults() {
+        assert_eq!(KdfConfig::default().to_params(), KdfParams::default());
+    }
+}
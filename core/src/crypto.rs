@@ -0,0 +1,188 @@
+//! Key derivation and authenticated encryption primitives used by the vault file.
+
+use crate::secret::LockedKey;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::fmt;
+use zeroize::Zeroizing;
+
+pub const KEY_LEN: usize = 32;
+pub const SALT_LEN: usize = 16;
+pub const NONCE_LEN: usize = 24;
+
+/// Argon2id cost parameters stored in the vault header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CryptoError {
+    InvalidKdfParams(argon2::Error),
+    KeyDerivation(argon2::Error),
+    Encryption,
+    Decryption,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKdfParams(error) => {
+                write!(f, "invalid key derivation parameters: {error}")
+            }
+            Self::KeyDerivation(error) => write!(f, "key derivation failed: {error}"),
+            Self::Encryption => write!(f, "encryption failed"),
+            Self::Decryption => write!(f, "decryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// Derives the vault key from the master password with Argon2id, straight
+/// into a [`LockedKey`] so it never sits in an ordinary buffer.
+pub fn derive_key(
+    password: &str,
+    salt: &[u8; SALT_LEN],
+    params: &KdfParams,
+) -> Result<LockedKey, CryptoError> {
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(CryptoError::InvalidKdfParams)?;
+
+    let mut key = LockedKey::zeroed();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, key.as_bytes_mut())
+        .map_err(CryptoError::KeyDerivation)?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext` with XChaCha20-Poly1305, binding `aad` to the ciphertext.
+pub fn encrypt(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    XChaCha20Poly1305::new(key.into())
+        .encrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| CryptoError::Encryption)
+}
+
+/// Decrypts and authenticates a payload produced by [`encrypt`]. The
+/// plaintext is wiped when the returned buffer is dropped.
+pub fn decrypt(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| CryptoError::Decryption)
+}
+
+/// Compares two keys without an early exit, so timing reveals nothing about
+/// where they differ.
+pub fn keys_match(a: &[u8; KEY_LEN], b: &[u8; KEY_LEN]) -> bool {
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Fills a fixed-size buffer from the operating system RNG.
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn derive_key_is_deterministic_per_salt() {
+        let salt = [7u8; SALT_LEN];
+        let first = derive_key("password", &salt, &TEST_KDF).unwrap();
+        let second = derive_key("password", &salt, &TEST_KDF).unwrap();
+        let other_salt = derive_key("password", &[8u8; SALT_LEN], &TEST_KDF).unwrap();
+
+        assert_eq!(first.as_bytes(), second.as_bytes());
+        assert_ne!(first.as_bytes(), other_salt.as_bytes());
+    }
+
+    #[test]
+    fn invalid_kdf_params_are_reported() {
+        let params = KdfParams {
+            memory_kib: 1,
+            ..TEST_KDF
+        };
+        assert!(matches!(
+            derive_key("password", &[0u8; SALT_LEN], &params),
+            Err(CryptoError::InvalidKdfParams(_))
+        ));
+    }
+
+    #[test]
+    fn keys_match_compares_every_byte() {
+        let key = [3u8; KEY_LEN];
+        let mut last_differs = key;
+        last_differs[KEY_LEN - 1] ^= 1;
+
+        assert!(keys_match(&key, &[3u8; KEY_LEN]));
+        assert!(!keys_match(&key, &last_differs));
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let key = [1u8; KEY_LEN];
+        let nonce = random_bytes::<NONCE_LEN>();
+        let ciphertext = encrypt(&key, &nonce, b"secret", b"header").unwrap();
+
+        assert_eq!(
+            decrypt(&key, &nonce, &ciphertext, b"header")
+                .unwrap()
+                .as_slice(),
+            b"secret"
+        );
+        assert!(decrypt(&key, &nonce, &ciphertext, b"other header").is_err());
+        assert!(decrypt(&[2u8; KEY_LEN], &nonce, &ciphertext, b"header").is_err());
+    }
+}
@@ -0,0 +1,555 @@
+//! Reader for password CSV exports: Chrome, Firefox, and LastPass, which are
+//! recognized by their headers, and any other CSV once its columns have been
+//! mapped by hand.
+//!
+//! A [`CsvTable`] holds the raw rows. A [`Mapping`] assigns each column a
+//! [`Field`]; presets produce one for the formats above and
+//! [`Mapping::guess`] matches common header names for the rest, leaving the
+//! frontend to let the user correct it. [`convert`] then builds entries,
+//! leaving out rows that duplicate an existing entry (or an earlier row) and
+//! reporting rows that cannot become an entry, with their line number.
+//!
+//! CSV rows get fresh ids, so duplicates are found by content instead: the
+//! same website, username, and password for logins, and the same title and
+//! text for notes.
+
+use crate::entry::{CustomField, Entry, EntryKind, Login};
+use crate::search::url_host;
+use crate::secret::Zeroizing;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// The website LastPass gives its secure notes.
+const LASTPASS_NOTE_URL: &str = "http://sn";
+
+#[derive(Debug)]
+pub enum CsvError {
+    Csv(csv::Error),
+    /// The file has no header row.
+    Empty,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv(error) => write!(f, "file is not valid CSV: {error}"),
+            Self::Empty => write!(f, "CSV file is empty"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        Self::Csv(error)
+    }
+}
+
+/// The entry field a column is read into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Title,
+    Url,
+    Username,
+    Password,
+    Notes,
+    Totp,
+    /// Folder path; LastPass's `\` separators become `/`.
+    Folder,
+    /// Comma- or semicolon-separated tags.
+    Tags,
+    /// A custom field named after the column header.
+    Custom,
+}
+
+impl Field {
+    pub const ALL: [Self; 9] = [
+        Self::Title,
+        Self::Url,
+        Self::Username,
+        Self::Password,
+        Self::Notes,
+        Self::Totp,
+        Self::Folder,
+        Self::Tags,
+        Self::Custom,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Url => "Website",
+            Self::Username => "Username",
+            Self::Password => "Password",
+            Self::Notes => "Notes",
+            Self::Totp => "TOTP seed",
+            Self::Folder => "Folder",
+            Self::Tags => "Tags",
+            Self::Custom => "Custom field",
+        }
+    }
+
+    /// The field a header most likely holds, from the names password
+    /// managers commonly use.
+    fn for_header(header: &str) -> Option<Self> {
+        let field = match header.trim().to_ascii_lowercase().as_str() {
+            "title" | "name" | "account" | "item" => Self::Title,
+            "url" | "uri" | "website" | "web site" | "login_uri" | "login uri" => Self::Url,
+            "username" | "user name" | "user" | "login" | "login_username" | "email" => {
+                Self::Username
+            }
+            "password" | "login_password" | "pass" => Self::Password,
+            "notes" | "note" | "extra" | "comments" | "comment" => Self::Notes,
+            "totp" | "otp" | "login_totp" | "one-time password" => Self::Totp,
+            "folder" | "grouping" | "group" => Self::Folder,
+            "tags" | "labels" => Self::Tags,
+            _ => return None,
+        };
+        Some(field)
+    }
+}
+
+/// Export formats recognized by their header row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Chrome,
+    Firefox,
+    LastPass,
+}
+
+impl Preset {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Chrome => "Chrome",
+            Self::Firefox => "Firefox",
+            Self::LastPass => "LastPass",
+        }
+    }
+
+    /// The columns each format writes, in order, and where they go. Columns
+    /// not listed (Firefox's timestamps and ids, LastPass's `fav`) are
+    /// ignored.
+    fn columns(self) -> &'static [(&'static str, Field)] {
+        match self {
+            Self::Chrome => &[
+                ("name", Field::Title),
+                ("url", Field::Url),
+                ("username", Field::Username),
+                ("password", Field::Password),
+                ("note", Field::Notes),
+            ],
+            Self::Firefox => &[
+                ("url", Field::Url),
+                ("username", Field::Username),
+                ("password", Field::Password),
+            ],
+            Self::LastPass => &[
+                ("url", Field::Url),
+                ("username", Field::Username),
+                ("password", Field::Password),
+                ("totp", Field::Totp),
+                ("extra", Field::Notes),
+                ("name", Field::Title),
+                ("grouping", Field::Folder),
+            ],
+        }
+    }
+
+    /// The preset whose required columns all appear in `headers`.
+    pub fn detect(headers: &[String]) -> Option<Self> {
+        let has = |name: &str| headers.iter().any(|header| header.trim() == name);
+        if has("grouping") && has("extra") && has("name") {
+            Some(Self::LastPass)
+        } else if has("httpRealm") && has("formActionOrigin") {
+            Some(Self::Firefox)
+        } else if ["name", "url", "username", "password"].into_iter().all(has) {
+            Some(Self::Chrome)
+        } else {
+            None
+        }
+    }
+
+    fn mapping(self, headers: &[String]) -> Mapping {
+        let columns = headers
+            .iter()
+            .map(|header| {
+                self.columns()
+                    .iter()
+                    .find(|(name, _)| *name == header.trim())
+                    .map(|(_, field)| *field)
+            })
+            .collect();
+        Mapping { columns }
+    }
+}
+
+/// Which field each column is read into; `None` ignores the column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mapping {
+    pub columns: Vec<Option<Field>>,
+}
+
+impl Mapping {
+    /// The detected preset's mapping, or a guess from the header names.
+    pub fn guess(table: &CsvTable) -> Self {
+        match Preset::detect(&table.headers) {
+            Some(preset) => preset.mapping(&table.headers),
+            None => Self {
+                columns: table
+                    .headers
+                    .iter()
+                    .map(|header| Field::for_header(header))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Whether any column holds a title, website, username, password, or
+    /// note; without one every row would fail.
+    pub fn is_usable(&self) -> bool {
+        self.columns.iter().flatten().any(|field| {
+            matches!(
+                field,
+                Field::Title | Field::Url | Field::Username | Field::Password | Field::Notes
+            )
+        })
+    }
+}
+
+/// A parsed CSV file: the header row and every record after it.
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    rows: Vec<Row>,
+}
+
+struct Row {
+    /// 1-based line number in the file, for the failed-rows report.
+    line: usize,
+    values: Vec<String>,
+}
+
+impl Drop for CsvTable {
+    fn drop(&mut self) {
+        for row in &mut self.rows {
+            row.values.zeroize();
+        }
+    }
+}
+
+impl CsvTable {
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The first non-empty value of column `index`, to show what a column
+    /// holds while mapping it.
+    pub fn sample(&self, index: usize) -> Option<&str> {
+        self.rows
+            .iter()
+            .filter_map(|row| row.values.get(index))
+            .map(|value| value.trim())
+            .find(|value| !value.is_empty())
+    }
+}
+
+/// A row [`convert`] could not turn into an entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailedRow {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// What [`convert`] found in a table.
+#[derive(Debug, Default)]
+pub struct CsvImport {
+    pub entries: Vec<Entry>,
+    /// Line numbers of rows that duplicate an entry already in the vault or
+    /// an earlier row.
+    pub duplicates: Vec<usize>,
+    pub failed: Vec<FailedRow>,
+}
+
+/// Parses `text` as comma-separated values with a header row. Rows may have
+/// a different number of columns; [`convert`] reports them.
+pub fn read(text: &str) -> Result<CsvTable, CsvError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+    if headers.iter().all(|header| header.trim().is_empty()) {
+        return Err(CsvError::Empty);
+    }
+    let mut table = CsvTable {
+        headers,
+        rows: Vec::new(),
+    };
+    for record in reader.records() {
+        let record = record?;
+        table.rows.push(Row {
+            line: record
+                .position()
+                .map_or(0, |position| position.line() as usize),
+            values: record.iter().map(str::to_string).collect(),
+        });
+    }
+    Ok(table)
+}
+
+/// Builds entries from the rows of `table` as `mapping` says, skipping any
+/// that duplicate one of `existing`.
+pub fn convert(table: &CsvTable, mapping: &Mapping, existing: &[Entry]) -> CsvImport {
+    let mut seen: HashSet<[u8; 32]> = existing.iter().map(duplicate_key).collect();
+    let mut import = CsvImport::default();
+    for row in &table.rows {
+        if row.values.len() != table.headers.len() {
+            import.failed.push(FailedRow {
+                line: row.line,
+                reason: format!(
+                    "has {} columns; the header has {}",
+                    row.values.len(),
+                    table.headers.len()
+                ),
+            });
+            continue;
+        }
+        match convert_row(&table.headers, &row.values, mapping) {
+            Ok(entry) => {
+                if seen.insert(duplicate_key(&entry)) {
+                    import.entries.push(entry);
+                } else {
+                    import.duplicates.push(row.line);
+                }
+            }
+            Err(reason) => import.failed.push(FailedRow {
+                line: row.line,
+                reason,
+            }),
+        }
+    }
+    import
+}
+
+fn convert_row(headers: &[String], values: &[String], mapping: &Mapping) -> Result<Entry, String> {
+    let mut title = String::new();
+    let mut login = Login::default();
+    let mut notes = Vec::new();
+    let mut folder = String::new();
+    let mut tags = Vec::new();
+    let mut custom = Vec::new();
+    for ((header, value), field) in headers.iter().zip(values).zip(&mapping.columns) {
+        let Some(field) = field else {
+            continue;
+        };
+        // Passwords keep their spaces; everything else is trimmed.
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match field {
+            Field::Title => title = trimmed.to_string(),
+            Field::Url => login.urls.push(trimmed.to_string()),
+            Field::Username => login.username = trimmed.to_string(),
+            Field::Password => login.password = value.clone(),
+            Field::Notes => notes.push(value.trim_end().to_string()),
+            Field::Totp => login.totp = trimmed.to_string(),
+            Field::Folder => folder = trimmed.replace('\\', "/"),
+            Field::Tags => tags.extend(
+                trimmed
+                    .split([',', ';'])
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string),
+            ),
+            Field::Custom => custom.push(CustomField {
+                name: header.trim().to_string(),
+                value: trimmed.to_string(),
+                hidden: false,
+            }),
+        }
+    }
+
+    let is_note = login.urls == [LASTPASS_NOTE_URL]
+        || (login.urls.is_empty()
+            && login.username.is_empty()
+            && login.password.is_empty()
+            && login.totp.is_empty()
+            && !notes.is_empty());
+    if title.is_empty() {
+        title = login
+            .urls
+            .first()
+            .filter(|_| !is_note)
+            .and_then(|url| url_host(url))
+            .map(|host| host.strip_prefix("www.").unwrap_or(host).to_string())
+            .unwrap_or_else(|| login.username.clone());
+    }
+    if title.is_empty() {
+        return Err("has no title, website, or username".to_string());
+    }
+
+    let kind = if is_note {
+        EntryKind::SecureNote
+    } else {
+        EntryKind::Login(login)
+    };
+    let mut entry = Entry::new(title, kind);
+    entry.notes = notes.join("\n");
+    entry.folder = folder;
+    entry.tags = tags;
+    entry.custom_fields = custom;
+    if let Err(errors) = entry.validate() {
+        let reasons: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        return Err(reasons.join("; "));
+    }
+    Ok(entry)
+}
+
+/// A hash of what makes two entries the same: website, username, and
+/// password for logins; title and text otherwise. Hashing keeps the set of
+/// seen entries free of plaintext passwords.
+fn duplicate_key(entry: &Entry) -> [u8; 32] {
+    let mut key = Zeroizing::new(String::new());
+    match &entry.kind {
+        EntryKind::Login(login) => {
+            let url = login.urls.first().map(String::as_str).unwrap_or_default();
+            key.push_str("login\0");
+            key.push_str(&url.trim_end_matches('/').to_lowercase());
+            key.push('\0');
+            key.push_str(&login.username);
+            key.push('\0');
+            key.push_str(&login.password);
+        }
+        _ => {
+            key.push_str(entry.kind.label());
+            key.push('\0');
+            key.push_str(&entry.title);
+            key.push('\0');
+            key.push_str(&entry.notes);
+        }
+    }
+    Sha256::digest(key.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(text: &str, existing: &[Entry]) -> (Option<Preset>, CsvImport) {
+        let table = read(text).unwrap();
+        let preset = Preset::detect(&table.headers);
+        let import = convert(&table, &Mapping::guess(&table), existing);
+        (preset, import)
+    }
+
+    fn login(entry: &Entry) -> &Login {
+        match &entry.kind {
+            EntryKind::Login(login) => login,
+            other => panic!("expected a login, got {}", other.label()),
+        }
+    }
+
+    #[test]
+    fn reads_chrome_exports() {
+        let (preset, import) = import(include_str!("../fixtures/chrome.csv"), &[]);
+        assert_eq!(preset, Some(Preset::Chrome));
+        let titles: Vec<_> = import.entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["example.com", "Mail"]);
+        assert_eq!(login(&import.entries[0]).password, "hunter2, with a comma");
+        assert_eq!(import.entries[1].notes, "Recovery codes\nare in the safe");
+        assert_eq!(import.duplicates, [5]);
+    }
+
+    #[test]
+    fn reads_firefox_exports_and_names_logins_after_the_host() {
+        let (preset, import) = import(include_str!("../fixtures/firefox.csv"), &[]);
+        assert_eq!(preset, Some(Preset::Firefox));
+        let titles: Vec<_> = import.entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["accounts.example.org", "192.168.1.1"]);
+        assert_eq!(login(&import.entries[0]).username, "ada");
+        assert!(import.entries[0].custom_fields.is_empty());
+    }
+
+    #[test]
+    fn titles_skip_user_info_and_ports_in_urls() {
+        let text = "url,username,password\n\
+                    https://frog@www.mail.example:8443/inbox,frog,pw\n\
+                    [::1]:8080,admin,pw\n";
+        let (_, import) = import(text, &[]);
+        let titles: Vec<_> = import.entries.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["mail.example", "::1"]);
+    }
+
+    #[test]
+    fn reads_lastpass_exports_with_notes_and_folders() {
+        let (preset, import) = import(include_str!("../fixtures/lastpass.csv"), &[]);
+        assert_eq!(preset, Some(Preset::LastPass));
+        let bank = &import.entries[0];
+        assert_eq!(bank.folder, "Finance/Banks");
+        assert_eq!(login(bank).totp, "JBSWY3DPEHPK3PXP");
+        let note = &import.entries[1];
+        assert_eq!(note.kind, EntryKind::SecureNote);
+        assert_eq!(note.notes, "Wi-Fi: pond-5G\nPassword: lily pads");
+        assert_eq!(
+            import.failed,
+            [FailedRow {
+                line: 5,
+                reason: "has 3 columns; the header has 8".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn skips_entries_already_in_the_vault() {
+        let mut existing = Entry::new(
+            "Example",
+            EntryKind::Login(Login {
+                username: "ada@example.com".to_string(),
+                password: "hunter2, with a comma".to_string(),
+                urls: vec!["https://EXAMPLE.com/".to_string()],
+                ..Login::default()
+            }),
+        );
+        existing.notes = "kept".to_string();
+        let (_, import) = import(include_str!("../fixtures/chrome.csv"), &[existing]);
+        assert_eq!(import.entries.len(), 1);
+        assert_eq!(import.duplicates, [2, 5]);
+    }
+
+    #[test]
+    fn unknown_headers_are_guessed_and_bad_rows_reported() {
+        let text = "Account,Login,Secret,Website\n\
+                    Forum,ada,pw,https://forum.example\n\
+                    ,,,\n\
+                    Bad,ada,pw,https://has space.example\n";
+        let table = read(text).unwrap();
+        let mut mapping = Mapping::guess(&table);
+        assert_eq!(
+            mapping.columns,
+            [
+                Some(Field::Title),
+                Some(Field::Username),
+                None,
+                Some(Field::Url)
+            ]
+        );
+        assert_eq!(table.sample(2), Some("pw"));
+        mapping.columns[2] = Some(Field::Password);
+
+        let import = convert(&table, &mapping, &[]);
+        assert_eq!(login(&import.entries[0]).password, "pw");
+        let lines: Vec<_> = import.failed.iter().map(|row| row.line).collect();
+        assert_eq!(lines, [3, 4]);
+        assert_eq!(import.failed[1].reason, "URLs: must not contain spaces");
+    }
+
+    #[test]
+    fn mapping_without_entry_fields_is_unusable() {
+        let table = read("guid,timeCreated\n1,2\n").unwrap();
+        assert!(!Mapping::guess(&table).is_usable());
+        assert!(matches!(read(""), Err(CsvError::Empty)));
+    }
+}
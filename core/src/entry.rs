@@ -0,0 +1,595 @@
+//! Items stored inside a vault.
+
+use crate::clock;
+use crate::password_rules::PasswordRules;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// A single item stored in the vault.
+///
+/// Fields shared by every kind (title, folder, notes, tags, custom fields,
+/// timestamps) live here; kind-specific fields live in [`EntryKind`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+pub struct Entry {
+    #[zeroize(skip)]
+    pub id: Uuid,
+    pub title: String,
+    pub kind: EntryKind,
+    /// Folder name, with `/` between nesting levels; empty when unfiled.
+    #[serde(default)]
+    pub folder: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Earlier versions of the entry, oldest first, as kept by KeePass. They
+    /// have no history of their own.
+    #[serde(default)]
+    pub history: Vec<Entry>,
+    /// Unix timestamp (seconds, UTC).
+    pub created_at: u64,
+    /// Unix timestamp (seconds, UTC).
+    pub modified_at: u64,
+}
+
+/// Kind-specific payload of an [`Entry`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EntryKind {
+    Login(Login),
+    /// A secure note keeps its body in [`Entry::notes`].
+    SecureNote,
+    Card(Card),
+    Identity(Identity),
+    Wifi(Wifi),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[serde(default)]
+pub struct Login {
+    pub username: String,
+    pub password: String,
+    pub urls: Vec<String>,
+    /// The site's password rules in `passwordrules` syntax; empty when none.
+    pub password_rules: String,
+    /// One-time password seed: an `otpauth://` URI or a base32 secret; empty
+    /// when none.
+    pub totp: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[serde(default)]
+pub struct Card {
+    pub cardholder_name: String,
+    pub brand: String,
+    pub number: String,
+    pub expiry_month: String,
+    pub expiry_year: String,
+    pub security_code: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[serde(default)]
+pub struct Identity {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: String,
+    pub company: String,
+    pub address: String,
+    pub city: String,
+    pub state: String,
+    pub postal_code: String,
+    pub country: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+#[serde(default)]
+pub struct Wifi {
+    pub ssid: String,
+    pub password: String,
+    #[zeroize(skip)]
+    pub security: WifiSecurity,
+    pub hidden: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WifiSecurity {
+    Open,
+    Wep,
+    WpaPersonal,
+    #[default]
+    Wpa2Personal,
+    Wpa3Personal,
+    Enterprise,
+}
+
+impl WifiSecurity {
+    pub const ALL: [Self; 6] = [
+        Self::Open,
+        Self::Wep,
+        Self::WpaPersonal,
+        Self::Wpa2Personal,
+        Self::Wpa3Personal,
+        Self::Enterprise,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Wep => "WEP",
+            Self::WpaPersonal => "WPA Personal",
+            Self::Wpa2Personal => "WPA2 Personal",
+            Self::Wpa3Personal => "WPA3 Personal",
+            Self::Enterprise => "Enterprise",
+        }
+    }
+}
+
+/// A user-defined field. Hidden fields are masked in the UI like passwords.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+pub struct CustomField {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// A file stored with an entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Zeroize)]
+pub struct Attachment {
+    pub name: String,
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+}
+
+/// Stores attachment contents as a base64 string rather than a JSON array of
+/// numbers.
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(data))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        BASE64.decode(text).map_err(de::Error::custom)
+    }
+}
+
+/// A problem that prevents an entry from being saved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Entry {
+    /// Creates an entry with a fresh UUID and both timestamps set to now.
+    pub fn new(title: impl Into<String>, kind: EntryKind) -> Self {
+        let now = clock::now();
+        Self {
+            id: Uuid::new_v4(),
+            title: title.into(),
+            kind,
+            folder: String::new(),
+            notes: String::new(),
+            tags: Vec::new(),
+            custom_fields: Vec::new(),
+            attachments: Vec::new(),
+            history: Vec::new(),
+            created_at: now,
+            modified_at: now,
+        }
+    }
+
+    /// Marks the entry as modified now.
+    pub fn touch(&mut self) {
+        self.modified_at = clock::now();
+    }
+
+    /// Short secondary line for list views: the username, SSID, card ending, …
+    pub fn subtitle(&self) -> String {
+        match &self.kind {
+            EntryKind::Login(login) => login.username.clone(),
+            EntryKind::SecureNote => self.notes.lines().next().unwrap_or_default().to_string(),
+            EntryKind::Card(card) => {
+                let digits: Vec<char> = card.number.chars().filter(char::is_ascii_digit).collect();
+                if digits.len() >= 4 {
+                    let last_four: String = digits[digits.len() - 4..].iter().collect();
+                    format!("•••• {last_four}")
+                } else {
+                    card.cardholder_name.clone()
+                }
+            }
+            EntryKind::Identity(identity) => {
+                let name = format!("{} {}", identity.first_name, identity.last_name);
+                if name.trim().is_empty() {
+                    identity.email.clone()
+                } else {
+                    name.trim().to_string()
+                }
+            }
+            EntryKind::Wifi(wifi) => wifi.ssid.clone(),
+        }
+    }
+
+    /// The account name for quick copying: a login username, identity email,
+    /// Wi-Fi SSID, or cardholder name. `None` when the kind has none or it is
+    /// empty.
+    pub fn username(&self) -> Option<&str> {
+        let username = match &self.kind {
+            EntryKind::Login(login) => &login.username,
+            EntryKind::Identity(identity) => &identity.email,
+            EntryKind::Wifi(wifi) => &wifi.ssid,
+            EntryKind::Card(card) => &card.cardholder_name,
+            EntryKind::SecureNote => return None,
+        };
+        Some(username.as_str()).filter(|username| !username.is_empty())
+    }
+
+    /// The entry's main secret for quick copying: a login or Wi-Fi password,
+    /// or a card number. `None` when the kind has none or it is empty.
+    pub fn primary_secret(&self) -> Option<&str> {
+        let secret = match &self.kind {
+            EntryKind::Login(login) => &login.password,
+            EntryKind::Card(card) => &card.number,
+            EntryKind::Wifi(wifi) => &wifi.password,
+            EntryKind::SecureNote | EntryKind::Identity(_) => return None,
+        };
+        Some(secret.as_str()).filter(|secret| !secret.is_empty())
+    }
+
+    /// Checks the fields a frontend must reject before saving. Empty optional
+    /// fields are always accepted; only malformed values are reported.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, field: &'static str, message: &'static str| {
+            if !ok {
+                errors.push(ValidationError { field, message });
+            }
+        };
+
+        check(!self.title.trim().is_empty(), "Title", "is required");
+
+        match &self.kind {
+            EntryKind::Login(login) => {
+                check(
+                    login
+                        .urls
+                        .iter()
+                        .all(|url| !url.trim().contains(char::is_whitespace)),
+                    "URLs",
+                    "must not contain spaces",
+                );
+                check(
+                    login.password_rules.trim().is_empty()
+                        || PasswordRules::parse(&login.password_rules).is_ok(),
+                    "Password rules",
+                    "could not be parsed",
+                );
+            }
+            EntryKind::SecureNote => {
+                check(!self.notes.trim().is_empty(), "Note", "is required");
+            }
+            EntryKind::Card(card) => {
+                let digits: String = card
+                    .number
+                    .chars()
+                    .filter(|c| *c != ' ' && *c != '-')
+                    .collect();
+                check(
+                    digits.is_empty()
+                        || (digits.chars().all(|c| c.is_ascii_digit())
+                            && (12..=19).contains(&digits.len())
+                            && luhn_valid(&digits)),
+                    "Card number",
+                    "is not a valid card number",
+                );
+                check(
+                    card.expiry_month.is_empty()
+                        || card
+                            .expiry_month
+                            .parse::<u8>()
+                            .is_ok_and(|month| (1..=12).contains(&month)),
+                    "Expiry month",
+                    "must be between 1 and 12",
+                );
+                check(
+                    card.expiry_year.is_empty()
+                        || (matches!(card.expiry_year.len(), 2 | 4)
+                            && card.expiry_year.chars().all(|c| c.is_ascii_digit())),
+                    "Expiry year",
+                    "must have 2 or 4 digits",
+                );
+                check(
+                    card.security_code.is_empty()
+                        || (matches!(card.security_code.len(), 3 | 4)
+                            && card.security_code.chars().all(|c| c.is_ascii_digit())),
+                    "Security code",
+                    "must have 3 or 4 digits",
+                );
+            }
+            EntryKind::Identity(identity) => {
+                check(
+                    identity.email.is_empty() || identity.email.contains('@'),
+                    "Email",
+                    "is not a valid email address",
+                );
+            }
+            EntryKind::Wifi(wifi) => {
+                check(!wifi.ssid.is_empty(), "SSID", "is required");
+                check(wifi.ssid.len() <= 32, "SSID", "must be at most 32 bytes");
+                let password_len = wifi.password.chars().count();
+                check(
+                    !matches!(
+                        wifi.security,
+                        WifiSecurity::WpaPersonal
+                            | WifiSecurity::Wpa2Personal
+                            | WifiSecurity::Wpa3Personal
+                    ) || (8..=63).contains(&password_len),
+                    "Wi-Fi password",
+                    "must be 8 to 63 characters for WPA networks",
+                );
+            }
+        }
+
+        check(
+            self.custom_fields
+                .iter()
+                .all(|field| !field.name.trim().is_empty()),
+            "Custom fields",
+            "every field needs a name",
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Luhn checksum used by payment card numbers.
+fn luhn_valid(digits: &str) -> bool {
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(index, digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+impl EntryKind {
+    /// One empty value of every kind, in display order.
+    pub fn templates() -> [Self; 5] {
+        [
+            Self::Login(Login::default()),
+            Self::SecureNote,
+            Self::Card(Card::default()),
+            Self::Identity(Identity::default()),
+            Self::Wifi(Wifi::default()),
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Login(_) => "Login",
+            Self::SecureNote => "Secure note",
+            Self::Card(_) => "Payment card",
+            Self::Identity(_) => "Identity",
+            Self::Wifi(_) => "Wi-Fi",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(username: &str) -> EntryKind {
+        EntryKind::Login(Login {
+            username: username.to_string(),
+            password: "hunter2".to_string(),
+            urls: vec!["https://mail.example.com".to_string()],
+            ..Login::default()
+        })
+    }
+
+    #[test]
+    fn new_entries_get_unique_ids_and_matching_timestamps() {
+        let first = Entry::new("Email", login("user@example.com"));
+        let second = Entry::new("Email", login("user@example.com"));
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.created_at, first.modified_at);
+    }
+
+    #[test]
+    fn every_kind_round_trips_through_json() {
+        for kind in EntryKind::templates() {
+            let mut entry = Entry::new("Item", kind);
+            entry.tags.push("work".to_string());
+            entry.custom_fields.push(CustomField {
+                name: "PIN".to_string(),
+                value: "1234".to_string(),
+                hidden: true,
+            });
+            entry.attachments.push(Attachment {
+                name: "key.pem".to_string(),
+                data: vec![0, 1, 254, 255],
+            });
+            entry.history.push(entry.clone());
+
+            let json = serde_json::to_string(&entry).unwrap();
+            assert_eq!(serde_json::from_str::<Entry>(&json).unwrap(), entry);
+        }
+    }
+
+    #[test]
+    fn kind_is_tagged_in_json() {
+        let entry = Entry::new("Home", EntryKind::Wifi(Wifi::default()));
+        let value = serde_json::to_value(&entry).unwrap();
+
+        assert_eq!(value["kind"]["type"], "wifi");
+        assert_eq!(value["kind"]["security"], "wpa2_personal");
+        assert!(value["attachments"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn validation_requires_title_and_kind_essentials() {
+        let mut note = Entry::new(" ", EntryKind::SecureNote);
+        let fields: Vec<_> = note
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["Title", "Note"]);
+
+        note.title = "Codes".to_string();
+        note.notes = "1234".to_string();
+        assert!(note.validate().is_ok());
+    }
+
+    #[test]
+    fn validation_checks_card_fields() {
+        let mut card = Card {
+            number: "4111 1111 1111 1111".to_string(),
+            expiry_month: "12".to_string(),
+            expiry_year: "2030".to_string(),
+            security_code: "123".to_string(),
+            ..Card::default()
+        };
+        assert!(Entry::new("Visa", EntryKind::Card(card.clone()))
+            .validate()
+            .is_ok());
+
+        card.number = "4111 1111 1111 1112".to_string();
+        card.expiry_month = "13".to_string();
+        let fields: Vec<_> = Entry::new("Visa", EntryKind::Card(card))
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["Card number", "Expiry month"]);
+    }
+
+    #[test]
+    fn validation_enforces_wpa_password_length() {
+        let mut wifi = Wifi {
+            ssid: "Home".to_string(),
+            password: "short".to_string(),
+            ..Wifi::default()
+        };
+        assert!(Entry::new("Home", EntryKind::Wifi(wifi.clone()))
+            .validate()
+            .is_err());
+
+        wifi.security = WifiSecurity::Open;
+        assert!(Entry::new("Home", EntryKind::Wifi(wifi)).validate().is_ok());
+    }
+
+    #[test]
+    fn login_password_rules_are_optional_and_validated() {
+        let json = r#"{"type":"login","username":"a","password":"b","urls":[]}"#;
+        let login: EntryKind = serde_json::from_str(json).unwrap();
+        let mut entry = Entry::new("Bank", login);
+        assert!(entry.validate().is_ok());
+
+        if let EntryKind::Login(login) = &mut entry.kind {
+            login.password_rules = "required: upper; minlength: eight".to_string();
+        }
+        let fields: Vec<_> = entry
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, ["Password rules"]);
+    }
+
+    #[test]
+    fn subtitle_summarizes_each_kind() {
+        assert_eq!(
+            Entry::new("Email", login("user@example.com")).subtitle(),
+            "user@example.com"
+        );
+
+        let card = Entry::new(
+            "Visa",
+            EntryKind::Card(Card {
+                number: "4111 1111 1111 1234".to_string(),
+                ..Card::default()
+            }),
+        );
+        assert_eq!(card.subtitle(), "•••• 1234");
+
+        let mut note = Entry::new("Recovery codes", EntryKind::SecureNote);
+        note.notes = "first line\nsecond line".to_string();
+        assert_eq!(note.subtitle(), "first line");
+    }
+
+    #[test]
+    fn primary_secret_picks_the_kind_password() {
+        assert_eq!(
+            Entry::new("Email", login("user@example.com")).primary_secret(),
+            Some("hunter2")
+        );
+        let open_wifi = Entry::new("Cafe", EntryKind::Wifi(Wifi::default()));
+        assert_eq!(open_wifi.primary_secret(), None);
+        let note = Entry::new("Codes", EntryKind::SecureNote);
+        assert_eq!(note.primary_secret(), None);
+    }
+
+    #[test]
+    fn username_picks_the_account_name() {
+        assert_eq!(
+            Entry::new("Email", login("user@example.com")).username(),
+            Some("user@example.com")
+        );
+        let wifi = Entry::new(
+            "Home",
+            EntryKind::Wifi(Wifi {
+                ssid: "home-net".to_string(),
+                ..Wifi::default()
+            }),
+        );
+        assert_eq!(wifi.username(), Some("home-net"));
+        assert_eq!(Entry::new("Anon", login("")).username(), None);
+    }
+}
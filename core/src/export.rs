@@ -0,0 +1,346 @@
+//! Lilypad's own plaintext interchange format: every entry as JSON, wrapped
+//! with a format tag and version so imports can tell it apart from other
+//! managers' exports. Entries can also be written as CSV for spreadsheets
+//! and other managers, which loses attachments, history, and custom fields.
+//!
+//! Both outputs hold every secret in the clear. Frontends must say so before
+//! writing them anywhere.
+
+use crate::clock;
+use crate::entry::{Entry, EntryKind};
+use crate::secret::Zeroizing;
+use crate::vault::Vault;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Value of the `format` field of an export file.
+pub const EXPORT_FORMAT: &str = "lilypad-export";
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Json(serde_json::Error),
+    /// The file is JSON but not a Lilypad export.
+    NotAnExport,
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "export file is not valid: {error}"),
+            Self::NotAnExport => write!(f, "file is not a Lilypad export"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "export version {version} is newer than this build supports"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportFile {
+    format: String,
+    version: u32,
+    /// Unix timestamp (seconds, UTC).
+    exported_at: u64,
+    entries: Vec<Entry>,
+}
+
+impl Drop for ExportFile {
+    fn drop(&mut self) {
+        self.entries.zeroize();
+    }
+}
+
+/// What [`import_entries`] did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    /// Entries whose id is already in the vault.
+    pub skipped: usize,
+}
+
+/// Serializes `entries` as a pretty-printed export document.
+pub fn to_json(entries: &[Entry]) -> Result<Zeroizing<String>, ExportError> {
+    let file = ExportFile {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: clock::now(),
+        entries: entries.to_vec(),
+    };
+    Ok(Zeroizing::new(serde_json::to_string_pretty(&file)?))
+}
+
+/// Column names of [`to_csv`]; Lilypad's CSV import recognizes each of them.
+pub const CSV_HEADERS: [&str; 9] = [
+    "title", "type", "url", "username", "password", "totp", "notes", "folder", "tags",
+];
+
+/// Writes one row per entry under [`CSV_HEADERS`]. Logins fill the login
+/// columns; the details of cards, identities, and Wi-Fi networks, and any
+/// website after the first, are appended to the notes as `Label: value`
+/// lines so nothing visible is lost.
+pub fn to_csv(entries: &[Entry]) -> Zeroizing<String> {
+    // Writing into a `Vec` cannot fail.
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_HEADERS).expect("writing to memory");
+    for entry in entries {
+        let empty = String::new();
+        let (url, username, password, totp) = match &entry.kind {
+            EntryKind::Login(login) => (
+                login.urls.first().unwrap_or(&empty),
+                &login.username,
+                &login.password,
+                &login.totp,
+            ),
+            _ => (&empty, &empty, &empty, &empty),
+        };
+        let mut notes = Zeroizing::new(entry.notes.clone());
+        append_details(&entry.kind, &mut notes);
+        let tags = entry.tags.join(",");
+        writer
+            .write_record([
+                entry.title.as_str(),
+                entry.kind.label(),
+                url,
+                username,
+                password,
+                totp,
+                &notes,
+                &entry.folder,
+                &tags,
+            ])
+            .expect("writing to memory");
+    }
+    let bytes = Zeroizing::new(writer.into_inner().expect("writing to memory"));
+    Zeroizing::new(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Adds the fields of `kind` that have no CSV column of their own to
+/// `notes`, one `Label: value` line each.
+fn append_details(kind: &EntryKind, notes: &mut String) {
+    let mut add = |label: &str, value: &str| {
+        if value.is_empty() {
+            return;
+        }
+        if !notes.is_empty() {
+            notes.push('\n');
+        }
+        notes.push_str(label);
+        notes.push_str(": ");
+        notes.push_str(value);
+    };
+    match kind {
+        EntryKind::Login(login) => {
+            for url in login.urls.iter().skip(1) {
+                add("Website", url);
+            }
+        }
+        EntryKind::SecureNote => {}
+        EntryKind::Card(card) => {
+            add("Cardholder", &card.cardholder_name);
+            add("Brand", &card.brand);
+            add("Number", &card.number);
+            add("Expiry month", &card.expiry_month);
+            add("Expiry year", &card.expiry_year);
+            add("Security code", &card.security_code);
+        }
+        EntryKind::Identity(identity) => {
+            add("First name", &identity.first_name);
+            add("Last name", &identity.last_name);
+            add("Email", &identity.email);
+            add("Phone", &identity.phone);
+            add("Company", &identity.company);
+            add("Address", &identity.address);
+            add("City", &identity.city);
+            add("State", &identity.state);
+            add("Postal code", &identity.postal_code);
+            add("Country", &identity.country);
+        }
+        EntryKind::Wifi(wifi) => {
+            add("SSID", &wifi.ssid);
+            add("Password", &wifi.password);
+            add("Security", wifi.security.label());
+        }
+    }
+}
+
+/// Parses an export document produced by [`to_json`].
+pub fn from_json(text: &str) -> Result<Vec<Entry>, ExportError> {
+    let mut file: ExportFile = serde_json::from_str(text).map_err(|error| {
+        if error.is_data() {
+            ExportError::NotAnExport
+        } else {
+            ExportError::Json(error)
+        }
+    })?;
+    if file.format != EXPORT_FORMAT {
+        return Err(ExportError::NotAnExport);
+    }
+    if file.version > EXPORT_VERSION {
+        return Err(ExportError::UnsupportedVersion(file.version));
+    }
+    Ok(std::mem::take(&mut file.entries))
+}
+
+/// What [`import_entries`] would do, without changing the vault; for a
+/// preview before importing.
+pub fn preview_import(vault: &Vault, entries: &[Entry]) -> ImportSummary {
+    let mut known: HashSet<_> = vault.entries().iter().map(|entry| entry.id).collect();
+    let added = entries
+        .iter()
+        .filter(|entry| known.insert(entry.id))
+        .count();
+    ImportSummary {
+        added,
+        skipped: entries.len() - added,
+    }
+}
+
+/// Adds `entries` to `vault` in memory, skipping any whose id it already
+/// holds so importing the same file twice changes nothing. Call
+/// [`Vault::save`] afterwards.
+pub fn import_entries(vault: &mut Vault, entries: Vec<Entry>) -> ImportSummary {
+    let mut known: HashSet<_> = vault.entries().iter().map(|entry| entry.id).collect();
+    let mut summary = ImportSummary::default();
+    for entry in entries {
+        if known.insert(entry.id) {
+            vault.add_entry(entry);
+            summary.added += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KdfParams;
+    use crate::entry::{EntryKind, Login};
+
+    /// Cheap parameters so tests do not pay for a production-strength KDF.
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 16 * 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn round_trips_entries() {
+        let mut entry = Entry::new(
+            "Mail",
+            EntryKind::Login(Login {
+                username: "me".to_string(),
+                password: "hunter2".to_string(),
+                ..Login::default()
+            }),
+        );
+        entry.tags.push("work".to_string());
+        let entries = vec![entry, Entry::new("Codes", EntryKind::SecureNote)];
+
+        let json = to_json(&entries).unwrap();
+        assert!(json.contains(EXPORT_FORMAT));
+        assert_eq!(from_json(&json).unwrap(), entries);
+    }
+
+    #[test]
+    fn csv_reads_back_through_the_csv_import() {
+        let mut login = Entry::new(
+            "Mail",
+            EntryKind::Login(Login {
+                username: "me".to_string(),
+                password: "hunter2, \"quoted\"".to_string(),
+                urls: vec![
+                    "https://mail.example.com".to_string(),
+                    "https://webmail.example.com".to_string(),
+                ],
+                ..Login::default()
+            }),
+        );
+        login.folder = "Work/Mail".to_string();
+        login.tags = vec!["work".to_string(), "mail".to_string()];
+        let mut card = Entry::new(
+            "Visa",
+            EntryKind::Card(crate::entry::Card {
+                number: "4111111111111111".to_string(),
+                ..Default::default()
+            }),
+        );
+        card.notes = "Backup card".to_string();
+
+        let text = to_csv(&[login, card]);
+        let table = crate::csv_import::read(&text).unwrap();
+        let mapping = crate::csv_import::Mapping::guess(&table);
+        let import = crate::csv_import::convert(&table, &mapping, &[]);
+        assert!(import.failed.is_empty());
+        let [mail, visa] = import.entries.as_slice() else {
+            panic!("expected two entries, got {:?}", import.entries);
+        };
+        let EntryKind::Login(read) = &mail.kind else {
+            panic!("expected a login");
+        };
+        assert_eq!(read.password, "hunter2, \"quoted\"");
+        assert_eq!(read.urls, ["https://mail.example.com"]);
+        assert_eq!(mail.notes, "Website: https://webmail.example.com");
+        assert_eq!((mail.folder.as_str(), mail.tags.len()), ("Work/Mail", 2));
+        assert_eq!(visa.notes, "Backup card\nNumber: 4111111111111111");
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(matches!(
+            from_json("{\"items\": []}"),
+            Err(ExportError::NotAnExport)
+        ));
+        assert!(matches!(
+            from_json(
+                "{\"format\": \"other\", \"version\": 1, \"exported_at\": 0, \"entries\": []}"
+            ),
+            Err(ExportError::NotAnExport)
+        ));
+        assert!(matches!(
+            from_json(&format!(
+                "{{\"format\": \"{EXPORT_FORMAT}\", \"version\": 9, \"exported_at\": 0, \"entries\": []}}"
+            )),
+            Err(ExportError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(from_json("not json"), Err(ExportError::Json(_))));
+    }
+
+    #[test]
+    fn import_skips_entries_already_present() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let mut vault = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        let existing = Entry::new("Existing", EntryKind::SecureNote);
+        vault.add_entry(existing.clone());
+
+        let entries = vec![existing, Entry::new("New", EntryKind::SecureNote)];
+        let preview = preview_import(&vault, &entries);
+        assert_eq!(vault.entries().len(), 1);
+
+        let summary = import_entries(&mut vault, entries);
+        assert_eq!(preview, summary);
+        assert_eq!(
+            summary,
+            ImportSummary {
+                added: 1,
+                skipped: 1
+            }
+        );
+        assert_eq!(vault.entries().len(), 2);
+    }
+}
@@ -0,0 +1,950 @@
+//! Random password generation.
+
+use crate::passphrase::PassphraseGenerator;
+use crate::secret::{self, SecretBuffer, Zeroizing};
+use crate::strength::Strength;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const DIGITS: &str = "0123456789";
+pub const SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+/// Characters that are easily mistaken for one another in common fonts.
+pub const AMBIGUOUS: &str = "Il1|O0o";
+
+/// Candidates drawn before giving up on a run limit that is technically
+/// possible but vanishingly unlikely to be met.
+const MAX_ATTEMPTS: usize = 100_000;
+
+/// Most distinct required sets a generator accepts. Building a password
+/// weighs every combination of sets a character can cover, which doubles
+/// with each set.
+pub const MAX_REQUIRED_SETS: usize = 8;
+
+/// The class a character counts toward for minimum-count constraints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    pub fn of(c: char) -> Self {
+        if c.is_ascii_lowercase() {
+            Self::Lowercase
+        } else if c.is_ascii_uppercase() {
+            Self::Uppercase
+        } else if c.is_ascii_digit() {
+            Self::Digit
+        } else {
+            Self::Symbol
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Lowercase => "lowercase letters",
+            Self::Uppercase => "uppercase letters",
+            Self::Digit => "digits",
+            Self::Symbol => "symbols",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorError {
+    NoCharacterClasses,
+    NoWords,
+    /// A minimum count is set for a class whose characters are all excluded.
+    ClassUnavailable(CharClass),
+    MinimumsExceedLength,
+    /// A required character set has no characters left in the pool.
+    RequiredSetUnavailable,
+    /// More than [`MAX_REQUIRED_SETS`] required sets remain after dropping
+    /// duplicates and sets that contain another.
+    TooManyRequiredSets,
+    /// No password meeting every constraint was found.
+    Unsatisfiable,
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCharacterClasses => write!(f, "enable at least one character class"),
+            Self::NoWords => write!(f, "a passphrase needs at least one word"),
+            Self::ClassUnavailable(class) => {
+                write!(
+                    f,
+                    "a minimum is set for {} but all of them are excluded",
+                    class.label()
+                )
+            }
+            Self::MinimumsExceedLength => {
+                write!(f, "the minimum counts add up to more than the length")
+            }
+            Self::RequiredSetUnavailable => {
+                write!(f, "a required character set has no usable characters")
+            }
+            Self::TooManyRequiredSets => write!(
+                f,
+                "at most {MAX_REQUIRED_SETS} different required character sets are supported"
+            ),
+            Self::Unsatisfiable => write!(f, "these constraints are too strict to satisfy"),
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {}
+
+/// Which generator the user picked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratorMode {
+    #[default]
+    Characters,
+    Passphrase,
+}
+
+/// Both generators plus the selected mode, so frontends can offer one
+/// "generate" action that honors whatever the user configured.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GeneratorSettings {
+    pub mode: GeneratorMode,
+    pub password: PasswordGenerator,
+    pub passphrase: PassphraseGenerator,
+}
+
+impl GeneratorSettings {
+    pub fn generate(&self) -> Result<SecretBuffer, GeneratorError> {
+        match self.mode {
+            GeneratorMode::Characters => self.password.generate(),
+            GeneratorMode::Passphrase => self.passphrase.generate(),
+        }
+    }
+
+    pub fn can_generate(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Explains why the selected generator cannot produce anything, if so.
+    pub fn check(&self) -> Result<(), GeneratorError> {
+        match self.mode {
+            GeneratorMode::Characters => self.password.check(),
+            GeneratorMode::Passphrase if self.passphrase.word_count == 0 => {
+                Err(GeneratorError::NoWords)
+            }
+            GeneratorMode::Passphrase => Ok(()),
+        }
+    }
+
+    pub fn entropy_bits(&self) -> f64 {
+        match self.mode {
+            GeneratorMode::Characters => self.password.entropy_bits(),
+            GeneratorMode::Passphrase => self.passphrase.entropy_bits(),
+        }
+    }
+
+    /// Rates the settings by the entropy of what they produce. Generated
+    /// secrets are uniformly random, so this is exact rather than estimated.
+    pub fn strength(&self) -> Strength {
+        Strength::from_entropy_bits(self.entropy_bits())
+    }
+}
+
+/// Settings for generating random character passwords.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordGenerator {
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Leave out characters that are easy to confuse, see [`AMBIGUOUS`].
+    pub exclude_ambiguous: bool,
+    /// Extra characters added to the pool on top of the enabled classes.
+    pub include_chars: String,
+    /// Characters never used, even when their class is enabled.
+    pub exclude_chars: String,
+    pub min_lowercase: usize,
+    pub min_uppercase: usize,
+    pub min_digits: usize,
+    pub min_symbols: usize,
+    /// Longest run of one repeated character; `Some(1)` forbids any repeat.
+    pub max_consecutive: Option<usize>,
+    /// Sets that must each contribute at least one character, e.g. from a
+    /// site's `required: [-_]` password rule.
+    pub required_sets: Vec<String>,
+}
+
+impl Default for PasswordGenerator {
+    fn default() -> Self {
+        Self {
+            length: 16,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            exclude_ambiguous: false,
+            include_chars: String::new(),
+            exclude_chars: String::new(),
+            min_lowercase: 0,
+            min_uppercase: 0,
+            min_digits: 0,
+            min_symbols: 0,
+            max_consecutive: None,
+            required_sets: Vec::new(),
+        }
+    }
+}
+
+impl PasswordGenerator {
+    /// Returns `true` when the constraints can be met.
+    pub fn can_generate(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Checks the constraints for contradictions without generating anything.
+    pub fn check(&self) -> Result<(), GeneratorError> {
+        let charset = self.charset();
+        if charset.is_empty() {
+            return Err(GeneratorError::NoCharacterClasses);
+        }
+        if let Some(max_run) = self.max_consecutive {
+            if max_run == 0 || (charset.len() == 1 && self.length > max_run) {
+                return Err(GeneratorError::Unsatisfiable);
+            }
+        }
+        let required_sets = self.reduced_required_sets(&charset);
+        if required_sets.iter().any(Vec::is_empty) {
+            return Err(GeneratorError::RequiredSetUnavailable);
+        }
+        if required_sets.len() > MAX_REQUIRED_SETS {
+            return Err(GeneratorError::TooManyRequiredSets);
+        }
+
+        let mut required = 0;
+        for (class, minimum) in self.minimums() {
+            if minimum > 0 && !charset.iter().any(|&c| CharClass::of(c) == class) {
+                return Err(GeneratorError::ClassUnavailable(class));
+            }
+            required += minimum;
+        }
+        if required > self.length {
+            return Err(GeneratorError::MinimumsExceedLength);
+        }
+        Ok(())
+    }
+
+    /// Generates a password that meets every constraint, chosen uniformly
+    /// among all passwords that do.
+    ///
+    /// How many characters come from each constrained class and required
+    /// set is drawn first, weighted by how many passwords share those
+    /// counts; the characters are then picked and shuffled into place, so
+    /// minimum counts and required sets hold by construction. Run limits
+    /// are left to rejection: a candidate with a longer run is drawn again.
+    /// Without other constraints, a limit of 1 is met directly by never
+    /// repeating the previous character.
+    pub fn generate(&self) -> Result<SecretBuffer, GeneratorError> {
+        self.check()?;
+
+        let charset = self.charset();
+        let mut rng = rand::thread_rng();
+        if self.max_consecutive == Some(1)
+            && self.minimums().is_empty()
+            && self.required_sets.is_empty()
+        {
+            let password = Zeroizing::new(self.sample_without_repeats(&charset, &mut rng));
+            return Ok(to_buffer(&password));
+        }
+
+        let required_sets = self.reduced_required_sets(&charset);
+        let layout =
+            Layout::new(self, &charset, &required_sets).ok_or(GeneratorError::Unsatisfiable)?;
+        for _ in 0..MAX_ATTEMPTS {
+            let candidate = Zeroizing::new(layout.sample(&mut rng));
+            if self
+                .max_consecutive
+                .is_none_or(|max_run| longest_run(&candidate) <= max_run)
+            {
+                return Ok(to_buffer(&candidate));
+            }
+        }
+        Err(GeneratorError::Unsatisfiable)
+    }
+
+    /// The required sets limited to `charset`, without duplicates and
+    /// without sets that contain another one, since meeting the smaller set
+    /// meets those too.
+    fn reduced_required_sets(&self, charset: &[char]) -> Vec<Vec<char>> {
+        let sets: Vec<Vec<char>> = self
+            .required_sets
+            .iter()
+            .map(|set| {
+                let mut chars: Vec<char> = set.chars().filter(|c| charset.contains(c)).collect();
+                chars.sort_unstable();
+                chars.dedup();
+                chars
+            })
+            .collect();
+        let contains = |set: &[char], other: &[char]| other.iter().all(|c| set.contains(c));
+        sets.iter()
+            .enumerate()
+            .filter(|&(index, set)| {
+                !sets.iter().enumerate().any(|(other_index, other)| {
+                    contains(set, other) && (other.len() < set.len() || other_index < index)
+                })
+            })
+            .map(|(_, set)| set.clone())
+            .collect()
+    }
+
+    /// Entropy in bits of the passwords these settings produce:
+    /// `length × log2(pool size)`, or `log2(n) + (length − 1) × log2(n − 1)`
+    /// without repeats. Minimum counts, required sets, and longer run limits
+    /// remove a few candidates, so with them this is a slight upper bound.
+    pub fn entropy_bits(&self) -> f64 {
+        let pool = self.charset().len() as f64;
+        if pool == 0.0 || self.length == 0 {
+            return 0.0;
+        }
+        if self.max_consecutive == Some(1) {
+            if pool < 2.0 {
+                return 0.0;
+            }
+            pool.log2() + (self.length - 1) as f64 * (pool - 1.0).log2()
+        } else {
+            self.length as f64 * pool.log2()
+        }
+    }
+
+    /// The de-duplicated character pool after inclusions and exclusions.
+    pub fn charset(&self) -> Vec<char> {
+        let mut charset: Vec<char> = Vec::new();
+        let classes = [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SYMBOLS),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .flat_map(|(_, class)| class.chars());
+
+        for c in classes.chain(self.include_chars.chars()) {
+            let excluded = self.exclude_chars.contains(c)
+                || (self.exclude_ambiguous && AMBIGUOUS.contains(c))
+                || c.is_control();
+            if !excluded && !charset.contains(&c) {
+                charset.push(c);
+            }
+        }
+        charset
+    }
+
+    /// Minimum counts for the enabled classes; disabled classes impose none.
+    fn minimums(&self) -> Vec<(CharClass, usize)> {
+        [
+            (self.lowercase, CharClass::Lowercase, self.min_lowercase),
+            (self.uppercase, CharClass::Uppercase, self.min_uppercase),
+            (self.digits, CharClass::Digit, self.min_digits),
+            (self.symbols, CharClass::Symbol, self.min_symbols),
+        ]
+        .into_iter()
+        .filter(|(enabled, _, minimum)| *enabled && *minimum > 0)
+        .map(|(_, class, minimum)| (class, minimum))
+        .collect()
+    }
+
+    /// Draws each character from the pool minus the previous character.
+    fn sample_without_repeats(&self, charset: &[char], rng: &mut impl Rng) -> Vec<char> {
+        let mut password = Vec::with_capacity(self.length);
+        let mut previous: Option<usize> = None;
+        for _ in 0..self.length {
+            let index = match previous {
+                Some(previous) => {
+                    let index = rng.gen_range(0..charset.len() - 1);
+                    if index >= previous {
+                        index + 1
+                    } else {
+                        index
+                    }
+                }
+                None => rng.gen_range(0..charset.len()),
+            };
+            password.push(charset[index]);
+            previous = Some(index);
+        }
+        password
+    }
+}
+
+fn to_buffer(password: &[char]) -> SecretBuffer {
+    let mut buffer = secret::secret_buffer();
+    buffer.extend(password.iter());
+    buffer
+}
+
+/// Natural-log weights keyed by the required sets hit (a bit mask), each
+/// indexed by the number of characters used.
+type Table = BTreeMap<usize, Vec<f64>>;
+
+/// How a password's characters can be split over the pool, for drawing
+/// passwords that meet minimum counts and required sets directly.
+///
+/// The pool is cut into atoms: characters that count toward the same
+/// minimum and belong to the same required sets. Taking `c` characters from
+/// an atom of `n` weighs `n^c / c!`, so the weight of a split is
+/// proportional to the number of passwords with it. Weights are kept as
+/// logarithms, since they span hundreds of orders of magnitude.
+struct Layout {
+    length: usize,
+    /// Every required set hit.
+    full_mask: usize,
+    /// One split per minimum-count class, then one for the other characters.
+    groups: Split,
+    /// The atoms of each group and how its characters split over them.
+    atoms: Vec<(Vec<Vec<char>>, Split)>,
+}
+
+/// Tables to pick one entry from each of, with their suffix convolutions.
+struct Split {
+    tables: Vec<Table>,
+    /// `suffix[i]` combines `tables[i..]`.
+    suffix: Vec<Table>,
+}
+
+impl Layout {
+    /// `None` when no password meets the minimums and required sets together.
+    fn new(
+        generator: &PasswordGenerator,
+        charset: &[char],
+        required_sets: &[Vec<char>],
+    ) -> Option<Self> {
+        let length = generator.length;
+        let full_mask = 1usize
+            .checked_shl(u32::try_from(required_sets.len()).ok()?)?
+            .wrapping_sub(1);
+        let minimums = generator.minimums();
+        let mut members: Vec<(usize, BTreeMap<usize, Vec<char>>)> = minimums
+            .iter()
+            .map(|&(_, minimum)| (minimum, BTreeMap::new()))
+            .collect();
+        members.push((0, BTreeMap::new()));
+        for &c in charset {
+            let group = minimums
+                .iter()
+                .position(|&(class, _)| CharClass::of(c) == class)
+                .unwrap_or(minimums.len());
+            let mask = required_sets
+                .iter()
+                .enumerate()
+                .filter(|(_, set)| set.contains(&c))
+                .fold(0, |mask, (index, _)| mask | 1 << index);
+            members[group].1.entry(mask).or_default().push(c);
+        }
+
+        let ln_factorial: Vec<f64> = std::iter::once(0.0)
+            .chain((1..=length).scan(0.0, |sum, n| {
+                *sum += (n as f64).ln();
+                Some(*sum)
+            }))
+            .collect();
+        let mut group_tables = Vec::new();
+        let mut atoms = Vec::new();
+        for (minimum, by_mask) in members {
+            let tables: Vec<Table> = by_mask
+                .iter()
+                .map(|(&mask, chars)| atom_table(chars.len(), mask, length, &ln_factorial))
+                .collect();
+            let split = Split::new(tables, length);
+            let mut table = split.suffix[0].clone();
+            for row in table.values_mut() {
+                row[..minimum.min(length + 1)].fill(f64::NEG_INFINITY);
+            }
+            table.retain(|_, row| row.iter().any(|weight| weight.is_finite()));
+            group_tables.push(table);
+            atoms.push((by_mask.into_values().collect(), split));
+        }
+        let groups = Split::new(group_tables, length);
+        let total = groups.suffix[0].get(&full_mask)?[length];
+        total.is_finite().then_some(Self {
+            length,
+            full_mask,
+            groups,
+            atoms,
+        })
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> Vec<char> {
+        let mut password = Vec::with_capacity(self.length);
+        let picks = self.groups.sample(self.full_mask, self.length, rng);
+        for ((mask, count), (chars, split)) in picks.into_iter().zip(&self.atoms) {
+            for ((_, count), atom) in split.sample(mask, count, rng).into_iter().zip(chars) {
+                password.extend((0..count).map(|_| atom[rng.gen_range(0..atom.len())]));
+            }
+        }
+        password.shuffle(rng);
+        password
+    }
+}
+
+impl Split {
+    fn new(tables: Vec<Table>, length: usize) -> Self {
+        let mut suffix = vec![unit_table(length)];
+        for table in tables.iter().rev() {
+            let next = convolve(table, &suffix[suffix.len() - 1], length);
+            suffix.push(next);
+        }
+        suffix.reverse();
+        Self { tables, suffix }
+    }
+
+    /// Picks a `(mask, count)` entry from each table so the masks together
+    /// cover exactly `target` and the counts sum to `total`, with odds
+    /// proportional to the product of the entries' weights.
+    fn sample(&self, target: usize, total: usize, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        let mut covered = 0;
+        let mut remaining = total;
+        let mut picks = Vec::with_capacity(self.tables.len());
+        for (table, rest) in self.tables.iter().zip(&self.suffix[1..]) {
+            let mut candidates = Vec::new();
+            for (&mask, row) in table {
+                for (count, &weight) in row.iter().enumerate().take(remaining + 1) {
+                    let tail = rest
+                        .iter()
+                        .filter(|(&other, _)| covered | mask | other == target)
+                        .map(|(_, row)| row[remaining - count])
+                        .fold(f64::NEG_INFINITY, log_add);
+                    if (weight + tail).is_finite() {
+                        candidates.push((mask, count, weight + tail));
+                    }
+                }
+            }
+            let top = candidates
+                .iter()
+                .map(|&(_, _, weight)| weight)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let sum: f64 = candidates
+                .iter()
+                .map(|&(_, _, weight)| (weight - top).exp())
+                .sum();
+            let mut point = rng.gen::<f64>() * sum;
+            let mut pick = candidates[candidates.len() - 1];
+            for &candidate in &candidates {
+                point -= (candidate.2 - top).exp();
+                if point < 0.0 {
+                    pick = candidate;
+                    break;
+                }
+            }
+            covered |= pick.0;
+            remaining -= pick.1;
+            picks.push((pick.0, pick.1));
+        }
+        picks
+    }
+}
+
+/// Nothing used and no set hit.
+fn unit_table(length: usize) -> Table {
+    let mut row = vec![f64::NEG_INFINITY; length + 1];
+    row[0] = 0.0;
+    Table::from([(0, row)])
+}
+
+/// Weights of taking 0 to `length` characters from an atom of `size`.
+fn atom_table(size: usize, mask: usize, length: usize, ln_factorial: &[f64]) -> Table {
+    let ln_size = (size as f64).ln();
+    let row: Vec<f64> = (0..=length)
+        .map(|count| count as f64 * ln_size - ln_factorial[count])
+        .collect();
+    if mask == 0 {
+        return Table::from([(0, row)]);
+    }
+    let mut some = row;
+    some[0] = f64::NEG_INFINITY;
+    let mut table = unit_table(length);
+    table.insert(mask, some);
+    table
+}
+
+/// The weights of using both `a` and `b`: masks are combined and counts
+/// added, up to `length`.
+fn convolve(a: &Table, b: &Table, length: usize) -> Table {
+    let mut combined = Table::new();
+    for (&mask_a, row_a) in a {
+        for (&mask_b, row_b) in b {
+            let row = combined
+                .entry(mask_a | mask_b)
+                .or_insert_with(|| vec![f64::NEG_INFINITY; length + 1]);
+            for (i, &weight_a) in row_a.iter().enumerate() {
+                if weight_a == f64::NEG_INFINITY {
+                    continue;
+                }
+                for (j, &weight_b) in row_b[..=length - i].iter().enumerate() {
+                    if weight_b != f64::NEG_INFINITY {
+                        row[i + j] = log_add(row[i + j], weight_a + weight_b);
+                    }
+                }
+            }
+        }
+    }
+    combined
+}
+
+/// `ln(e^a + e^b)` without overflowing.
+fn log_add(a: f64, b: f64) -> f64 {
+    let (high, low) = if a >= b { (a, b) } else { (b, a) };
+    if low == f64::NEG_INFINITY {
+        return high;
+    }
+    high + (low - high).exp().ln_1p()
+}
+
+fn longest_run(password: &[char]) -> usize {
+    password
+        .chunk_by(|a, b| a == b)
+        .map(<[char]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_requested_length() {
+        let generator = PasswordGenerator {
+            length: 42,
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(generator.generate().unwrap().chars().count(), 42);
+    }
+
+    #[test]
+    fn only_uses_enabled_classes() {
+        let generator = PasswordGenerator {
+            length: 200,
+            lowercase: false,
+            uppercase: false,
+            digits: true,
+            symbols: false,
+            ..PasswordGenerator::default()
+        };
+        let password = generator.generate().unwrap();
+        assert!(password.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn rejects_empty_charset() {
+        let generator = PasswordGenerator {
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            ..PasswordGenerator::default()
+        };
+        assert!(!generator.can_generate());
+        assert_eq!(
+            generator.generate(),
+            Err(GeneratorError::NoCharacterClasses)
+        );
+    }
+
+    #[test]
+    fn entropy_reflects_charset_and_length() {
+        let digits_only = PasswordGenerator {
+            length: 10,
+            lowercase: false,
+            uppercase: false,
+            digits: true,
+            symbols: false,
+            ..PasswordGenerator::default()
+        };
+        assert!((digits_only.entropy_bits() - 33.219).abs() < 0.01);
+        assert!(PasswordGenerator::default().entropy_bits() > digits_only.entropy_bits());
+    }
+
+    #[test]
+    fn settings_dispatch_on_mode() {
+        let mut settings = GeneratorSettings::default();
+        assert_eq!(settings.generate().unwrap().chars().count(), 16);
+
+        settings.mode = GeneratorMode::Passphrase;
+        settings.passphrase.separator = " ".to_string();
+        let passphrase = settings.generate().unwrap();
+        assert_eq!(passphrase.split(' ').count(), 6);
+        assert_eq!(settings.entropy_bits(), settings.passphrase.entropy_bits());
+    }
+
+    #[test]
+    fn strength_follows_entropy_not_enabled_classes() {
+        let mut settings = GeneratorSettings::default();
+        settings.password.length = 6;
+        assert_eq!(settings.strength(), Strength::Weak);
+
+        settings.password.length = 24;
+        assert_eq!(settings.strength(), Strength::VeryStrong);
+
+        settings.mode = GeneratorMode::Passphrase;
+        assert_eq!(settings.strength(), Strength::Strong);
+    }
+
+    #[test]
+    fn excludes_ambiguous_and_listed_characters() {
+        let generator = PasswordGenerator {
+            length: 500,
+            exclude_ambiguous: true,
+            exclude_chars: "abc$".to_string(),
+            ..PasswordGenerator::default()
+        };
+        let password = generator.generate().unwrap();
+        assert!(!password
+            .chars()
+            .any(|c| AMBIGUOUS.contains(c) || "abc$".contains(c)));
+    }
+
+    #[test]
+    fn include_chars_extend_the_pool() {
+        let generator = PasswordGenerator {
+            length: 300,
+            lowercase: false,
+            uppercase: false,
+            digits: true,
+            symbols: false,
+            include_chars: "-_".to_string(),
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(generator.charset().len(), 12);
+        let password = generator.generate().unwrap();
+        assert!(password
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '-' || c == '_'));
+        assert!(password.contains('-') || password.contains('_'));
+    }
+
+    #[test]
+    fn meets_minimum_counts() {
+        let generator = PasswordGenerator {
+            length: 10,
+            min_lowercase: 2,
+            min_uppercase: 2,
+            min_digits: 3,
+            min_symbols: 3,
+            ..PasswordGenerator::default()
+        };
+        for _ in 0..50 {
+            let password = generator.generate().unwrap();
+            let count = |class| {
+                password
+                    .chars()
+                    .filter(|&c| CharClass::of(c) == class)
+                    .count()
+            };
+            assert_eq!(count(CharClass::Lowercase), 2);
+            assert_eq!(count(CharClass::Uppercase), 2);
+            assert_eq!(count(CharClass::Digit), 3);
+            assert_eq!(count(CharClass::Symbol), 3);
+        }
+    }
+
+    #[test]
+    fn meets_minimums_that_nearly_fill_the_length() {
+        let generator = PasswordGenerator {
+            length: 16,
+            min_digits: 12,
+            min_symbols: 3,
+            ..PasswordGenerator::default()
+        };
+        for _ in 0..200 {
+            let password = generator.generate().unwrap();
+            assert_eq!(password.chars().count(), 16);
+            assert!(password.chars().filter(char::is_ascii_digit).count() >= 12);
+            let symbols = password.chars().filter(|c| SYMBOLS.contains(*c)).count();
+            assert!(symbols >= 3);
+        }
+
+        // The one free character must also cover the required set.
+        let tight = PasswordGenerator {
+            length: 16,
+            min_digits: 15,
+            required_sets: vec!["-".to_string()],
+            ..PasswordGenerator::default()
+        };
+        for _ in 0..50 {
+            let password = tight.generate().unwrap();
+            assert_eq!(password.chars().filter(char::is_ascii_digit).count(), 15);
+            assert!(password.contains('-'));
+        }
+
+        let impossible = PasswordGenerator {
+            length: 16,
+            min_digits: 16,
+            required_sets: vec!["-".to_string()],
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(impossible.generate(), Err(GeneratorError::Unsatisfiable));
+    }
+
+    #[test]
+    fn tight_minimums_stay_uniform_over_positions() {
+        // 15 digits and one letter: the letter should land anywhere.
+        let generator = PasswordGenerator {
+            length: 16,
+            uppercase: false,
+            symbols: false,
+            min_digits: 15,
+            min_lowercase: 1,
+            ..PasswordGenerator::default()
+        };
+        let mut positions = [0usize; 16];
+        for _ in 0..3200 {
+            let password = generator.generate().unwrap();
+            let index = password
+                .chars()
+                .position(|c| c.is_ascii_lowercase())
+                .unwrap();
+            positions[index] += 1;
+        }
+        assert!(
+            positions.iter().all(|&count| (120..=280).contains(&count)),
+            "letter positions {positions:?}"
+        );
+    }
+
+    #[test]
+    fn never_repeats_consecutive_characters() {
+        let generator = PasswordGenerator {
+            length: 1000,
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            include_chars: "ab".to_string(),
+            max_consecutive: Some(1),
+            ..PasswordGenerator::default()
+        };
+        let password: Vec<char> = generator.generate().unwrap().chars().collect();
+        assert!(password.windows(2).all(|pair| pair[0] != pair[1]));
+        assert!((generator.entropy_bits() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reports_contradictory_constraints() {
+        let too_many = PasswordGenerator {
+            length: 8,
+            min_digits: 5,
+            min_symbols: 4,
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(too_many.check(), Err(GeneratorError::MinimumsExceedLength));
+
+        let excluded = PasswordGenerator {
+            min_digits: 1,
+            exclude_chars: DIGITS.to_string(),
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(
+            excluded.generate(),
+            Err(GeneratorError::ClassUnavailable(CharClass::Digit))
+        );
+
+        let single = PasswordGenerator {
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            include_chars: "x".to_string(),
+            max_consecutive: Some(1),
+            ..PasswordGenerator::default()
+        };
+        assert!(!single.can_generate());
+    }
+
+    #[test]
+    fn output_is_not_biased_toward_required_classes() {
+        // Digits are 10 of 94 characters; with at least one required in a
+        // 16-character password the share should stay near 10/94 ≈ 10.6 %,
+        // not climb the way forcing one digit into a fixed slot would.
+        let generator = PasswordGenerator {
+            min_digits: 1,
+            ..PasswordGenerator::default()
+        };
+        let mut first_char_digits = 0;
+        for _ in 0..4000 {
+            let password = generator.generate().unwrap();
+            if password.chars().next().unwrap().is_ascii_digit() {
+                first_char_digits += 1;
+            }
+        }
+        let share = first_char_digits as f64 / 4000.0;
+        assert!(
+            (0.08..0.16).contains(&share),
+            "first-character digit share {share}"
+        );
+    }
+
+    #[test]
+    fn caps_required_sets_after_dropping_redundant_ones() {
+        let digits = |count: usize| -> Vec<String> {
+            DIGITS.chars().take(count).map(String::from).collect()
+        };
+        let mut generator = PasswordGenerator {
+            required_sets: digits(MAX_REQUIRED_SETS + 1),
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(generator.check(), Err(GeneratorError::TooManyRequiredSets));
+        assert_eq!(
+            generator.generate().map(|_| ()),
+            Err(GeneratorError::TooManyRequiredSets)
+        );
+
+        // Repeats, and sets that contain one of the others, add no work.
+        generator.required_sets = digits(MAX_REQUIRED_SETS);
+        for _ in 0..20 {
+            generator.required_sets.push("0".to_string());
+            generator.required_sets.push(format!("{DIGITS}{LOWERCASE}"));
+        }
+        for _ in 0..20 {
+            let password = generator.generate().unwrap();
+            assert!(DIGITS[..MAX_REQUIRED_SETS]
+                .chars()
+                .all(|digit| password.contains(digit)));
+        }
+    }
+
+    #[test]
+    fn honors_longer_run_limits_and_required_sets() {
+        let generator = PasswordGenerator {
+            length: 40,
+            lowercase: false,
+            uppercase: false,
+            digits: true,
+            symbols: false,
+            include_chars: "-_".to_string(),
+            max_consecutive: Some(2),
+            required_sets: vec!["-_".to_string(), "13579".to_string()],
+            ..PasswordGenerator::default()
+        };
+        for _ in 0..50 {
+            let password: Vec<char> = generator.generate().unwrap().chars().collect();
+            assert!(longest_run(&password) <= 2);
+            assert!(password.iter().any(|c| "-_".contains(*c)));
+            assert!(password.iter().any(|c| "13579".contains(*c)));
+        }
+
+        let missing = PasswordGenerator {
+            required_sets: vec!["é".to_string()],
+            ..PasswordGenerator::default()
+        };
+        assert_eq!(missing.check(), Err(GeneratorError::RequiredSetUnavailable));
+    }
+}
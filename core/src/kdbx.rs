@@ -0,0 +1,1508 @@
+//! Reader and writer for KeePass KDBX 4 databases, the format KeePassXC and
+//! KeePass 2 save.
+//!
+//! A database starts with a plaintext header (payload cipher, compression,
+//! master seed, and key derivation settings), followed by the payload split
+//! into HMAC-SHA256 authenticated blocks. The password is hashed into a
+//! composite key and stretched with Argon2d, Argon2id, or the older AES-KDF;
+//! the result keys both the block HMACs and the payload cipher (AES-256-CBC
+//! or ChaCha20). The decrypted payload is gzipped XML behind an inner header
+//! that holds the attachments and the key of a second ChaCha20 stream, which
+//! masks protected values (passwords, hidden fields) inside the XML.
+//!
+//! Groups become folders, KeePass's standard strings map onto login fields,
+//! and every other string becomes a custom field. Cards, identities, and
+//! Wi-Fi networks are written as named strings plus a `Lilypad.Kind` custom
+//! data item, so KeePassXC shows them as ordinary entries and they read back
+//! as the same kind. Attachments, history, tags, and entry ids round-trip.
+//! Key files, Twofish, and KDBX 3 databases are not supported, and entries
+//! in the recycle bin are not imported.
+
+use crate::clock;
+use crate::crypto::{self, KdfParams};
+use crate::entry::{
+    Attachment, Card, CustomField, Entry, EntryKind, Identity, Login, Wifi, WifiSecurity,
+};
+use crate::secret::Zeroizing;
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::Aes256;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use chacha20::cipher::StreamCipher;
+use chacha20::ChaCha20;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use roxmltree::{Document, Node};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+use uuid::Uuid;
+
+const SIGNATURE_1: u32 = 0x9AA2_D903;
+const SIGNATURE_2: u32 = 0xB54B_FB67;
+const VERSION_MAJOR: u16 = 4;
+
+const HEADER_END: u8 = 0;
+const HEADER_CIPHER: u8 = 2;
+const HEADER_COMPRESSION: u8 = 3;
+const HEADER_MASTER_SEED: u8 = 4;
+const HEADER_IV: u8 = 7;
+const HEADER_KDF: u8 = 11;
+
+const INNER_END: u8 = 0;
+const INNER_STREAM_ID: u8 = 1;
+const INNER_STREAM_KEY: u8 = 2;
+const INNER_BINARY: u8 = 3;
+const INNER_STREAM_CHACHA20: u32 = 3;
+
+const CIPHER_AES256: Uuid = Uuid::from_u128(0x31c1f2e6_bf71_4350_be58_05216afc5aff);
+const CIPHER_CHACHA20: Uuid = Uuid::from_u128(0xd6038a2b_8b6f_4cb5_a524_339a31dbb59a);
+const KDF_AES: Uuid = Uuid::from_u128(0xc9d9f39a_628a_4460_bf74_0d08c18a4fea);
+const KDF_ARGON2D: Uuid = Uuid::from_u128(0xef636ddf_8c29_444b_91f7_a9a403e30a0c);
+const KDF_ARGON2ID: Uuid = Uuid::from_u128(0x9e298b19_56db_4773_b23d_fc3ec6f0a1e6);
+
+/// Variant dictionary value types used by the KDF parameters.
+const VARIANT_END: u8 = 0x00;
+const VARIANT_U32: u8 = 0x04;
+const VARIANT_U64: u8 = 0x05;
+const VARIANT_BYTES: u8 = 0x42;
+const VARIANT_VERSION: u16 = 0x0100;
+
+/// Upper bounds well above what KeePassXC's one-second benchmark picks, so a
+/// crafted file cannot make the import run for hours or allocate gigabytes.
+const MAX_AES_ROUNDS: u64 = 500_000_000;
+const MAX_ARGON2_ITERATIONS: u64 = 1_000;
+const MAX_ARGON2_MEMORY_KIB: u64 = 2 * 1024 * 1024;
+const MAX_ARGON2_PARALLELISM: u32 = 64;
+/// Largest decompressed payload accepted, far beyond any real database.
+const MAX_INFLATED_LEN: usize = 1024 * 1024 * 1024;
+/// How much of the payload is decompressed at a time.
+const INFLATE_CHUNK: usize = 64 * 1024;
+/// Deflate never expands data by more than this factor.
+const MAX_DEFLATE_RATIO: usize = 1032;
+
+/// Seconds between 0001-01-01 (KDBX 4's epoch) and 1970-01-01.
+const EPOCH_OFFSET: i64 = 62_135_596_800;
+
+/// Payload block size used when writing, as KeePassXC does.
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Custom data key recording the Lilypad kind of an exported entry.
+const KIND_KEY: &str = "Lilypad.Kind";
+
+const TITLE: &str = "Title";
+const USERNAME: &str = "UserName";
+const PASSWORD: &str = "Password";
+const URL: &str = "URL";
+const NOTES: &str = "Notes";
+const OTP: &str = "otp";
+/// Prefix KeePassXC and Keepass2Android use for additional URLs.
+const EXTRA_URL: &str = "KP2A_URL";
+const PASSWORD_RULES: &str = "Password rules";
+
+const CARD_FIELDS: [&str; 6] = [
+    "Cardholder",
+    "Card brand",
+    "Card number",
+    "Expiry month",
+    "Expiry year",
+    "Security code",
+];
+const IDENTITY_FIELDS: [&str; 10] = [
+    "First name",
+    "Last name",
+    "Email",
+    "Phone",
+    "Company",
+    "Address",
+    "City",
+    "State",
+    "Postal code",
+    "Country",
+];
+const SSID: &str = "SSID";
+const WIFI_SECURITY: &str = "Wi-Fi security";
+const WIFI_HIDDEN: &str = "Hidden network";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug)]
+pub enum KdbxError {
+    /// The file does not start with the KeePass signature.
+    NotKdbx,
+    UnsupportedVersion(u16),
+    UnsupportedCipher,
+    UnsupportedKdf,
+    InvalidKdfParams,
+    /// Also reported for databases that need a key file.
+    WrongPassword,
+    /// The named part of the database cannot be decoded.
+    Malformed(&'static str),
+    Xml(roxmltree::Error),
+}
+
+impl fmt::Display for KdbxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotKdbx => write!(f, "file is not a KeePass database"),
+            Self::UnsupportedVersion(major) => write!(
+                f,
+                "KDBX {major} databases are not supported; save it as KDBX 4 in KeePassXC first"
+            ),
+            Self::UnsupportedCipher => {
+                write!(
+                    f,
+                    "database is encrypted with a cipher other than AES-256 or ChaCha20"
+                )
+            }
+            Self::UnsupportedKdf => write!(f, "database uses an unsupported key derivation"),
+            Self::InvalidKdfParams => write!(f, "database has invalid key derivation settings"),
+            Self::WrongPassword => write!(
+                f,
+                "database password is incorrect, or the database also needs a key file"
+            ),
+            Self::Malformed(part) => write!(f, "database {part} is damaged"),
+            Self::Xml(error) => write!(f, "database XML is invalid: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for KdbxError {}
+
+impl From<roxmltree::Error> for KdbxError {
+    fn from(error: roxmltree::Error) -> Self {
+        Self::Xml(error)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Cipher {
+    Aes256Cbc,
+    ChaCha20,
+}
+
+enum Kdf {
+    Aes {
+        seed: Vec<u8>,
+        rounds: u64,
+    },
+    Argon2 {
+        algorithm: Algorithm,
+        version: Version,
+        salt: Vec<u8>,
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+struct Header {
+    cipher: Cipher,
+    compressed: bool,
+    master_seed: Vec<u8>,
+    iv: Vec<u8>,
+    kdf: Kdf,
+    /// Length of the header; the hash and HMAC after it cover these bytes.
+    len: usize,
+}
+
+/// The keys derived from the password and the header.
+struct Keys {
+    cipher: Zeroizing<[u8; 32]>,
+    hmac: Zeroizing<[u8; 64]>,
+}
+
+/// Reads a little-endian layout front to back, reporting `part` as damaged
+/// when the data runs out.
+struct Cursor<'a> {
+    data: &'a [u8],
+    part: &'static str,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], KdbxError> {
+        if self.data.len() < len {
+            return Err(KdbxError::Malformed(self.part));
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, KdbxError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, KdbxError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, KdbxError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+/// Checks that `data` is a KDBX 4 database this module can read, without
+/// needing the password.
+pub fn check(data: &[u8]) -> Result<(), KdbxError> {
+    parse_header(data).map(|_| ())
+}
+
+/// Decrypts a database and converts its entries.
+pub fn read(data: &[u8], password: &str) -> Result<Vec<Entry>, KdbxError> {
+    let header = parse_header(data)?;
+    let mut rest = Cursor {
+        data: &data[header.len..],
+        part: "header",
+    };
+    let hash = rest.take(32)?;
+    if Sha256::digest(&data[..header.len]).as_slice() != hash {
+        return Err(KdbxError::Malformed("header"));
+    }
+    let header_mac = rest.take(32)?;
+
+    let keys = derive_keys(&header.kdf, &header.master_seed, password)?;
+    let mut check = block_mac(&keys, u64::MAX);
+    check.update(&data[..header.len]);
+    check
+        .verify_slice(header_mac)
+        .map_err(|_| KdbxError::WrongPassword)?;
+
+    rest.part = "payload";
+    let payload = read_blocks(rest, &keys)?;
+    let mut plain = decrypt(header.cipher, &keys, &header.iv, payload)?;
+    if header.compressed {
+        plain = inflate(&plain)?;
+    }
+
+    let mut inner = Cursor {
+        data: &plain,
+        part: "inner header",
+    };
+    let mut stream_key = None;
+    let mut binaries = Vec::new();
+    loop {
+        let id = inner.u8()?;
+        let len = inner.u32()? as usize;
+        let value = inner.take(len)?;
+        match id {
+            INNER_END => break,
+            INNER_STREAM_ID if value != INNER_STREAM_CHACHA20.to_le_bytes() => {
+                return Err(KdbxError::Malformed("inner header"));
+            }
+            INNER_STREAM_KEY => stream_key = Some(value),
+            // The first byte holds flags; only the protection flag exists.
+            INNER_BINARY if !value.is_empty() => binaries.push(&value[1..]),
+            _ => {}
+        }
+    }
+    let stream = inner_stream(stream_key.ok_or(KdbxError::Malformed("inner header"))?);
+    let xml = std::str::from_utf8(inner.data).map_err(|_| KdbxError::Malformed("XML"))?;
+    let document = Document::parse(xml)?;
+    Reader::new(&document, stream, binaries)?.entries(&document)
+}
+
+/// Writes `entries` as a KDBX 4 database protected by `password`: ChaCha20,
+/// gzip, and Argon2id with the vault's own cost settings.
+pub fn write(entries: &[Entry], password: &str, kdf: KdfParams) -> Result<Vec<u8>, KdbxError> {
+    let master_seed = crypto::random_bytes::<32>();
+    let iv = crypto::random_bytes::<12>();
+    let salt = crypto::random_bytes::<32>();
+    let stream_key = Zeroizing::new(crypto::random_bytes::<64>());
+
+    let mut kdf_dictionary = VARIANT_VERSION.to_le_bytes().to_vec();
+    let mut variant = |kind: u8, name: &str, value: &[u8]| {
+        kdf_dictionary.push(kind);
+        kdf_dictionary.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        kdf_dictionary.extend_from_slice(name.as_bytes());
+        kdf_dictionary.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        kdf_dictionary.extend_from_slice(value);
+    };
+    variant(VARIANT_BYTES, "$UUID", KDF_ARGON2ID.as_bytes());
+    variant(VARIANT_BYTES, "S", &salt);
+    variant(VARIANT_U32, "P", &kdf.parallelism.to_le_bytes());
+    variant(
+        VARIANT_U64,
+        "M",
+        &(u64::from(kdf.memory_kib) * 1024).to_le_bytes(),
+    );
+    variant(VARIANT_U64, "I", &u64::from(kdf.iterations).to_le_bytes());
+    variant(VARIANT_U32, "V", &0x13u32.to_le_bytes());
+    kdf_dictionary.push(VARIANT_END);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&SIGNATURE_1.to_le_bytes());
+    out.extend_from_slice(&SIGNATURE_2.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&VERSION_MAJOR.to_le_bytes());
+    let mut field = |id: u8, value: &[u8]| {
+        out.push(id);
+        out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        out.extend_from_slice(value);
+    };
+    field(HEADER_CIPHER, CIPHER_CHACHA20.as_bytes());
+    field(HEADER_COMPRESSION, &1u32.to_le_bytes());
+    field(HEADER_MASTER_SEED, &master_seed);
+    field(HEADER_IV, &iv);
+    field(HEADER_KDF, &kdf_dictionary);
+    field(HEADER_END, b"\r\n\r\n");
+
+    let argon2 = Kdf::Argon2 {
+        algorithm: Algorithm::Argon2id,
+        version: Version::V0x13,
+        salt: salt.to_vec(),
+        memory_kib: kdf.memory_kib,
+        iterations: kdf.iterations,
+        parallelism: kdf.parallelism,
+    };
+    let keys = derive_keys(&argon2, &master_seed, password)?;
+    let header_hash = Sha256::digest(&out);
+    let mut header_mac = block_mac(&keys, u64::MAX);
+    header_mac.update(&out);
+    out.extend_from_slice(&header_hash);
+    out.extend_from_slice(&header_mac.finalize().into_bytes());
+
+    let mut writer = Writer::new(inner_stream(stream_key.as_slice()));
+    writer.database(entries);
+
+    let mut plain = Zeroizing::new(Vec::new());
+    let mut inner = |id: u8, value: &[&[u8]]| {
+        let len: usize = value.iter().map(|part| part.len()).sum();
+        plain.push(id);
+        plain.extend_from_slice(&(len as u32).to_le_bytes());
+        for part in value {
+            plain.extend_from_slice(part);
+        }
+    };
+    inner(INNER_STREAM_ID, &[&INNER_STREAM_CHACHA20.to_le_bytes()]);
+    inner(INNER_STREAM_KEY, &[stream_key.as_slice()]);
+    for binary in &writer.binaries {
+        inner(INNER_BINARY, &[&[0], binary]);
+    }
+    inner(INNER_END, &[]);
+    plain.extend_from_slice(writer.xml.as_bytes());
+
+    // Reserved up front so the compressed plaintext never reallocates,
+    // which would leave copies behind that `Zeroizing` cannot reach.
+    let mut payload = Zeroizing::new(Vec::with_capacity(plain.len() + plain.len() / 1000 + 64));
+    let mut compressor = GzEncoder::new(&mut *payload, Compression::default());
+    compressor
+        .write_all(&plain)
+        .map_err(|_| KdbxError::Malformed("payload"))?;
+    compressor
+        .finish()
+        .map_err(|_| KdbxError::Malformed("payload"))?;
+    ChaCha20::new((&*keys.cipher).into(), (&iv).into()).apply_keystream(&mut payload);
+
+    let mut chunks: Vec<&[u8]> = payload.chunks(BLOCK_SIZE).collect();
+    chunks.push(&[]);
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let index = index as u64;
+        let len = (chunk.len() as u32).to_le_bytes();
+        let mut mac = block_mac(&keys, index);
+        mac.update(&index.to_le_bytes());
+        mac.update(&len);
+        mac.update(chunk);
+        out.extend_from_slice(&mac.finalize().into_bytes());
+        out.extend_from_slice(&len);
+        out.extend_from_slice(chunk);
+    }
+    Ok(out)
+}
+
+fn parse_header(data: &[u8]) -> Result<Header, KdbxError> {
+    let mut cursor = Cursor {
+        data,
+        part: "header",
+    };
+    let signature = (cursor.u32(), cursor.u32());
+    if !matches!(signature, (Ok(SIGNATURE_1), Ok(SIGNATURE_2))) {
+        return Err(KdbxError::NotKdbx);
+    }
+    let _minor = cursor.u16()?;
+    let major = cursor.u16()?;
+    if major != VERSION_MAJOR {
+        return Err(KdbxError::UnsupportedVersion(major));
+    }
+
+    let (mut cipher, mut compressed, mut master_seed, mut iv, mut kdf) =
+        (None, false, None, None, None);
+    loop {
+        let id = cursor.u8()?;
+        let len = cursor.u32()? as usize;
+        let value = cursor.take(len)?;
+        match id {
+            HEADER_END => break,
+            HEADER_CIPHER => {
+                cipher = Some(match Uuid::from_slice(value) {
+                    Ok(CIPHER_AES256) => Cipher::Aes256Cbc,
+                    Ok(CIPHER_CHACHA20) => Cipher::ChaCha20,
+                    _ => return Err(KdbxError::UnsupportedCipher),
+                });
+            }
+            HEADER_COMPRESSION => compressed = value != [0, 0, 0, 0],
+            HEADER_MASTER_SEED => master_seed = Some(value.to_vec()),
+            HEADER_IV => iv = Some(value.to_vec()),
+            HEADER_KDF => kdf = Some(parse_kdf(value)?),
+            _ => {}
+        }
+    }
+
+    let missing = || KdbxError::Malformed("header");
+    let cipher = cipher.ok_or_else(missing)?;
+    let iv = iv.ok_or_else(missing)?;
+    let iv_len = match cipher {
+        Cipher::Aes256Cbc => 16,
+        Cipher::ChaCha20 => 12,
+    };
+    let master_seed = master_seed.filter(|seed| seed.len() == 32);
+    if iv.len() != iv_len || master_seed.is_none() {
+        return Err(missing());
+    }
+    Ok(Header {
+        cipher,
+        compressed,
+        master_seed: master_seed.ok_or_else(missing)?,
+        iv,
+        kdf: kdf.ok_or_else(missing)?,
+        len: data.len() - cursor.data.len(),
+    })
+}
+
+/// Parses the KDF parameters, a "variant dictionary" of typed values.
+fn parse_kdf(data: &[u8]) -> Result<Kdf, KdbxError> {
+    let mut cursor = Cursor {
+        data,
+        part: "key derivation settings",
+    };
+    if cursor.u16()? >> 8 != VARIANT_VERSION >> 8 {
+        return Err(KdbxError::Malformed("key derivation settings"));
+    }
+    let mut values = HashMap::new();
+    loop {
+        let kind = cursor.u8()?;
+        if kind == VARIANT_END {
+            break;
+        }
+        let name_len = cursor.u32()? as usize;
+        let name = cursor.take(name_len)?;
+        let value_len = cursor.u32()? as usize;
+        values.insert(name, cursor.take(value_len)?);
+    }
+
+    let bytes = |name: &str| values.get(name.as_bytes()).copied();
+    let number = |name: &str| -> Result<u64, KdbxError> {
+        match bytes(name) {
+            Some(value) if value.len() == 4 => {
+                Ok(u32::from_le_bytes(value.try_into().unwrap()).into())
+            }
+            Some(value) if value.len() == 8 => Ok(u64::from_le_bytes(value.try_into().unwrap())),
+            _ => Err(KdbxError::InvalidKdfParams),
+        }
+    };
+    let uuid = bytes("$UUID").and_then(|value| Uuid::from_slice(value).ok());
+    let salt = bytes("S").ok_or(KdbxError::InvalidKdfParams)?.to_vec();
+
+    let algorithm = match uuid {
+        Some(KDF_AES) => {
+            let rounds = number("R")?;
+            if salt.len() != 32 || rounds > MAX_AES_ROUNDS {
+                return Err(KdbxError::InvalidKdfParams);
+            }
+            return Ok(Kdf::Aes { seed: salt, rounds });
+        }
+        Some(KDF_ARGON2D) => Algorithm::Argon2d,
+        Some(KDF_ARGON2ID) => Algorithm::Argon2id,
+        _ => return Err(KdbxError::UnsupportedKdf),
+    };
+    let version = match number("V")? {
+        0x10 => Version::V0x10,
+        0x13 => Version::V0x13,
+        _ => return Err(KdbxError::InvalidKdfParams),
+    };
+    let memory_kib = number("M")? / 1024;
+    let iterations = number("I")?;
+    let parallelism = number("P")?;
+    if memory_kib > MAX_ARGON2_MEMORY_KIB
+        || iterations > MAX_ARGON2_ITERATIONS
+        || parallelism > u64::from(MAX_ARGON2_PARALLELISM)
+    {
+        return Err(KdbxError::InvalidKdfParams);
+    }
+    Ok(Kdf::Argon2 {
+        algorithm,
+        version,
+        salt,
+        memory_kib: memory_kib as u32,
+        iterations: iterations as u32,
+        parallelism: parallelism as u32,
+    })
+}
+
+fn derive_keys(kdf: &Kdf, master_seed: &[u8], password: &str) -> Result<Keys, KdbxError> {
+    // The composite key hashes each key component; a password is the only
+    // one supported.
+    let composite = Zeroizing::new(<[u8; 32]>::from(Sha256::digest(Sha256::digest(
+        password.as_bytes(),
+    ))));
+    let mut transformed = Zeroizing::new([0u8; 32]);
+    match kdf {
+        Kdf::Aes { seed, rounds } => {
+            let cipher = Aes256::new_from_slice(seed).map_err(|_| KdbxError::InvalidKdfParams)?;
+            let mut blocks = Zeroizing::new(*composite);
+            let (first, second) = blocks.split_at_mut(16);
+            for _ in 0..*rounds {
+                cipher.encrypt_block(first.into());
+                cipher.encrypt_block(second.into());
+            }
+            transformed.copy_from_slice(&Sha256::digest(blocks.as_slice()));
+        }
+        Kdf::Argon2 {
+            algorithm,
+            version,
+            salt,
+            memory_kib,
+            iterations,
+            parallelism,
+        } => {
+            let params = Params::new(*memory_kib, *iterations, *parallelism, Some(32))
+                .map_err(|_| KdbxError::InvalidKdfParams)?;
+            Argon2::new(*algorithm, *version, params)
+                .hash_password_into(composite.as_slice(), salt, transformed.as_mut())
+                .map_err(|_| KdbxError::InvalidKdfParams)?;
+        }
+    }
+
+    let mut cipher = Sha256::new();
+    cipher.update(master_seed);
+    cipher.update(transformed.as_slice());
+    let mut hmac = Sha512::new();
+    hmac.update(master_seed);
+    hmac.update(transformed.as_slice());
+    hmac.update([1]);
+    Ok(Keys {
+        cipher: Zeroizing::new(cipher.finalize().into()),
+        hmac: Zeroizing::new(hmac.finalize().into()),
+    })
+}
+
+/// The HMAC of block `index`; the header uses index `u64::MAX`.
+fn block_mac(keys: &Keys, index: u64) -> HmacSha256 {
+    let mut key = Sha512::new();
+    key.update(index.to_le_bytes());
+    key.update(keys.hmac.as_slice());
+    let key = Zeroizing::new(<[u8; 64]>::from(key.finalize()));
+    <HmacSha256 as Mac>::new_from_slice(key.as_slice()).expect("HMAC accepts any key length")
+}
+
+fn read_blocks(mut blocks: Cursor<'_>, keys: &Keys) -> Result<Vec<u8>, KdbxError> {
+    let mut payload = Vec::new();
+    for index in 0u64.. {
+        let mac = blocks.take(32)?;
+        let len = blocks.take(4)?;
+        let block = blocks.take(u32::from_le_bytes(len.try_into().unwrap()) as usize)?;
+        let mut check = block_mac(keys, index);
+        check.update(&index.to_le_bytes());
+        check.update(len);
+        check.update(block);
+        check
+            .verify_slice(mac)
+            .map_err(|_| KdbxError::Malformed("payload"))?;
+        if block.is_empty() {
+            break;
+        }
+        payload.extend_from_slice(block);
+    }
+    Ok(payload)
+}
+
+fn decrypt(
+    cipher: Cipher,
+    keys: &Keys,
+    iv: &[u8],
+    payload: Vec<u8>,
+) -> Result<Zeroizing<Vec<u8>>, KdbxError> {
+    let mut buffer = Zeroizing::new(payload);
+    match cipher {
+        Cipher::Aes256Cbc => {
+            let length = cbc::Decryptor::<Aes256>::new((&*keys.cipher).into(), iv.into())
+                .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+                .map_err(|_| KdbxError::Malformed("payload"))?
+                .len();
+            buffer.truncate(length);
+        }
+        Cipher::ChaCha20 => {
+            ChaCha20::new((&*keys.cipher).into(), iv.into()).apply_keystream(&mut buffer);
+        }
+    }
+    Ok(buffer)
+}
+
+/// Decompresses the gzip payload into zeroizing buffers only. The buffer is
+/// sized from the gzip trailer, which records the original length, and when
+/// that turns out too small it is moved into a larger one by hand rather
+/// than reallocated, so no stray copy of the plaintext is left behind.
+fn inflate(compressed: &[u8]) -> Result<Zeroizing<Vec<u8>>, KdbxError> {
+    let malformed = |_| KdbxError::Malformed("payload");
+    // The trailer is only checked once everything is decompressed, so until
+    // then it is a hint, bounded by what the compressed size can produce.
+    let hint = compressed.len().checked_sub(4).map_or(0, |at| {
+        u32::from_le_bytes(compressed[at..].try_into().unwrap()) as usize
+    });
+    let limit = compressed.len().saturating_mul(MAX_DEFLATE_RATIO);
+    let reserve = hint.min(limit).min(MAX_INFLATED_LEN);
+    let mut inflated = Zeroizing::new(Vec::with_capacity(reserve));
+    let mut chunk = Zeroizing::new(vec![0; INFLATE_CHUNK]);
+    let mut decoder = GzDecoder::new(compressed);
+    loop {
+        let read = decoder.read(&mut chunk).map_err(malformed)?;
+        if read == 0 {
+            return Ok(inflated);
+        }
+        let needed = inflated.len() + read;
+        if needed > MAX_INFLATED_LEN {
+            return Err(KdbxError::Malformed("payload"));
+        }
+        if needed > inflated.capacity() {
+            let capacity = (inflated.capacity() * 2).clamp(needed, MAX_INFLATED_LEN);
+            let mut larger = Zeroizing::new(Vec::with_capacity(capacity));
+            larger.extend_from_slice(&inflated);
+            inflated = larger;
+        }
+        inflated.extend_from_slice(&chunk[..read]);
+    }
+}
+
+/// The stream that masks protected values, keyed from the inner header.
+fn inner_stream(key: &[u8]) -> ChaCha20 {
+    let hash = Zeroizing::new(<[u8; 64]>::from(Sha512::digest(key)));
+    ChaCha20::new(hash[..32].into(), hash[32..44].into())
+}
+
+/// One string of an entry as stored in the XML.
+struct Field {
+    key: String,
+    value: Zeroizing<String>,
+    protected: bool,
+}
+
+/// Walks the decrypted XML. Protected values are unmasked up front, since
+/// the stream must be applied in document order.
+struct Reader<'a> {
+    protected: HashMap<roxmltree::NodeId, Zeroizing<String>>,
+    binaries: Vec<&'a [u8]>,
+    recycle_bin: Option<&'a str>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(
+        document: &'a Document<'a>,
+        mut stream: ChaCha20,
+        binaries: Vec<&'a [u8]>,
+    ) -> Result<Self, KdbxError> {
+        let mut protected = HashMap::new();
+        for node in document.descendants() {
+            if node.has_tag_name("Value")
+                && node
+                    .attribute("Protected")
+                    .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+            {
+                let mut value = Zeroizing::new(
+                    BASE64
+                        .decode(node.text().unwrap_or_default().trim())
+                        .map_err(|_| KdbxError::Malformed("protected value"))?,
+                );
+                stream.apply_keystream(&mut value);
+                let text = String::from_utf8(std::mem::take(&mut *value))
+                    .map_err(|_| KdbxError::Malformed("protected value"))?;
+                protected.insert(node.id(), Zeroizing::new(text));
+            }
+        }
+
+        let meta = child(document.root_element(), "Meta");
+        let recycle_bin_enabled = meta
+            .and_then(|meta| child_text(meta, "RecycleBinEnabled"))
+            .is_none_or(|enabled| enabled.eq_ignore_ascii_case("true"));
+        let recycle_bin = meta
+            .and_then(|meta| child_text(meta, "RecycleBinUUID"))
+            .filter(|_| recycle_bin_enabled);
+        Ok(Self {
+            protected,
+            binaries,
+            recycle_bin,
+        })
+    }
+
+    fn entries(&self, document: &Document<'_>) -> Result<Vec<Entry>, KdbxError> {
+        let root = document.root_element();
+        if !root.has_tag_name("KeePassFile") {
+            return Err(KdbxError::Malformed("XML"));
+        }
+        let group = child(root, "Root")
+            .and_then(|root| child(root, "Group"))
+            .ok_or(KdbxError::Malformed("XML"))?;
+        let mut entries = Vec::new();
+        // The root group's name is the database name, not a folder.
+        self.group(group, "", &mut entries)?;
+        Ok(entries)
+    }
+
+    fn group(
+        &self,
+        group: Node<'_, '_>,
+        folder: &str,
+        entries: &mut Vec<Entry>,
+    ) -> Result<(), KdbxError> {
+        if self.recycle_bin.is_some() && child_text(group, "UUID") == self.recycle_bin {
+            return Ok(());
+        }
+        for node in group.children() {
+            if node.has_tag_name("Entry") {
+                let mut entry = self.entry(node)?;
+                entry.folder = folder.to_string();
+                entry.history = match child(node, "History") {
+                    Some(history) => history
+                        .children()
+                        .filter(|node| node.has_tag_name("Entry"))
+                        .map(|node| {
+                            let mut old = self.entry(node)?;
+                            old.folder = folder.to_string();
+                            Ok(old)
+                        })
+                        .collect::<Result<_, KdbxError>>()?,
+                    None => Vec::new(),
+                };
+                entries.push(entry);
+            } else if node.has_tag_name("Group") {
+                let name = child_text(node, "Name")
+                    .unwrap_or_default()
+                    .replace('/', "-");
+                let path = if folder.is_empty() {
+                    name
+                } else {
+                    format!("{folder}/{name}")
+                };
+                self.group(node, &path, entries)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn entry(&self, node: Node<'_, '_>) -> Result<Entry, KdbxError> {
+        let mut fields = Vec::new();
+        let mut attachments = Vec::new();
+        for item in node.children() {
+            let Some(value) = child(item, "Value") else {
+                continue;
+            };
+            let key = child_text(item, "Key").unwrap_or_default().to_string();
+            if item.has_tag_name("String") {
+                let protected = self.protected.get(&value.id());
+                fields.push(Field {
+                    key,
+                    value: Zeroizing::new(match protected {
+                        Some(text) => text.to_string(),
+                        None => value.text().unwrap_or_default().to_string(),
+                    }),
+                    protected: protected.is_some(),
+                });
+            } else if item.has_tag_name("Binary") {
+                let data = value
+                    .attribute("Ref")
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .and_then(|index| self.binaries.get(index))
+                    .ok_or(KdbxError::Malformed("attachment"))?;
+                attachments.push(Attachment {
+                    name: key,
+                    data: data.to_vec(),
+                });
+            }
+        }
+        let kind = child(node, "CustomData")
+            .into_iter()
+            .flat_map(|data| data.children())
+            .find(|item| child_text(*item, "Key") == Some(KIND_KEY))
+            .and_then(|item| child_text(item, "Value"));
+
+        let times = child(node, "Times");
+        let time = |name: &str| {
+            times
+                .and_then(|times| child_text(times, name))
+                .and_then(parse_time)
+        };
+        let modified_at = time("LastModificationTime").unwrap_or_else(clock::now);
+
+        let mut entry = Entry::new(
+            take(&mut fields, TITLE).to_string(),
+            entry_kind(kind, &mut fields),
+        );
+        entry.id = child_text(node, "UUID")
+            .and_then(|uuid| BASE64.decode(uuid).ok())
+            .and_then(|bytes| Uuid::from_slice(&bytes).ok())
+            .unwrap_or(entry.id);
+        entry.notes = take(&mut fields, NOTES).to_string();
+        entry.tags = child_text(node, "Tags")
+            .unwrap_or_default()
+            .split([';', ','])
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+        // KeePassXC writes the standard strings even when they are empty.
+        fields.retain(|field| {
+            !field.value.is_empty() || ![USERNAME, PASSWORD, URL].contains(&field.key.as_str())
+        });
+        entry.custom_fields = fields
+            .into_iter()
+            .map(|field| CustomField {
+                name: field.key,
+                value: field.value.to_string(),
+                hidden: field.protected,
+            })
+            .collect();
+        entry.attachments = attachments;
+        entry.created_at = time("CreationTime").unwrap_or(modified_at);
+        entry.modified_at = modified_at;
+        Ok(entry)
+    }
+}
+
+/// Builds the entry kind from the strings, removing the ones it uses.
+/// Entries KeePass created have no `kind` marker: they are logins unless
+/// they only hold notes.
+fn entry_kind(kind: Option<&str>, fields: &mut Vec<Field>) -> EntryKind {
+    match kind {
+        Some("card") => {
+            let [cardholder_name, brand, number, expiry_month, expiry_year, security_code] =
+                CARD_FIELDS.map(|name| take(fields, name).to_string());
+            EntryKind::Card(Card {
+                cardholder_name,
+                brand,
+                number,
+                expiry_month,
+                expiry_year,
+                security_code,
+            })
+        }
+        Some("identity") => {
+            let [first_name, last_name, email, phone, company, address, city, state, postal_code, country] =
+                IDENTITY_FIELDS.map(|name| take(fields, name).to_string());
+            EntryKind::Identity(Identity {
+                first_name,
+                last_name,
+                email,
+                phone,
+                company,
+                address,
+                city,
+                state,
+                postal_code,
+                country,
+            })
+        }
+        Some("wifi") => {
+            let security = take(fields, WIFI_SECURITY);
+            EntryKind::Wifi(Wifi {
+                ssid: take(fields, SSID).to_string(),
+                password: take(fields, PASSWORD).to_string(),
+                security: WifiSecurity::ALL
+                    .into_iter()
+                    .find(|option| option.label() == security.as_str())
+                    .unwrap_or_default(),
+                hidden: take(fields, WIFI_HIDDEN).as_str() == "true",
+            })
+        }
+        Some("secure_note") => EntryKind::SecureNote,
+        kind => {
+            let is_login = |field: &Field| {
+                [USERNAME, PASSWORD, URL, OTP].contains(&field.key.as_str())
+                    || field.key.starts_with(EXTRA_URL)
+            };
+            let has_login_field = fields
+                .iter()
+                .any(|field| is_login(field) && !field.value.is_empty());
+            if kind.is_none() && !has_login_field {
+                return EntryKind::SecureNote;
+            }
+            let mut urls = vec![take(fields, URL).to_string()];
+            while let Some(index) = fields
+                .iter()
+                .position(|field| field.key.starts_with(EXTRA_URL))
+            {
+                urls.push(fields.remove(index).value.to_string());
+            }
+            urls.retain(|url| !url.is_empty());
+            EntryKind::Login(Login {
+                username: take(fields, USERNAME).to_string(),
+                password: take(fields, PASSWORD).to_string(),
+                urls,
+                password_rules: if kind.is_some() {
+                    take(fields, PASSWORD_RULES).to_string()
+                } else {
+                    String::new()
+                },
+                totp: take(fields, OTP).to_string(),
+            })
+        }
+    }
+}
+
+/// Removes the string named `key`, returning its value or an empty string.
+fn take(fields: &mut Vec<Field>, key: &str) -> Zeroizing<String> {
+    match fields.iter().position(|field| field.key == key) {
+        Some(index) => fields.remove(index).value,
+        None => Zeroizing::new(String::new()),
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|child| child.text())
+}
+
+/// KDBX 4 stores times as base64 seconds since year 1; older files and
+/// some tools use ISO 8601 text.
+fn parse_time(text: &str) -> Option<u64> {
+    match BASE64.decode(text.trim()) {
+        Ok(bytes) if bytes.len() == 8 => {
+            let seconds = i64::from_le_bytes(bytes.try_into().ok()?);
+            u64::try_from(seconds - EPOCH_OFFSET).ok()
+        }
+        _ => clock::parse_rfc3339(text.trim()),
+    }
+}
+
+fn format_time(timestamp: u64) -> String {
+    let seconds = i64::try_from(timestamp)
+        .unwrap_or(i64::MAX)
+        .saturating_add(EPOCH_OFFSET);
+    BASE64.encode(seconds.to_le_bytes())
+}
+
+/// Folders as a tree of groups, in the order entries first use them.
+#[derive(Default)]
+struct Group<'a> {
+    entries: Vec<&'a Entry>,
+    groups: Vec<(&'a str, Group<'a>)>,
+}
+
+impl<'a> Group<'a> {
+    fn insert(&mut self, path: &[&'a str], entry: &'a Entry) {
+        let Some((name, rest)) = path.split_first() else {
+            self.entries.push(entry);
+            return;
+        };
+        let index = match self
+            .groups
+            .iter()
+            .position(|(existing, _)| existing == name)
+        {
+            Some(index) => index,
+            None => {
+                self.groups.push((name, Group::default()));
+                self.groups.len() - 1
+            }
+        };
+        self.groups[index].1.insert(rest, entry);
+    }
+}
+
+/// Builds the XML, masking protected values as it goes and collecting the
+/// attachments for the inner header.
+struct Writer {
+    xml: Zeroizing<String>,
+    stream: ChaCha20,
+    binaries: Vec<Zeroizing<Vec<u8>>>,
+}
+
+impl Writer {
+    fn new(stream: ChaCha20) -> Self {
+        Self {
+            xml: Zeroizing::new(String::new()),
+            stream,
+            binaries: Vec::new(),
+        }
+    }
+
+    fn database(&mut self, entries: &[Entry]) {
+        let mut root = Group::default();
+        for entry in entries {
+            let path: Vec<&str> = entry
+                .folder
+                .split('/')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .collect();
+            root.insert(&path, entry);
+        }
+
+        self.xml
+            .push_str("<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n");
+        self.open("KeePassFile");
+        self.open("Meta");
+        self.element("Generator", "Lilypad");
+        self.element("DatabaseName", "Lilypad");
+        self.element("RecycleBinEnabled", "False");
+        self.close("Meta");
+        self.open("Root");
+        self.group("Root", &root);
+        self.close("Root");
+        self.close("KeePassFile");
+    }
+
+    fn group(&mut self, name: &str, group: &Group<'_>) {
+        self.open("Group");
+        self.element("UUID", &BASE64.encode(Uuid::new_v4().as_bytes()));
+        self.element("Name", name);
+        self.element("IsExpanded", "True");
+        for entry in &group.entries {
+            self.entry(entry, true);
+        }
+        for (name, child) in &group.groups {
+            self.group(name, child);
+        }
+        self.close("Group");
+    }
+
+    fn entry(&mut self, entry: &Entry, with_history: bool) {
+        self.open("Entry");
+        self.element("UUID", &BASE64.encode(entry.id.as_bytes()));
+        if !entry.tags.is_empty() {
+            self.element("Tags", &entry.tags.join(";"));
+        }
+        self.open("Times");
+        self.element("CreationTime", &format_time(entry.created_at));
+        for name in ["LastModificationTime", "LastAccessTime", "LocationChanged"] {
+            self.element(name, &format_time(entry.modified_at));
+        }
+        self.element("ExpiryTime", &format_time(entry.modified_at));
+        self.element("Expires", "False");
+        self.element("UsageCount", "0");
+        self.close("Times");
+
+        let mut written = Vec::new();
+        let mut string = |writer: &mut Self, key: &str, value: &str, protected: bool| {
+            // Keys must be unique within an entry.
+            let mut unique = key.to_string();
+            let mut counter = 1;
+            while written.contains(&unique) {
+                counter += 1;
+                unique = format!("{key} ({counter})");
+            }
+            writer.string(&unique, value, protected);
+            written.push(unique);
+        };
+
+        string(self, TITLE, &entry.title, false);
+        let kind = match &entry.kind {
+            EntryKind::Login(login) => {
+                let first_url = login.urls.first().map(String::as_str).unwrap_or_default();
+                string(self, USERNAME, &login.username, false);
+                string(self, PASSWORD, &login.password, true);
+                string(self, URL, first_url, false);
+                for (index, url) in login.urls.iter().enumerate().skip(1) {
+                    let key = if index == 1 {
+                        EXTRA_URL.to_string()
+                    } else {
+                        format!("{EXTRA_URL}_{}", index - 1)
+                    };
+                    string(self, &key, url, false);
+                }
+                if !login.totp.is_empty() {
+                    string(self, OTP, &login.totp, true);
+                }
+                if !login.password_rules.is_empty() {
+                    string(self, PASSWORD_RULES, &login.password_rules, false);
+                }
+                "login"
+            }
+            EntryKind::SecureNote => "secure_note",
+            EntryKind::Card(card) => {
+                let values = [
+                    &card.cardholder_name,
+                    &card.brand,
+                    &card.number,
+                    &card.expiry_month,
+                    &card.expiry_year,
+                    &card.security_code,
+                ];
+                for (name, value) in CARD_FIELDS.into_iter().zip(values) {
+                    let secret = name == "Card number" || name == "Security code";
+                    string(self, name, value, secret);
+                }
+                "card"
+            }
+            EntryKind::Identity(identity) => {
+                let values = [
+                    &identity.first_name,
+                    &identity.last_name,
+                    &identity.email,
+                    &identity.phone,
+                    &identity.company,
+                    &identity.address,
+                    &identity.city,
+                    &identity.state,
+                    &identity.postal_code,
+                    &identity.country,
+                ];
+                for (name, value) in IDENTITY_FIELDS.into_iter().zip(values) {
+                    string(self, name, value, false);
+                }
+                "identity"
+            }
+            EntryKind::Wifi(wifi) => {
+                string(self, SSID, &wifi.ssid, false);
+                string(self, PASSWORD, &wifi.password, true);
+                string(self, WIFI_SECURITY, wifi.security.label(), false);
+                string(
+                    self,
+                    WIFI_HIDDEN,
+                    if wifi.hidden { "true" } else { "false" },
+                    false,
+                );
+                "wifi"
+            }
+        };
+        string(self, NOTES, &entry.notes, false);
+        for field in &entry.custom_fields {
+            string(self, &field.name, &field.value, field.hidden);
+        }
+
+        for attachment in &entry.attachments {
+            let index = match self
+                .binaries
+                .iter()
+                .position(|binary| binary.as_slice() == attachment.data.as_slice())
+            {
+                Some(index) => index,
+                None => {
+                    self.binaries.push(Zeroizing::new(attachment.data.clone()));
+                    self.binaries.len() - 1
+                }
+            };
+            self.open("Binary");
+            self.element("Key", &attachment.name);
+            self.xml.push_str(&format!("<Value Ref=\"{index}\"/>"));
+            self.close("Binary");
+        }
+
+        self.open("CustomData");
+        self.open("Item");
+        self.element("Key", KIND_KEY);
+        self.element("Value", kind);
+        self.close("Item");
+        self.close("CustomData");
+
+        if with_history && !entry.history.is_empty() {
+            self.open("History");
+            for old in &entry.history {
+                self.entry(old, false);
+            }
+            self.close("History");
+        }
+        self.close("Entry");
+    }
+
+    fn string(&mut self, key: &str, value: &str, protected: bool) {
+        self.open("String");
+        self.element("Key", key);
+        if protected {
+            let mut masked = Zeroizing::new(value.as_bytes().to_vec());
+            self.stream.apply_keystream(&mut masked);
+            self.xml.push_str("<Value Protected=\"True\">");
+            self.xml.push_str(&BASE64.encode(masked.as_slice()));
+            self.xml.push_str("</Value>");
+        } else {
+            self.element("Value", value);
+        }
+        self.close("String");
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.xml.push('<');
+        self.xml.push_str(tag);
+        self.xml.push('>');
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.xml.push_str("</");
+        self.xml.push_str(tag);
+        self.xml.push('>');
+    }
+
+    fn element(&mut self, tag: &str, text: &str) {
+        self.open(tag);
+        for c in text.chars() {
+            match c {
+                '&' => self.xml.push_str("&amp;"),
+                '<' => self.xml.push_str("&lt;"),
+                '>' => self.xml.push_str("&gt;"),
+                '"' => self.xml.push_str("&quot;"),
+                // Other control characters are not allowed in XML 1.0.
+                '\t' | '\n' | '\r' => self.xml.push(c),
+                c if c.is_control() => {}
+                c => self.xml.push(c),
+            }
+        }
+        self.close(tag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Password of the databases from the kdbx4 crate.
+    const KDBX4_PASSWORD: &str = "P@ssw0rd";
+
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn login(entry: &Entry) -> &Login {
+        match &entry.kind {
+            EntryKind::Login(login) => login,
+            other => panic!("expected a login, got {}", other.label()),
+        }
+    }
+
+    fn assert_keepassxc_sample(entries: &[Entry]) {
+        let titles: Vec<_> = entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["Foo", "Bar"]);
+        assert_eq!(login(&entries[0]).password, "FooPassword");
+        assert_eq!(entries[0].folder, "");
+
+        let bar = &entries[1];
+        assert_eq!(bar.folder, "Subgroup");
+        assert_eq!(login(bar).password, "BarPassword3");
+        let old_usernames: Vec<_> = bar
+            .history
+            .iter()
+            .map(|old| login(old).username.as_str())
+            .collect();
+        assert_eq!(old_usernames, ["BarUser", "BarUser2"]);
+        assert!(bar.history.iter().all(|old| old.id == bar.id));
+    }
+
+    #[test]
+    fn reads_chacha20_with_argon2d() {
+        let data = include_bytes!("../fixtures/keepass-chacha20-argon2d.kdbx");
+        assert_keepassxc_sample(&read(data, KDBX4_PASSWORD).unwrap());
+    }
+
+    #[test]
+    fn reads_aes_with_aes_kdf() {
+        let data = include_bytes!("../fixtures/keepass-aes-aeskdf.kdbx");
+        assert_keepassxc_sample(&read(data, KDBX4_PASSWORD).unwrap());
+    }
+
+    #[test]
+    fn reads_protected_fields_attachments_and_groups() {
+        let data = include_bytes!("../fixtures/keepass-argon2id.kdbx");
+        let entries = read(data, "lilypad-fixture").unwrap();
+        // The entry in the recycle bin is not imported.
+        let titles: Vec<_> = entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["Mail", "Wi-Fi at home", "Build server"]);
+
+        let mail = &entries[0];
+        assert_eq!(mail.id, Uuid::from_u128(0x100));
+        assert_eq!(login(mail).password, "p<ss>word &");
+        assert_eq!(
+            login(mail).urls,
+            ["https://mail.example.com", "https://webmail.example.com"]
+        );
+        assert!(login(mail).totp.starts_with("otpauth://totp/"));
+        assert_eq!(mail.notes, "Line one\nLine & two");
+        assert_eq!(mail.tags, ["work", "mail"]);
+        assert_eq!(
+            mail.custom_fields,
+            [
+                CustomField {
+                    name: "Recovery email".to_string(),
+                    value: "backup@example.com".to_string(),
+                    hidden: false,
+                },
+                CustomField {
+                    name: "PIN".to_string(),
+                    value: "4321".to_string(),
+                    hidden: true,
+                },
+            ]
+        );
+        assert_eq!(mail.attachments[0].data, b"Remember the milk.\n");
+        assert_eq!(login(&mail.history[0]).password, "old-password");
+        assert_eq!(
+            (mail.created_at, mail.modified_at),
+            (1_700_000_000, 1_700_000_500)
+        );
+
+        assert_eq!(entries[1].kind, EntryKind::SecureNote);
+        let server = &entries[2];
+        assert_eq!(server.folder, "Work/Servers");
+        let names: Vec<_> = server.attachments.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["id_ed25519", "notes.txt"]);
+        assert_eq!(server.attachments[1], mail.attachments[0]);
+    }
+
+    #[test]
+    fn wrong_password_is_reported() {
+        let data = include_bytes!("../fixtures/keepass-argon2id.kdbx");
+        assert!(matches!(
+            read(data, "not-the-password"),
+            Err(KdbxError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn check_rejects_other_files() {
+        assert!(matches!(check(b"{\"items\": []}"), Err(KdbxError::NotKdbx)));
+        let mut kdbx3 = include_bytes!("../fixtures/keepass-argon2id.kdbx").to_vec();
+        kdbx3[10..12].copy_from_slice(&3u16.to_le_bytes());
+        assert!(matches!(
+            check(&kdbx3),
+            Err(KdbxError::UnsupportedVersion(3))
+        ));
+        assert!(check(include_bytes!("../fixtures/keepass-aes-aeskdf.kdbx")).is_ok());
+    }
+
+    #[test]
+    fn inflates_into_a_buffer_sized_by_the_trailer() {
+        let plain: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut compressor = GzEncoder::new(Vec::new(), Compression::default());
+        compressor.write_all(&plain).unwrap();
+        let mut compressed = compressor.finish().unwrap();
+
+        let inflated = inflate(&compressed).unwrap();
+        assert_eq!(*inflated, plain);
+        assert_eq!(inflated.capacity(), plain.len());
+
+        // A trailer that lies about the length is refused.
+        let at = compressed.len() - 4;
+        for claimed in [0, 1, u32::MAX] {
+            compressed[at..].copy_from_slice(&claimed.to_le_bytes());
+            assert!(matches!(
+                inflate(&compressed),
+                Err(KdbxError::Malformed("payload"))
+            ));
+        }
+    }
+
+    #[test]
+    fn every_kind_round_trips() {
+        let mut entries: Vec<Entry> = EntryKind::templates()
+            .into_iter()
+            .map(|kind| Entry::new(kind.label(), kind))
+            .collect();
+        entries[0].kind = EntryKind::Login(Login {
+            username: "user".to_string(),
+            password: "hunter2 <&>".to_string(),
+            urls: vec![
+                "https://a.example".to_string(),
+                "https://b.example".to_string(),
+            ],
+            password_rules: "minlength: 12;".to_string(),
+            totp: "JBSWY3DPEHPK3PXP".to_string(),
+        });
+        entries[0].folder = "Work/Mail".to_string();
+        entries[0].tags = vec!["work".to_string()];
+        entries[0].attachments.push(Attachment {
+            name: "key.bin".to_string(),
+            data: vec![0, 1, 2, 255],
+        });
+        let mut old = entries[0].clone();
+        old.title = "Old title".to_string();
+        entries[0].history.push(old);
+        entries[1].notes = "Keep this safe".to_string();
+        entries[1].folder = "Work".to_string();
+        entries[2].kind = EntryKind::Card(Card {
+            number: "4111 1111 1111 1111".to_string(),
+            security_code: "123".to_string(),
+            ..Card::default()
+        });
+        entries[3].kind = EntryKind::Identity(Identity {
+            first_name: "Ada".to_string(),
+            email: "ada@example.com".to_string(),
+            ..Identity::default()
+        });
+        entries[4].kind = EntryKind::Wifi(Wifi {
+            ssid: "home".to_string(),
+            password: "correct horse".to_string(),
+            security: WifiSecurity::Wpa3Personal,
+            hidden: true,
+        });
+        entries[4].custom_fields.push(CustomField {
+            name: "Router PIN".to_string(),
+            value: "0000".to_string(),
+            hidden: true,
+        });
+
+        let data = write(&entries, "round-trip", TEST_KDF).unwrap();
+        // Entries come back grouped by folder.
+        let mut read_back = read(&data, "round-trip").unwrap();
+        read_back.sort_by_key(|entry| entry.id);
+        entries.sort_by_key(|entry| entry.id);
+        assert_eq!(read_back, entries);
+    }
+
+    #[test]
+    fn exports_open_in_another_reader() {
+        let mut mail = Entry::new(
+            "Mail",
+            EntryKind::Login(Login {
+                username: "frog".to_string(),
+                password: "hunter2 <&>".to_string(),
+                urls: vec!["https://mail.example".to_string()],
+                ..Login::default()
+            }),
+        );
+        mail.notes = "Second factor on the phone".to_string();
+        mail.folder = "Work".to_string();
+        mail.attachments.push(Attachment {
+            name: "key.bin".to_string(),
+            data: vec![0, 1, 2, 255],
+        });
+        let note = Entry::new("Router", EntryKind::SecureNote);
+        let data = write(&[mail, note], "round-trip", TEST_KDF).unwrap();
+
+        let key = keepass::DatabaseKey::new().with_password("round-trip");
+        let db = keepass::Database::parse(&data, key).unwrap();
+        let root = db.root();
+        assert!(root.entry_by_name("Router").is_some());
+        let work = root.group_by_name("Work").unwrap();
+        let mail = work.entry_by_name("Mail").unwrap();
+        assert_eq!(mail.get_username(), Some("frog"));
+        assert_eq!(mail.get_password(), Some("hunter2 <&>"));
+        assert_eq!(mail.get_url(), Some("https://mail.example"));
+        assert_eq!(mail.get("Notes"), Some("Second factor on the phone"));
+        let attachments: Vec<_> = mail
+            .attachments_named()
+            .map(|(name, attachment)| (name.to_string(), attachment.get().clone()))
+            .collect();
+        assert_eq!(attachments, [("key.bin".to_string(), vec![0, 1, 2, 255])]);
+    }
+
+    #[test]
+    fn clashing_field_names_are_renamed() {
+        let mut entry = Entry::new("Note", EntryKind::SecureNote);
+        entry.custom_fields.push(CustomField {
+            name: "Title".to_string(),
+            value: "shadow".to_string(),
+            hidden: false,
+        });
+        let data = write(&[entry], "round-trip", TEST_KDF).unwrap();
+        let entries = read(&data, "round-trip").unwrap();
+        assert_eq!(entries[0].title, "Note");
+        assert_eq!(entries[0].custom_fields[0].name, "Title (2)");
+    }
+}
@@ -0,0 +1,383 @@
+//! Keyboard shortcuts shared by the frontends.
+//!
+//! Every command a frontend offers is an [`Action`] with a stable config name
+//! and a default [`Shortcut`]. [`Keymap`] applies the user's overrides from
+//! the `[keymap]` table of `config.toml` on top of the defaults and rejects
+//! unknown actions, malformed shortcuts, shortcuts the text fields need, and
+//! two actions bound to the same keys.
+//!
+//! Shortcuts are written like `Ctrl+Shift+K`. `Ctrl` stands for Command on
+//! macOS.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Key names a shortcut may end with, in their canonical spelling.
+const KEYS: [&str; 61] = [
+    "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S",
+    "T", "U", "V", "W", "X", "Y", "Z", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "F1",
+    "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "Escape", "Enter", "Tab",
+    "Space", "Delete", "Home", "End", "PageUp", "PageDown", "Comma", "Period", "Slash", "Minus",
+];
+
+/// Keys that, with Ctrl, text fields rely on: select all, clipboard, undo
+/// and redo. The windowing layer turns Ctrl+C/X/V into clipboard events
+/// whatever the other modifiers, so these are reserved with any of them.
+const RESERVED: [&str; 6] = ["A", "C", "V", "X", "Y", "Z"];
+
+/// A command that can be bound to a shortcut and run from the command palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    CommandPalette,
+    FocusSearch,
+    NewEntry,
+    CopyUsername,
+    CopyPassword,
+    GeneratePassword,
+    Lock,
+    ShowVault,
+    ShowGenerator,
+    ShowAlerts,
+    ShowAccount,
+    ShowSecurity,
+    OpenSettings,
+}
+
+impl Action {
+    /// Every action, in the order the command palette lists them.
+    pub const ALL: [Self; 13] = [
+        Self::CommandPalette,
+        Self::FocusSearch,
+        Self::NewEntry,
+        Self::CopyUsername,
+        Self::CopyPassword,
+        Self::GeneratePassword,
+        Self::Lock,
+        Self::ShowVault,
+        Self::ShowGenerator,
+        Self::ShowAlerts,
+        Self::ShowAccount,
+        Self::ShowSecurity,
+        Self::OpenSettings,
+    ];
+
+    /// Key of the action in the `[keymap]` config table.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CommandPalette => "command-palette",
+            Self::FocusSearch => "focus-search",
+            Self::NewEntry => "new-entry",
+            Self::CopyUsername => "copy-username",
+            Self::CopyPassword => "copy-password",
+            Self::GeneratePassword => "generate-password",
+            Self::Lock => "lock",
+            Self::ShowVault => "show-vault",
+            Self::ShowGenerator => "show-generator",
+            Self::ShowAlerts => "show-alerts",
+            Self::ShowAccount => "show-account",
+            Self::ShowSecurity => "show-security",
+            Self::OpenSettings => "open-settings",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::CommandPalette => "Command palette",
+            Self::FocusSearch => "Search entries",
+            Self::NewEntry => "New entry",
+            Self::CopyUsername => "Copy username of selected entry",
+            Self::CopyPassword => "Copy password of selected entry",
+            Self::GeneratePassword => "Generate and copy a password",
+            Self::Lock => "Lock vault",
+            Self::ShowVault => "Go to Vault",
+            Self::ShowGenerator => "Go to Generator",
+            Self::ShowAlerts => "Go to Alerts",
+            Self::ShowAccount => "Go to Account",
+            Self::ShowSecurity => "Go to Security",
+            Self::OpenSettings => "Open Settings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn default_shortcut(self) -> Shortcut {
+        let key = match self {
+            Self::CommandPalette => "K",
+            Self::FocusSearch => "F",
+            Self::NewEntry => "N",
+            Self::CopyUsername => "U",
+            Self::CopyPassword => "P",
+            Self::GeneratePassword => "G",
+            Self::Lock => "L",
+            Self::ShowVault => "1",
+            Self::ShowGenerator => "2",
+            Self::ShowAlerts => "3",
+            Self::ShowAccount => "4",
+            Self::ShowSecurity => "5",
+            Self::OpenSettings => "Comma",
+        };
+        Shortcut {
+            ctrl: true,
+            alt: false,
+            shift: false,
+            key,
+        }
+    }
+}
+
+/// A key with modifiers, such as `Ctrl+Shift+K`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    /// Control, or Command on macOS.
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Canonical key name: `A`–`Z`, `0`–`9`, `F1`–`F12`, or a named key such
+    /// as `Escape`, `PageUp`, or `Comma`.
+    pub key: &'static str,
+}
+
+impl Shortcut {
+    fn modifier_count(self) -> usize {
+        [self.ctrl, self.alt, self.shift]
+            .into_iter()
+            .filter(|&on| on)
+            .count()
+    }
+
+    /// Whether text fields need the keys, so a binding would break typing.
+    fn is_reserved(self) -> bool {
+        let function_key = self.key.len() > 1 && self.key.starts_with('F');
+        if !(self.ctrl || self.alt || function_key) {
+            return true;
+        }
+        self.ctrl && RESERVED.contains(&self.key)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (on, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.alt, "Alt"),
+            (self.shift, "Shift"),
+        ] {
+            if on {
+                write!(f, "{name}+")?;
+            }
+        }
+        f.write_str(self.key)
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = KeymapError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || KeymapError::InvalidShortcut(text.to_string());
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key_name = parts.pop().ok_or_else(invalid)?;
+        let key = KEYS
+            .into_iter()
+            .find(|key| key.eq_ignore_ascii_case(key_name))
+            .or_else(|| key_name.eq_ignore_ascii_case("esc").then_some("Escape"))
+            .ok_or_else(invalid)?;
+
+        let mut shortcut = Shortcut {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            key,
+        };
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" | "command" => &mut shortcut.ctrl,
+                "alt" | "option" => &mut shortcut.alt,
+                "shift" => &mut shortcut.shift,
+                _ => return Err(invalid()),
+            };
+            if std::mem::replace(flag, true) {
+                return Err(invalid());
+            }
+        }
+        Ok(shortcut)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeymapError {
+    UnknownAction(String),
+    InvalidShortcut(String),
+    Reserved(Shortcut),
+    Conflict {
+        shortcut: Shortcut,
+        first: Action,
+        second: Action,
+    },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownAction(name) => write!(f, "unknown keymap action \"{name}\""),
+            Self::InvalidShortcut(text) => write!(f, "\"{text}\" is not a valid shortcut"),
+            Self::Reserved(shortcut) => write!(
+                f,
+                "{shortcut} is needed for typing; add Ctrl or Alt, or use a function key"
+            ),
+            Self::Conflict {
+                shortcut,
+                first,
+                second,
+            } => write!(
+                f,
+                "{shortcut} is bound to both \"{}\" and \"{}\"",
+                first.label(),
+                second.label()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+/// The shortcut bound to each action after applying the user's overrides.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keymap {
+    /// Most modifiers first, so `Ctrl+Shift+K` is matched before `Ctrl+K`.
+    bindings: Vec<(Action, Shortcut)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_overrides(&BTreeMap::new()).expect("default keymap is valid")
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from `[keymap]` overrides keyed by [`Action::name`].
+    /// An empty value unbinds the action.
+    pub fn from_overrides(overrides: &BTreeMap<String, String>) -> Result<Self, KeymapError> {
+        for name in overrides.keys() {
+            if Action::from_name(name).is_none() {
+                return Err(KeymapError::UnknownAction(name.clone()));
+            }
+        }
+
+        let mut bindings: Vec<(Action, Shortcut)> = Vec::new();
+        for action in Action::ALL {
+            let shortcut = match overrides.get(action.name()).map(|text| text.trim()) {
+                Some("") => continue,
+                Some(text) => text.parse()?,
+                None => action.default_shortcut(),
+            };
+            if shortcut.is_reserved() {
+                return Err(KeymapError::Reserved(shortcut));
+            }
+            if let Some((first, _)) = bindings.iter().find(|(_, bound)| *bound == shortcut) {
+                return Err(KeymapError::Conflict {
+                    shortcut,
+                    first: *first,
+                    second: action,
+                });
+            }
+            bindings.push((action, shortcut));
+        }
+        bindings.sort_by_key(|(_, shortcut)| std::cmp::Reverse(shortcut.modifier_count()));
+        Ok(Self { bindings })
+    }
+
+    pub fn shortcut(&self, action: Action) -> Option<Shortcut> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, shortcut)| *shortcut)
+    }
+
+    /// Bound actions, ordered so that checking them in turn never lets a
+    /// shortcut shadow a more specific one.
+    pub fn bindings(&self) -> &[(Action, Shortcut)] {
+        &self.bindings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(action, shortcut)| (action.to_string(), shortcut.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_formats_shortcuts() {
+        let shortcut: Shortcut = "shift + cmd+k".parse().unwrap();
+        assert_eq!(shortcut.to_string(), "Ctrl+Shift+K");
+        assert_eq!("Alt+pageup".parse::<Shortcut>().unwrap().key, "PageUp");
+        assert_eq!("F5".parse::<Shortcut>().unwrap().to_string(), "F5");
+
+        for invalid in ["", "Ctrl+", "Ctrl+Ctrl+K", "Hyper+K", "Ctrl+F13"] {
+            assert_eq!(
+                invalid.parse::<Shortcut>(),
+                Err(KeymapError::InvalidShortcut(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn defaults_cover_every_action() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.bindings().len(), Action::ALL.len());
+        assert_eq!(
+            keymap.shortcut(Action::ShowSecurity).unwrap().to_string(),
+            "Ctrl+5"
+        );
+        for action in Action::ALL {
+            assert_eq!(Action::from_name(action.name()), Some(action));
+        }
+    }
+
+    #[test]
+    fn overrides_rebind_and_unbind() {
+        let keymap =
+            Keymap::from_overrides(&overrides(&[("lock", "Ctrl+Alt+L"), ("new-entry", "")]))
+                .unwrap();
+
+        assert_eq!(
+            keymap.shortcut(Action::Lock).unwrap().to_string(),
+            "Ctrl+Alt+L"
+        );
+        assert_eq!(keymap.shortcut(Action::NewEntry), None);
+        assert_eq!(keymap.bindings()[0].0, Action::Lock);
+    }
+
+    #[test]
+    fn rejects_bad_overrides() {
+        assert_eq!(
+            Keymap::from_overrides(&overrides(&[("launch", "Ctrl+L")])),
+            Err(KeymapError::UnknownAction("launch".to_string()))
+        );
+        assert!(matches!(
+            Keymap::from_overrides(&overrides(&[("lock", "L")])),
+            Err(KeymapError::Reserved(_))
+        ));
+        assert!(matches!(
+            Keymap::from_overrides(&overrides(&[("lock", "Ctrl+Shift+V")])),
+            Err(KeymapError::Reserved(_))
+        ));
+        assert_eq!(
+            Keymap::from_overrides(&overrides(&[("lock", "Ctrl+K")])),
+            Err(KeymapError::Conflict {
+                shortcut: "Ctrl+K".parse().unwrap(),
+                first: Action::CommandPalette,
+                second: Action::Lock,
+            })
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Lilypad domain core.
+//!
+//! Everything that must behave identically across the desktop, CLI, and TUI
+//! frontends lives here: the encrypted vault file, the entry model, the
+//! password generator, strength estimation, master-password policy checks,
+//! and the user's saved preferences and shortcuts. Frontends only translate
+//! user intent into calls on these types.
+
+pub mod archive;
+pub mod backup;
+pub mod bitwarden;
+pub mod clipboard;
+pub mod clock;
+pub mod config;
+pub mod crypto;
+pub mod csv_import;
+pub mod entry;
+pub mod export;
+pub mod generator;
+pub mod kdbx;
+pub mod keymap;
+pub mod passphrase;
+pub mod password_rules;
+pub mod paths;
+pub mod policy;
+pub mod search;
+pub mod secret;
+pub mod session;
+pub mod strength;
+pub mod text;
+pub mod theme;
+pub mod vault;
+
+pub use archive::{Archive, ArchiveError, ArchiveMetadata};
+pub use backup::{Backups, OpenedSnapshot, Snapshot};
+pub use bitwarden::{BitwardenError, BitwardenImport};
+pub use clipboard::ClipboardClear;
+pub use config::{Config, ConfigError};
+pub use csv_import::{CsvError, CsvImport};
+pub use entry::{
+    Attachment, Card, CustomField, Entry, EntryKind, Identity, Login, ValidationError, Wifi,
+    WifiSecurity,
+};
+pub use export::{ExportError, ImportSummary};
+pub use generator::{
+    CharClass, GeneratorError, GeneratorMode, GeneratorSettings, PasswordGenerator,
+};
+pub use kdbx::KdbxError;
+pub use keymap::{Action, Keymap, KeymapError, Shortcut};
+pub use passphrase::PassphraseGenerator;
+pub use password_rules::{PasswordRules, RulesError};
+pub use paths::AppDirs;
+pub use policy::{PasswordPolicy, Requirement};
+pub use search::{fuzzy_score, MatchField, SearchHit, SearchIndex, TextMatch};
+pub use strength::{Strength, StrengthEstimate};
+pub use theme::{Palette, Theme};
+pub use uuid::Uuid;
+pub use vault::{MasterKey, PasswordCheck, Vault, VaultError};
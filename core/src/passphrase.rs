@@ -0,0 +1,154 @@
+//! Word-based (diceware) passphrase generation using the EFF large wordlist.
+
+use crate::generator::GeneratorError;
+use crate::secret::{self, SecretBuffer};
+use crate::text::push_capitalized;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::sync::OnceLock;
+
+const EFF_LARGE_WORDLIST: &str = include_str!("../assets/eff_large_wordlist.txt");
+
+/// The 7,776 words of the EFF large wordlist, in file order.
+pub fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        EFF_LARGE_WORDLIST
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .collect()
+    })
+}
+
+/// Settings for generating memorable passphrases such as `Stark-Canopy-Pushy-Elbow`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassphraseGenerator {
+    pub word_count: usize,
+    pub separator: String,
+    /// Capitalize the first letter of every word.
+    pub capitalize: bool,
+    /// Append one random digit to the end of the passphrase.
+    pub append_digit: bool,
+}
+
+impl Default for PassphraseGenerator {
+    fn default() -> Self {
+        Self {
+            word_count: 6,
+            separator: "-".to_string(),
+            capitalize: false,
+            append_digit: false,
+        }
+    }
+}
+
+impl PassphraseGenerator {
+    pub fn generate(&self) -> Result<SecretBuffer, GeneratorError> {
+        if self.word_count == 0 {
+            return Err(GeneratorError::NoWords);
+        }
+
+        let words = wordlist();
+        let mut rng = rand::thread_rng();
+        let mut passphrase = secret::secret_buffer();
+        let chosen = (0..self.word_count).filter_map(|_| words.choose(&mut rng));
+        for (index, word) in chosen.enumerate() {
+            if index > 0 {
+                passphrase.push_str(&self.separator);
+            }
+            if self.capitalize {
+                push_capitalized(&mut passphrase, word);
+            } else {
+                passphrase.push_str(word);
+            }
+        }
+
+        if self.append_digit {
+            passphrase.push(char::from(b'0' + rng.gen_range(0..10u8)));
+        }
+        Ok(passphrase)
+    }
+
+    /// Entropy in bits of the passphrases these settings produce.
+    ///
+    /// Each word contributes log2(7776) ≈ 12.92 bits and the appended digit
+    /// log2(10) ≈ 3.32 bits; separator and capitalization are deterministic and
+    /// add nothing. With an empty separator and no capitalization, adjacent
+    /// words can run together ambiguously, so the figure is an upper bound.
+    pub fn entropy_bits(&self) -> f64 {
+        let word_bits = (wordlist().len() as f64).log2();
+        let digit_bits = if self.append_digit { 10f64.log2() } else { 0.0 };
+        self.word_count as f64 * word_bits + digit_bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn bundled_wordlist_has_7776_unique_words() {
+        let words = wordlist();
+        assert_eq!(words.len(), 7776);
+        assert_eq!(words.iter().collect::<HashSet<_>>().len(), 7776);
+        assert_eq!(words.first(), Some(&"abacus"));
+        assert_eq!(words.last(), Some(&"zoom"));
+    }
+
+    #[test]
+    fn generates_requested_word_count_with_separator() {
+        let generator = PassphraseGenerator {
+            word_count: 5,
+            separator: " / ".to_string(),
+            ..PassphraseGenerator::default()
+        };
+        let passphrase = generator.generate().unwrap();
+        let parts: Vec<_> = passphrase.split(" / ").collect();
+
+        assert_eq!(parts.len(), 5);
+        assert!(parts.iter().all(|word| wordlist().contains(word)));
+    }
+
+    #[test]
+    fn capitalizes_and_appends_digit() {
+        let generator = PassphraseGenerator {
+            word_count: 4,
+            separator: ".".to_string(),
+            capitalize: true,
+            append_digit: true,
+        };
+        let passphrase = generator.generate().unwrap();
+        let (words, digit) = passphrase.split_at(passphrase.len() - 1);
+
+        assert!(digit.chars().all(|c| c.is_ascii_digit()));
+        for word in words.split('.') {
+            assert!(word.chars().next().unwrap().is_uppercase());
+            assert!(wordlist().contains(&word.to_lowercase().as_str()));
+        }
+    }
+
+    #[test]
+    fn entropy_counts_words_and_digit() {
+        let six_words = PassphraseGenerator::default();
+        assert!((six_words.entropy_bits() - 77.548).abs() < 0.01);
+
+        let with_digit = PassphraseGenerator {
+            append_digit: true,
+            capitalize: true,
+            ..six_words
+        };
+        assert!((with_digit.entropy_bits() - 80.870).abs() < 0.01);
+    }
+
+    #[test]
+    fn rejects_zero_words() {
+        let generator = PassphraseGenerator {
+            word_count: 0,
+            ..PassphraseGenerator::default()
+        };
+        assert_eq!(generator.generate(), Err(GeneratorError::NoWords));
+    }
+}
@@ -0,0 +1,471 @@
+//! Site password rules in the `passwordrules` attribute syntax, e.g.
+//! `required: upper; required: digit; allowed: [-_]; max-consecutive: 2;
+//! minlength: 8; maxlength: 20`.
+//!
+//! Rules are parsed into [`PasswordRules`] and turned into a
+//! [`PasswordGenerator`] whose output always complies. Unknown properties
+//! are ignored, as the syntax asks, so rules written for newer browsers
+//! still load.
+
+use crate::generator::{
+    PasswordGenerator, DIGITS, LOWERCASE, MAX_REQUIRED_SETS, SYMBOLS, UPPERCASE,
+};
+use std::fmt;
+
+/// The `special` class of the syntax.
+pub const SPECIAL: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.? ]";
+
+/// One entry of a `required` or `allowed` list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleClass {
+    Upper,
+    Lower,
+    Digit,
+    Special,
+    AsciiPrintable,
+    Unicode,
+    /// Characters listed in brackets, e.g. `[-_.]`.
+    Custom(String),
+}
+
+impl RuleClass {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "upper" => Some(Self::Upper),
+            "lower" => Some(Self::Lower),
+            "digit" => Some(Self::Digit),
+            "special" => Some(Self::Special),
+            "ascii-printable" => Some(Self::AsciiPrintable),
+            "unicode" => Some(Self::Unicode),
+            _ => None,
+        }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Upper => c.is_ascii_uppercase(),
+            Self::Lower => c.is_ascii_lowercase(),
+            Self::Digit => c.is_ascii_digit(),
+            Self::Special => SPECIAL.contains(c),
+            Self::AsciiPrintable => c.is_ascii() && !c.is_ascii_control(),
+            Self::Unicode => !c.is_control(),
+            Self::Custom(chars) => chars.contains(c),
+        }
+    }
+
+    /// The characters the generator may draw from for this class.
+    fn generator_chars(&self) -> String {
+        match self {
+            Self::Upper => UPPERCASE.to_string(),
+            Self::Lower => LOWERCASE.to_string(),
+            Self::Digit => DIGITS.to_string(),
+            // A space complies but trips up too many forms to generate.
+            Self::Special => SPECIAL.replace(' ', ""),
+            Self::AsciiPrintable | Self::Unicode => {
+                [LOWERCASE, UPPERCASE, DIGITS, SYMBOLS].concat()
+            }
+            Self::Custom(chars) => chars.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RulesError {
+    /// A property without the `name: value` shape.
+    MalformedProperty(String),
+    UnknownClass(String),
+    InvalidNumber(String),
+    UnterminatedSet,
+    /// `minlength` is greater than `maxlength`.
+    LengthRange,
+    /// More distinct `required` properties than the generator supports.
+    TooManyRequired,
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedProperty(property) => {
+                write!(f, "expected `name: value`, found `{property}`")
+            }
+            Self::UnknownClass(name) => write!(f, "unknown character class `{name}`"),
+            Self::InvalidNumber(value) => write!(f, "`{value}` is not a whole number"),
+            Self::UnterminatedSet => write!(f, "a `[` character set is never closed"),
+            Self::LengthRange => write!(f, "minlength is greater than maxlength"),
+            Self::TooManyRequired => write!(
+                f,
+                "more than {MAX_REQUIRED_SETS} different `required` properties"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// A parsed password-rules string.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PasswordRules {
+    /// Each `required` property: the password needs one character from it.
+    pub required: Vec<Vec<RuleClass>>,
+    pub allowed: Vec<RuleClass>,
+    pub max_consecutive: Option<usize>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl PasswordRules {
+    pub fn parse(rules: &str) -> Result<Self, RulesError> {
+        let mut parsed = Self::default();
+        for property in split_properties(rules)? {
+            let property = property.trim();
+            if property.is_empty() {
+                continue;
+            }
+            let (name, value) = property
+                .split_once(':')
+                .ok_or_else(|| RulesError::MalformedProperty(property.to_string()))?;
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "required" => parsed.required.push(parse_classes(value)?),
+                "allowed" => parsed.allowed.extend(parse_classes(value)?),
+                "max-consecutive" => {
+                    let limit = parse_number(value)?;
+                    parsed.max_consecutive =
+                        Some(parsed.max_consecutive.map_or(limit, |old| old.min(limit)));
+                }
+                "minlength" => {
+                    let limit = parse_number(value)?;
+                    parsed.min_length = Some(parsed.min_length.map_or(limit, |old| old.max(limit)));
+                }
+                "maxlength" => {
+                    let limit = parse_number(value)?;
+                    parsed.max_length = Some(parsed.max_length.map_or(limit, |old| old.min(limit)));
+                }
+                _ => {}
+            }
+        }
+
+        if let (Some(min), Some(max)) = (parsed.min_length, parsed.max_length) {
+            if min > max {
+                return Err(RulesError::LengthRange);
+            }
+        }
+        parsed.merge_required();
+        if parsed.required.len() > MAX_REQUIRED_SETS {
+            return Err(RulesError::TooManyRequired);
+        }
+        Ok(parsed)
+    }
+
+    /// Drops `required` properties that repeat another or list every class
+    /// of another, since a password meeting the shorter one meets them too.
+    fn merge_required(&mut self) {
+        let required = std::mem::take(&mut self.required);
+        let covers = |alternatives: &[RuleClass], other: &[RuleClass]| {
+            other.iter().all(|class| alternatives.contains(class))
+        };
+        for (index, alternatives) in required.iter().enumerate() {
+            let redundant = required.iter().enumerate().any(|(other_index, other)| {
+                other_index != index
+                    && covers(alternatives, other)
+                    && (!covers(other, alternatives) || other_index < index)
+            });
+            if !redundant {
+                self.required.push(alternatives.clone());
+            }
+        }
+    }
+
+    /// Returns a copy of `base` restricted to what the rules allow.
+    ///
+    /// The character pool becomes the union of the required and allowed
+    /// classes (everything printable when neither is given), each `required`
+    /// property becomes a required set, and the length is clamped into the
+    /// allowed range. `base`'s look-alike exclusion carries over since a
+    /// smaller pool still complies.
+    pub fn configure(&self, base: &PasswordGenerator) -> PasswordGenerator {
+        let classes: Vec<&RuleClass> = self
+            .required
+            .iter()
+            .flatten()
+            .chain(&self.allowed)
+            .collect();
+        let pool: String = if classes.is_empty() {
+            RuleClass::AsciiPrintable.generator_chars()
+        } else {
+            classes
+                .iter()
+                .map(|class| class.generator_chars())
+                .collect()
+        };
+
+        let mut length = base.length;
+        if let Some(max) = self.max_length {
+            length = length.min(max);
+        }
+        if let Some(min) = self.min_length {
+            length = length.max(min);
+        }
+
+        PasswordGenerator {
+            length,
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            exclude_ambiguous: base.exclude_ambiguous,
+            include_chars: pool,
+            exclude_chars: String::new(),
+            min_lowercase: 0,
+            min_uppercase: 0,
+            min_digits: 0,
+            min_symbols: 0,
+            max_consecutive: self.max_consecutive,
+            required_sets: self
+                .required
+                .iter()
+                .map(|alternatives| {
+                    alternatives
+                        .iter()
+                        .map(RuleClass::generator_chars)
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether `password` satisfies every rule.
+    pub fn complies(&self, password: &str) -> bool {
+        let chars: Vec<char> = password.chars().collect();
+        let length_ok = self.min_length.is_none_or(|min| chars.len() >= min)
+            && self.max_length.is_none_or(|max| chars.len() <= max);
+        let runs_ok = self
+            .max_consecutive
+            .is_none_or(|max| chars.chunk_by(|a, b| a == b).all(|run| run.len() <= max));
+        let required_ok = self.required.iter().all(|alternatives| {
+            chars
+                .iter()
+                .any(|&c| alternatives.iter().any(|class| class.contains(c)))
+        });
+        let classes: Vec<&RuleClass> = self
+            .required
+            .iter()
+            .flatten()
+            .chain(&self.allowed)
+            .collect();
+        let allowed_ok = chars.iter().all(|&c| {
+            if classes.is_empty() {
+                RuleClass::AsciiPrintable.contains(c)
+            } else {
+                classes.iter().any(|class| class.contains(c))
+            }
+        });
+        length_ok && runs_ok && required_ok && allowed_ok
+    }
+}
+
+/// Splits on `;` outside of `[...]` sets, since a set may list `;` itself.
+fn split_properties(rules: &str) -> Result<Vec<&str>, RulesError> {
+    let mut properties = Vec::new();
+    let mut start = 0;
+    let mut in_set = false;
+    for (index, c) in rules.char_indices() {
+        match c {
+            '[' if !in_set => in_set = true,
+            ']' if in_set && closes_set(&rules[index + 1..]) => in_set = false,
+            ';' if !in_set => {
+                properties.push(&rules[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if in_set {
+        return Err(RulesError::UnterminatedSet);
+    }
+    properties.push(&rules[start..]);
+    Ok(properties)
+}
+
+/// A `]` ends a set only when followed by a list or property separator, so
+/// `[]]` lists the bracket itself.
+fn closes_set(rest: &str) -> bool {
+    matches!(rest.trim_start().chars().next(), None | Some(',' | ';'))
+}
+
+fn parse_classes(value: &str) -> Result<Vec<RuleClass>, RulesError> {
+    let mut classes = Vec::new();
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        if let Some(set) = rest.strip_prefix('[') {
+            let end = set
+                .char_indices()
+                .find(|&(index, c)| c == ']' && closes_set(&set[index + 1..]))
+                .map(|(index, _)| index)
+                .ok_or(RulesError::UnterminatedSet)?;
+            let mut chars = String::new();
+            for c in set[..end].chars() {
+                if !chars.contains(c) {
+                    chars.push(c);
+                }
+            }
+            classes.push(RuleClass::Custom(chars));
+            rest = &set[end + 1..];
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let name = rest[..end].trim();
+            classes.push(
+                RuleClass::parse(name).ok_or_else(|| RulesError::UnknownClass(name.to_string()))?,
+            );
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Ok(classes)
+}
+
+fn parse_number(value: &str) -> Result<usize, RulesError> {
+    value
+        .parse()
+        .map_err(|_| RulesError::InvalidNumber(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str =
+        "required: upper; allowed: digit, [-_]; max-consecutive: 2; minlength: 8; maxlength: 20";
+
+    #[test]
+    fn parses_every_property() {
+        let rules = PasswordRules::parse(EXAMPLE).unwrap();
+        assert_eq!(rules.required, [vec![RuleClass::Upper]]);
+        assert_eq!(
+            rules.allowed,
+            [RuleClass::Digit, RuleClass::Custom("-_".to_string())]
+        );
+        assert_eq!(rules.max_consecutive, Some(2));
+        assert_eq!((rules.min_length, rules.max_length), (Some(8), Some(20)));
+    }
+
+    #[test]
+    fn sets_may_contain_separators_and_brackets() {
+        let rules = PasswordRules::parse("allowed: [;,]], lower; required: [[]").unwrap();
+        assert_eq!(
+            rules.allowed,
+            [RuleClass::Custom(";,]".to_string()), RuleClass::Lower]
+        );
+        assert_eq!(rules.required, [vec![RuleClass::Custom("[".to_string())]]);
+    }
+
+    #[test]
+    fn ignores_unknown_properties_and_rejects_bad_values() {
+        assert!(PasswordRules::parse("passwordrules-future: 3; minlength: 4").is_ok());
+        assert_eq!(
+            PasswordRules::parse("required: uppercase"),
+            Err(RulesError::UnknownClass("uppercase".to_string()))
+        );
+        assert_eq!(
+            PasswordRules::parse("minlength: eight"),
+            Err(RulesError::InvalidNumber("eight".to_string()))
+        );
+        assert_eq!(
+            PasswordRules::parse("allowed: [abc"),
+            Err(RulesError::UnterminatedSet)
+        );
+        assert_eq!(
+            PasswordRules::parse("minlength: 30; maxlength: 20"),
+            Err(RulesError::LengthRange)
+        );
+        assert!(matches!(
+            PasswordRules::parse("lower"),
+            Err(RulesError::MalformedProperty(_))
+        ));
+    }
+
+    #[test]
+    fn repeated_required_properties_merge_and_too_many_are_refused() {
+        let rules = PasswordRules::parse(
+            "required: upper; required: upper; required: upper, digit; required: [-]; required: [-], lower",
+        )
+        .unwrap();
+        assert_eq!(
+            rules.required,
+            [
+                vec![RuleClass::Upper],
+                vec![RuleClass::Custom("-".to_string())]
+            ]
+        );
+
+        let distinct = |count: usize| -> String {
+            DIGITS
+                .chars()
+                .take(count)
+                .map(|digit| format!("required: [{digit}]; "))
+                .collect()
+        };
+        let rules = PasswordRules::parse(&distinct(MAX_REQUIRED_SETS)).unwrap();
+        assert!(rules
+            .configure(&PasswordGenerator::default())
+            .generate()
+            .is_ok());
+        assert_eq!(
+            PasswordRules::parse(&distinct(MAX_REQUIRED_SETS + 1)),
+            Err(RulesError::TooManyRequired)
+        );
+        let pasted = distinct(MAX_REQUIRED_SETS).repeat(50);
+        assert_eq!(
+            PasswordRules::parse(&pasted).unwrap().required.len(),
+            MAX_REQUIRED_SETS
+        );
+    }
+
+    #[test]
+    fn configured_generator_always_complies() {
+        let rules = PasswordRules::parse(EXAMPLE).unwrap();
+        let base = PasswordGenerator {
+            length: 32,
+            ..PasswordGenerator::default()
+        };
+        let generator = rules.configure(&base);
+        assert_eq!(generator.length, 20);
+
+        for _ in 0..200 {
+            let password = generator.generate().unwrap();
+            assert!(rules.complies(&password), "{}", password.as_str());
+        }
+    }
+
+    #[test]
+    fn length_is_raised_to_the_minimum() {
+        let rules = PasswordRules::parse("minlength: 24; required: lower, digit").unwrap();
+        let generator = rules.configure(&PasswordGenerator::default());
+        assert_eq!(generator.length, 24);
+        let password = generator.generate().unwrap();
+        assert!(password
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn special_class_uses_the_rules_definition() {
+        let rules = PasswordRules::parse("required: special; allowed: lower").unwrap();
+        let generator = rules.configure(&PasswordGenerator::default());
+        assert!(!generator.charset().contains(&'/'));
+        assert!(rules.complies("abc-def!ghij"));
+        assert!(!rules.complies("abc/defghij"));
+    }
+
+    #[test]
+    fn complies_checks_each_rule() {
+        let rules = PasswordRules::parse(EXAMPLE).unwrap();
+        assert!(rules.complies("AB-123_XYZ"));
+        assert!(!rules.complies("12-345_678"), "missing upper");
+        assert!(!rules.complies("AB-123_xyz"), "lowercase not allowed");
+        assert!(!rules.complies("ABBBC-123"), "run of three");
+        assert!(!rules.complies("A-1"), "too short");
+        assert!(!rules.complies("AB 123 XYZ"), "space not allowed");
+    }
+}
@@ -0,0 +1,95 @@
+//! Platform directories shared by the frontends, so the desktop app, the CLI,
+//! and the TUI read the same `config.toml` and open the same vault file.
+
+use crate::backup::Backups;
+use crate::config::Config;
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+/// File name of the vault in the platform data directory.
+pub const VAULT_FILE: &str = "vault.lilypad";
+/// Folder in the platform data directory holding vault snapshots.
+pub const BACKUP_DIR: &str = "backups";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppDirs {
+    /// Holds `config.toml` and the `themes` folder.
+    pub config_dir: PathBuf,
+    /// Holds the vault unless the config points elsewhere.
+    pub data_dir: PathBuf,
+}
+
+impl AppDirs {
+    /// The per-user Lilypad directories, or `None` when the platform has no
+    /// home directory to derive them from.
+    pub fn platform() -> Option<Self> {
+        let project_dirs = ProjectDirs::from("", "", "Lilypad")?;
+        Some(Self {
+            config_dir: project_dirs.config_dir().to_path_buf(),
+            data_dir: project_dirs.data_dir().to_path_buf(),
+        })
+    }
+
+    pub fn default_vault_path(&self) -> PathBuf {
+        self.data_dir.join(VAULT_FILE)
+    }
+
+    /// The vault `config` points at, or the default location.
+    pub fn vault_path(&self, config: &Config) -> PathBuf {
+        config
+            .vault
+            .path
+            .clone()
+            .unwrap_or_else(|| self.default_vault_path())
+    }
+
+    pub fn backup_dir(&self) -> PathBuf {
+        self.data_dir.join(BACKUP_DIR)
+    }
+
+    /// Where saves keep snapshots under `config`, or `None` when they are
+    /// turned off.
+    pub fn backups(&self, config: &Config) -> Option<Backups> {
+        (config.backup.keep > 0).then(|| Backups::new(self.backup_dir(), config.backup.keep))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn config_path_overrides_the_default() {
+        let dirs = AppDirs {
+            config_dir: PathBuf::from("/config"),
+            data_dir: PathBuf::from("/data"),
+        };
+        let mut config = Config::default();
+        assert_eq!(
+            dirs.vault_path(&config),
+            PathBuf::from("/data/vault.lilypad")
+        );
+
+        config.vault.path = Some(PathBuf::from("/elsewhere/work.lilypad"));
+        assert_eq!(
+            dirs.vault_path(&config),
+            PathBuf::from("/elsewhere/work.lilypad")
+        );
+    }
+
+    #[test]
+    fn backups_follow_the_config() {
+        let dirs = AppDirs {
+            config_dir: PathBuf::from("/config"),
+            data_dir: PathBuf::from("/data"),
+        };
+        let mut config = Config::default();
+        let backups = dirs.backups(&config).unwrap();
+        assert_eq!(backups.dir(), Path::new("/data/backups"));
+        assert_eq!(backups.keep(), 10);
+
+        config.backup.keep = 0;
+        assert_eq!(dirs.backups(&config), None);
+    }
+}
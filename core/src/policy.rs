@@ -0,0 +1,156 @@
+//! Requirement checks for user-chosen passwords such as the master password.
+
+use crate::strength::{self, Strength};
+
+/// Whether a rule is enabled, its checklist label, and the character test.
+type ClassRule = (bool, &'static str, fn(char) -> bool);
+
+/// One line of a requirement checklist and whether the password satisfies it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Requirement {
+    pub label: String,
+    pub satisfied: bool,
+}
+
+/// Rules a chosen password must meet. The default is the master-password policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Minimum rating from the [`strength::estimate`] guess estimator, so
+    /// padding a weak password with one of each class does not pass.
+    pub min_strength: Option<Strength>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 12,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: false,
+            require_symbol: true,
+            min_strength: Some(Strength::Moderate),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Evaluates every enabled rule against `password`, in display order.
+    pub fn requirements(&self, password: &str) -> Vec<Requirement> {
+        let mut requirements = vec![Requirement {
+            label: format!("At least {} characters", self.min_length),
+            satisfied: password.chars().count() >= self.min_length,
+        }];
+
+        let class_rules: [ClassRule; 4] = [
+            (self.require_lowercase, "Contains a lowercase letter", |c| {
+                c.is_ascii_lowercase()
+            }),
+            (
+                self.require_uppercase,
+                "Contains an uppercase letter",
+                |c| c.is_ascii_uppercase(),
+            ),
+            (self.require_digit, "Contains a digit", |c| {
+                c.is_ascii_digit()
+            }),
+            (self.require_symbol, "Contains a special character", |c| {
+                !c.is_ascii_alphanumeric() && !c.is_whitespace()
+            }),
+        ];
+
+        requirements.extend(
+            class_rules
+                .into_iter()
+                .filter(|(enabled, _, _)| *enabled)
+                .map(|(_, label, predicate)| Requirement {
+                    label: label.to_string(),
+                    satisfied: password.chars().any(predicate),
+                }),
+        );
+
+        if let Some(min_strength) = self.min_strength {
+            let estimate = strength::estimate(password, &[]);
+            requirements.push(Requirement {
+                label: format!("Hard to guess (rated {} or better)", min_strength.label()),
+                satisfied: estimate.strength >= min_strength,
+            });
+        }
+        requirements
+    }
+
+    pub fn is_satisfied(&self, password: &str) -> bool {
+        self.requirements(password)
+            .iter()
+            .all(|requirement| requirement.satisfied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_lists_master_password_rules() {
+        let labels: Vec<_> = PasswordPolicy::default()
+            .requirements("")
+            .into_iter()
+            .map(|requirement| requirement.label)
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "At least 12 characters",
+                "Contains a lowercase letter",
+                "Contains an uppercase letter",
+                "Contains a special character",
+                "Hard to guess (rated Moderate or better)",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_password_meeting_every_rule() {
+        assert!(PasswordPolicy::default().is_satisfied("Quiet-Lantern-Orbit-Fennel-9"));
+    }
+
+    #[test]
+    fn reports_each_unmet_rule() {
+        let requirements = PasswordPolicy::default().requirements("short");
+        let unmet: Vec<_> = requirements
+            .iter()
+            .filter(|requirement| !requirement.satisfied)
+            .map(|requirement| requirement.label.as_str())
+            .collect();
+        assert_eq!(
+            unmet,
+            [
+                "At least 12 characters",
+                "Contains an uppercase letter",
+                "Contains a special character",
+                "Hard to guess (rated Moderate or better)",
+            ]
+        );
+    }
+
+    #[test]
+    fn class_checks_alone_do_not_pass_weak_passwords() {
+        let requirements = PasswordPolicy::default().requirements("aaaaaaaaaaaA!");
+        let unmet: Vec<_> = requirements
+            .iter()
+            .filter(|requirement| !requirement.satisfied)
+            .map(|requirement| requirement.label.as_str())
+            .collect();
+        assert_eq!(unmet, ["Hard to guess (rated Moderate or better)"]);
+    }
+
+    #[test]
+    fn whitespace_is_not_a_special_character() {
+        let policy = PasswordPolicy::default();
+        assert!(!policy.is_satisfied("Long enough password"));
+    }
+}
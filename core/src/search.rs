@@ -0,0 +1,501 @@
+//! Ranked search over vault entries.
+//!
+//! [`SearchIndex`] folds the searchable text of every entry once, so each
+//! keystroke only scans pre-lowercased strings. A query is split on whitespace
+//! and every term must match at least one field; terms score higher for
+//! prefix and word-boundary hits than for plain substrings, and fuzzy
+//! (in-order subsequence) hits score lowest. Matches in the title outrank
+//! matches in the username, URL host, or tags, which in turn outrank notes.
+//!
+//! [`fuzzy_score`] applies the same term scoring to a single string, for
+//! frontends ranking short labels such as command-palette actions.
+
+use crate::entry::{Entry, EntryKind};
+use std::ops::Range;
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// Which part of an entry produced its best match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchField {
+    Title,
+    Username,
+    Host,
+    Tag,
+    Notes,
+}
+
+impl MatchField {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Username => "username",
+            Self::Host => "website",
+            Self::Tag => "tag",
+            Self::Notes => "notes",
+        }
+    }
+
+    fn weight(self) -> u32 {
+        match self {
+            Self::Title => 3,
+            Self::Username | Self::Host | Self::Tag => 2,
+            Self::Notes => 1,
+        }
+    }
+
+    /// Notes are long free text where fuzzy subsequences match almost anything.
+    fn allows_fuzzy(self) -> bool {
+        !matches!(self, Self::Notes)
+    }
+}
+
+/// One entry matching a query, with what is needed to render it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchHit {
+    /// Position of the entry in the slice the index was built from.
+    pub index: usize,
+    pub id: Uuid,
+    pub score: u32,
+    /// Field that contributed the strongest match for the first query term.
+    pub field: MatchField,
+    /// Matched character ranges (char indices) within the entry title.
+    pub title_matches: Vec<Range<usize>>,
+}
+
+struct IndexedEntry {
+    id: Uuid,
+    /// Folded field text; the title is always first.
+    fields: Vec<(MatchField, String)>,
+}
+
+/// Folded copies of usernames and notes are as sensitive as the originals.
+impl Drop for IndexedEntry {
+    fn drop(&mut self) {
+        for (_, text) in &mut self.fields {
+            text.zeroize();
+        }
+    }
+}
+
+/// Pre-folded searchable text for a snapshot of vault entries.
+///
+/// Rebuild it whenever the entries change; hit indices refer to that snapshot.
+pub struct SearchIndex {
+    entries: Vec<IndexedEntry>,
+}
+
+impl SearchIndex {
+    pub fn build(entries: &[Entry]) -> Self {
+        Self {
+            entries: entries.iter().map(index_entry).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns matching entries, best first. An empty query returns every
+    /// entry in its original order.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms: Vec<String> = query.split_whitespace().map(fold).collect();
+        if terms.is_empty() {
+            return self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| SearchHit {
+                    index,
+                    id: entry.id,
+                    score: 0,
+                    field: MatchField::Title,
+                    title_matches: Vec::new(),
+                })
+                .collect();
+        }
+
+        let mut hits: Vec<SearchHit> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| score_entry(index, entry, &terms))
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| {
+                    self.entries[a.index].fields[0]
+                        .1
+                        .cmp(&self.entries[b.index].fields[0].1)
+                })
+                .then_with(|| a.index.cmp(&b.index))
+        });
+        hits
+    }
+}
+
+fn index_entry(entry: &Entry) -> IndexedEntry {
+    let mut fields = vec![(MatchField::Title, fold(&entry.title))];
+
+    if let Some(account) = entry.username() {
+        fields.push((MatchField::Username, fold(account)));
+    }
+
+    if let EntryKind::Login(login) = &entry.kind {
+        fields.extend(
+            login
+                .urls
+                .iter()
+                .filter_map(|url| url_host(url))
+                .map(|host| (MatchField::Host, fold(host))),
+        );
+    }
+
+    fields.extend(entry.tags.iter().map(|tag| (MatchField::Tag, fold(tag))));
+
+    if !entry.notes.is_empty() {
+        fields.push((MatchField::Notes, fold(&entry.notes)));
+    }
+
+    IndexedEntry {
+        id: entry.id,
+        fields,
+    }
+}
+
+fn score_entry(index: usize, entry: &IndexedEntry, terms: &[String]) -> Option<SearchHit> {
+    let mut total = 0;
+    let mut first_field = None;
+    let mut title_matches = Vec::new();
+
+    for term in terms {
+        let mut best: Option<(u32, MatchField)> = None;
+        for (field, text) in &entry.fields {
+            let Some(found) = match_term(text, term, field.allows_fuzzy()) else {
+                continue;
+            };
+            let score = found.score * field.weight();
+            if *field == MatchField::Title {
+                title_matches.extend(found.ranges);
+            }
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, *field));
+            }
+        }
+
+        let (score, field) = best?;
+        total += score;
+        first_field.get_or_insert(field);
+    }
+
+    title_matches.sort_by_key(|range| range.start);
+    Some(SearchHit {
+        index,
+        id: entry.id,
+        score: total,
+        field: first_field.unwrap_or(MatchField::Title),
+        title_matches: merge_ranges(title_matches),
+    })
+}
+
+/// How well a query matched a string, from [`fuzzy_score`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextMatch {
+    pub score: u32,
+    /// Matched character ranges (char indices), sorted and merged.
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// Scores `text` against every whitespace-separated term of `query`, with the
+/// prefix, word-boundary, substring, and fuzzy tiers used for entries.
+/// Returns `None` unless every term matches; an empty query matches with a
+/// score of zero.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<TextMatch> {
+    let text = fold(text);
+    let mut score = 0;
+    let mut ranges = Vec::new();
+    for term in query.split_whitespace().map(fold) {
+        let found = match_term(&text, &term, true)?;
+        score += found.score;
+        ranges.extend(found.ranges);
+    }
+    ranges.sort_by_key(|range| range.start);
+    Some(TextMatch {
+        score,
+        ranges: merge_ranges(ranges),
+    })
+}
+
+struct TermMatch {
+    score: u32,
+    ranges: Vec<Range<usize>>,
+}
+
+fn match_term(text: &str, term: &str, allow_fuzzy: bool) -> Option<TermMatch> {
+    if let Some(byte_start) = text.find(term) {
+        let start = text[..byte_start].chars().count();
+        let length = term.chars().count();
+        let at_word_boundary = text[..byte_start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_alphanumeric());
+        let score = match (byte_start == 0, at_word_boundary) {
+            (true, _) => 100,
+            (false, true) => 80,
+            (false, false) => 60,
+        };
+        return Some(TermMatch {
+            score,
+            ranges: std::iter::once(start..start + length).collect(),
+        });
+    }
+
+    if allow_fuzzy && term.chars().count() > 1 {
+        return fuzzy_match(text, term);
+    }
+    None
+}
+
+/// Matches `term` as an in-order subsequence of `text`, preferring tight runs.
+fn fuzzy_match(text: &str, term: &str) -> Option<TermMatch> {
+    let mut needle = term.chars().peekable();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut gaps = 0u32;
+    let mut last_match = None;
+
+    for (position, c) in text.chars().enumerate() {
+        let Some(&wanted) = needle.peek() else {
+            break;
+        };
+        if c != wanted {
+            continue;
+        }
+        needle.next();
+
+        match (ranges.last_mut(), last_match) {
+            (Some(range), Some(previous)) if previous + 1 == position => range.end = position + 1,
+            _ => {
+                if let Some(previous) = last_match {
+                    gaps += u32::try_from(position - previous - 1).unwrap_or(u32::MAX);
+                }
+                ranges.push(position..position + 1);
+            }
+        }
+        last_match = Some(position);
+    }
+
+    if needle.peek().is_some() {
+        return None;
+    }
+
+    let fragment_penalty = 4 * u32::try_from(ranges.len()).unwrap_or(u32::MAX);
+    let score = 40u32
+        .saturating_sub(fragment_penalty)
+        .saturating_sub(gaps.min(20))
+        .max(1);
+    Some(TermMatch { score, ranges })
+}
+
+fn merge_ranges(ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Lowercases one char at a time so char indices in the folded string line up
+/// with the original, which keeps title highlight ranges valid.
+fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+/// Extracts the host from a URL, tolerating missing schemes (`example.com/login`).
+pub fn url_host(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if host_port.starts_with('[') {
+        host_port
+            .split_once(']')
+            .map_or(host_port, |(host, _)| host.trim_start_matches('['))
+    } else {
+        host_port.split(':').next().unwrap_or_default()
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::Login;
+
+    fn login(title: &str, username: &str, url: &str) -> Entry {
+        Entry::new(
+            title,
+            EntryKind::Login(Login {
+                username: username.to_string(),
+                password: "secret".to_string(),
+                urls: vec![url.to_string()],
+                ..Login::default()
+            }),
+        )
+    }
+
+    fn titles(index: &SearchIndex, entries: &[Entry], query: &str) -> Vec<String> {
+        index
+            .search(query)
+            .into_iter()
+            .map(|hit| entries[hit.index].title.clone())
+            .collect()
+    }
+
+    #[test]
+    fn empty_query_returns_everything_in_order() {
+        let entries = vec![login("Zeta", "", ""), login("Alpha", "", "")];
+        let index = SearchIndex::build(&entries);
+        assert_eq!(titles(&index, &entries, "  "), ["Zeta", "Alpha"]);
+    }
+
+    #[test]
+    fn prefix_beats_word_boundary_beats_substring_beats_fuzzy() {
+        let entries = vec![
+            login("Webmail", "", ""),
+            login("Mailbox", "", ""),
+            login("My mail", "", ""),
+            login("Gmail", "", ""),
+            login("Main library", "", ""),
+        ];
+        let index = SearchIndex::build(&entries);
+        assert_eq!(
+            titles(&index, &entries, "mail"),
+            ["Mailbox", "My mail", "Gmail", "Webmail", "Main library"]
+        );
+    }
+
+    #[test]
+    fn title_matches_outrank_other_fields() {
+        let mut tagged = login("Bank", "", "");
+        tagged.tags.push("github".to_string());
+        let entries = vec![
+            tagged,
+            login("Code", "dev", "https://github.com/login"),
+            login("GitHub", "", ""),
+        ];
+        let index = SearchIndex::build(&entries);
+        let hits = index.search("github");
+
+        assert_eq!(entries[hits[0].index].title, "GitHub");
+        assert_eq!(hits[0].field, MatchField::Title);
+        assert_eq!(hits.len(), 3);
+    }
+
+    #[test]
+    fn every_term_must_match_some_field() {
+        let mut note = Entry::new("Router", EntryKind::SecureNote);
+        note.notes = "Admin password for the basement router".to_string();
+        let entries = vec![note, login("Router portal", "admin", "")];
+        let index = SearchIndex::build(&entries);
+
+        assert_eq!(titles(&index, &entries, "router basement"), ["Router"]);
+        assert!(index.search("router kitchen").is_empty());
+    }
+
+    #[test]
+    fn notes_do_not_match_fuzzily() {
+        let mut note = Entry::new("Plan", EntryKind::SecureNote);
+        note.notes = "a quick brown fox".to_string();
+        let index = SearchIndex::build(&[note]);
+
+        assert_eq!(index.search("brown").len(), 1);
+        assert!(index.search("qbf").is_empty());
+    }
+
+    #[test]
+    fn reports_title_highlight_ranges() {
+        let entries = vec![login("Ünïcode Mail", "", "")];
+        let index = SearchIndex::build(&entries);
+
+        let mail = &index.search("mail")[0].title_matches;
+        assert_eq!((mail.len(), &mail[0]), (1, &(8..12)));
+        assert_eq!(index.search("ümail")[0].title_matches, [0..1, 8..12]);
+        assert_eq!(index.search("ÜNÏ mail")[0].title_matches, [0..3, 8..12]);
+    }
+
+    #[test]
+    fn matches_url_host_but_not_path() {
+        let entries = vec![login(
+            "Work",
+            "me",
+            "https://user@intranet.example.com:8443/payroll",
+        )];
+        let index = SearchIndex::build(&entries);
+
+        assert_eq!(index.search("intranet")[0].field, MatchField::Host);
+        assert!(index.search("payroll").is_empty());
+    }
+
+    #[test]
+    fn scores_single_strings() {
+        let lock = fuzzy_score("lock", "Lock vault").unwrap();
+        let fuzzy = fuzzy_score("lkvt", "Lock vault").unwrap();
+        assert!(lock.score > fuzzy.score);
+        assert_eq!(lock.ranges, vec![0..4]);
+
+        assert_eq!(
+            fuzzy_score("go sec", "Go to Security").unwrap().ranges,
+            [0..2, 6..9]
+        );
+        assert_eq!(fuzzy_score("", "Anything").unwrap().score, 0);
+        assert!(fuzzy_score("gen xyz", "Generate").is_none());
+    }
+
+    #[test]
+    fn extracts_hosts() {
+        assert_eq!(url_host("https://example.com/login"), Some("example.com"));
+        assert_eq!(url_host("example.com:8080"), Some("example.com"));
+        assert_eq!(
+            url_host("ftp://me@files.example.org"),
+            Some("files.example.org")
+        );
+        assert_eq!(url_host("http://[::1]:80/"), Some("::1"));
+        assert_eq!(url_host(""), None);
+    }
+
+    #[test]
+    fn handles_large_vaults() {
+        let entries: Vec<Entry> = (0..20_000)
+            .map(|n| {
+                login(
+                    &format!("Account {n}"),
+                    &format!("user{n}"),
+                    "https://example.com",
+                )
+            })
+            .collect();
+        let index = SearchIndex::build(&entries);
+
+        assert_eq!(index.len(), 20_000);
+        assert_eq!(
+            index.search("account 19999").first().map(|hit| hit.index),
+            Some(19_999)
+        );
+    }
+}
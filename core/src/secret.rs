@@ -0,0 +1,144 @@
+//! Types that hold secrets in memory and wipe them when dropped.
+//!
+//! - [`LockedKey`] holds the derived vault key on its own page, locked into
+//!   RAM where the OS allows it so it is never written to swap.
+//! - [`SecretBuffer`] is a zeroizing `String` for editable secrets such as
+//!   the master password field. Its capacity is reserved up front so typing
+//!   does not reallocate and leave stale copies behind.
+//! - [`SecretString`] (from `secrecy`) is for secrets that are only passed
+//!   around, never edited in place; reading one requires `expose_secret`.
+
+use crate::crypto::KEY_LEN;
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ptr::NonNull;
+
+pub use secrecy::{ExposeSecret, SecretString};
+pub use zeroize::{Zeroize, Zeroizing};
+
+/// Bytes reserved for an editable secret; longer input still works but may
+/// reallocate.
+pub const SECRET_CAPACITY: usize = 256;
+
+/// A zeroizing string for secrets edited in place.
+pub type SecretBuffer = Zeroizing<String>;
+
+/// An empty [`SecretBuffer`] with [`SECRET_CAPACITY`] reserved.
+pub fn secret_buffer() -> SecretBuffer {
+    Zeroizing::new(String::with_capacity(SECRET_CAPACITY))
+}
+
+/// Copies `value` into a fresh [`SecretBuffer`].
+pub fn secret_buffer_from(value: &str) -> SecretBuffer {
+    let mut buffer = Zeroizing::new(String::with_capacity(SECRET_CAPACITY.max(value.len())));
+    buffer.push_str(value);
+    buffer
+}
+
+/// A vault key on a page of its own, `mlock`ed when possible and zeroed
+/// before the page is unlocked and freed.
+pub struct LockedKey {
+    // Declared first so the page is unlocked before the memory is freed.
+    guard: Option<region::LockGuard>,
+    bytes: NonNull<[u8; KEY_LEN]>,
+    layout: Layout,
+}
+
+// SAFETY: `LockedKey` exclusively owns its allocation, like a `Box`.
+unsafe impl Send for LockedKey {}
+unsafe impl Sync for LockedKey {}
+
+impl LockedKey {
+    /// Allocates a zeroed key. Locking failures (e.g. `RLIMIT_MEMLOCK` is
+    /// exhausted) are tolerated; check [`LockedKey::is_locked`].
+    pub fn zeroed() -> Self {
+        // A whole page per key, so unlocking one key's page can never unlock
+        // memory belonging to another.
+        let page = region::page::size().max(KEY_LEN);
+        let layout = Layout::from_size_align(page, page).expect("page size is a power of two");
+        // SAFETY: `layout` has a non-zero size.
+        let pointer = unsafe { alloc::alloc_zeroed(layout) };
+        let Some(bytes) = NonNull::new(pointer.cast::<[u8; KEY_LEN]>()) else {
+            alloc::handle_alloc_error(layout);
+        };
+        let guard = region::lock(pointer.cast_const(), layout.size()).ok();
+        Self {
+            guard,
+            bytes,
+            layout,
+        }
+    }
+
+    pub fn from_bytes(mut bytes: [u8; KEY_LEN]) -> Self {
+        let mut key = Self::zeroed();
+        key.as_bytes_mut().copy_from_slice(&bytes);
+        bytes.zeroize();
+        key
+    }
+
+    /// Whether the OS agreed to keep the key out of swap.
+    pub fn is_locked(&self) -> bool {
+        self.guard.is_some()
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        // SAFETY: `bytes` points to a live, initialized allocation owned by `self`.
+        unsafe { self.bytes.as_ref() }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8; KEY_LEN] {
+        // SAFETY: as above, and `&mut self` guarantees exclusive access.
+        unsafe { self.bytes.as_mut() }
+    }
+}
+
+impl Drop for LockedKey {
+    fn drop(&mut self) {
+        self.as_bytes_mut().zeroize();
+        self.guard.take();
+        // SAFETY: allocated in `zeroed` with this exact layout.
+        unsafe { alloc::dealloc(self.bytes.as_ptr().cast(), self.layout) };
+    }
+}
+
+impl fmt::Debug for LockedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockedKey")
+            .field("locked", &self.is_locked())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locked_key_round_trips_bytes() {
+        let key = LockedKey::from_bytes([7; KEY_LEN]);
+        assert_eq!(key.as_bytes(), &[7; KEY_LEN]);
+        assert!(!format!("{key:?}").contains('7'));
+    }
+
+    #[test]
+    fn keys_do_not_share_pages() {
+        let first = LockedKey::zeroed();
+        let second = LockedKey::zeroed();
+        let page = region::page::size();
+        let first_page = first.as_bytes().as_ptr() as usize / page;
+        let second_page = second.as_bytes().as_ptr() as usize / page;
+        assert_ne!(first_page, second_page);
+    }
+
+    #[test]
+    fn secret_buffers_reserve_capacity() {
+        let mut buffer = secret_buffer();
+        let capacity = buffer.capacity();
+        buffer.push_str("correct horse battery staple");
+        assert_eq!(buffer.capacity(), capacity);
+        assert!(capacity >= SECRET_CAPACITY);
+
+        let long = "x".repeat(SECRET_CAPACITY * 2);
+        assert_eq!(secret_buffer_from(&long).as_str(), long);
+    }
+}
@@ -0,0 +1,301 @@
+//! When an unlocked vault should lock itself again.
+//!
+//! [`AutoLock`] is fed activity and window state by the frontend and answers
+//! whether the session must end. Idle time is measured on both the monotonic
+//! and the wall clock: on most platforms the monotonic clock stops while the
+//! machine sleeps, so only the wall clock notices a laptop that was suspended
+//! with the vault open.
+
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long the vault may sit untouched before it locks itself: five
+/// minutes, long enough to read an entry without the vault closing under you.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// A wall-clock jump this much larger than the monotonic time between two
+/// checks is treated as a suspend/resume cycle.
+pub const SUSPEND_GAP: Duration = Duration::from_secs(30);
+
+/// Failed unlock attempts allowed before back-off kicks in.
+pub const FREE_UNLOCK_ATTEMPTS: u32 = 3;
+pub const MAX_UNLOCK_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Idle timer choices the settings screens offer, in seconds; `None` never
+/// locks on inactivity.
+pub const IDLE_TIMEOUTS: [(Option<u64>, &str); 7] = [
+    (Some(60), "1 minute"),
+    (Some(2 * 60), "2 minutes"),
+    (Some(5 * 60), "5 minutes"),
+    (Some(10 * 60), "10 minutes"),
+    (Some(15 * 60), "15 minutes"),
+    (Some(30 * 60), "30 minutes"),
+    (None, "Never"),
+];
+
+/// Choices for clearing copied secrets, in seconds; `None` waits for lock or
+/// exit.
+pub const CLIPBOARD_TIMEOUTS: [(Option<u64>, &str); 7] = [
+    (Some(10), "10 seconds"),
+    (Some(20), "20 seconds"),
+    (Some(30), "30 seconds"),
+    (Some(60), "1 minute"),
+    (Some(120), "2 minutes"),
+    (Some(300), "5 minutes"),
+    (None, "Only on lock or exit"),
+];
+
+/// Label of the preset matching `current`, or a plain duration for a value
+/// set by hand in the config.
+pub fn timeout_label(presets: &[(Option<u64>, &str)], current: Option<u64>) -> String {
+    presets
+        .iter()
+        .find(|(seconds, _)| *seconds == current)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| format!("{} seconds", current.unwrap_or_default()))
+}
+
+/// Why a session was locked, for the message on the unlock screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockReason {
+    Manual,
+    Idle,
+    Minimized,
+    FocusLost,
+    Suspended,
+}
+
+impl LockReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Manual => "locked",
+            Self::Idle => "locked after inactivity",
+            Self::Minimized => "locked when the window was minimized",
+            Self::FocusLost => "locked when the window lost focus",
+            Self::Suspended => "locked after the computer resumed from sleep",
+        }
+    }
+}
+
+/// Window state as reported by the frontend on each check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowState {
+    pub minimized: bool,
+    pub focused: bool,
+}
+
+/// Auto-lock rules and the clocks they are measured against.
+#[derive(Clone, Debug)]
+pub struct AutoLock {
+    /// `None` disables the idle timer.
+    pub idle_timeout: Option<Duration>,
+    pub lock_on_minimize: bool,
+    pub lock_on_focus_loss: bool,
+    last_activity: (Instant, SystemTime),
+    last_check: (Instant, SystemTime),
+}
+
+impl Default for AutoLock {
+    fn default() -> Self {
+        Self::new(
+            Some(DEFAULT_IDLE_TIMEOUT),
+            Instant::now(),
+            SystemTime::now(),
+        )
+    }
+}
+
+impl AutoLock {
+    pub fn new(idle_timeout: Option<Duration>, now: Instant, wall: SystemTime) -> Self {
+        Self {
+            idle_timeout,
+            lock_on_minimize: true,
+            lock_on_focus_loss: false,
+            last_activity: (now, wall),
+            last_check: (now, wall),
+        }
+    }
+
+    /// Starts a fresh session, e.g. right after unlocking.
+    pub fn reset(&mut self, now: Instant, wall: SystemTime) {
+        self.last_activity = (now, wall);
+        self.last_check = (now, wall);
+    }
+
+    /// Records user input.
+    pub fn activity(&mut self, now: Instant, wall: SystemTime) {
+        self.last_activity = (now, wall);
+    }
+
+    /// Returns the reason the session must lock now, if any.
+    pub fn check(
+        &mut self,
+        now: Instant,
+        wall: SystemTime,
+        window: WindowState,
+    ) -> Option<LockReason> {
+        let (checked_at, checked_wall) = self.last_check;
+        self.last_check = (now, wall);
+
+        let monotonic_gap = now.saturating_duration_since(checked_at);
+        let wall_gap = wall.duration_since(checked_wall).unwrap_or_default();
+        if wall_gap > monotonic_gap + SUSPEND_GAP {
+            return Some(LockReason::Suspended);
+        }
+
+        if self.lock_on_minimize && window.minimized {
+            return Some(LockReason::Minimized);
+        }
+        if self.lock_on_focus_loss && !window.focused {
+            return Some(LockReason::FocusLost);
+        }
+
+        let timeout = self.idle_timeout?;
+        (self.idle_for(now, wall) >= timeout).then_some(LockReason::Idle)
+    }
+
+    /// Time left before the idle timer locks the session.
+    pub fn remaining(&self, now: Instant, wall: SystemTime) -> Option<Duration> {
+        let timeout = self.idle_timeout?;
+        Some(timeout.saturating_sub(self.idle_for(now, wall)))
+    }
+
+    /// The longer of the monotonic and wall-clock time since the last input.
+    fn idle_for(&self, now: Instant, wall: SystemTime) -> Duration {
+        let (active_at, active_wall) = self.last_activity;
+        let monotonic = now.saturating_duration_since(active_at);
+        let wall = wall.duration_since(active_wall).unwrap_or_default();
+        monotonic.max(wall)
+    }
+}
+
+/// How long the unlock prompt waits after `failed_attempts` wrong
+/// passwords: nothing for the free attempts, then 1 s, 2 s, 4 s, … capped at
+/// [`MAX_UNLOCK_BACKOFF`].
+pub fn unlock_backoff(failed_attempts: u32) -> Option<Duration> {
+    let exponent = failed_attempts.checked_sub(FREE_UNLOCK_ATTEMPTS)?;
+    let seconds = 1u64.checked_shl(exponent).unwrap_or(u64::MAX);
+    Some(Duration::from_secs(seconds).min(MAX_UNLOCK_BACKOFF))
+}
+
+/// How much of the back-off after `failed_attempts` is left at `now`, when
+/// the latest failure happened at `last_failure_at` (both Unix seconds). A
+/// clock set back restarts the wait rather than skipping it.
+pub fn unlock_wait(failed_attempts: u32, last_failure_at: u64, now: u64) -> Option<Duration> {
+    let backoff = unlock_backoff(failed_attempts)?;
+    let elapsed = Duration::from_secs(now.saturating_sub(last_failure_at));
+    backoff
+        .checked_sub(elapsed)
+        .filter(|remaining| !remaining.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FOCUSED: WindowState = WindowState {
+        minimized: false,
+        focused: true,
+    };
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn timeouts_are_labelled_by_preset_or_in_seconds() {
+        assert_eq!(timeout_label(&IDLE_TIMEOUTS, Some(300)), "5 minutes");
+        assert_eq!(timeout_label(&IDLE_TIMEOUTS, None), "Never");
+        assert_eq!(
+            timeout_label(&CLIPBOARD_TIMEOUTS, None),
+            "Only on lock or exit"
+        );
+        assert_eq!(timeout_label(&CLIPBOARD_TIMEOUTS, Some(45)), "45 seconds");
+    }
+
+    #[test]
+    fn locks_after_idle_timeout_and_activity_resets_it() {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let mut lock = AutoLock::new(Some(secs(60)), now, wall);
+
+        assert_eq!(lock.check(now + secs(50), wall + secs(50), FOCUSED), None);
+        lock.activity(now + secs(50), wall + secs(50));
+        assert_eq!(
+            lock.remaining(now + secs(80), wall + secs(80)),
+            Some(secs(30))
+        );
+        assert_eq!(lock.check(now + secs(100), wall + secs(100), FOCUSED), None);
+        assert_eq!(
+            lock.check(now + secs(110), wall + secs(110), FOCUSED),
+            Some(LockReason::Idle)
+        );
+    }
+
+    #[test]
+    fn detects_suspend_through_the_wall_clock() {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let mut lock = AutoLock::new(None, now, wall);
+
+        // The monotonic clock moved one second while an hour passed outside.
+        assert_eq!(
+            lock.check(now + secs(1), wall + secs(3600), FOCUSED),
+            Some(LockReason::Suspended)
+        );
+        assert_eq!(lock.check(now + secs(2), wall + secs(3601), FOCUSED), None);
+    }
+
+    #[test]
+    fn window_rules_are_configurable() {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let mut lock = AutoLock::new(None, now, wall);
+        let minimized = WindowState {
+            minimized: true,
+            focused: false,
+        };
+        let unfocused = WindowState {
+            minimized: false,
+            focused: false,
+        };
+
+        assert_eq!(
+            lock.check(now, wall, minimized),
+            Some(LockReason::Minimized)
+        );
+        assert_eq!(lock.check(now, wall, unfocused), None);
+
+        lock.lock_on_minimize = false;
+        lock.lock_on_focus_loss = true;
+        assert_eq!(
+            lock.check(now, wall, minimized),
+            Some(LockReason::FocusLost)
+        );
+    }
+
+    #[test]
+    fn idle_timer_can_be_disabled() {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let mut lock = AutoLock::new(None, now, wall);
+        assert_eq!(
+            lock.check(now + secs(86_400), wall + secs(86_400), FOCUSED),
+            None
+        );
+        assert_eq!(lock.remaining(now, wall), None);
+    }
+
+    #[test]
+    fn unlock_backoff_doubles_after_the_free_attempts() {
+        assert_eq!(unlock_backoff(FREE_UNLOCK_ATTEMPTS - 1), None);
+        assert_eq!(unlock_backoff(FREE_UNLOCK_ATTEMPTS), Some(secs(1)));
+        assert_eq!(unlock_backoff(FREE_UNLOCK_ATTEMPTS + 2), Some(secs(4)));
+        assert_eq!(unlock_backoff(u32::MAX), Some(MAX_UNLOCK_BACKOFF));
+    }
+
+    #[test]
+    fn unlock_wait_counts_down_from_the_last_failure() {
+        let attempts = FREE_UNLOCK_ATTEMPTS + 3;
+        assert_eq!(unlock_wait(attempts, 1_000, 1_000), Some(secs(8)));
+        assert_eq!(unlock_wait(attempts, 1_000, 1_005), Some(secs(3)));
+        assert_eq!(unlock_wait(attempts, 1_000, 1_008), None);
+        assert_eq!(unlock_wait(attempts, 1_000, 900), Some(secs(8)));
+        assert_eq!(unlock_wait(FREE_UNLOCK_ATTEMPTS - 1, 1_000, 1_000), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Password strength estimation.
+//!
+//! Character-class checks rate "aaaaaaaaaaaA!" as strong. Instead, passwords
+//! are scored by how many guesses an attacker needs once it knows the usual
+//! tricks: dictionary words and common passwords, keyboard walks, repeats,
+//! sequences, dates, and l33t substitutions. The pattern matching is done by
+//! the `zxcvbn` crate; this module turns its result into plain data.
+
+use std::f64::consts::LOG2_10;
+
+/// Coarse strength rating shared by the generator and the password estimator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strength {
+    Weak,
+    Moderate,
+    Strong,
+    VeryStrong,
+}
+
+impl Strength {
+    /// Rates a secret by its entropy: under 50 bits is weak, under 70
+    /// moderate, under 90 strong, and anything above very strong.
+    pub fn from_entropy_bits(bits: f64) -> Self {
+        match bits {
+            bits if bits < 50.0 => Self::Weak,
+            bits if bits < 70.0 => Self::Moderate,
+            bits if bits < 90.0 => Self::Strong,
+            _ => Self::VeryStrong,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Weak => "Weak",
+            Self::Moderate => "Moderate",
+            Self::Strong => "Strong",
+            Self::VeryStrong => "Very strong",
+        }
+    }
+}
+
+/// How hard a specific password is to guess.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrengthEstimate {
+    /// Base-10 logarithm of the estimated number of guesses.
+    pub guesses_log10: f64,
+    pub strength: Strength,
+    /// Human-readable time to crack offline at 10,000 guesses per second,
+    /// e.g. "3 hours" or "centuries".
+    pub crack_time: String,
+    /// The main weakness found, if any ("This is a top-10 common password").
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl StrengthEstimate {
+    /// The estimate expressed as bits of entropy: `log2(guesses)`.
+    pub fn entropy_bits(&self) -> f64 {
+        self.guesses_log10 * LOG2_10
+    }
+}
+
+/// Estimates the strength of `password`. `user_inputs` are strings the
+/// password should not be built from, such as the entry title or username.
+pub fn estimate(password: &str, user_inputs: &[&str]) -> StrengthEstimate {
+    let result = zxcvbn::zxcvbn(password, user_inputs);
+    let guesses_log10 = result.guesses_log10();
+    let feedback = result.feedback();
+
+    StrengthEstimate {
+        guesses_log10,
+        strength: Strength::from_entropy_bits(guesses_log10 * LOG2_10),
+        crack_time: result
+            .crack_times()
+            .offline_slow_hashing_1e4_per_second()
+            .to_string(),
+        warning: feedback
+            .and_then(|feedback| feedback.warning())
+            .map(|warning| warning.to_string()),
+        suggestions: feedback
+            .map(|feedback| {
+                feedback
+                    .suggestions()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strength_from_entropy_thresholds() {
+        assert_eq!(Strength::from_entropy_bits(40.0), Strength::Weak);
+        assert_eq!(Strength::from_entropy_bits(60.0), Strength::Moderate);
+        assert_eq!(Strength::from_entropy_bits(77.5), Strength::Strong);
+        assert_eq!(Strength::from_entropy_bits(128.0), Strength::VeryStrong);
+    }
+
+    #[test]
+    fn class_variety_does_not_hide_repeats() {
+        let estimate = estimate("aaaaaaaaaaaA!", &[]);
+        assert_eq!(estimate.strength, Strength::Weak);
+        assert!(estimate.warning.is_some());
+    }
+
+    #[test]
+    fn recognizes_common_patterns() {
+        for password in [
+            "Password1!",
+            "qwertyuiop",
+            "P@ssw0rd2024",
+            "abcdefgh123",
+            "12/25/1990",
+        ] {
+            assert_eq!(
+                estimate(password, &[]).strength,
+                Strength::Weak,
+                "{password}"
+            );
+        }
+    }
+
+    #[test]
+    fn random_passwords_rate_higher() {
+        let weak = estimate("correct-horse", &[]);
+        let strong = estimate("v9#Tq!2mZr@Lp7xW&k", &[]);
+        assert!(strong.guesses_log10 > weak.guesses_log10);
+        assert!(strong.strength >= Strength::Moderate);
+        assert_eq!(strong.crack_time, "centuries");
+        assert!((strong.entropy_bits() - strong.guesses_log10 * LOG2_10).abs() < 1e-9);
+    }
+
+    #[test]
+    fn user_inputs_count_as_dictionary_words() {
+        let plain = estimate("lilypad-marguerite", &[]);
+        let personal = estimate("lilypad-marguerite", &["marguerite"]);
+        assert!(personal.guesses_log10 < plain.guesses_log10);
+    }
+
+    #[test]
+    fn empty_password_is_weak() {
+        assert_eq!(estimate("", &[]).strength, Strength::Weak);
+    }
+}
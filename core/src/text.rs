@@ -0,0 +1,58 @@
+//! Small formatting helpers the frontends share, so the same value reads the
+//! same everywhere.
+
+/// A byte count as shown next to attachments and snapshots: bytes below
+/// 1 KiB, then KiB or MiB with one decimal.
+pub fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes} bytes")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// `text` with its first letter uppercased, such as a core error message
+/// shown as a sentence.
+pub fn capitalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    push_capitalized(&mut out, text);
+    out
+}
+
+/// Appends `text` with its first letter uppercased, straight into `out` so a
+/// secret such as a passphrase word leaves no loose copy behind.
+pub fn push_capitalized(out: &mut String, text: &str) {
+    let mut chars = text.chars();
+    if let Some(first) = chars.next() {
+        out.extend(first.to_uppercase());
+        out.extend(chars);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_use_binary_units() {
+        assert_eq!(format_size(0), "0 bytes");
+        assert_eq!(format_size(1023), "1023 bytes");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn capitalizes_the_first_letter_only() {
+        assert_eq!(
+            capitalize("incorrect master password"),
+            "Incorrect master password"
+        );
+        assert_eq!(capitalize("élan"), "Élan");
+        assert_eq!(capitalize(""), "");
+        let mut out = "Pond-".to_string();
+        push_capitalized(&mut out, "frog");
+        assert_eq!(out, "Pond-Frog");
+    }
+}
@@ -0,0 +1,403 @@
+//! Color themes shared by the graphical and terminal frontends.
+//!
+//! A [`Theme`] names colors by purpose (surface, accent, danger, …) rather
+//! than by value, so render code never hard-codes a color. Three themes are
+//! built in; users can add their own as TOML files in the `themes` folder
+//! next to `config.toml`:
+//!
+//! ```toml
+//! name = "Solarized Dark"
+//! base = "dark"          # built-in theme supplying every color not listed
+//!
+//! [colors]
+//! background = "#002b36"
+//! accent = "#268bd2"
+//! highlight = "#b5890080" # optional alpha as a fourth byte
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Folder under the config directory that holds user themes.
+pub const THEMES_DIR: &str = "themes";
+/// Theme name meaning "dark or light, whichever the OS uses".
+pub const FOLLOW_SYSTEM: &str = "system";
+pub const DARK: &str = "dark";
+pub const LIGHT: &str = "light";
+pub const HIGH_CONTRAST: &str = "high-contrast";
+/// Names of the built-in themes, in the order frontends list them.
+pub const BUILTIN_THEMES: [&str; 3] = [DARK, LIGHT, HIGH_CONTRAST];
+
+/// An sRGB color with alpha, written as `#rrggbb` or `#rrggbbaa`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Color {
+    type Err = ThemeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || ThemeError::InvalidColor(value.to_string());
+        let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let byte = |index: usize| {
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| invalid())
+        };
+        let alpha = if hex.len() == 8 { byte(3)? } else { 255 };
+        Ok(Self::rgba(byte(0)?, byte(1)?, byte(2)?, alpha))
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = ThemeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    InvalidColor(String),
+    UnknownBase(String),
+    MissingName(PathBuf),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            Self::Parse(path, error) => write!(f, "{}: {error}", path.display()),
+            Self::InvalidColor(value) => {
+                write!(f, "\"{value}\" is not a color; use #rrggbb or #rrggbbaa")
+            }
+            Self::UnknownBase(name) => write!(f, "unknown base theme \"{name}\""),
+            Self::MissingName(path) => write!(f, "{}: theme has no name", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+/// Semantic color tokens. Frontends map these onto their widgets; nothing
+/// should pick a color outside this list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Palette {
+    /// Window and panel background.
+    pub background: Color,
+    /// Cards, dialogs, and other raised areas.
+    pub surface: Color,
+    /// Outlines and separators.
+    pub border: Color,
+    pub text: Color,
+    /// Secondary text: hints, captions, inactive items.
+    pub text_muted: Color,
+    /// Primary actions and the selected navigation item.
+    pub accent: Color,
+    /// Text drawn on top of `accent`.
+    pub on_accent: Color,
+    /// Fill of actions that cannot be used right now.
+    pub disabled: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub danger: Color,
+    /// Background behind search matches.
+    pub highlight: Color,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    /// Whether the theme is dark, for frontends that style their own widgets.
+    pub dark: bool,
+    pub colors: Palette,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: DARK.to_string(),
+            dark: true,
+            colors: Palette {
+                background: Color::rgb(14, 22, 33),
+                surface: Color::rgb(24, 36, 54),
+                border: Color::rgb(45, 78, 120),
+                text: Color::rgb(205, 225, 255),
+                text_muted: Color::rgb(170, 185, 205),
+                accent: Color::rgb(111, 207, 151),
+                on_accent: Color::rgb(16, 22, 32),
+                disabled: Color::rgb(70, 94, 124),
+                success: Color::rgb(111, 207, 151),
+                warning: Color::rgb(255, 193, 107),
+                danger: Color::rgb(240, 105, 105),
+                highlight: Color::rgba(255, 193, 107, 110),
+            },
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: LIGHT.to_string(),
+            dark: false,
+            colors: Palette {
+                background: Color::rgb(245, 247, 250),
+                surface: Color::rgb(255, 255, 255),
+                border: Color::rgb(210, 214, 220),
+                text: Color::rgb(16, 28, 46),
+                text_muted: Color::rgb(85, 96, 112),
+                accent: Color::rgb(40, 125, 80),
+                on_accent: Color::rgb(255, 255, 255),
+                disabled: Color::rgb(190, 198, 208),
+                success: Color::rgb(35, 125, 55),
+                warning: Color::rgb(170, 100, 0),
+                danger: Color::rgb(190, 40, 40),
+                highlight: Color::rgba(255, 200, 80, 140),
+            },
+        }
+    }
+
+    /// Maximum contrast for low-vision users: pure black and white with
+    /// saturated signal colors.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: HIGH_CONTRAST.to_string(),
+            dark: true,
+            colors: Palette {
+                background: Color::rgb(0, 0, 0),
+                surface: Color::rgb(0, 0, 0),
+                border: Color::rgb(255, 255, 255),
+                text: Color::rgb(255, 255, 255),
+                text_muted: Color::rgb(230, 230, 230),
+                accent: Color::rgb(255, 255, 0),
+                on_accent: Color::rgb(0, 0, 0),
+                disabled: Color::rgb(100, 100, 100),
+                success: Color::rgb(0, 255, 127),
+                warning: Color::rgb(255, 200, 0),
+                danger: Color::rgb(255, 90, 90),
+                highlight: Color::rgba(255, 255, 0, 160),
+            },
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            DARK => Some(Self::dark()),
+            LIGHT => Some(Self::light()),
+            HIGH_CONTRAST => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Parses a user theme; `path` is only used in error messages.
+    pub fn from_toml(contents: &str, path: &Path) -> Result<Self, ThemeError> {
+        let file: ThemeFile =
+            toml::from_str(contents).map_err(|error| ThemeError::Parse(path.into(), error))?;
+        let name = file.name.trim();
+        if name.is_empty() {
+            return Err(ThemeError::MissingName(path.into()));
+        }
+        let base_name = file.base.as_deref().unwrap_or(DARK);
+        let base =
+            Self::builtin(base_name).ok_or_else(|| ThemeError::UnknownBase(base_name.into()))?;
+        Ok(Self {
+            name: name.to_string(),
+            dark: file.dark.unwrap_or(base.dark),
+            colors: file.colors.over(base.colors),
+        })
+    }
+
+    /// Loads every `*.toml` file in `dir`. A missing folder simply has no
+    /// themes; a broken file is reported without hiding the others.
+    pub fn load_dir(dir: &Path) -> (Vec<Self>, Vec<ThemeError>) {
+        let mut themes = Vec::new();
+        let mut errors = Vec::new();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return (themes, errors),
+            Err(error) => return (themes, vec![ThemeError::Io(dir.into(), error)]),
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .collect();
+        paths.sort();
+        for path in paths {
+            let theme = fs::read_to_string(&path)
+                .map_err(|error| ThemeError::Io(path.clone(), error))
+                .and_then(|contents| Self::from_toml(&contents, &path));
+            match theme {
+                Ok(theme) => themes.push(theme),
+                Err(error) => errors.push(error),
+            }
+        }
+        (themes, errors)
+    }
+}
+
+/// On-disk shape of a user theme.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: String,
+    base: Option<String>,
+    dark: Option<bool>,
+    #[serde(default)]
+    colors: PaletteOverrides,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PaletteOverrides {
+    background: Option<Color>,
+    surface: Option<Color>,
+    border: Option<Color>,
+    text: Option<Color>,
+    text_muted: Option<Color>,
+    accent: Option<Color>,
+    on_accent: Option<Color>,
+    disabled: Option<Color>,
+    success: Option<Color>,
+    warning: Option<Color>,
+    danger: Option<Color>,
+    highlight: Option<Color>,
+}
+
+impl PaletteOverrides {
+    fn over(self, base: Palette) -> Palette {
+        Palette {
+            background: self.background.unwrap_or(base.background),
+            surface: self.surface.unwrap_or(base.surface),
+            border: self.border.unwrap_or(base.border),
+            text: self.text.unwrap_or(base.text),
+            text_muted: self.text_muted.unwrap_or(base.text_muted),
+            accent: self.accent.unwrap_or(base.accent),
+            on_accent: self.on_accent.unwrap_or(base.on_accent),
+            disabled: self.disabled.unwrap_or(base.disabled),
+            success: self.success.unwrap_or(base.success),
+            warning: self.warning.unwrap_or(base.warning),
+            danger: self.danger.unwrap_or(base.danger),
+            highlight: self.highlight.unwrap_or(base.highlight),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_round_trip_through_hex() {
+        let opaque: Color = "#6fcf97".parse().unwrap();
+        assert_eq!(opaque, Color::rgb(111, 207, 151));
+        assert_eq!(opaque.to_string(), "#6fcf97");
+
+        let translucent: Color = "#FFC16B6E".parse().unwrap();
+        assert_eq!(translucent, Color::rgba(255, 193, 107, 110));
+        assert_eq!(translucent.to_string(), "#ffc16b6e");
+
+        for bad in ["6fcf97", "#6fcf9", "#6fcf97f", "#gggggg", "#ééé"] {
+            assert!(bad.parse::<Color>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn builtins_are_listed_and_named() {
+        for name in BUILTIN_THEMES {
+            assert_eq!(Theme::builtin(name).unwrap().name, name);
+        }
+        assert!(Theme::builtin(FOLLOW_SYSTEM).is_none());
+    }
+
+    #[test]
+    fn user_themes_inherit_from_their_base() {
+        let theme = Theme::from_toml(
+            "name = \"Paper\"\nbase = \"light\"\n[colors]\naccent = \"#112233\"\n",
+            Path::new("paper.toml"),
+        )
+        .unwrap();
+        assert_eq!(theme.name, "Paper");
+        assert!(!theme.dark);
+        assert_eq!(theme.colors.accent, Color::rgb(0x11, 0x22, 0x33));
+        assert_eq!(theme.colors.danger, Theme::light().colors.danger);
+    }
+
+    #[test]
+    fn user_theme_errors_are_reported() {
+        let path = Path::new("broken.toml");
+        assert!(matches!(
+            Theme::from_toml("name = \"X\"\nbase = \"sepia\"\n", path),
+            Err(ThemeError::UnknownBase(_))
+        ));
+        assert!(matches!(
+            Theme::from_toml("name = \"X\"\n[colors]\naccent = \"red\"\n", path),
+            Err(ThemeError::Parse(..))
+        ));
+        assert!(matches!(
+            Theme::from_toml("name = \"X\"\n[colors]\naccnet = \"#ffffff\"\n", path),
+            Err(ThemeError::Parse(..))
+        ));
+        assert!(matches!(
+            Theme::from_toml("name = \" \"\n", path),
+            Err(ThemeError::MissingName(_))
+        ));
+    }
+
+    #[test]
+    fn load_dir_keeps_good_themes_when_one_is_broken() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.toml"), "name = \"Alpha\"\n").unwrap();
+        fs::write(dir.path().join("b.toml"), "name = ").unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let (themes, errors) = Theme::load_dir(dir.path());
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].name, "Alpha");
+        assert_eq!(errors.len(), 1);
+
+        let (themes, errors) = Theme::load_dir(&dir.path().join("missing"));
+        assert!(themes.is_empty() && errors.is_empty());
+    }
+}
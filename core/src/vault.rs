@@ -0,0 +1,860 @@
+//! Encrypted on-disk vault file.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | Field        | Size | Notes                                   |
+//! |--------------|------|-----------------------------------------|
+//! | magic        | 8    | `LILYPAD\0`                             |
+//! | version      | 2    | format version, currently `1`           |
+//! | memory_kib   | 4    | Argon2id memory cost                    |
+//! | iterations   | 4    | Argon2id time cost                      |
+//! | parallelism  | 4    | Argon2id lanes                          |
+//! | salt         | 16   | KDF salt, fixed for a given password    |
+//! | nonce        | 24   | XChaCha20-Poly1305 nonce, fresh per save|
+//! | ciphertext   | rest | encrypted JSON payload                  |
+//!
+//! The whole header is passed as associated data, so tampering with the KDF
+//! parameters or version is detected just like tampering with the payload.
+
+use crate::backup::Backups;
+use crate::clock;
+use crate::config::KdfConfig;
+use crate::crypto::{self, CryptoError, KdfParams, NONCE_LEN, SALT_LEN};
+use crate::entry::Entry;
+use crate::secret::LockedKey;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
+
+const MAGIC: &[u8; 8] = b"LILYPAD\0";
+const FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 2 + 4 * 3 + SALT_LEN + NONCE_LEN;
+
+#[derive(Debug)]
+pub enum VaultError {
+    Io(io::Error),
+    InvalidFormat(&'static str),
+    UnsupportedVersion(u16),
+    WrongPassword,
+    /// The file was saved by someone else, such as the CLI, since this
+    /// vault read or wrote it; saving would erase their changes.
+    ChangedOnDisk,
+    /// A new vault, or one being moved, would replace a file already at its
+    /// path.
+    AlreadyExists,
+    Crypto(CryptoError),
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "vault file error: {error}"),
+            Self::InvalidFormat(reason) => write!(f, "not a valid Lilypad vault: {reason}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported vault format version {version}")
+            }
+            Self::WrongPassword => write!(f, "incorrect master password"),
+            Self::ChangedOnDisk => write!(
+                f,
+                "the vault file was changed by another program since it was opened; \
+                 open it again to pick up those changes before saving"
+            ),
+            Self::AlreadyExists => write!(
+                f,
+                "a file already exists at that location; open it or pick another one"
+            ),
+            Self::Crypto(error) => write!(f, "{error}"),
+            Self::Serialization(error) => write!(f, "vault contents are corrupted: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+impl From<io::Error> for VaultError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<CryptoError> for VaultError {
+    fn from(error: CryptoError) -> Self {
+        Self::Crypto(error)
+    }
+}
+
+impl From<serde_json::Error> for VaultError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serialization(error)
+    }
+}
+
+/// Decrypted vault contents, serialized as JSON inside the encrypted payload.
+/// Every entry is wiped from memory when the data is dropped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultData {
+    pub created_at: u64,
+    pub modified_at: u64,
+    #[serde(default)]
+    pub entries: Vec<Entry>,
+}
+
+impl Drop for VaultData {
+    fn drop(&mut self) {
+        self.entries.zeroize();
+    }
+}
+
+impl VaultData {
+    fn new() -> Self {
+        let now = clock::now();
+        Self {
+            created_at: now,
+            modified_at: now,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// An unlocked vault: the decrypted data plus the key needed to save it again.
+/// Dropping it wipes both.
+pub struct Vault {
+    path: PathBuf,
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    key: LockedKey,
+    data: VaultData,
+    /// Nonce of the file at `path` as this vault last read or wrote it;
+    /// `None` before a new vault's first save.
+    nonce: Option<[u8; NONCE_LEN]>,
+    backups: Option<Backups>,
+    backup_error: Option<io::Error>,
+}
+
+/// A key derived from a master password together with the salt and cost
+/// parameters it was derived with.
+///
+/// Deriving one is the slow, memory-hard part of creating a vault or
+/// changing its password; it needs nothing from the vault itself, so
+/// frontends can run [`MasterKey::derive`] on a worker thread and hand the
+/// result to [`Vault::create_with_key`] or [`Vault::set_master_key`].
+#[derive(Debug)]
+pub struct MasterKey {
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    key: LockedKey,
+}
+
+impl MasterKey {
+    /// Derives a key for `password` under a fresh random salt.
+    pub fn derive(password: &str, kdf: KdfParams) -> Result<Self, VaultError> {
+        let salt = crypto::random_bytes::<SALT_LEN>();
+        let key = crypto::derive_key(password, &salt, &kdf)?;
+        Ok(Self { kdf, salt, key })
+    }
+}
+
+/// Checks a candidate master password against an open vault's key without
+/// borrowing the vault, so the check can run on a worker thread.
+#[derive(Debug)]
+pub struct PasswordCheck {
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    key: LockedKey,
+}
+
+impl PasswordCheck {
+    /// Fails with [`VaultError::WrongPassword`] unless `password` derives the
+    /// vault's current key.
+    pub fn verify(&self, password: &str) -> Result<(), VaultError> {
+        let candidate = crypto::derive_key(password, &self.salt, &self.kdf)?;
+        if crypto::keys_match(candidate.as_bytes(), self.key.as_bytes()) {
+            Ok(())
+        } else {
+            Err(VaultError::WrongPassword)
+        }
+    }
+}
+
+struct Header {
+    version: u16,
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+}
+
+impl Vault {
+    /// Creates a new vault protected by `password` and writes it to `path`.
+    pub fn create(path: &Path, password: &str) -> Result<Self, VaultError> {
+        Self::create_with_params(path, password, KdfParams::default())
+    }
+
+    /// Like [`Vault::create`], with explicit Argon2id cost parameters.
+    pub fn create_with_params(
+        path: &Path,
+        password: &str,
+        kdf: KdfParams,
+    ) -> Result<Self, VaultError> {
+        Self::create_with_key(path, MasterKey::derive(password, kdf)?)
+    }
+
+    /// Creates a new, empty vault from an already derived key and writes it
+    /// to `path`.
+    pub fn create_with_key(path: &Path, master_key: MasterKey) -> Result<Self, VaultError> {
+        Self::create_with_data(path, master_key, VaultData::new())
+    }
+
+    /// Creates a vault already holding `data`, such as a restored backup,
+    /// and writes it to `path`.
+    pub fn create_with_data(
+        path: &Path,
+        master_key: MasterKey,
+        data: VaultData,
+    ) -> Result<Self, VaultError> {
+        let MasterKey { kdf, salt, key } = master_key;
+        let mut vault = Self {
+            path: path.to_path_buf(),
+            kdf,
+            salt,
+            key,
+            data,
+            nonce: None,
+            backups: None,
+            backup_error: None,
+        };
+        vault.save()?;
+        Ok(vault)
+    }
+
+    /// Reads the vault at `path` and decrypts it with `password`.
+    pub fn open(path: &Path, password: &str) -> Result<Self, VaultError> {
+        Self::from_bytes(path, &fs::read(path)?, password)
+    }
+
+    /// Decrypts `bytes`, the contents of a vault file, as the vault at
+    /// `path`.
+    pub(crate) fn from_bytes(
+        path: &Path,
+        bytes: &[u8],
+        password: &str,
+    ) -> Result<Self, VaultError> {
+        let header = Header::parse(bytes)?;
+        let (aad, ciphertext) = bytes.split_at(HEADER_LEN);
+
+        let key = crypto::derive_key(password, &header.salt, &header.kdf)?;
+        let plaintext = crypto::decrypt(key.as_bytes(), &header.nonce, ciphertext, aad)
+            .map_err(|_| VaultError::WrongPassword)?;
+        let data = serde_json::from_slice(&plaintext)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            kdf: header.kdf,
+            salt: header.salt,
+            key,
+            data,
+            nonce: Some(header.nonce),
+            backups: None,
+            backup_error: None,
+        })
+    }
+
+    /// Encrypts the current data with a fresh nonce and atomically replaces the file.
+    ///
+    /// Fails with [`VaultError::ChangedOnDisk`], writing nothing, when the
+    /// file was saved by someone else since this vault read or wrote it.
+    ///
+    /// With [`Vault::set_backups`] configured, the written file is then also
+    /// kept as a snapshot. A failed snapshot does not fail the save; it is
+    /// reported by [`Vault::backup_error`] instead.
+    ///
+    /// The first save of a new vault fails with [`VaultError::AlreadyExists`]
+    /// instead of replacing a file that is already at its path.
+    pub fn save(&mut self) -> Result<(), VaultError> {
+        self.check_unchanged()?;
+        self.write(self.nonce.is_none())
+    }
+
+    /// Encrypts and writes the vault; `new_file` refuses to replace an
+    /// existing file.
+    fn write(&mut self, new_file: bool) -> Result<(), VaultError> {
+        self.data.modified_at = clock::now();
+
+        let header = Header {
+            version: FORMAT_VERSION,
+            kdf: self.kdf,
+            salt: self.salt,
+            nonce: crypto::random_bytes::<NONCE_LEN>(),
+        };
+        let mut bytes = header.to_bytes();
+        let plaintext = Zeroizing::new(serde_json::to_vec(&self.data)?);
+        let ciphertext = crypto::encrypt(self.key.as_bytes(), &header.nonce, &plaintext, &bytes)?;
+        bytes.extend_from_slice(&ciphertext);
+
+        let written = if new_file {
+            write_atomic_new(&self.path, &bytes)
+        } else {
+            write_atomic(&self.path, &bytes)
+        };
+        written.map_err(|error| match error.kind() {
+            io::ErrorKind::AlreadyExists => VaultError::AlreadyExists,
+            _ => error.into(),
+        })?;
+        self.nonce = Some(header.nonce);
+        if let Some(backups) = &self.backups {
+            self.backup_error = backups
+                .snapshot(&self.path, &bytes, self.data.modified_at)
+                .err();
+        }
+        Ok(())
+    }
+
+    /// Fails with [`VaultError::ChangedOnDisk`] when the file at `path` no
+    /// longer carries the nonce this vault last read or wrote. Every save
+    /// draws a fresh nonce, so any other save shows. A missing file is fine;
+    /// saving recreates it.
+    fn check_unchanged(&self) -> Result<(), VaultError> {
+        let Some(nonce) = self.nonce else {
+            return Ok(());
+        };
+        let mut header = [0; HEADER_LEN];
+        let read = fs::File::open(&self.path).and_then(|mut file| file.read_exact(&mut header));
+        match read {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(VaultError::ChangedOnDisk)
+            }
+            Err(error) => return Err(error.into()),
+        }
+        match Header::parse(&header) {
+            Ok(header) if header.nonce == nonce => Ok(()),
+            _ => Err(VaultError::ChangedOnDisk),
+        }
+    }
+
+    /// Where every later save keeps a snapshot of the file, or `None` to stop
+    /// taking them.
+    pub fn set_backups(&mut self, backups: Option<Backups>) {
+        self.backups = backups;
+        self.backup_error = None;
+    }
+
+    pub fn backups(&self) -> Option<&Backups> {
+        self.backups.as_ref()
+    }
+
+    /// Why the snapshot of the last save could not be written, if it could
+    /// not. The save itself went through.
+    pub fn backup_error(&self) -> Option<&io::Error> {
+        self.backup_error.as_ref()
+    }
+
+    /// Re-encrypts the vault under `new_password` after checking
+    /// `current_password`. The file keeps its current KDF cost parameters.
+    pub fn change_password(
+        &mut self,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), VaultError> {
+        self.password_check().verify(current_password)?;
+        self.set_master_key(MasterKey::derive(new_password, self.kdf)?)
+    }
+
+    /// A standalone check of the current master password; see
+    /// [`PasswordCheck`].
+    pub fn password_check(&self) -> PasswordCheck {
+        PasswordCheck {
+            kdf: self.kdf,
+            salt: self.salt,
+            key: LockedKey::from_bytes(*self.key.as_bytes()),
+        }
+    }
+
+    /// Switches to `master_key` and saves. If the save fails the vault keeps
+    /// its previous key, which still matches the file on disk.
+    pub fn set_master_key(&mut self, master_key: MasterKey) -> Result<(), VaultError> {
+        let MasterKey { kdf, salt, key } = master_key;
+        let previous = (
+            std::mem::replace(&mut self.kdf, kdf),
+            std::mem::replace(&mut self.salt, salt),
+            std::mem::replace(&mut self.key, key),
+        );
+        if let Err(error) = self.save() {
+            (self.kdf, self.salt, self.key) = previous;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Saves the vault at `path` and removes the file at the old location.
+    /// Refuses to overwrite an existing file, which is likely another vault,
+    /// and to move a file changed on disk, as `save` does. If only the removal fails, the vault has still moved.
+    pub fn relocate(&mut self, path: &Path) -> Result<(), VaultError> {
+        self.check_unchanged()?;
+        let previous = std::mem::replace(&mut self.path, path.to_path_buf());
+        if let Err(error) = self.write(true) {
+            self.path = previous;
+            return Err(error);
+        }
+        fs::remove_file(previous)?;
+        Ok(())
+    }
+
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn data(&self) -> &VaultData {
+        &self.data
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.data.entries
+    }
+
+    pub fn entry(&self, id: Uuid) -> Option<&Entry> {
+        self.data.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Appends an entry in memory; call [`Vault::save`] to persist it.
+    pub fn add_entry(&mut self, entry: Entry) {
+        self.data.entries.push(entry);
+    }
+
+    /// Replaces the stored entry with the same id and refreshes its modified
+    /// timestamp. Returns `false` when no such entry exists.
+    pub fn update_entry(&mut self, mut entry: Entry) -> bool {
+        let Some(slot) = self
+            .data
+            .entries
+            .iter_mut()
+            .find(|stored| stored.id == entry.id)
+        else {
+            return false;
+        };
+        entry.touch();
+        *slot = entry;
+        true
+    }
+
+    pub fn remove_entry(&mut self, id: Uuid) -> Option<Entry> {
+        let index = self.data.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.data.entries.remove(index))
+    }
+}
+
+/// Checks that `bytes` start with a vault header this build can read,
+/// without decrypting anything.
+pub(crate) fn check_header(bytes: &[u8]) -> Result<(), VaultError> {
+    Header::parse(bytes).map(|_| ())
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> Result<Self, VaultError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(VaultError::InvalidFormat("file is truncated"));
+        }
+        if &bytes[..MAGIC.len()] != MAGIC {
+            return Err(VaultError::InvalidFormat("missing Lilypad header"));
+        }
+
+        let mut cursor = MAGIC.len();
+        let mut take = |len: usize| {
+            let slice = &bytes[cursor..cursor + len];
+            cursor += len;
+            slice
+        };
+
+        let version = u16::from_le_bytes(take(2).try_into().unwrap_or_default());
+        if version != FORMAT_VERSION {
+            return Err(VaultError::UnsupportedVersion(version));
+        }
+
+        let mut read_u32 = || u32::from_le_bytes(take(4).try_into().unwrap_or_default());
+        let kdf = KdfParams {
+            memory_kib: read_u32(),
+            iterations: read_u32(),
+            parallelism: read_u32(),
+        };
+        // Checked before anything is derived: a tampered cost would
+        // otherwise be paid in full before the header fails to verify.
+        if !KdfConfig::from_params(kdf).is_supported() {
+            return Err(VaultError::InvalidFormat(
+                "key derivation cost is out of range",
+            ));
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(take(SALT_LEN));
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(take(NONCE_LEN));
+
+        Ok(Self {
+            version,
+            kdf,
+            salt,
+            nonce,
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&self.kdf.memory_kib.to_le_bytes());
+        bytes.extend_from_slice(&self.kdf.iterations.to_le_bytes());
+        bytes.extend_from_slice(&self.kdf.parallelism.to_le_bytes());
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce);
+        bytes
+    }
+}
+
+/// Writes to a sibling temporary file and renames it over `path`, so a crash
+/// mid-write never leaves a half-written vault (or config) behind. The file
+/// is readable by the owner only.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let temp_path = write_temp(path, bytes)?;
+    fs::rename(&temp_path, path)
+}
+
+/// Like [`write_atomic`], but fails with `AlreadyExists` rather than replace
+/// a file at `path`, even one created a moment ago by another program. The
+/// temporary file is hard-linked into place, which never overwrites.
+fn write_atomic_new(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let temp_path = write_temp(path, bytes)?;
+    let linked = fs::hard_link(&temp_path, path);
+    fs::remove_file(&temp_path)?;
+    match linked {
+        Err(error) if error.kind() != io::ErrorKind::AlreadyExists => {
+            // File systems without hard links, such as FAT on a USB stick.
+            write_new(path, bytes)
+        }
+        linked => linked,
+    }
+}
+
+/// Writes `bytes` to the sibling temporary file of `path` and returns its
+/// path.
+fn write_temp(path: &Path, bytes: &[u8]) -> io::Result<PathBuf> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut options = private_options();
+    options.create(true).truncate(true);
+    let mut file = options.open(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(temp_path)
+}
+
+/// Creates `path` holding `bytes`, readable by the owner only, refusing to
+/// overwrite an existing file. For exports, which land wherever the user
+/// points them.
+pub fn write_new(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut options = private_options();
+    options.create_new(true);
+    let mut file = options.open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Options for writing a file only its owner can read.
+fn private_options() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{EntryKind, Login};
+
+    /// The cheapest parameters a vault file may carry, so tests do not pay
+    /// for a production-strength KDF.
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 16 * 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn create_then_open_round_trips_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+
+        let mut vault = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        let entry = Entry::new("Email", EntryKind::Login(Login::default()));
+        vault.add_entry(entry.clone());
+        vault.save().unwrap();
+
+        let reopened = Vault::open(&path, "correct horse").unwrap();
+        assert_eq!(reopened.entries(), [entry]);
+        assert_eq!(reopened.kdf, TEST_KDF);
+    }
+
+    #[test]
+    fn update_and_remove_entries_by_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let mut vault = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+
+        let mut entry = Entry::new("Router", EntryKind::SecureNote);
+        vault.add_entry(entry.clone());
+
+        entry.title = "Home router".to_string();
+        assert!(vault.update_entry(entry.clone()));
+        assert_eq!(vault.entry(entry.id).unwrap().title, "Home router");
+
+        let stranger = Entry::new("Unknown", EntryKind::SecureNote);
+        assert!(!vault.update_entry(stranger));
+
+        assert_eq!(vault.remove_entry(entry.id).unwrap().title, "Home router");
+        assert!(vault.entries().is_empty());
+    }
+
+    #[test]
+    fn change_password_requires_the_current_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let mut vault = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        vault.add_entry(Entry::new("Router", EntryKind::SecureNote));
+
+        assert!(matches!(
+            vault.change_password("wrong", "battery staple"),
+            Err(VaultError::WrongPassword)
+        ));
+        vault
+            .change_password("correct horse", "battery staple")
+            .unwrap();
+
+        assert!(matches!(
+            Vault::open(&path, "correct horse"),
+            Err(VaultError::WrongPassword)
+        ));
+        let reopened = Vault::open(&path, "battery staple").unwrap();
+        assert_eq!(reopened.entries().len(), 1);
+        assert_eq!(reopened.kdf_params(), TEST_KDF);
+    }
+
+    #[test]
+    fn master_keys_can_be_derived_ahead_of_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+
+        let master_key = MasterKey::derive("correct horse", TEST_KDF).unwrap();
+        let vault = Vault::create_with_key(&path, master_key).unwrap();
+        let check = vault.password_check();
+        drop(vault);
+
+        assert!(check.verify("correct horse").is_ok());
+        assert!(matches!(
+            check.verify("battery staple"),
+            Err(VaultError::WrongPassword)
+        ));
+        assert!(Vault::open(&path, "correct horse").is_ok());
+    }
+
+    #[test]
+    fn relocate_moves_the_file_without_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let moved = dir.path().join("elsewhere").join("vault.lilypad");
+        let mut vault = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+
+        vault.relocate(&moved).unwrap();
+        assert_eq!(vault.path(), moved);
+        assert!(!path.exists());
+        assert!(Vault::open(&moved, "correct horse").is_ok());
+
+        fs::write(&path, b"someone else's file").unwrap();
+        assert!(matches!(
+            vault.relocate(&path),
+            Err(VaultError::AlreadyExists)
+        ));
+        assert_eq!(vault.path(), moved);
+        assert_eq!(fs::read(&path).unwrap(), b"someone else's file");
+    }
+
+    #[test]
+    fn creating_never_replaces_an_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let mut existing = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        existing.add_entry(Entry::new("Bank", EntryKind::SecureNote));
+        existing.save().unwrap();
+        let before = fs::read(&path).unwrap();
+
+        assert!(matches!(
+            Vault::create_with_params(&path, "battery staple", TEST_KDF),
+            Err(VaultError::AlreadyExists)
+        ));
+        let key = MasterKey::derive("battery staple", TEST_KDF).unwrap();
+        assert!(matches!(
+            Vault::create_with_data(&path, key, VaultData::new()),
+            Err(VaultError::AlreadyExists)
+        ));
+        assert_eq!(fs::read(&path).unwrap(), before);
+        assert!(!dir.path().join("vault.lilypad.tmp").exists());
+        // The vault that owns the file keeps saving over it.
+        existing.save().unwrap();
+    }
+
+    #[test]
+    fn refuses_to_save_over_changes_made_elsewhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let mut desktop = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        let mut cli = Vault::open(&path, "correct horse").unwrap();
+        cli.add_entry(Entry::new("Router", EntryKind::SecureNote));
+        cli.save().unwrap();
+
+        desktop.add_entry(Entry::new("Bank", EntryKind::SecureNote));
+        assert!(matches!(desktop.save(), Err(VaultError::ChangedOnDisk)));
+        let moved = dir.path().join("moved.lilypad");
+        assert!(matches!(
+            desktop.relocate(&moved),
+            Err(VaultError::ChangedOnDisk)
+        ));
+        assert!(!moved.exists());
+        let on_disk = Vault::open(&path, "correct horse").unwrap();
+        assert_eq!(on_disk.entries()[0].title, "Router");
+
+        // The vault that saved last keeps saving, and a deleted file is
+        // simply written again.
+        cli.save().unwrap();
+        fs::remove_file(&path).unwrap();
+        cli.save().unwrap();
+        fs::write(&path, b"garbage").unwrap();
+        assert!(matches!(cli.save(), Err(VaultError::ChangedOnDisk)));
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+
+        assert!(matches!(
+            Vault::open(&path, "battery staple"),
+            Err(VaultError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn tampered_header_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        // Flip a bit in the salt: the header is authenticated, so this must fail.
+        bytes[MAGIC.len() + 2 + 12] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        assert!(Vault::open(&path, "correct horse").is_err());
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+
+        fs::write(&path, b"not a vault").unwrap();
+        assert!(matches!(
+            Vault::open(&path, "pw"),
+            Err(VaultError::InvalidFormat(_))
+        ));
+
+        fs::write(
+            &path,
+            [b"NOTLILY\0".as_slice(), &[0u8; HEADER_LEN]].concat(),
+        )
+        .unwrap();
+        assert!(matches!(
+            Vault::open(&path, "pw"),
+            Err(VaultError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_out_of_range_kdf_costs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+
+        let good = fs::read(&path).unwrap();
+        let memory = MAGIC.len() + 2;
+        for (offset, value) in [(memory, u32::MAX), (memory, 1024), (memory + 8, 0)] {
+            let mut bytes = good.clone();
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            fs::write(&path, bytes).unwrap();
+            assert!(matches!(
+                Vault::open(&path, "correct horse"),
+                Err(VaultError::InvalidFormat(_))
+            ));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_are_private_to_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let export = dir.path().join("export.json");
+        write_new(&export, b"[]").unwrap();
+        let mode = fs::metadata(&export).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn write_new_never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.json");
+        write_new(&path, b"first").unwrap();
+        let error = write_new(&path, b"second").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+    }
+
+    #[test]
+    fn rejects_unknown_format_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&99u16.to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+
+        assert!(matches!(
+            Vault::open(&path, "correct horse"),
+            Err(VaultError::UnsupportedVersion(99))
+        ));
+    }
+}
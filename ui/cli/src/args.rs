@@ -0,0 +1,247 @@
+//! Command-line syntax, declared with `clap`'s derive API. Doc comments on
+//! the fields become the `--help` text.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(
+    name = "lilypad-cli",
+    version,
+    about = "Manage a Lilypad vault from the command line",
+    long_about = "Manage a Lilypad vault from the command line. Every command that reads or \
+                  changes the vault asks for the master password; nothing stays unlocked \
+                  between commands."
+)]
+pub(crate) struct Cli {
+    /// Vault file to use instead of the one in the config (the desktop app's vault).
+    #[arg(long, global = true, value_name = "PATH")]
+    pub vault: Option<PathBuf>,
+    /// Print machine-readable JSON on stdout.
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Read the master password, then any other requested secret, from stdin, one per line.
+    #[arg(long, global = true)]
+    pub password_stdin: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Create a new, empty vault.
+    Init(InitArgs),
+    /// Create the vault from an encrypted backup archive written by
+    /// `export --format archive`.
+    Restore(RestoreArgs),
+    /// Add an entry.
+    Add(AddArgs),
+    /// Show one entry, or print a single field of it.
+    Get(GetArgs),
+    /// List entries, optionally filtered by a search.
+    List(ListArgs),
+    /// Change fields of an entry.
+    Edit(EditArgs),
+    /// Delete an entry.
+    Rm(RmArgs),
+    /// Generate a password or passphrase without touching the vault.
+    Generate(GenerateArgs),
+    /// Write every entry to an encrypted backup archive, a KeePass database,
+    /// or unencrypted JSON or CSV.
+    Export(ExportArgs),
+    /// Add the entries of a Lilypad, Bitwarden, KeePass, or CSV export to the
+    /// vault.
+    Import(ImportArgs),
+}
+
+#[derive(Args)]
+pub(crate) struct RestoreArgs {
+    /// The archive to restore.
+    pub file: PathBuf,
+    #[command(flatten)]
+    pub cost: InitArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct InitArgs {
+    /// Argon2id memory cost in MiB [default: from the config].
+    #[arg(long, value_name = "MIB")]
+    pub memory_mib: Option<u32>,
+    /// Argon2id iterations [default: from the config].
+    #[arg(long)]
+    pub iterations: Option<u32>,
+    /// Argon2id lanes [default: from the config].
+    #[arg(long)]
+    pub parallelism: Option<u32>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum KindArg {
+    Login,
+    Note,
+    Card,
+    Identity,
+    Wifi,
+}
+
+/// Field options shared by `add` and `edit`.
+#[derive(Args)]
+pub(crate) struct EntryFields {
+    /// Login username, identity email, Wi-Fi SSID, or cardholder name.
+    #[arg(long)]
+    pub username: Option<String>,
+    /// Prompt for the password (card number for cards).
+    #[arg(long, conflicts_with = "generate")]
+    pub ask_password: bool,
+    /// Generate the password with the configured generator defaults.
+    #[arg(long)]
+    pub generate: bool,
+    /// Website address; repeat for several. On `edit`, replaces all URLs.
+    #[arg(long = "url", value_name = "URL")]
+    pub urls: Vec<String>,
+    /// Folder, with `/` between levels; an empty value removes it.
+    #[arg(long)]
+    pub folder: Option<String>,
+    #[arg(long)]
+    pub notes: Option<String>,
+    /// Tag; repeat for several. On `edit`, replaces all tags.
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Set a kind-specific field, e.g. `--set brand=Visa` or `--set city=Oslo`.
+    #[arg(long = "set", value_name = "FIELD=VALUE")]
+    pub kind_fields: Vec<String>,
+    /// Set a custom field; repeat for several.
+    #[arg(long = "field", value_name = "NAME=VALUE")]
+    pub custom_fields: Vec<String>,
+    /// Set a custom field that is masked like a password.
+    #[arg(long = "hidden-field", value_name = "NAME=VALUE")]
+    pub hidden_fields: Vec<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct AddArgs {
+    pub title: String,
+    #[arg(long, value_enum, default_value = "login")]
+    pub kind: KindArg,
+    #[command(flatten)]
+    pub fields: EntryFields,
+}
+
+#[derive(Args)]
+pub(crate) struct GetArgs {
+    /// Entry id, exact title, or a search that matches one entry.
+    pub entry: String,
+    /// Print only this field: title, username, password, url, folder,
+    /// notes, tags, id, a kind-specific field, or a custom field name.
+    #[arg(long)]
+    pub field: Option<String>,
+    /// Show secrets instead of masking them.
+    #[arg(long)]
+    pub reveal: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct ListArgs {
+    /// Search over titles, usernames, websites, tags, and notes.
+    pub query: Option<String>,
+}
+
+#[derive(Args)]
+pub(crate) struct EditArgs {
+    /// Entry id or exact title.
+    pub entry: String,
+    #[arg(long)]
+    pub title: Option<String>,
+    #[command(flatten)]
+    pub fields: EntryFields,
+}
+
+#[derive(Args)]
+pub(crate) struct RmArgs {
+    /// Entry id or exact title.
+    pub entry: String,
+    /// Delete without asking.
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct GenerateArgs {
+    /// Generate a passphrase instead of random characters.
+    #[arg(long)]
+    pub passphrase: bool,
+    /// Password length in characters.
+    #[arg(long, conflicts_with = "passphrase")]
+    pub length: Option<usize>,
+    /// Passphrase length in words.
+    #[arg(long, requires = "passphrase")]
+    pub words: Option<usize>,
+    #[arg(long)]
+    pub no_uppercase: bool,
+    #[arg(long)]
+    pub no_lowercase: bool,
+    #[arg(long)]
+    pub no_digits: bool,
+    #[arg(long)]
+    pub no_symbols: bool,
+    /// Leave out look-alike characters such as `l` and `1`.
+    #[arg(long)]
+    pub exclude_ambiguous: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct ExportArgs {
+    /// File to write; stdout when omitted. Archives and KeePass databases
+    /// need one.
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+    /// The format to write.
+    #[arg(long, value_enum, default_value = "lilypad")]
+    pub format: ExportFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ExportFormat {
+    /// Plaintext Lilypad JSON.
+    Lilypad,
+    /// Plaintext CSV with one row per entry, for spreadsheets and other
+    /// managers. Attachments, history, and custom fields are left out.
+    Csv,
+    /// A backup archive encrypted with a passphrase of its own, holding
+    /// every entry with its attachments and history. `restore` recreates
+    /// the vault from it.
+    Archive,
+    /// A KeePass KDBX 4 database protected by its own password, for
+    /// KeePassXC and KeePass 2.
+    Keepass,
+}
+
+#[derive(Args)]
+pub(crate) struct ImportArgs {
+    /// A file written by `lilypad-cli export`, or by the app named in
+    /// `--from`.
+    pub file: PathBuf,
+    /// The app that wrote the file.
+    #[arg(long, value_enum, default_value = "lilypad")]
+    pub from: ImportFormat,
+    /// For CSV files, read column HEADER into FIELD, overriding the
+    /// detected layout. FIELD is one of title, url, username, password,
+    /// notes, totp, folder, tags, custom, or ignore. Repeatable.
+    #[arg(long = "map", value_name = "HEADER=FIELD")]
+    pub map: Vec<String>,
+    /// Report what would be imported without changing the vault.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ImportFormat {
+    Lilypad,
+    /// A Bitwarden JSON export, plain or password protected.
+    Bitwarden,
+    /// A KeePass KDBX 4 database, as saved by KeePassXC and KeePass 2.
+    Keepass,
+    /// A CSV export from Chrome, Firefox, or LastPass, or any file with a
+    /// header row whose columns are given with --map.
+    Csv,
+}
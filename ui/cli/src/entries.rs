@@ -0,0 +1,383 @@
+//! `add`, `get`, `list`, `edit`, and `rm`.
+//!
+//! `get` names an entry by id, exact title (case-insensitive), or a search
+//! that matches exactly one entry. `edit` and `rm` change what they find, so
+//! they take only an id or an exact title. Anything ambiguous is refused with
+//! the candidates listed rather than guessed.
+
+use crate::args::{AddArgs, EditArgs, EntryFields, GetArgs, KindArg, ListArgs, RmArgs};
+use crate::error::CliError;
+use crate::output::{self, KindFieldMut};
+use crate::Context;
+use lilypad_core::secret::Zeroize;
+use lilypad_core::{
+    Card, CustomField, Entry, EntryKind, Identity, Login, SearchIndex, Uuid, Vault, Wifi,
+    WifiSecurity,
+};
+use serde_json::{json, Value};
+
+pub(crate) fn add(context: &mut Context, args: AddArgs) -> Result<(), CliError> {
+    let mut vault = context.open_vault()?;
+    let kind = match args.kind {
+        KindArg::Login => EntryKind::Login(Login::default()),
+        KindArg::Note => EntryKind::SecureNote,
+        KindArg::Card => EntryKind::Card(Card::default()),
+        KindArg::Identity => EntryKind::Identity(Identity::default()),
+        KindArg::Wifi => EntryKind::Wifi(Wifi::default()),
+    };
+    let mut entry = Entry::new(args.title, kind);
+    apply_fields(context, &mut entry, args.fields)?;
+    validate(&entry)?;
+
+    let (id, title) = (entry.id, entry.title.clone());
+    vault.add_entry(entry);
+    context.save(&mut vault)?;
+    if context.json {
+        output::print_json(&json!({ "id": id.to_string(), "title": title }));
+    } else {
+        outln!("Added \"{title}\" ({id})");
+    }
+    Ok(())
+}
+
+pub(crate) fn get(context: &mut Context, args: GetArgs) -> Result<(), CliError> {
+    let vault = context.open_vault()?;
+    let entry = find_entry(&vault, &args.entry)?;
+
+    match (&args.field, context.json) {
+        (Some(field), false) => outln!("{}", output::field_value(entry, field)?),
+        (Some(field), true) => output::print_json(&json!({
+            "field": field,
+            "value": output::field_value(entry, field)?,
+        })),
+        (None, false) => output::print_entry(entry, args.reveal),
+        (None, true) => output::print_entry_json(entry, args.reveal)?,
+    }
+    Ok(())
+}
+
+pub(crate) fn list(context: &mut Context, args: ListArgs) -> Result<(), CliError> {
+    let vault = context.open_vault()?;
+    let index = SearchIndex::build(vault.entries());
+    let hits = index.search(args.query.as_deref().unwrap_or_default());
+    let entries = hits.iter().filter_map(|hit| vault.entries().get(hit.index));
+
+    if context.json {
+        output::print_json(&Value::Array(entries.map(output::summary).collect()));
+    } else {
+        for entry in entries {
+            outln!("{}", output::summary_line(entry));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn edit(context: &mut Context, args: EditArgs) -> Result<(), CliError> {
+    let mut vault = context.open_vault()?;
+    let mut entry = find_exact_entry(&vault, &args.entry)?.clone();
+    if let Some(title) = args.title {
+        entry.title = title;
+    }
+    apply_fields(context, &mut entry, args.fields)?;
+    validate(&entry)?;
+
+    let (id, title) = (entry.id, entry.title.clone());
+    vault.update_entry(entry);
+    context.save(&mut vault)?;
+    output::print_message(context, &format!("Updated \"{title}\" ({id})"));
+    Ok(())
+}
+
+pub(crate) fn remove(context: &mut Context, args: RmArgs) -> Result<(), CliError> {
+    let mut vault = context.open_vault()?;
+    let entry = find_exact_entry(&vault, &args.entry)?;
+    let (id, title) = (entry.id, entry.title.clone());
+
+    if !args.yes
+        && !context
+            .prompt
+            .confirm(&format!("Delete \"{title}\" ({id})?"))?
+    {
+        return Err(CliError::Cancelled);
+    }
+    vault.remove_entry(id);
+    context.save(&mut vault)?;
+    output::print_message(context, &format!("Deleted \"{title}\" ({id})"));
+    Ok(())
+}
+
+/// Resolves an id, exact title, or single-match search to an entry.
+pub(crate) fn find_entry<'a>(vault: &'a Vault, query: &str) -> Result<&'a Entry, CliError> {
+    let query = query.trim();
+    let exact = exact_matches(vault, query)?;
+    let candidates = if exact.is_empty() {
+        search(vault, query)
+    } else {
+        exact
+    };
+    match candidates.as_slice() {
+        [] => Err(CliError::NotFound(query.to_string())),
+        [entry] => Ok(entry),
+        several => Err(CliError::Ambiguous(query.to_string(), describe(several))),
+    }
+}
+
+/// Resolves an id or exact title to an entry, for commands that change or
+/// delete it. A search is never enough; its matches are listed instead.
+pub(crate) fn find_exact_entry<'a>(vault: &'a Vault, query: &str) -> Result<&'a Entry, CliError> {
+    let query = query.trim();
+    match exact_matches(vault, query)?.as_slice() {
+        [] => match search(vault, query).as_slice() {
+            [] => Err(CliError::NotFound(query.to_string())),
+            matches => Err(CliError::NotExact(query.to_string(), describe(matches))),
+        },
+        [entry] => Ok(entry),
+        several => Err(CliError::Ambiguous(query.to_string(), describe(several))),
+    }
+}
+
+/// The entry with id `query`, or every entry titled `query` ignoring case.
+fn exact_matches<'a>(vault: &'a Vault, query: &str) -> Result<Vec<&'a Entry>, CliError> {
+    if query.is_empty() {
+        return Err(CliError::Invalid("name an entry to use".to_string()));
+    }
+    if let Some(entry) = Uuid::parse_str(query).ok().and_then(|id| vault.entry(id)) {
+        return Ok(vec![entry]);
+    }
+    let folded = query.to_lowercase();
+    Ok(vault
+        .entries()
+        .iter()
+        .filter(|entry| entry.title.to_lowercase() == folded)
+        .collect())
+}
+
+fn search<'a>(vault: &'a Vault, query: &str) -> Vec<&'a Entry> {
+    SearchIndex::build(vault.entries())
+        .search(query)
+        .iter()
+        .filter_map(|hit| vault.entries().get(hit.index))
+        .collect()
+}
+
+/// `id  title` lines for listing candidates in an error.
+fn describe(entries: &[&Entry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| format!("{}  {}", entry.id, entry.title))
+        .collect()
+}
+
+/// Applies the field options of `add` and `edit` to `entry`.
+fn apply_fields(
+    context: &mut Context,
+    entry: &mut Entry,
+    fields: EntryFields,
+) -> Result<(), CliError> {
+    for assignment in &fields.kind_fields {
+        let (name, value) = split_assignment(assignment)?;
+        set_kind_field(entry, name, value)?;
+    }
+    if let Some(username) = fields.username {
+        match &mut entry.kind {
+            EntryKind::Login(login) => login.username = username,
+            EntryKind::Identity(identity) => identity.email = username,
+            EntryKind::Wifi(wifi) => wifi.ssid = username,
+            EntryKind::Card(card) => card.cardholder_name = username,
+            EntryKind::SecureNote => {
+                return Err(CliError::Invalid(
+                    "secure notes have no username".to_string(),
+                ))
+            }
+        }
+    }
+
+    let password = if fields.generate {
+        Some(context.config.generator.to_settings().generate()?)
+    } else if fields.ask_password {
+        Some(context.prompt.new_secret("Entry password")?)
+    } else {
+        None
+    };
+    if let Some(password) = password {
+        let slot = match &mut entry.kind {
+            EntryKind::Login(login) => &mut login.password,
+            EntryKind::Wifi(wifi) => &mut wifi.password,
+            EntryKind::Card(card) => &mut card.number,
+            EntryKind::SecureNote | EntryKind::Identity(_) => {
+                return Err(CliError::Invalid(format!(
+                    "{} entries have no password",
+                    entry.kind.label().to_lowercase()
+                )))
+            }
+        };
+        slot.clear();
+        slot.push_str(&password);
+    }
+
+    if !fields.urls.is_empty() {
+        let EntryKind::Login(login) = &mut entry.kind else {
+            return Err(CliError::Invalid("only logins have URLs".to_string()));
+        };
+        login.urls = fields.urls;
+    }
+    if let Some(folder) = fields.folder {
+        entry.folder = folder.trim().to_string();
+    }
+    if let Some(notes) = fields.notes {
+        entry.notes = notes;
+    }
+    if !fields.tags.is_empty() {
+        entry.tags = fields.tags;
+    }
+    for (assignment, hidden) in fields
+        .custom_fields
+        .iter()
+        .map(|field| (field, false))
+        .chain(fields.hidden_fields.iter().map(|field| (field, true)))
+    {
+        let (name, value) = split_assignment(assignment)?;
+        let field = CustomField {
+            name: name.to_string(),
+            value: value.to_string(),
+            hidden,
+        };
+        match entry
+            .custom_fields
+            .iter_mut()
+            .find(|existing| existing.name == name)
+        {
+            Some(existing) => *existing = field,
+            None => entry.custom_fields.push(field),
+        }
+    }
+    Ok(())
+}
+
+/// Sets one kind-specific field by its stored name, wiping the old value.
+fn set_kind_field(entry: &mut Entry, name: &str, value: &str) -> Result<(), CliError> {
+    let kind = entry.kind.label().to_lowercase();
+    let unknown = || CliError::Invalid(format!("{kind} entries have no field \"{name}\""));
+    match output::kind_field_mut(&mut entry.kind, name).ok_or_else(unknown)? {
+        KindFieldMut::Text(text) => {
+            text.zeroize();
+            text.push_str(value);
+        }
+        KindFieldMut::Flag(flag) => {
+            *flag = match value {
+                "true" | "yes" => true,
+                "false" | "no" => false,
+                _ => return Err(CliError::Invalid(format!("{name} must be true or false"))),
+            }
+        }
+        KindFieldMut::Security(security) => {
+            *security = WifiSecurity::ALL
+                .into_iter()
+                .find(|&mode| output::security_name(mode) == value)
+                .ok_or_else(|| {
+                    let names: Vec<_> = WifiSecurity::ALL
+                        .into_iter()
+                        .map(output::security_name)
+                        .collect();
+                    CliError::Invalid(format!("invalid {name}: use one of {}", names.join(", ")))
+                })?;
+        }
+    }
+    Ok(())
+}
+
+fn split_assignment(assignment: &str) -> Result<(&str, &str), CliError> {
+    assignment
+        .split_once('=')
+        .map(|(name, value)| (name.trim(), value))
+        .filter(|(name, _)| !name.is_empty())
+        .ok_or_else(|| CliError::Invalid(format!("expected NAME=VALUE, got \"{assignment}\"")))
+}
+
+fn validate(entry: &Entry) -> Result<(), CliError> {
+    entry.validate().map_err(|errors| {
+        CliError::Invalid(
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lilypad_core::crypto::KdfParams;
+
+    /// Cheap parameters so tests do not pay for a production-strength KDF.
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 16 * 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn finds_entries_by_id_title_or_unique_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.lilypad");
+        let mut vault = Vault::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        let mail = Entry::new("Mail", EntryKind::Login(Login::default()));
+        let mail_id = mail.id;
+        vault.add_entry(mail);
+        vault.add_entry(Entry::new("Mailbox", EntryKind::SecureNote));
+        vault.add_entry(Entry::new("Bank", EntryKind::Card(Card::default())));
+
+        assert_eq!(
+            find_entry(&vault, &mail_id.to_string()).unwrap().id,
+            mail_id
+        );
+        assert_eq!(find_entry(&vault, "mail").unwrap().id, mail_id);
+        assert_eq!(find_entry(&vault, "ban").unwrap().title, "Bank");
+        assert!(matches!(
+            find_entry(&vault, "mai"),
+            Err(CliError::Ambiguous(_, candidates)) if candidates.len() == 2
+        ));
+        assert!(matches!(
+            find_entry(&vault, "nothing"),
+            Err(CliError::NotFound(_))
+        ));
+
+        // Commands that change entries take no search matches.
+        assert_eq!(find_exact_entry(&vault, "MAIL").unwrap().id, mail_id);
+        assert_eq!(
+            find_exact_entry(&vault, &mail_id.to_string()).unwrap().id,
+            mail_id
+        );
+        assert!(matches!(
+            find_exact_entry(&vault, "ban"),
+            Err(CliError::NotExact(_, candidates)) if candidates.len() == 1
+        ));
+        assert!(matches!(
+            find_exact_entry(&vault, "nothing"),
+            Err(CliError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn sets_kind_fields_by_stored_name() {
+        let mut entry = Entry::new("Home", EntryKind::Wifi(Wifi::default()));
+        set_kind_field(&mut entry, "ssid", "pond").unwrap();
+        set_kind_field(&mut entry, "hidden", "yes").unwrap();
+        set_kind_field(&mut entry, "security", "wpa3_personal").unwrap();
+        let EntryKind::Wifi(wifi) = &entry.kind else {
+            unreachable!()
+        };
+        assert_eq!(wifi.ssid, "pond");
+        assert!(wifi.hidden);
+        assert_eq!(wifi.security, WifiSecurity::Wpa3Personal);
+
+        assert!(set_kind_field(&mut entry, "brand", "Visa").is_err());
+        assert!(set_kind_field(&mut entry, "type", "login").is_err());
+        assert!(set_kind_field(&mut entry, "hidden", "maybe").is_err());
+        assert!(set_kind_field(&mut entry, "security", "WPA3").is_err());
+        assert!(split_assignment("=value").is_err());
+        assert_eq!(split_assignment("a=b=c").unwrap(), ("a", "b=c"));
+    }
+}
@@ -0,0 +1,135 @@
+//! The one error type every command returns; `main` prints it and exits
+//! with a failure status.
+
+use lilypad_core::{
+    ArchiveError, BitwardenError, ConfigError, CsvError, ExportError, GeneratorError, KdbxError,
+    VaultError,
+};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub(crate) enum CliError {
+    Io(io::Error),
+    Config(ConfigError),
+    Vault(VaultError),
+    Export(ExportError),
+    Bitwarden(BitwardenError),
+    Kdbx(KdbxError),
+    Csv(CsvError),
+    Archive(ArchiveError),
+    Generator(GeneratorError),
+    /// No vault file exists at the path; run `init` first.
+    NoVault(PathBuf),
+    /// `init`, `restore`, or `export` would overwrite an existing file.
+    FileExists(PathBuf),
+    /// No entry matches the given id, title, or search.
+    NotFound(String),
+    /// Several entries match; the titles and ids are listed.
+    Ambiguous(String, Vec<String>),
+    /// Only a search matches, which `edit` and `rm` do not act on; the
+    /// matches are listed.
+    NotExact(String, Vec<String>),
+    /// Arguments that parse but make no sense together.
+    Invalid(String),
+    Cancelled,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Config(error) => write!(f, "{error}"),
+            Self::Vault(error) => write!(f, "{error}"),
+            Self::Export(error) => write!(f, "{error}"),
+            Self::Bitwarden(error) => write!(f, "{error}"),
+            Self::Kdbx(error) => write!(f, "{error}"),
+            Self::Csv(error) => write!(f, "{error}"),
+            Self::Archive(error) => write!(f, "{error}"),
+            Self::Generator(error) => write!(f, "unable to generate a password: {error}"),
+            Self::NoVault(path) => write!(
+                f,
+                "no vault at {}; create one with `lilypad-cli init`",
+                path.display()
+            ),
+            Self::FileExists(path) => write!(f, "{} already exists", path.display()),
+            Self::NotFound(query) => write!(f, "no entry matches \"{query}\""),
+            Self::Ambiguous(query, matches) => {
+                write!(f, "\"{query}\" matches several entries; use an id:")?;
+                for candidate in matches {
+                    write!(f, "\n  {candidate}")?;
+                }
+                Ok(())
+            }
+            Self::NotExact(query, matches) => {
+                write!(
+                    f,
+                    "no entry is titled \"{query}\"; name the one to change by id or exact title:"
+                )?;
+                for candidate in matches {
+                    write!(f, "\n  {candidate}")?;
+                }
+                Ok(())
+            }
+            Self::Invalid(reason) => write!(f, "{reason}"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(error: ConfigError) -> Self {
+        Self::Config(error)
+    }
+}
+
+impl From<VaultError> for CliError {
+    fn from(error: VaultError) -> Self {
+        Self::Vault(error)
+    }
+}
+
+impl From<ExportError> for CliError {
+    fn from(error: ExportError) -> Self {
+        Self::Export(error)
+    }
+}
+
+impl From<BitwardenError> for CliError {
+    fn from(error: BitwardenError) -> Self {
+        Self::Bitwarden(error)
+    }
+}
+
+impl From<KdbxError> for CliError {
+    fn from(error: KdbxError) -> Self {
+        Self::Kdbx(error)
+    }
+}
+
+impl From<CsvError> for CliError {
+    fn from(error: CsvError) -> Self {
+        Self::Csv(error)
+    }
+}
+
+impl From<ArchiveError> for CliError {
+    fn from(error: ArchiveError) -> Self {
+        Self::Archive(error)
+    }
+}
+
+impl From<GeneratorError> for CliError {
+    fn from(error: GeneratorError) -> Self {
+        Self::Generator(error)
+    }
+}
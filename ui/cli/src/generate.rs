@@ -0,0 +1,39 @@
+//! `generate`: the configured generator, adjusted by flags, without opening
+//! the vault.
+
+use crate::args::GenerateArgs;
+use crate::error::CliError;
+use crate::{output, Context};
+use lilypad_core::GeneratorMode;
+use serde_json::json;
+
+pub(crate) fn generate(context: &Context, args: GenerateArgs) -> Result<(), CliError> {
+    let mut settings = context.config.generator.to_settings();
+    if args.passphrase {
+        settings.mode = GeneratorMode::Passphrase;
+    } else if args.length.is_some() {
+        settings.mode = GeneratorMode::Characters;
+    }
+
+    let password = &mut settings.password;
+    password.length = args.length.unwrap_or(password.length);
+    password.uppercase &= !args.no_uppercase;
+    password.lowercase &= !args.no_lowercase;
+    password.digits &= !args.no_digits;
+    password.symbols &= !args.no_symbols;
+    password.exclude_ambiguous |= args.exclude_ambiguous;
+    if let Some(words) = args.words {
+        settings.passphrase.word_count = words;
+    }
+
+    let generated = settings.generate()?;
+    if context.json {
+        output::print_json(&json!({
+            "password": &*generated,
+            "entropy_bits": settings.entropy_bits().round(),
+        }));
+    } else {
+        outln!("{}", &*generated);
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! `lilypad-cli`: scriptable access to the vault the desktop app uses.
+//!
+//! There is no session: each command that touches the vault asks for the
+//! master password, does its work, saves, and drops the decrypted vault
+//! before exiting.
+
+/// `println!` that stops quietly instead of panicking when stdout is closed,
+/// as when the output is piped into `head`.
+macro_rules! outln {
+    ($($arg:tt)*) => {{
+        use std::io::Write as _;
+        let _ = writeln!(std::io::stdout(), $($arg)*);
+    }};
+}
+
+mod args;
+mod entries;
+mod error;
+mod generate;
+mod output;
+mod prompt;
+mod transfer;
+
+use args::{Cli, Command, InitArgs};
+use clap::Parser;
+use error::CliError;
+use lilypad_core::config::{
+    KdfConfig, KDF_ITERATIONS_RANGE, KDF_MEMORY_MIB_RANGE, KDF_PARALLELISM_RANGE,
+};
+use lilypad_core::secret::SecretBuffer;
+use lilypad_core::{AppDirs, Backups, Config, PasswordPolicy, Vault};
+use prompt::Prompt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("lilypad-cli: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// What every command needs: the loaded config, which vault to use, the
+/// output mode, and where secrets come from.
+pub(crate) struct Context {
+    pub config: Config,
+    pub vault_path: PathBuf,
+    /// Where saves keep snapshots, per the config.
+    pub backups: Option<Backups>,
+    pub json: bool,
+    pub prompt: Prompt,
+}
+
+impl Context {
+    fn load(cli: &Cli) -> Result<Self, CliError> {
+        let dirs = AppDirs::platform();
+        let config = match &dirs {
+            Some(dirs) => Config::load(&dirs.config_dir)?,
+            None => Config::default(),
+        };
+        let vault_path = match (&cli.vault, &dirs) {
+            (Some(path), _) => path.clone(),
+            (None, Some(dirs)) => dirs.vault_path(&config),
+            (None, None) => {
+                return Err(CliError::Invalid(
+                    "no home directory to find the vault in; pass --vault".to_string(),
+                ))
+            }
+        };
+        let backups = dirs.and_then(|dirs| dirs.backups(&config));
+        Ok(Self {
+            config,
+            vault_path,
+            backups,
+            json: cli.json,
+            prompt: Prompt::new(cli.password_stdin),
+        })
+    }
+
+    /// Asks for the master password and opens the vault.
+    pub(crate) fn open_vault(&mut self) -> Result<Vault, CliError> {
+        if !self.vault_path.exists() {
+            return Err(CliError::NoVault(self.vault_path.clone()));
+        }
+        let password = self.prompt.secret("Master password")?;
+        let mut vault = Vault::open(&self.vault_path, &password)?;
+        vault.set_backups(self.backups.clone());
+        Ok(vault)
+    }
+
+    /// Saves the vault, warning when its snapshot could not be kept.
+    pub(crate) fn save(&self, vault: &mut Vault) -> Result<(), CliError> {
+        vault.save()?;
+        if let Some(error) = vault.backup_error() {
+            eprintln!("warning: saved, but the backup snapshot failed: {error}");
+        }
+        Ok(())
+    }
+}
+
+fn run(cli: Cli) -> Result<(), CliError> {
+    let mut context = Context::load(&cli)?;
+    match cli.command {
+        Command::Init(args) => init(&mut context, args),
+        Command::Restore(args) => transfer::restore(&mut context, args),
+        Command::Add(args) => entries::add(&mut context, args),
+        Command::Get(args) => entries::get(&mut context, args),
+        Command::List(args) => entries::list(&mut context, args),
+        Command::Edit(args) => entries::edit(&mut context, args),
+        Command::Rm(args) => entries::remove(&mut context, args),
+        Command::Generate(args) => generate::generate(&context, args),
+        Command::Export(args) => transfer::export(&mut context, args),
+        Command::Import(args) => transfer::import(&mut context, args),
+    }
+}
+
+/// Creates an empty vault.
+fn init(context: &mut Context, args: InitArgs) -> Result<(), CliError> {
+    if context.vault_path.exists() {
+        return Err(CliError::FileExists(context.vault_path.clone()));
+    }
+    let kdf = kdf_settings(context, &args)?;
+    let password = new_master_password(context)?;
+
+    if let Some(parent) = context.vault_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let vault = Vault::create_with_params(&context.vault_path, &password, kdf.to_params())?;
+    output::print_message(
+        context,
+        &format!("Created vault at {}", vault.path().display()),
+    );
+    Ok(())
+}
+
+/// The configured Argon2id settings with any overrides from the command
+/// line, checked against the supported ranges.
+pub(crate) fn kdf_settings(context: &Context, args: &InitArgs) -> Result<KdfConfig, CliError> {
+    let mut kdf = context.config.security.kdf;
+    kdf.memory_mib = args.memory_mib.unwrap_or(kdf.memory_mib);
+    kdf.iterations = args.iterations.unwrap_or(kdf.iterations);
+    kdf.parallelism = args.parallelism.unwrap_or(kdf.parallelism);
+    if !(KDF_MEMORY_MIB_RANGE.0..=KDF_MEMORY_MIB_RANGE.1).contains(&kdf.memory_mib)
+        || !(KDF_ITERATIONS_RANGE.0..=KDF_ITERATIONS_RANGE.1).contains(&kdf.iterations)
+        || !(KDF_PARALLELISM_RANGE.0..=KDF_PARALLELISM_RANGE.1).contains(&kdf.parallelism)
+    {
+        return Err(CliError::Invalid(format!(
+            "key derivation settings must be {}-{} MiB, {}-{} iterations, and {}-{} lanes",
+            KDF_MEMORY_MIB_RANGE.0,
+            KDF_MEMORY_MIB_RANGE.1,
+            KDF_ITERATIONS_RANGE.0,
+            KDF_ITERATIONS_RANGE.1,
+            KDF_PARALLELISM_RANGE.0,
+            KDF_PARALLELISM_RANGE.1
+        )));
+    }
+    Ok(kdf)
+}
+
+/// Asks for the master password of a new vault and checks it against the
+/// same policy as the desktop create-vault form.
+pub(crate) fn new_master_password(context: &mut Context) -> Result<SecretBuffer, CliError> {
+    let password = context.prompt.new_secret("New master password")?;
+    let unmet: Vec<String> = PasswordPolicy::default()
+        .requirements(&password)
+        .into_iter()
+        .filter(|requirement| !requirement.satisfied)
+        .map(|requirement| requirement.label)
+        .collect();
+    if !unmet.is_empty() {
+        return Err(CliError::Invalid(format!(
+            "the master password does not meet the requirements:\n  {}",
+            unmet.join("\n  ")
+        )));
+    }
+    Ok(password)
+}
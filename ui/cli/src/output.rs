@@ -0,0 +1,421 @@
+//! Human-readable and `--json` rendering of entries and messages, and access
+//! to entry fields by name.
+//!
+//! Kind-specific fields go by the names they are stored under in the vault
+//! and used in exports (`security_code`, `postal_code`, …). They are read
+//! and written in place, never through a JSON copy that would leave secrets
+//! behind on the heap.
+
+use crate::error::CliError;
+use crate::Context;
+use lilypad_core::secret::{Zeroize, Zeroizing};
+use lilypad_core::text::{capitalize, format_size};
+use lilypad_core::{clock, Entry, EntryKind, WifiSecurity};
+use serde_json::{json, Value};
+
+/// Kind-specific fields masked unless `--reveal` is given.
+const SECRET_FIELDS: [&str; 4] = ["password", "number", "security_code", "totp"];
+/// What a masked secret shows instead of its value.
+const MASK: &str = "••••••••";
+
+pub(crate) fn print_json(value: &Value) {
+    let text = Zeroizing::new(serde_json::to_string_pretty(value).unwrap_or_default());
+    outln!("{}", text.as_str());
+}
+
+/// A kind-specific field, borrowed from the entry.
+pub(crate) enum KindField<'a> {
+    Text(&'a str),
+    List(&'a [String]),
+    Flag(bool),
+    Security(WifiSecurity),
+}
+
+/// A kind-specific field that `--set` can change.
+pub(crate) enum KindFieldMut<'a> {
+    Text(&'a mut String),
+    Flag(&'a mut bool),
+    Security(&'a mut WifiSecurity),
+}
+
+/// The kind-specific fields of `entry` in stored order, with their names.
+pub(crate) fn kind_fields(entry: &Entry) -> Vec<(&'static str, KindField<'_>)> {
+    use KindField::{Flag, List, Security, Text};
+    match &entry.kind {
+        EntryKind::Login(login) => vec![
+            ("username", Text(&login.username)),
+            ("password", Text(&login.password)),
+            ("urls", List(&login.urls)),
+            ("password_rules", Text(&login.password_rules)),
+            ("totp", Text(&login.totp)),
+        ],
+        EntryKind::SecureNote => Vec::new(),
+        EntryKind::Card(card) => vec![
+            ("cardholder_name", Text(&card.cardholder_name)),
+            ("brand", Text(&card.brand)),
+            ("number", Text(&card.number)),
+            ("expiry_month", Text(&card.expiry_month)),
+            ("expiry_year", Text(&card.expiry_year)),
+            ("security_code", Text(&card.security_code)),
+        ],
+        EntryKind::Identity(identity) => vec![
+            ("first_name", Text(&identity.first_name)),
+            ("last_name", Text(&identity.last_name)),
+            ("email", Text(&identity.email)),
+            ("phone", Text(&identity.phone)),
+            ("company", Text(&identity.company)),
+            ("address", Text(&identity.address)),
+            ("city", Text(&identity.city)),
+            ("state", Text(&identity.state)),
+            ("postal_code", Text(&identity.postal_code)),
+            ("country", Text(&identity.country)),
+        ],
+        EntryKind::Wifi(wifi) => vec![
+            ("ssid", Text(&wifi.ssid)),
+            ("password", Text(&wifi.password)),
+            ("security", Security(wifi.security)),
+            ("hidden", Flag(wifi.hidden)),
+        ],
+    }
+}
+
+/// The kind-specific field of `kind` stored as `name`, if `--set` can
+/// change it; lists such as `urls` have options of their own.
+pub(crate) fn kind_field_mut<'a>(kind: &'a mut EntryKind, name: &str) -> Option<KindFieldMut<'a>> {
+    use KindFieldMut::{Flag, Security, Text};
+    Some(match (kind, name) {
+        (EntryKind::Login(login), "username") => Text(&mut login.username),
+        (EntryKind::Login(login), "password") => Text(&mut login.password),
+        (EntryKind::Login(login), "password_rules") => Text(&mut login.password_rules),
+        (EntryKind::Login(login), "totp") => Text(&mut login.totp),
+        (EntryKind::Card(card), "cardholder_name") => Text(&mut card.cardholder_name),
+        (EntryKind::Card(card), "brand") => Text(&mut card.brand),
+        (EntryKind::Card(card), "number") => Text(&mut card.number),
+        (EntryKind::Card(card), "expiry_month") => Text(&mut card.expiry_month),
+        (EntryKind::Card(card), "expiry_year") => Text(&mut card.expiry_year),
+        (EntryKind::Card(card), "security_code") => Text(&mut card.security_code),
+        (EntryKind::Identity(identity), "first_name") => Text(&mut identity.first_name),
+        (EntryKind::Identity(identity), "last_name") => Text(&mut identity.last_name),
+        (EntryKind::Identity(identity), "email") => Text(&mut identity.email),
+        (EntryKind::Identity(identity), "phone") => Text(&mut identity.phone),
+        (EntryKind::Identity(identity), "company") => Text(&mut identity.company),
+        (EntryKind::Identity(identity), "address") => Text(&mut identity.address),
+        (EntryKind::Identity(identity), "city") => Text(&mut identity.city),
+        (EntryKind::Identity(identity), "state") => Text(&mut identity.state),
+        (EntryKind::Identity(identity), "postal_code") => Text(&mut identity.postal_code),
+        (EntryKind::Identity(identity), "country") => Text(&mut identity.country),
+        (EntryKind::Wifi(wifi), "ssid") => Text(&mut wifi.ssid),
+        (EntryKind::Wifi(wifi), "password") => Text(&mut wifi.password),
+        (EntryKind::Wifi(wifi), "security") => Security(&mut wifi.security),
+        (EntryKind::Wifi(wifi), "hidden") => Flag(&mut wifi.hidden),
+        _ => return None,
+    })
+}
+
+/// The stored name of a Wi-Fi security mode, e.g. `wpa2_personal`.
+pub(crate) fn security_name(security: WifiSecurity) -> &'static str {
+    match security {
+        WifiSecurity::Open => "open",
+        WifiSecurity::Wep => "wep",
+        WifiSecurity::WpaPersonal => "wpa_personal",
+        WifiSecurity::Wpa2Personal => "wpa2_personal",
+        WifiSecurity::Wpa3Personal => "wpa3_personal",
+        WifiSecurity::Enterprise => "enterprise",
+    }
+}
+
+/// Wipes every string and number in `value`, which may hold secrets,
+/// before it is dropped.
+fn wipe(value: &mut Value) {
+    match value {
+        Value::String(text) => text.zeroize(),
+        Value::Number(number) => *number = 0.into(),
+        Value::Array(items) => items.iter_mut().for_each(wipe),
+        Value::Object(fields) => fields.values_mut().for_each(wipe),
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+/// A confirmation such as "Deleted …": a line of text, or
+/// `{"message": …}` with `--json`.
+pub(crate) fn print_message(context: &Context, message: &str) {
+    if context.json {
+        print_json(&json!({ "message": message }));
+    } else {
+        outln!("{message}");
+    }
+}
+
+/// The non-secret overview `list` prints for each entry.
+pub(crate) fn summary(entry: &Entry) -> Value {
+    json!({
+        "id": entry.id.to_string(),
+        "title": entry.title,
+        "kind": kind_name(entry),
+        "username": entry.username(),
+        "folder": entry.folder,
+        "tags": entry.tags,
+        "modified_at": entry.modified_at,
+    })
+}
+
+pub(crate) fn summary_line(entry: &Entry) -> String {
+    format!("{}  {:<32}  {}", entry.id, entry.title, entry.subtitle())
+}
+
+/// Prints every field of `entry`, masking secrets unless `reveal` is set.
+pub(crate) fn print_entry(entry: &Entry, reveal: bool) {
+    let mask = |value: &str, secret: bool| {
+        if secret && !reveal && !value.is_empty() {
+            MASK.to_string()
+        } else {
+            value.to_string()
+        }
+    };
+
+    outln!("{:<16}{}", "Title", entry.title);
+    outln!("{:<16}{}", "Type", entry.kind.label());
+    if !entry.folder.is_empty() {
+        outln!("{:<16}{}", "Folder", entry.folder);
+    }
+    for (name, value) in kind_fields(entry) {
+        let text = Zeroizing::new(match value {
+            KindField::Text(text) => text.to_string(),
+            KindField::List(items) => items.join(", "),
+            KindField::Flag(flag) => if flag { "yes" } else { "no" }.to_string(),
+            KindField::Security(security) => security_name(security).to_string(),
+        });
+        if !text.is_empty() {
+            let shown = Zeroizing::new(mask(&text, SECRET_FIELDS.contains(&name)));
+            outln!("{:<16}{}", field_label(name), shown.as_str());
+        }
+    }
+    for field in &entry.custom_fields {
+        outln!("{:<16}{}", field.name, mask(&field.value, field.hidden));
+    }
+    if !entry.tags.is_empty() {
+        outln!("{:<16}{}", "Tags", entry.tags.join(", "));
+    }
+    for attachment in &entry.attachments {
+        outln!(
+            "{:<16}{} ({})",
+            "Attachment",
+            attachment.name,
+            format_size(attachment.data.len() as u64)
+        );
+    }
+    if !entry.history.is_empty() {
+        outln!("{:<16}{} earlier versions", "History", entry.history.len());
+    }
+    if !entry.notes.is_empty() {
+        outln!(
+            "{:<16}{}",
+            "Notes",
+            entry.notes.replace('\n', "\n                ")
+        );
+    }
+    outln!("{:<16}{}", "Modified", clock::format_utc(entry.modified_at));
+    outln!("{:<16}{}", "ID", entry.id);
+    if !reveal {
+        print_reveal_hint();
+    }
+}
+
+/// Prints `entry` as JSON. Unless `reveal` is set, the secrets the text
+/// output masks are masked here too, in earlier versions as well, and
+/// attachments show their size instead of their contents.
+pub(crate) fn print_entry_json(entry: &Entry, reveal: bool) -> Result<(), CliError> {
+    let mut value =
+        serde_json::to_value(entry).map_err(|error| CliError::Invalid(error.to_string()))?;
+    if !reveal {
+        mask_json(&mut value, entry);
+    }
+    print_json(&value);
+    wipe(&mut value);
+    if !reveal {
+        print_reveal_hint();
+    }
+    Ok(())
+}
+
+fn mask_json(value: &mut Value, entry: &Entry) {
+    let mask = |value: &mut Value| {
+        if let Value::String(text) = value {
+            if !text.is_empty() {
+                text.zeroize();
+                text.push_str(MASK);
+            }
+        }
+    };
+    if let Some(Value::Object(kind)) = value.get_mut("kind") {
+        for name in SECRET_FIELDS {
+            if let Some(field) = kind.get_mut(name) {
+                mask(field);
+            }
+        }
+    }
+    if let Some(Value::Array(fields)) = value.get_mut("custom_fields") {
+        for (field, stored) in fields.iter_mut().zip(&entry.custom_fields) {
+            if stored.hidden {
+                if let Some(value) = field.get_mut("value") {
+                    mask(value);
+                }
+            }
+        }
+    }
+    if let Some(Value::Array(attachments)) = value.get_mut("attachments") {
+        for (attachment, stored) in attachments.iter_mut().zip(&entry.attachments) {
+            if let Value::Object(attachment) = attachment {
+                if let Some(mut data) = attachment.remove("data") {
+                    wipe(&mut data);
+                }
+                attachment.insert("size".to_string(), Value::from(stored.data.len()));
+            }
+        }
+    }
+    if let Some(Value::Array(history)) = value.get_mut("history") {
+        for (version, stored) in history.iter_mut().zip(&entry.history) {
+            mask_json(version, stored);
+        }
+    }
+}
+
+fn print_reveal_hint() {
+    eprintln!("(secrets hidden; use --reveal or --field to show them)");
+}
+
+/// The value `get --field` prints. `url` is the first website, and names
+/// that are not built-in fields are looked up among the custom fields.
+pub(crate) fn field_value(entry: &Entry, name: &str) -> Result<String, CliError> {
+    let value = match name {
+        "title" => Some(entry.title.clone()),
+        "id" => Some(entry.id.to_string()),
+        "folder" => Some(entry.folder.clone()),
+        "notes" => Some(entry.notes.clone()),
+        "tags" => Some(entry.tags.join(",")),
+        "username" => entry.username().map(str::to_string),
+        "password" => entry.primary_secret().map(str::to_string),
+        _ => None,
+    };
+    if let Some(value) = value {
+        return Ok(value);
+    }
+
+    let lookup = if name == "url" { "urls" } else { name };
+    let field = kind_fields(entry)
+        .into_iter()
+        .find(|(stored, _)| *stored == lookup);
+    if let Some((_, value)) = field {
+        return Ok(match value {
+            KindField::Text(text) => text.to_string(),
+            KindField::List(items) if name == "url" => items.first().cloned().unwrap_or_default(),
+            KindField::List(items) => items.join("\n"),
+            KindField::Flag(flag) => flag.to_string(),
+            KindField::Security(security) => security_name(security).to_string(),
+        });
+    }
+    entry
+        .custom_fields
+        .iter()
+        .find(|field| field.name.eq_ignore_ascii_case(name))
+        .map(|field| field.value.clone())
+        .ok_or_else(|| CliError::Invalid(format!("\"{}\" has no field \"{name}\"", entry.title)))
+}
+
+/// The `type` tag of the entry kind, as stored in the vault.
+pub(crate) fn kind_name(entry: &Entry) -> &'static str {
+    match entry.kind {
+        EntryKind::Login(_) => "login",
+        EntryKind::SecureNote => "secure_note",
+        EntryKind::Card(_) => "card",
+        EntryKind::Identity(_) => "identity",
+        EntryKind::Wifi(_) => "wifi",
+    }
+}
+
+/// `security_code` → `Security code`.
+fn field_label(name: &str) -> String {
+    match name {
+        "urls" => return "URLs".to_string(),
+        "totp" => return "TOTP".to_string(),
+        _ => {}
+    }
+    capitalize(&name.replace('_', " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lilypad_core::{Attachment, Card, CustomField, Identity, Login, Wifi};
+
+    #[test]
+    fn json_masks_what_the_text_output_masks() {
+        let login = Login {
+            username: "frog".to_string(),
+            password: "hunter2".to_string(),
+            ..Login::default()
+        };
+        let mut earlier = Entry::new("Mail", EntryKind::Login(login.clone()));
+        earlier.notes = "old notes".to_string();
+        let mut entry = Entry::new("Mail", EntryKind::Login(login));
+        entry.custom_fields = vec![
+            CustomField {
+                name: "PIN".to_string(),
+                value: "1234".to_string(),
+                hidden: true,
+            },
+            CustomField {
+                name: "Account".to_string(),
+                value: "42".to_string(),
+                hidden: false,
+            },
+        ];
+        entry.attachments = vec![Attachment {
+            name: "key.txt".to_string(),
+            data: b"secret".to_vec(),
+        }];
+        entry.history = vec![earlier];
+
+        let mut value = serde_json::to_value(&entry).unwrap();
+        mask_json(&mut value, &entry);
+        assert_eq!(value["kind"]["username"], "frog");
+        assert_eq!(value["kind"]["password"], MASK);
+        assert_eq!(value["custom_fields"][0]["value"], MASK);
+        assert_eq!(value["custom_fields"][1]["value"], "42");
+        assert_eq!(value["attachments"][0]["size"], 6);
+        assert!(value["attachments"][0].get("data").is_none());
+        assert_eq!(value["history"][0]["kind"]["password"], MASK);
+        assert!(!value.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn kind_fields_go_by_their_stored_names() {
+        let kinds = [
+            EntryKind::Login(Login::default()),
+            EntryKind::SecureNote,
+            EntryKind::Card(Card::default()),
+            EntryKind::Identity(Identity::default()),
+            EntryKind::Wifi(Wifi::default()),
+        ];
+        for kind in kinds {
+            let entry = Entry::new("Any", kind);
+            let stored = serde_json::to_value(&entry.kind).unwrap();
+            assert_eq!(stored["type"], kind_name(&entry));
+            let mut names: Vec<_> = stored.as_object().unwrap().keys().cloned().collect();
+            names.retain(|name| name != "type");
+            let mut typed: Vec<_> = kind_fields(&entry)
+                .into_iter()
+                .map(|(name, _)| name.to_string())
+                .collect();
+            names.sort();
+            typed.sort();
+            assert_eq!(typed, names);
+        }
+        for security in WifiSecurity::ALL {
+            assert_eq!(
+                serde_json::to_value(security).unwrap(),
+                security_name(security)
+            );
+        }
+    }
+}
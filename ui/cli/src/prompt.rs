@@ -0,0 +1,64 @@
+//! Reads secrets and confirmations either from the terminal, without echo,
+//! or one line at a time from stdin when `--password-stdin` is given.
+
+use crate::error::CliError;
+use lilypad_core::secret::{self, SecretBuffer, Zeroize};
+use std::io::{self, BufRead, Write};
+
+pub(crate) struct Prompt {
+    from_stdin: bool,
+}
+
+impl Prompt {
+    pub(crate) fn new(from_stdin: bool) -> Self {
+        Self { from_stdin }
+    }
+
+    /// Reads one secret. An empty answer is returned as is; callers decide
+    /// whether that is allowed.
+    pub(crate) fn secret(&mut self, label: &str) -> Result<SecretBuffer, CliError> {
+        let mut line = if self.from_stdin {
+            read_stdin_line()?
+        } else {
+            rpassword::prompt_password(format!("{label}: "))?
+        };
+        let mut secret = secret::secret_buffer();
+        secret.push_str(line.trim_end_matches(['\r', '\n']));
+        line.zeroize();
+        Ok(secret)
+    }
+
+    /// Reads a new secret, asking twice on a terminal so a typo cannot lock
+    /// the user out.
+    pub(crate) fn new_secret(&mut self, label: &str) -> Result<SecretBuffer, CliError> {
+        let secret = self.secret(label)?;
+        if !self.from_stdin {
+            let confirmation = self.secret(&format!("Confirm {}", label.to_lowercase()))?;
+            if *confirmation != *secret {
+                return Err(CliError::Invalid("the entries do not match".to_string()));
+            }
+        }
+        Ok(secret)
+    }
+
+    /// Asks a yes/no question on stderr; anything but `y` or `yes` is no.
+    pub(crate) fn confirm(&mut self, question: &str) -> Result<bool, CliError> {
+        eprint!("{question} [y/N] ");
+        io::stderr().flush()?;
+        let answer = read_stdin_line()?;
+        Ok(matches!(
+            answer.trim().to_ascii_lowercase().as_str(),
+            "y" | "yes"
+        ))
+    }
+}
+
+fn read_stdin_line() -> Result<String, CliError> {
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(CliError::Invalid(
+            "stdin ended before all input was read".to_string(),
+        ));
+    }
+    Ok(line)
+}
@@ -0,0 +1,264 @@
+//! `export` of encrypted backup archives, KeePass databases, and plaintext
+//! JSON or CSV; `import` of Lilypad, Bitwarden, KeePass, and CSV files; and
+//! `restore` of a vault from an archive.
+
+use crate::args::{ExportArgs, ExportFormat, ImportArgs, ImportFormat, RestoreArgs};
+use crate::error::CliError;
+use crate::{output, Context};
+use lilypad_core::csv_import::{self, Field, Mapping, Preset};
+use lilypad_core::secret::Zeroizing;
+use lilypad_core::{
+    archive, bitwarden, clock, export, kdbx, vault, ArchiveMetadata, CsvImport, MasterKey,
+};
+use std::fs;
+
+pub(crate) fn export(context: &mut Context, args: ExportArgs) -> Result<(), CliError> {
+    if let Some(path) = &args.output {
+        if path.exists() {
+            return Err(CliError::FileExists(path.clone()));
+        }
+    }
+    match args.format {
+        ExportFormat::Keepass | ExportFormat::Archive if args.output.is_none() => {
+            return Err(CliError::Invalid(
+                "archives and KeePass databases must be written to a file; pass --output"
+                    .to_string(),
+            ));
+        }
+        _ => {}
+    }
+    let vault = context.open_vault()?;
+    let contents = match args.format {
+        ExportFormat::Lilypad | ExportFormat::Csv => {
+            let text = if matches!(args.format, ExportFormat::Csv) {
+                export::to_csv(vault.entries())
+            } else {
+                export::to_json(vault.entries())?
+            };
+            eprintln!(
+                "warning: the export is not encrypted; anyone who can read it sees every password"
+            );
+            Zeroizing::new(text.as_bytes().to_vec())
+        }
+        ExportFormat::Archive => {
+            let passphrase = context.prompt.new_secret("Archive passphrase")?;
+            if passphrase.is_empty() {
+                return Err(CliError::Invalid(
+                    "the archive needs a passphrase".to_string(),
+                ));
+            }
+            let metadata = ArchiveMetadata::of(&vault);
+            Zeroizing::new(archive::write(&metadata, vault.entries(), &passphrase)?)
+        }
+        ExportFormat::Keepass => {
+            let password = context.prompt.new_secret("KeePass database password")?;
+            if password.is_empty() {
+                return Err(CliError::Invalid(
+                    "the KeePass database needs a password".to_string(),
+                ));
+            }
+            Zeroizing::new(kdbx::write(vault.entries(), &password, vault.kdf_params())?)
+        }
+    };
+
+    let Some(path) = args.output else {
+        // Only the plaintext formats get here without a path.
+        outln!("{}", String::from_utf8_lossy(&contents));
+        return Ok(());
+    };
+    vault::write_new(&path, &contents)?;
+    output::print_message(
+        context,
+        &format!(
+            "Exported {} entries to {}",
+            vault.entries().len(),
+            path.display()
+        ),
+    );
+    Ok(())
+}
+
+pub(crate) fn import(context: &mut Context, args: ImportArgs) -> Result<(), CliError> {
+    let data = Zeroizing::new(fs::read(&args.file)?);
+    let text = || {
+        std::str::from_utf8(&data)
+            .map_err(|_| CliError::Invalid(format!("{} is not a text file", args.file.display())))
+    };
+    if !args.map.is_empty() && !matches!(args.from, ImportFormat::Csv) {
+        return Err(CliError::Invalid(
+            "--map only applies to --from csv".to_string(),
+        ));
+    }
+    let mut duplicates = Vec::new();
+    let mut failed = Vec::new();
+    // Each format is checked before any password is asked for.
+    let (mut vault, entries, unsupported) = match args.from {
+        ImportFormat::Lilypad => {
+            let entries = export::from_json(text()?)?;
+            (context.open_vault()?, entries, Vec::new())
+        }
+        ImportFormat::Keepass => {
+            kdbx::check(&data)?;
+            let vault = context.open_vault()?;
+            let password = context.prompt.secret("KeePass database password")?;
+            (vault, kdbx::read(&data, &password)?, Vec::new())
+        }
+        ImportFormat::Bitwarden => {
+            let text = text()?;
+            let protected = bitwarden::is_password_protected(text)?;
+            let vault = context.open_vault()?;
+            let password = if protected {
+                Some(context.prompt.secret("Bitwarden export password")?)
+            } else {
+                None
+            };
+            let import = bitwarden::read(text, password.as_ref().map(|p| p.as_str()))?;
+            (vault, import.entries, import.skipped)
+        }
+        ImportFormat::Csv => {
+            let table = csv_import::read(text()?)?;
+            let mut mapping = Mapping::guess(&table);
+            for pair in &args.map {
+                apply_column(&mut mapping, &table.headers, pair)?;
+            }
+            if !mapping.is_usable() {
+                return Err(CliError::Invalid(format!(
+                    "no column of {} holds a title, website, username, password, or note; \
+                     name them with --map HEADER=FIELD",
+                    args.file.display()
+                )));
+            }
+            if let (Some(preset), true) = (Preset::detect(&table.headers), args.map.is_empty()) {
+                eprintln!(
+                    "note: reading {} as a {} export",
+                    args.file.display(),
+                    preset.label()
+                );
+            }
+            let vault = context.open_vault()?;
+            let import = csv_import::convert(&table, &mapping, vault.entries());
+            let CsvImport {
+                entries,
+                duplicates: duplicate_lines,
+                failed: failed_rows,
+            } = import;
+            duplicates = duplicate_lines;
+            failed = failed_rows;
+            (vault, entries, Vec::new())
+        }
+    };
+
+    let summary = if args.dry_run {
+        export::preview_import(&vault, &entries)
+    } else {
+        let summary = export::import_entries(&mut vault, entries);
+        if summary.added > 0 {
+            context.save(&mut vault)?;
+        }
+        summary
+    };
+    if context.json {
+        output::print_json(&serde_json::json!({
+            "added": summary.added,
+            "skipped": summary.skipped,
+            "unsupported": unsupported,
+            "duplicates": duplicates,
+            "failed": failed
+                .iter()
+                .map(|row| serde_json::json!({ "line": row.line, "reason": row.reason }))
+                .collect::<Vec<_>>(),
+            "dry_run": args.dry_run,
+        }));
+        return Ok(());
+    }
+    outln!(
+        "{} {} entries ({} already in the vault were skipped)",
+        if args.dry_run {
+            "Would import"
+        } else {
+            "Imported"
+        },
+        summary.added,
+        summary.skipped
+    );
+    for name in &unsupported {
+        eprintln!("warning: \"{name}\" has a type Lilypad cannot store; not imported");
+    }
+    if !duplicates.is_empty() {
+        let lines: Vec<_> = duplicates.iter().map(ToString::to_string).collect();
+        eprintln!(
+            "note: skipped {} rows repeating a vault entry or an earlier row (lines {})",
+            duplicates.len(),
+            lines.join(", ")
+        );
+    }
+    for row in &failed {
+        eprintln!("warning: line {}: {}; not imported", row.line, row.reason);
+    }
+    Ok(())
+}
+
+/// Applies one `--map HEADER=FIELD` override.
+fn apply_column(mapping: &mut Mapping, headers: &[String], pair: &str) -> Result<(), CliError> {
+    let Some((header, field)) = pair.rsplit_once('=') else {
+        return Err(CliError::Invalid(format!(
+            "--map takes HEADER=FIELD, not \"{pair}\""
+        )));
+    };
+    let Some(index) = headers.iter().position(|name| name.trim() == header.trim()) else {
+        return Err(CliError::Invalid(format!(
+            "the file has no column named \"{header}\"; its columns are {}",
+            headers.join(", ")
+        )));
+    };
+    mapping.columns[index] = match field.trim().to_ascii_lowercase().as_str() {
+        "ignore" => None,
+        "title" => Some(Field::Title),
+        "url" => Some(Field::Url),
+        "username" => Some(Field::Username),
+        "password" => Some(Field::Password),
+        "notes" => Some(Field::Notes),
+        "totp" => Some(Field::Totp),
+        "folder" => Some(Field::Folder),
+        "tags" => Some(Field::Tags),
+        "custom" => Some(Field::Custom),
+        other => {
+            return Err(CliError::Invalid(format!(
+                "unknown field \"{other}\"; use title, url, username, password, notes, totp, \
+                 folder, tags, custom, or ignore"
+            )))
+        }
+    };
+    Ok(())
+}
+
+/// Recreates the vault from a backup archive, under a new master password
+/// held to the same policy as `init`.
+pub(crate) fn restore(context: &mut Context, args: RestoreArgs) -> Result<(), CliError> {
+    if context.vault_path.exists() {
+        return Err(CliError::FileExists(context.vault_path.clone()));
+    }
+    let kdf = crate::kdf_settings(context, &args.cost)?;
+    let data = fs::read(&args.file)?;
+    archive::check(&data)?;
+    let passphrase = context.prompt.secret("Archive passphrase")?;
+    let archive = archive::read(&data, &passphrase)?;
+    let password = crate::new_master_password(context)?;
+
+    if let Some(parent) = context.vault_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let archived_at = archive.metadata.archived_at;
+    let master_key = MasterKey::derive(&password, kdf.to_params())?;
+    let vault = archive::restore(&context.vault_path, master_key, archive)?;
+    output::print_message(
+        context,
+        &format!(
+            "Restored {} entries from the backup of {} into {}",
+            vault.entries().len(),
+            clock::format_utc(archived_at),
+            vault.path().display()
+        ),
+    );
+    Ok(())
+}
@@ -0,0 +1,236 @@
+//! Runs the `lilypad-cli` binary against a vault in a temporary folder,
+//! feeding secrets through `--password-stdin`.
+
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const PASSWORD: &str = "Correct-Horse-42-battery!";
+
+/// A home, config, and data folder of its own, so runs never touch the
+/// user's config or vault.
+struct Sandbox {
+    dir: tempfile::TempDir,
+}
+
+impl Sandbox {
+    fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    /// Runs a command on `vault.lilypad` with `stdin` as its input.
+    fn run(&self, args: &[&str], stdin: &str) -> Output {
+        self.run_on(&self.path("vault.lilypad"), args, stdin)
+    }
+
+    fn run_on(&self, vault: &Path, args: &[&str], stdin: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_lilypad-cli"))
+            .env("HOME", self.dir.path())
+            .env("XDG_CONFIG_HOME", self.path("config"))
+            .env("XDG_DATA_HOME", self.path("data"))
+            .arg("--vault")
+            .arg(vault)
+            .arg("--password-stdin")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Commands that fail early exit without reading their input.
+        let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+        child.wait_with_output().unwrap()
+    }
+
+    /// Runs a command that must succeed and returns its stdout.
+    fn ok(&self, args: &[&str], stdin: &str) -> String {
+        let output = self.run(args, stdin);
+        assert!(
+            output.status.success(),
+            "{args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Runs a command that must fail and returns its stderr.
+    fn fails(&self, args: &[&str], stdin: &str) -> String {
+        let output = self.run(args, stdin);
+        assert_eq!(output.status.code(), Some(1), "{args:?} should fail");
+        String::from_utf8(output.stderr).unwrap()
+    }
+
+    fn init(&self) {
+        self.ok(&init_args(), &format!("{PASSWORD}\n"));
+    }
+}
+
+/// `init` with the cheapest key derivation a vault may use.
+fn init_args() -> Vec<&'static str> {
+    vec![
+        "init",
+        "--memory-mib",
+        "16",
+        "--iterations",
+        "1",
+        "--parallelism",
+        "1",
+    ]
+}
+
+fn unlock() -> String {
+    format!("{PASSWORD}\n")
+}
+
+fn json(stdout: &str) -> Value {
+    serde_json::from_str(stdout).unwrap()
+}
+
+#[test]
+fn init_refuses_weak_passwords_and_existing_vaults() {
+    let sandbox = Sandbox::new();
+    let stderr = sandbox.fails(&init_args(), "short\n");
+    assert!(
+        stderr.contains("does not meet the requirements"),
+        "{stderr}"
+    );
+    assert!(!sandbox.path("vault.lilypad").exists());
+
+    sandbox.init();
+    assert!(sandbox.path("vault.lilypad").exists());
+    let stderr = sandbox.fails(&init_args(), &unlock());
+    assert!(stderr.contains("already exists"), "{stderr}");
+}
+
+#[test]
+fn commands_need_a_vault_and_its_password() {
+    let sandbox = Sandbox::new();
+    let stderr = sandbox.fails(&["list"], &unlock());
+    assert!(stderr.contains("lilypad-cli init"), "{stderr}");
+
+    sandbox.init();
+    let stderr = sandbox.fails(&["list"], "not the password\n");
+    assert!(stderr.contains("incorrect master password"), "{stderr}");
+    let stderr = sandbox.fails(&["list"], "");
+    assert!(stderr.contains("stdin ended"), "{stderr}");
+}
+
+#[test]
+fn add_get_edit_and_remove_an_entry() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    let added = json(&sandbox.ok(
+        &[
+            "--json",
+            "add",
+            "Mail",
+            "--username",
+            "frog",
+            "--ask-password",
+            "--url",
+            "https://mail.example.com",
+            "--hidden-field",
+            "PIN=1234",
+        ],
+        &format!("{PASSWORD}\nhunter2\n"),
+    ));
+    let id = added["id"].as_str().unwrap().to_string();
+    sandbox.ok(
+        &["add", "Mailbox", "--kind", "note", "--notes", "pond"],
+        &unlock(),
+    );
+
+    let text = sandbox.ok(&["get", &id], &unlock());
+    assert!(text.contains("frog"));
+    assert!(!text.contains("hunter2") && !text.contains("1234"));
+    let masked = json(&sandbox.ok(&["--json", "get", "mail"], &unlock()));
+    assert_eq!(masked["kind"]["username"], "frog");
+    assert_ne!(masked["kind"]["password"], "hunter2");
+    assert_ne!(masked["custom_fields"][0]["value"], "1234");
+    let revealed = json(&sandbox.ok(&["--json", "get", "mail", "--reveal"], &unlock()));
+    assert_eq!(revealed["kind"]["password"], "hunter2");
+    assert_eq!(
+        sandbox.ok(&["get", "Mail", "--field", "password"], &unlock()),
+        "hunter2\n"
+    );
+    let stderr = sandbox.fails(&["get", "Mai"], &unlock());
+    assert!(stderr.contains("matches several entries"), "{stderr}");
+
+    // Changes need an id or the exact title, never a search match.
+    let stderr = sandbox.fails(&["edit", "mail.example", "--username", "toad"], &unlock());
+    assert!(stderr.contains("no entry is titled"), "{stderr}");
+    sandbox.ok(&["edit", "MAIL", "--username", "toad"], &unlock());
+    assert_eq!(
+        sandbox.ok(&["get", &id, "--field", "username"], &unlock()),
+        "toad\n"
+    );
+    let stderr = sandbox.fails(&["rm", "mail.example", "--yes"], &unlock());
+    assert!(stderr.contains("no entry is titled"), "{stderr}");
+
+    // Anything but yes at the prompt keeps the entry.
+    let stderr = sandbox.fails(&["rm", &id], &format!("{PASSWORD}\nn\n"));
+    assert!(stderr.contains("cancelled"), "{stderr}");
+    sandbox.ok(&["rm", &id, "--yes"], &unlock());
+    let stderr = sandbox.fails(&["get", &id], &unlock());
+    assert!(stderr.contains("no entry matches"), "{stderr}");
+    let listed = json(&sandbox.ok(&["--json", "list"], &unlock()));
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["title"], "Mailbox");
+}
+
+#[test]
+fn export_and_import_round_trip() {
+    let sandbox = Sandbox::new();
+    sandbox.init();
+    sandbox.ok(
+        &["add", "Mail", "--username", "frog", "--ask-password"],
+        &format!("{PASSWORD}\nhunter2\n"),
+    );
+    sandbox.ok(
+        &["add", "Router", "--kind", "note", "--notes", "admin/admin"],
+        &unlock(),
+    );
+
+    let export = sandbox.path("export.json");
+    let export_arg = export.to_str().unwrap();
+    let output = sandbox.run(&["export", "--output", export_arg], &unlock());
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("warning"));
+    let stderr = sandbox.fails(&["export", "--output", export_arg], &unlock());
+    assert!(stderr.contains("already exists"), "{stderr}");
+    let stderr = sandbox.fails(&["export", "--format", "archive"], &unlock());
+    assert!(stderr.contains("--output"), "{stderr}");
+
+    let other = sandbox.path("other.lilypad");
+    let init = sandbox.run_on(&other, &init_args(), &unlock());
+    assert!(init.status.success());
+    let dry_run = sandbox.run_on(
+        &other,
+        &["--json", "import", export_arg, "--dry-run"],
+        &unlock(),
+    );
+    let summary = json(&String::from_utf8(dry_run.stdout).unwrap());
+    assert_eq!(summary["added"], 2);
+    assert_eq!(summary["dry_run"], true);
+
+    let import = sandbox.run_on(&other, &["--json", "import", export_arg], &unlock());
+    assert_eq!(json(&String::from_utf8(import.stdout).unwrap())["added"], 2);
+    let again = sandbox.run_on(&other, &["--json", "import", export_arg], &unlock());
+    let summary = json(&String::from_utf8(again.stdout).unwrap());
+    assert_eq!(
+        (summary["added"].clone(), summary["skipped"].clone()),
+        (0.into(), 2.into())
+    );
+
+    let password = sandbox.run_on(&other, &["get", "Mail", "--field", "password"], &unlock());
+    assert_eq!(String::from_utf8(password.stdout).unwrap(), "hunter2\n");
+    let stderr = sandbox.fails(&["import", "missing.json"], &unlock());
+    assert!(!stderr.is_empty());
+}
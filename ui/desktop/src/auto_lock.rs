@@ -0,0 +1,82 @@
+//! Locking the vault: on demand, after inactivity, on minimize or focus loss,
+//! and after the computer resumes from sleep.
+
+use crate::LilypadApp;
+use eframe::egui;
+use lilypad_core::secret::Zeroize;
+use lilypad_core::session::{LockReason, WindowState};
+use std::time::{Duration, Instant, SystemTime};
+
+impl LilypadApp {
+    /// Feeds this frame's input and window state to the auto-lock rules and
+    /// locks when one of them fires. Only call while the vault is unlocked.
+    pub(crate) fn poll_auto_lock(&mut self, ctx: &egui::Context) {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let (active, window) = ctx.input(|input| {
+            let viewport = input.viewport();
+            (
+                input.events.iter().any(is_user_input),
+                WindowState {
+                    minimized: viewport.minimized.unwrap_or(false),
+                    focused: viewport.focused.unwrap_or(input.focused),
+                },
+            )
+        });
+
+        if active {
+            self.auto_lock.activity(now, wall);
+        }
+        if let Some(reason) = self.auto_lock.check(now, wall, window) {
+            self.lock_vault(reason);
+            return;
+        }
+        if let Some(remaining) = self.auto_lock.remaining(now, wall) {
+            ctx.request_repaint_after(remaining.min(Duration::from_secs(30)));
+        }
+    }
+
+    /// Drops every piece of decrypted state and returns to the unlock screen.
+    pub(crate) fn lock_vault(&mut self, reason: LockReason) {
+        self.clear_clipboard_now();
+        self.vault = None;
+        self.editor = None;
+        self.command_palette = None;
+        self.invalidate_search();
+        self.search_results.clear();
+        self.search_query.clear();
+        self.selected_result = 0;
+        self.generated_password.zeroize();
+        self.master_password.zeroize();
+        self.confirm_password.zeroize();
+        self.password_change.clear();
+        self.import_form.clear();
+        self.export_form.clear();
+        self.backup_form.clear();
+        self.key_task = None;
+        self.selected_category = 0;
+        self.status_message = Some(format!("Vault {}", reason.label()));
+    }
+
+    /// Starts the idle timer afresh once the vault is open.
+    pub(crate) fn start_session(&mut self) {
+        self.auto_lock.reset(Instant::now(), SystemTime::now());
+    }
+}
+
+/// Input that counts as the user being present. Window events such as focus
+/// changes and repaint requests do not.
+fn is_user_input(event: &egui::Event) -> bool {
+    matches!(
+        event,
+        egui::Event::Key { .. }
+            | egui::Event::Text(_)
+            | egui::Event::Paste(_)
+            | egui::Event::Copy
+            | egui::Event::Cut
+            | egui::Event::PointerMoved(_)
+            | egui::Event::PointerButton { .. }
+            | egui::Event::MouseWheel { .. }
+            | egui::Event::Zoom(_)
+            | egui::Event::Touch { .. }
+    )
+}
@@ -0,0 +1,285 @@
+//! Backups section of the Security panel: how many snapshots each save
+//! keeps, the snapshots on disk with their integrity, and restoring the
+//! vault from one of them.
+
+use crate::key_task::KeyTaskKind;
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::backup::Integrity;
+use lilypad_core::config::BACKUP_KEEP_RANGE;
+use lilypad_core::secret::{self, SecretBuffer, Zeroize};
+use lilypad_core::text::format_size;
+use lilypad_core::{clock, Backups, OpenedSnapshot, Snapshot, Vault, VaultError};
+use std::path::Path;
+
+/// The snapshot list and the restore picker.
+pub(crate) struct BackupForm {
+    /// Snapshots of the open vault, listed when the vault last changed.
+    snapshots: Result<Vec<Snapshot>, String>,
+    /// `modified_at` of the vault when `snapshots` was listed; `None` when
+    /// stale.
+    listed_at: Option<u64>,
+    selected: Option<Snapshot>,
+    password: SecretBuffer,
+    error: Option<String>,
+}
+
+impl Default for BackupForm {
+    fn default() -> Self {
+        Self {
+            snapshots: Ok(Vec::new()),
+            listed_at: None,
+            selected: None,
+            password: secret::secret_buffer(),
+            error: None,
+        }
+    }
+}
+
+impl BackupForm {
+    /// A copy of the snapshot's master password for the worker thread.
+    pub(crate) fn password(&self) -> SecretBuffer {
+        secret::secret_buffer_from(&self.password)
+    }
+
+    /// A form with `password` typed in.
+    #[cfg(test)]
+    pub(crate) fn with_password(password: &str) -> Self {
+        let mut form = Self::default();
+        form.password.push_str(password);
+        form
+    }
+
+    /// Forgets the password and the choice, and lists the snapshots again
+    /// next time.
+    pub(crate) fn clear(&mut self) {
+        self.listed_at = None;
+        self.selected = None;
+        self.password.zeroize();
+        self.error = None;
+    }
+}
+
+impl LilypadApp {
+    /// Where saves keep snapshots under the current config, or `None` when
+    /// they are turned off or the data folder is unknown.
+    pub(crate) fn backups(&self) -> Option<Backups> {
+        let keep = self.config.backup.keep;
+        let dir = self.backup_dir.clone()?;
+        (keep > 0).then(|| Backups::new(dir, keep))
+    }
+
+    /// Points the open vault's snapshots at the configured folder.
+    pub(crate) fn attach_backups(&mut self) {
+        let backups = self.backups();
+        if let Some(vault) = self.vault.as_mut() {
+            vault.set_backups(backups);
+        }
+    }
+
+    pub(crate) fn render_backup_settings(&mut self, ui: &mut egui::Ui) {
+        let Some(dir) = self.backup_dir.clone() else {
+            return;
+        };
+        let palette = self.palette;
+        ui.label(RichText::new("Backups").strong());
+        ui.horizontal(|ui| {
+            ui.label("Snapshots to keep");
+            let keep = &mut self.config.backup.keep;
+            let response =
+                ui.add(egui::DragValue::new(keep).range(BACKUP_KEEP_RANGE.0..=BACKUP_KEEP_RANGE.1));
+            if *keep == 0 {
+                ui.label(RichText::new("Off").color(palette.warning));
+            }
+            if response.changed() {
+                self.attach_backups();
+                self.save_config();
+            }
+        });
+        ui.label(
+            RichText::new(format!(
+                "Each save keeps an encrypted copy of the vault in {}, locked with the master \
+                 password the vault had at the time.",
+                dir.display()
+            ))
+            .small()
+            .weak(),
+        );
+        if let Some(error) = self.vault.as_ref().and_then(Vault::backup_error) {
+            ui.colored_label(
+                palette.danger,
+                format!("The last save went through, but its snapshot failed: {error}"),
+            );
+        }
+
+        self.refresh_snapshots(&dir);
+        ui.add_space(6.0);
+        let busy = self.key_task.is_some();
+        let restoring = self
+            .key_task
+            .as_ref()
+            .is_some_and(|task| task.kind() == KeyTaskKind::RestoreSnapshot);
+        let form = &mut self.backup_form;
+        let mut refresh = false;
+        match &form.snapshots {
+            Err(error) => {
+                ui.colored_label(palette.danger, error);
+            }
+            Ok(snapshots) if snapshots.is_empty() => {
+                ui.label(RichText::new("No snapshots yet.").color(palette.text_muted));
+            }
+            Ok(snapshots) => {
+                ui.add_enabled_ui(!busy, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_salt("backup_snapshots")
+                        .max_height(160.0)
+                        .show(ui, |ui| {
+                            for snapshot in snapshots {
+                                ui.horizontal(|ui| {
+                                    let label = format!(
+                                        "{}  ·  {}",
+                                        clock::format_utc(snapshot.taken_at),
+                                        format_size(snapshot.size)
+                                    );
+                                    let restorable = snapshot.integrity != Integrity::Damaged;
+                                    ui.add_enabled_ui(restorable, |ui| {
+                                        ui.radio_value(
+                                            &mut form.selected,
+                                            Some(snapshot.clone()),
+                                            label,
+                                        );
+                                    });
+                                    let color = match snapshot.integrity {
+                                        Integrity::Intact => palette.success,
+                                        Integrity::Unverified => palette.warning,
+                                        Integrity::Damaged => palette.danger,
+                                    };
+                                    ui.colored_label(color, snapshot.integrity.label());
+                                });
+                            }
+                        });
+                });
+            }
+        }
+        refresh |= ui
+            .add_enabled(!busy, egui::Button::new("Refresh"))
+            .clicked();
+
+        let mut restore_requested = false;
+        let mut cancel = false;
+        if form.selected.is_some() {
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label("Master password of the snapshot");
+                let field = ui.add_enabled(
+                    !busy,
+                    egui::TextEdit::singleline(&mut *form.password).password(true),
+                );
+                restore_requested |=
+                    field.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            });
+            if let Some(error) = &form.error {
+                ui.colored_label(palette.danger, error);
+            }
+            if restoring {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new());
+                    ui.label(KeyTaskKind::RestoreSnapshot.label());
+                    cancel = ui.button("Cancel").clicked();
+                });
+            } else {
+                restore_requested |= ui
+                    .add_enabled(
+                        !busy && !form.password.is_empty(),
+                        egui::Button::new("Restore from backup"),
+                    )
+                    .clicked();
+            }
+            ui.label(
+                RichText::new(
+                    "The vault is replaced by the snapshot. Its current file is kept as a \
+                     snapshot first, so a restore can be undone.",
+                )
+                .small()
+                .weak(),
+            );
+        }
+
+        if refresh {
+            self.backup_form.listed_at = None;
+        }
+        if cancel {
+            self.cancel_key_task();
+        }
+        if restore_requested && !busy && !self.backup_form.password.is_empty() {
+            if let Some(snapshot) = self.backup_form.selected.clone() {
+                self.backup_form.error = None;
+                let backups = Backups::new(dir, self.config.backup.keep);
+                self.start_snapshot_restore(backups, snapshot.path, snapshot.taken_at);
+            }
+        }
+    }
+
+    /// Lists the snapshots again when the vault was saved since the last
+    /// listing, dropping a choice that no longer exists.
+    fn refresh_snapshots(&mut self, dir: &Path) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let modified_at = vault.data().modified_at;
+        let form = &mut self.backup_form;
+        if form.listed_at == Some(modified_at) {
+            return;
+        }
+        form.listed_at = Some(modified_at);
+        form.snapshots = Backups::new(dir.to_path_buf(), self.config.backup.keep)
+            .list(vault.path())
+            .map_err(|error| format!("Unable to list snapshots: {error}"));
+        let still_listed = form.snapshots.as_ref().is_ok_and(|snapshots| {
+            snapshots.iter().any(|snapshot| {
+                form.selected.as_ref() == Some(snapshot) && snapshot.integrity != Integrity::Damaged
+            })
+        });
+        if !still_listed {
+            form.selected = None;
+        }
+    }
+
+    /// Writes the opened snapshot over the vault file and swaps in the
+    /// restored vault, or says why the snapshot did not open. Runs on the UI
+    /// thread, so a lock either happens before the file is touched or after
+    /// the restore is complete.
+    pub(crate) fn finish_snapshot_restore(
+        &mut self,
+        taken_at: u64,
+        result: Result<OpenedSnapshot, VaultError>,
+    ) {
+        self.backup_form.password.zeroize();
+        match result.and_then(OpenedSnapshot::replace) {
+            Ok(vault) => {
+                self.vault = Some(vault);
+                self.attach_backups();
+                self.editor = None;
+                self.invalidate_search();
+                self.backup_form.clear();
+                self.status_message = Some(format!(
+                    "Restored the vault as saved on {}",
+                    clock::format_utc(taken_at)
+                ));
+            }
+            Err(VaultError::WrongPassword) => {
+                self.backup_form.error = Some(
+                    "That password does not open this snapshot. Use the master password the \
+                     vault had when it was taken."
+                        .to_string(),
+                );
+            }
+            Err(error) => {
+                // The snapshot may have been damaged since it was listed.
+                self.backup_form.listed_at = None;
+                self.backup_form.error = Some(format!("Unable to restore the snapshot: {error}"));
+            }
+        }
+    }
+}
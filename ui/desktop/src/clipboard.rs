@@ -0,0 +1,76 @@
+//! Copying secrets to the system clipboard and clearing them again after the
+//! configured timeout, on lock, and on exit.
+
+use crate::LilypadApp;
+use eframe::egui;
+use egui::OutputCommand;
+use lilypad_core::clipboard;
+use lilypad_core::secret::{SecretString, Zeroizing};
+use std::time::{Duration, Instant};
+
+impl LilypadApp {
+    /// Copies `secret` and schedules it for clearing; `what` names it in the
+    /// status bar ("Password", "Card number", …).
+    pub(crate) fn copy_secret(&mut self, ctx: &egui::Context, secret: &str, what: &str) {
+        if secret.is_empty() {
+            return;
+        }
+
+        if self.system_clipboard.is_none() {
+            self.system_clipboard = arboard::Clipboard::new().ok();
+        }
+        let copied = self
+            .system_clipboard
+            .as_mut()
+            .is_some_and(|system| system.set_text(secret).is_ok());
+
+        if copied {
+            self.clipboard_clear.copied(secret, Instant::now());
+            self.status_message = Some(format!("{what} copied to clipboard"));
+        } else {
+            // Without direct clipboard access we cannot check what it holds
+            // later, so fall back to egui and say the secret will stay.
+            ctx.send_cmd(OutputCommand::CopyText(secret.to_string()));
+            self.status_message = Some(format!(
+                "{what} copied to clipboard (it will not be cleared automatically)"
+            ));
+        }
+    }
+
+    /// Clears a copied secret whose timeout has passed and keeps repainting
+    /// while one is pending so the countdown stays current.
+    pub(crate) fn poll_clipboard(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        if let Some(secret) = self.clipboard_clear.take_due(now) {
+            if self.clear_clipboard_if_unchanged(&secret) {
+                self.status_message = Some("Clipboard cleared".to_string());
+            }
+        }
+        if let Some(remaining) = self.clipboard_clear.remaining(now) {
+            ctx.request_repaint_after(remaining.min(Duration::from_secs(1)));
+        }
+    }
+
+    /// Clears a pending secret right away; used on lock and exit.
+    pub(crate) fn clear_clipboard_now(&mut self) {
+        if let Some(secret) = self.clipboard_clear.take() {
+            self.clear_clipboard_if_unchanged(&secret);
+        }
+    }
+
+    /// Seconds until the pending secret is cleared, for the status bar.
+    pub(crate) fn clipboard_countdown(&self) -> Option<u64> {
+        self.clipboard_clear
+            .remaining(Instant::now())
+            .map(|remaining| remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+    }
+
+    fn clear_clipboard_if_unchanged(&mut self, secret: &SecretString) -> bool {
+        let Some(system) = self.system_clipboard.as_mut() else {
+            return false;
+        };
+        let current = system.get_text().ok().map(Zeroizing::new);
+        clipboard::still_holds(current.as_deref().map(String::as_str), secret)
+            && system.clear().is_ok()
+    }
+}
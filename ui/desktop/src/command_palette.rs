@@ -0,0 +1,107 @@
+//! Ctrl+K command palette: every other `Action` with its shortcut, filtered
+//! by the same fuzzy scorer as entry search. Up/Down move the selection,
+//! Enter runs it, and Escape or a click outside closes the palette.
+
+use crate::entry_list::highlighted_title;
+use crate::LilypadApp;
+use eframe::egui;
+use egui::{Key, RichText};
+use lilypad_core::{fuzzy_score, Action, TextMatch};
+
+#[derive(Default)]
+pub(crate) struct CommandPalette {
+    query: String,
+    selected: usize,
+}
+
+impl LilypadApp {
+    pub(crate) fn toggle_command_palette(&mut self) {
+        self.command_palette = match self.command_palette {
+            Some(_) => None,
+            None => Some(CommandPalette::default()),
+        };
+    }
+
+    pub(crate) fn render_command_palette(&mut self, ctx: &egui::Context) {
+        let Some(mut palette) = self.command_palette.take() else {
+            return;
+        };
+
+        let (down, up, enter) = ctx.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, Key::ArrowDown),
+                input.consume_key(egui::Modifiers::NONE, Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, Key::Enter),
+            )
+        });
+
+        let mut chosen = None;
+        let modal = egui::Modal::new(egui::Id::new("command_palette")).show(ctx, |ui| {
+            ui.set_width(420.0);
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command")
+                    .desired_width(f32::INFINITY),
+            );
+            search.request_focus();
+            if search.changed() {
+                palette.selected = 0;
+            }
+
+            let matches = rank_actions(&palette.query);
+            let last = matches.len().saturating_sub(1);
+            if down {
+                palette.selected = (palette.selected + 1).min(last);
+            }
+            if up {
+                palette.selected = palette.selected.saturating_sub(1);
+            }
+            if enter {
+                chosen = matches.get(palette.selected).map(|(action, _)| *action);
+            }
+
+            ui.separator();
+            if matches.is_empty() {
+                ui.label(RichText::new("No matching commands").weak());
+            }
+            for (position, (action, found)) in matches.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let label = highlighted_title(
+                        ui,
+                        self.palette.highlight,
+                        action.label(),
+                        &found.ranges,
+                    );
+                    if ui
+                        .selectable_label(position == palette.selected, label)
+                        .clicked()
+                    {
+                        chosen = Some(*action);
+                    }
+                    if let Some(shortcut) = self.shortcut_label(*action) {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.label(RichText::new(shortcut).weak());
+                        });
+                    }
+                });
+            }
+        });
+
+        if let Some(action) = chosen {
+            self.run_action(ctx, action);
+        } else if !modal.should_close() {
+            self.command_palette = Some(palette);
+        }
+    }
+}
+
+/// Matching actions, best first; ties keep the `Action::ALL` order.
+fn rank_actions(query: &str) -> Vec<(Action, TextMatch)> {
+    let mut matches: Vec<(Action, TextMatch)> = Action::ALL
+        .into_iter()
+        .filter(|action| *action != Action::CommandPalette)
+        .filter_map(|action| Some((action, fuzzy_score(query, action.label())?)))
+        .collect();
+    matches.sort_by_key(|(_, found)| std::cmp::Reverse(found.score));
+    matches
+}
@@ -0,0 +1,550 @@
+//! Side-panel editor for creating and editing vault entries of every kind.
+
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::secret::{self, SecretBuffer, Zeroize};
+use lilypad_core::text::format_size;
+use lilypad_core::{
+    CustomField, Entry, EntryKind, GeneratorError, GeneratorMode, GeneratorSettings, Login,
+    PasswordRules, Uuid, ValidationError, WifiSecurity,
+};
+use std::collections::HashSet;
+
+/// Draft state for the entry being created or edited.
+pub(crate) struct EntryEditor {
+    draft: Entry,
+    is_new: bool,
+    /// Tags are edited as one comma-separated line and split on save.
+    tags_text: String,
+    /// Keys of secret fields currently shown in clear text.
+    revealed: HashSet<String>,
+    errors: Vec<ValidationError>,
+}
+
+enum EditorAction {
+    None,
+    Save,
+    Cancel,
+}
+
+impl EntryEditor {
+    fn new(draft: Entry, is_new: bool) -> Self {
+        Self {
+            tags_text: draft.tags.join(", "),
+            draft,
+            is_new,
+            revealed: HashSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn entry_id(&self) -> Uuid {
+        self.draft.id
+    }
+}
+
+/// The draft holds decrypted secrets, so wipe it whether the editor was
+/// saved, cancelled, or dropped by a lock.
+impl Drop for EntryEditor {
+    fn drop(&mut self) {
+        self.draft.zeroize();
+        self.tags_text.zeroize();
+    }
+}
+
+impl LilypadApp {
+    pub(crate) fn open_new_entry_editor(&mut self) {
+        let draft = Entry::new("", EntryKind::Login(Login::default()));
+        self.editor = Some(EntryEditor::new(draft, true));
+    }
+
+    pub(crate) fn open_entry_editor(&mut self, id: Uuid) {
+        let Some(entry) = self.vault.as_ref().and_then(|vault| vault.entry(id)) else {
+            return;
+        };
+        self.editor = Some(EntryEditor::new(entry.clone(), false));
+    }
+
+    pub(crate) fn render_entry_editor(&mut self, ctx: &egui::Context) {
+        let Some(mut editor) = self.editor.take() else {
+            return;
+        };
+
+        let mut action = EditorAction::None;
+        egui::SidePanel::right("entry_editor")
+            .resizable(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.add_space(8.0);
+                ui.heading(if editor.is_new {
+                    "New entry"
+                } else {
+                    "Edit entry"
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.render_editor_fields(ui, &mut editor);
+
+                    if !editor.errors.is_empty() {
+                        ui.add_space(8.0);
+                        for error in &editor.errors {
+                            ui.colored_label(self.palette.danger, error.to_string());
+                        }
+                    }
+
+                    ui.add_space(12.0);
+                    ui.horizontal(|ui| {
+                        let save = egui::Button::new(
+                            RichText::new("Save").strong().color(self.palette.on_accent),
+                        )
+                        .fill(self.palette.accent)
+                        .min_size(egui::vec2(96.0, 28.0));
+                        if ui.add(save).clicked() {
+                            action = EditorAction::Save;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            action = EditorAction::Cancel;
+                        }
+                    });
+                });
+            });
+
+        match action {
+            EditorAction::None => self.editor = Some(editor),
+            EditorAction::Cancel => {}
+            EditorAction::Save => {
+                if !self.save_editor_entry(&mut editor) {
+                    self.editor = Some(editor);
+                }
+            }
+        }
+    }
+
+    fn render_editor_fields(&mut self, ui: &mut egui::Ui, editor: &mut EntryEditor) {
+        let EntryEditor {
+            draft,
+            is_new,
+            tags_text,
+            revealed,
+            ..
+        } = editor;
+        let generator = &self.generator;
+        let mut generator_error = None;
+        let mut copy_request = None;
+
+        egui::Grid::new("entry_editor_fields")
+            .num_columns(2)
+            .spacing([12.0, 8.0])
+            .show(ui, |ui| {
+                ui.label("Type");
+                if *is_new {
+                    egui::ComboBox::from_id_salt("entry_kind")
+                        .selected_text(draft.kind.label())
+                        .show_ui(ui, |ui| {
+                            for template in EntryKind::templates() {
+                                let selected = template.label() == draft.kind.label();
+                                if ui.selectable_label(selected, template.label()).clicked()
+                                    && !selected
+                                {
+                                    draft.kind = template;
+                                }
+                            }
+                        });
+                } else {
+                    ui.label(draft.kind.label());
+                }
+                ui.end_row();
+
+                text_row(ui, "Title", &mut draft.title);
+
+                match &mut draft.kind {
+                    EntryKind::Login(login) => {
+                        text_row(ui, "Username", &mut login.username);
+                        let site_generator = site_generator(generator, &login.password_rules);
+                        ui.label("Password");
+                        generator_error = generator_error.or(secret_input(
+                            ui,
+                            &mut login.password,
+                            "login_password",
+                            revealed,
+                            &mut copy_request,
+                            Some(site_generator.as_ref().unwrap_or(generator)),
+                        ));
+                        ui.end_row();
+
+                        ui.label("URLs");
+                        ui.vertical(|ui| {
+                            let mut removed = None;
+                            for (index, url) in login.urls.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        egui::TextEdit::singleline(url)
+                                            .hint_text("https://example.com"),
+                                    );
+                                    if ui.small_button("✖").on_hover_text("Remove URL").clicked()
+                                    {
+                                        removed = Some(index);
+                                    }
+                                });
+                            }
+                            if let Some(index) = removed {
+                                login.urls.remove(index);
+                            }
+                            if ui.small_button("+ Add URL").clicked() {
+                                login.urls.push(String::new());
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Password rules");
+                        ui.vertical(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut login.password_rules)
+                                    .hint_text("required: upper; minlength: 12"),
+                            )
+                            .on_hover_text(
+                                "The site's passwordrules; Generate will always comply with them",
+                            );
+                            if !login.password_rules.trim().is_empty() {
+                                match PasswordRules::parse(&login.password_rules) {
+                                    Err(error) => {
+                                        ui.colored_label(
+                                            self.palette.danger,
+                                            RichText::new(error.to_string()).small(),
+                                        );
+                                    }
+                                    Ok(rules)
+                                        if !login.password.is_empty()
+                                            && !rules.complies(&login.password) =>
+                                    {
+                                        ui.colored_label(
+                                            self.palette.warning,
+                                            RichText::new("The password does not meet these rules")
+                                                .small(),
+                                        );
+                                    }
+                                    Ok(_) => {}
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("TOTP seed");
+                        secret_input(
+                            ui,
+                            &mut login.totp,
+                            "login_totp",
+                            revealed,
+                            &mut copy_request,
+                            None,
+                        );
+                        ui.end_row();
+                    }
+                    EntryKind::SecureNote => {}
+                    EntryKind::Card(card) => {
+                        text_row(ui, "Cardholder", &mut card.cardholder_name);
+                        text_row(ui, "Brand", &mut card.brand);
+                        ui.label("Number");
+                        secret_input(
+                            ui,
+                            &mut card.number,
+                            "card_number",
+                            revealed,
+                            &mut copy_request,
+                            None,
+                        );
+                        ui.end_row();
+                        ui.label("Expiry");
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut card.expiry_month)
+                                    .hint_text("MM")
+                                    .desired_width(40.0),
+                            );
+                            ui.label("/");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut card.expiry_year)
+                                    .hint_text("YYYY")
+                                    .desired_width(56.0),
+                            );
+                        });
+                        ui.end_row();
+                        ui.label("Security code");
+                        secret_input(
+                            ui,
+                            &mut card.security_code,
+                            "card_security_code",
+                            revealed,
+                            &mut copy_request,
+                            None,
+                        );
+                        ui.end_row();
+                    }
+                    EntryKind::Identity(identity) => {
+                        text_row(ui, "First name", &mut identity.first_name);
+                        text_row(ui, "Last name", &mut identity.last_name);
+                        text_row(ui, "Email", &mut identity.email);
+                        text_row(ui, "Phone", &mut identity.phone);
+                        text_row(ui, "Company", &mut identity.company);
+                        text_row(ui, "Address", &mut identity.address);
+                        text_row(ui, "City", &mut identity.city);
+                        text_row(ui, "State", &mut identity.state);
+                        text_row(ui, "Postal code", &mut identity.postal_code);
+                        text_row(ui, "Country", &mut identity.country);
+                    }
+                    EntryKind::Wifi(wifi) => {
+                        text_row(ui, "SSID", &mut wifi.ssid);
+                        ui.label("Password");
+                        generator_error = generator_error.or(secret_input(
+                            ui,
+                            &mut wifi.password,
+                            "wifi_password",
+                            revealed,
+                            &mut copy_request,
+                            Some(generator),
+                        ));
+                        ui.end_row();
+                        ui.label("Security");
+                        egui::ComboBox::from_id_salt("wifi_security")
+                            .selected_text(wifi.security.label())
+                            .show_ui(ui, |ui| {
+                                for security in WifiSecurity::ALL {
+                                    ui.selectable_value(
+                                        &mut wifi.security,
+                                        security,
+                                        security.label(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                        ui.label("Hidden network");
+                        ui.checkbox(&mut wifi.hidden, "");
+                        ui.end_row();
+                    }
+                }
+
+                ui.label(if matches!(draft.kind, EntryKind::SecureNote) {
+                    "Note"
+                } else {
+                    "Notes"
+                });
+                ui.add(egui::TextEdit::multiline(&mut draft.notes).desired_rows(4));
+                ui.end_row();
+
+                ui.label("Tags");
+                ui.add(egui::TextEdit::singleline(tags_text).hint_text("work, email"));
+                ui.end_row();
+
+                ui.label("Folder");
+                ui.add(egui::TextEdit::singleline(&mut draft.folder).hint_text("Work/Servers"));
+                ui.end_row();
+            });
+
+        ui.add_space(8.0);
+        ui.label(RichText::new("Custom fields").strong());
+        let mut removed = None;
+        for (index, field) in draft.custom_fields.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut field.name)
+                        .hint_text("Name")
+                        .desired_width(100.0),
+                );
+                if field.hidden {
+                    secret_input(
+                        ui,
+                        &mut field.value,
+                        &format!("custom_{index}"),
+                        revealed,
+                        &mut copy_request,
+                        None,
+                    );
+                } else {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut field.value)
+                            .hint_text("Value")
+                            .desired_width(140.0),
+                    );
+                }
+                ui.checkbox(&mut field.hidden, "Hidden");
+                if ui.small_button("✖").on_hover_text("Remove field").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            draft.custom_fields.remove(index);
+        }
+        if ui.small_button("+ Add field").clicked() {
+            draft.custom_fields.push(CustomField::default());
+        }
+
+        if !draft.attachments.is_empty() {
+            ui.add_space(8.0);
+            ui.label(RichText::new("Attachments").strong());
+            let mut removed = None;
+            for (index, attachment) in draft.attachments.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(&attachment.name);
+                    ui.label(RichText::new(format_size(attachment.data.len() as u64)).weak());
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text("Remove attachment")
+                        .clicked()
+                    {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                draft.attachments.remove(index);
+            }
+        }
+        if !draft.history.is_empty() {
+            ui.label(
+                RichText::new(format!(
+                    "{} earlier versions from KeePass are kept and exported with the entry.",
+                    draft.history.len()
+                ))
+                .small()
+                .weak(),
+            );
+        }
+
+        if let Some(error) = generator_error {
+            self.status_message = Some(format!("Unable to generate password: {error}"));
+        }
+        if let Some(secret) = copy_request {
+            self.copy_secret(ui.ctx(), &secret, "Secret");
+        }
+    }
+
+    /// Validates the draft and persists it. Returns `true` when the editor can close.
+    fn save_editor_entry(&mut self, editor: &mut EntryEditor) -> bool {
+        editor.draft.tags = editor
+            .tags_text
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+        editor.draft.folder = editor.draft.folder.trim().to_string();
+        if let EntryKind::Login(login) = &mut editor.draft.kind {
+            login.urls.retain(|url| !url.trim().is_empty());
+        }
+
+        if let Err(errors) = editor.draft.validate() {
+            editor.errors = errors;
+            return false;
+        }
+        editor.errors.clear();
+
+        let Some(vault) = self.vault.as_mut() else {
+            return false;
+        };
+
+        let previous = vault.entry(editor.entry_id()).cloned();
+        if editor.is_new {
+            vault.add_entry(editor.draft.clone());
+        } else {
+            vault.update_entry(editor.draft.clone());
+        }
+
+        match vault.save() {
+            Ok(()) => {
+                self.status_message = Some(format!("Saved \"{}\"", editor.draft.title));
+                self.invalidate_search();
+                true
+            }
+            Err(error) => {
+                // Keep memory in sync with what is actually on disk.
+                match previous {
+                    Some(previous) => {
+                        vault.update_entry(previous);
+                    }
+                    None => {
+                        vault.remove_entry(editor.entry_id());
+                    }
+                }
+                self.status_message = Some(format!("Unable to save entry: {error}"));
+                false
+            }
+        }
+    }
+}
+
+/// The Generator panel settings narrowed to a login's site rules, if it has
+/// valid ones.
+fn site_generator(settings: &GeneratorSettings, rules: &str) -> Option<GeneratorSettings> {
+    if rules.trim().is_empty() {
+        return None;
+    }
+    let rules = PasswordRules::parse(rules).ok()?;
+    Some(GeneratorSettings {
+        mode: GeneratorMode::Characters,
+        password: rules.configure(&settings.password),
+        ..settings.clone()
+    })
+}
+
+fn text_row(ui: &mut egui::Ui, label: &str, value: &mut String) {
+    ui.label(label);
+    ui.add(egui::TextEdit::singleline(value));
+    ui.end_row();
+}
+
+/// A masked text field with reveal and copy buttons and, when a generator is
+/// given, a button that fills it with a freshly generated password. Copying
+/// is reported through `copy_request` so the caller can schedule clearing.
+fn secret_input(
+    ui: &mut egui::Ui,
+    value: &mut String,
+    key: &str,
+    revealed: &mut HashSet<String>,
+    copy_request: &mut Option<SecretBuffer>,
+    generator: Option<&GeneratorSettings>,
+) -> Option<GeneratorError> {
+    let mut error = None;
+    ui.horizontal(|ui| {
+        let is_revealed = revealed.contains(key);
+        ui.add(
+            egui::TextEdit::singleline(value)
+                .password(!is_revealed)
+                .desired_width(160.0),
+        );
+        if ui
+            .small_button(if is_revealed { "Hide" } else { "Show" })
+            .clicked()
+        {
+            if is_revealed {
+                revealed.remove(key);
+            } else {
+                revealed.insert(key.to_string());
+            }
+        }
+        if ui
+            .add_enabled(!value.is_empty(), egui::Button::new("Copy").small())
+            .clicked()
+        {
+            *copy_request = Some(secret::secret_buffer_from(value));
+        }
+        if let Some(generator) = generator {
+            if ui
+                .small_button("Generate")
+                .on_hover_text("Fill with a password using the Generator settings")
+                .clicked()
+            {
+                match generator.generate() {
+                    Ok(password) => {
+                        value.zeroize();
+                        value.push_str(&password);
+                    }
+                    Err(generation_error) => error = Some(generation_error),
+                }
+            }
+        }
+    });
+    error
+}
@@ -0,0 +1,257 @@
+//! Vault panel entry list with live search, keyboard navigation, and match
+//! highlighting. Rows are virtualized so large vaults stay responsive.
+
+use crate::LilypadApp;
+use eframe::egui;
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, Key, RichText};
+use lilypad_core::{clock, secret, MatchField, SearchIndex};
+use std::ops::Range;
+
+pub(crate) const SEARCH_BOX_ID: &str = "vault_search";
+const ROW_HEIGHT: f32 = 48.0;
+
+impl LilypadApp {
+    /// Drops the cached index; call after any change to the vault's entries.
+    pub(crate) fn invalidate_search(&mut self) {
+        self.search_index = None;
+        self.search_results_query = None;
+    }
+
+    /// Rebuilds the index and re-runs the query only when something changed.
+    pub(crate) fn refresh_search(&mut self) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+
+        if self.search_index.is_none() {
+            self.search_index = Some(SearchIndex::build(vault.entries()));
+            self.search_results_query = None;
+        }
+
+        if self.search_results_query.as_deref() != Some(self.search_query.as_str()) {
+            if let Some(index) = &self.search_index {
+                self.search_results = index.search(&self.search_query);
+            }
+            self.search_results_query = Some(self.search_query.clone());
+            self.selected_result = 0;
+            self.scroll_to_selection = true;
+        }
+    }
+
+    pub(crate) fn render_entry_list(&mut self, ui: &mut egui::Ui) {
+        self.refresh_search();
+        self.handle_list_keys(ui);
+
+        let Some(vault) = &self.vault else {
+            return;
+        };
+
+        if vault.entries().is_empty() {
+            ui.label("Your vault is empty.");
+            return;
+        }
+        if self.search_results.is_empty() {
+            ui.label(format!(
+                "No entries match \"{}\".",
+                self.search_query.trim()
+            ));
+            return;
+        }
+
+        if !self.search_query.trim().is_empty() {
+            ui.label(
+                RichText::new(format!(
+                    "{} of {} entries",
+                    self.search_results.len(),
+                    vault.entries().len()
+                ))
+                .weak(),
+            );
+        }
+
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        if std::mem::take(&mut self.scroll_to_selection) {
+            let viewport = ui.available_height();
+            let row_span = ROW_HEIGHT + ui.spacing().item_spacing.y;
+            let centered = self.selected_result as f32 * row_span - (viewport - ROW_HEIGHT) / 2.0;
+            scroll_area = scroll_area.vertical_scroll_offset(centered.max(0.0));
+        }
+
+        let mut clicked = None;
+        let mut open_request = None;
+        let mut copy_request = None;
+        scroll_area.show_rows(ui, ROW_HEIGHT, self.search_results.len(), |ui, rows| {
+            for position in rows {
+                let hit = &self.search_results[position];
+                let Some(entry) = vault.entries().get(hit.index) else {
+                    continue;
+                };
+                let selected = position == self.selected_result;
+
+                let (rect, response) = ui.allocate_exact_size(
+                    egui::vec2(ui.available_width(), ROW_HEIGHT),
+                    egui::Sense::click(),
+                );
+                if selected {
+                    ui.painter()
+                        .rect_filled(rect, 6.0, ui.visuals().selection.bg_fill);
+                } else if response.hovered() {
+                    ui.painter()
+                        .rect_filled(rect, 6.0, ui.visuals().widgets.hovered.weak_bg_fill);
+                }
+                if response.clicked() {
+                    clicked = Some(position);
+                }
+                if response.double_clicked() {
+                    open_request = Some(entry.id);
+                }
+
+                let mut row = ui.new_child(
+                    egui::UiBuilder::new()
+                        .max_rect(rect.shrink2(egui::vec2(8.0, 4.0)))
+                        .layout(egui::Layout::top_down(egui::Align::Min)),
+                );
+                row.horizontal(|ui| {
+                    ui.label(highlighted_title(
+                        ui,
+                        self.palette.highlight,
+                        &entry.title,
+                        &hit.title_matches,
+                    ));
+                    ui.separator();
+                    ui.label(entry.subtitle());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Edit").clicked() {
+                            open_request = Some(entry.id);
+                        }
+                        if let Some(secret) = entry.primary_secret() {
+                            if ui
+                                .button("Copy")
+                                .on_hover_text("Copy the password")
+                                .clicked()
+                            {
+                                copy_request = Some(secret::secret_buffer_from(secret));
+                            }
+                        }
+                        ui.label(RichText::new(entry.kind.label()).weak());
+                    });
+                });
+                let mut details = format!("Last updated: {}", clock::format_utc(entry.modified_at));
+                if !self.search_query.trim().is_empty() && hit.field != MatchField::Title {
+                    details.push_str(&format!(" · matched {}", hit.field.label()));
+                }
+                row.label(RichText::new(details).small().weak());
+            }
+        });
+
+        if let Some(position) = clicked {
+            self.selected_result = position;
+        }
+        if let Some(id) = open_request {
+            self.open_entry_editor(id);
+        }
+        if let Some(secret) = copy_request {
+            self.copy_secret(ui.ctx(), &secret, "Password");
+        }
+    }
+
+    /// Arrow keys move the selection and Enter opens it, while the search box
+    /// or nothing at all has keyboard focus. Escape clears the query.
+    fn handle_list_keys(&mut self, ui: &egui::Ui) {
+        if self.editor.is_some() {
+            return;
+        }
+
+        let search_id = egui::Id::new(SEARCH_BOX_ID);
+        let (search_focused, nothing_focused) = ui
+            .ctx()
+            .memory(|memory| (memory.has_focus(search_id), memory.focused().is_none()));
+        if !search_focused && !nothing_focused {
+            return;
+        }
+
+        let last = self.search_results.len().saturating_sub(1);
+        let (down, up, page_down, page_up, enter, escape) = ui.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, Key::ArrowDown),
+                input.consume_key(egui::Modifiers::NONE, Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, Key::PageDown),
+                input.consume_key(egui::Modifiers::NONE, Key::PageUp),
+                input.key_pressed(Key::Enter),
+                input.key_pressed(Key::Escape),
+            )
+        });
+
+        let previous = self.selected_result;
+        if down {
+            self.selected_result = (self.selected_result + 1).min(last);
+        }
+        if up {
+            self.selected_result = self.selected_result.saturating_sub(1);
+        }
+        if page_down {
+            self.selected_result = (self.selected_result + 10).min(last);
+        }
+        if page_up {
+            self.selected_result = self.selected_result.saturating_sub(10);
+        }
+        if self.selected_result != previous {
+            self.scroll_to_selection = true;
+        }
+
+        if enter {
+            if let Some(hit) = self.search_results.get(self.selected_result) {
+                let id = hit.id;
+                self.open_entry_editor(id);
+            }
+        }
+        if escape && !self.search_query.is_empty() {
+            self.search_query.clear();
+        }
+    }
+}
+
+/// Lays out `title` with the matched char ranges drawn on a highlight.
+pub(crate) fn highlighted_title(
+    ui: &egui::Ui,
+    background: Color32,
+    title: &str,
+    matches: &[Range<usize>],
+) -> LayoutJob {
+    let normal = TextFormat {
+        font_id: egui::TextStyle::Body.resolve(ui.style()),
+        color: ui.visuals().strong_text_color(),
+        ..TextFormat::default()
+    };
+    let highlight = TextFormat {
+        background,
+        ..normal.clone()
+    };
+
+    let mut job = LayoutJob::default();
+    let mut segment = String::new();
+    let mut segment_highlighted = false;
+    for (position, c) in title.chars().enumerate() {
+        let highlighted = matches.iter().any(|range| range.contains(&position));
+        if highlighted != segment_highlighted && !segment.is_empty() {
+            let format = if segment_highlighted {
+                &highlight
+            } else {
+                &normal
+            };
+            job.append(&std::mem::take(&mut segment), 0.0, format.clone());
+        }
+        segment_highlighted = highlighted;
+        segment.push(c);
+    }
+    if !segment.is_empty() {
+        let format = if segment_highlighted {
+            highlight
+        } else {
+            normal
+        };
+        job.append(&segment, 0.0, format);
+    }
+    job
+}
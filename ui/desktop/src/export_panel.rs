@@ -0,0 +1,261 @@
+//! Export section of the Settings view: writes the vault to a backup
+//! archive or KeePass database protected by a passphrase of its own, or,
+//! once the user acknowledges the risk, to plaintext JSON or CSV.
+
+use crate::key_task::KeyTaskKind;
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::secret::{self, SecretBuffer, Zeroize};
+use lilypad_core::{export, vault};
+use std::path::{Path, PathBuf};
+
+/// File formats the export writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Archive,
+    KeePass,
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    const ALL: [Self; 4] = [Self::Archive, Self::KeePass, Self::Json, Self::Csv];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Archive => "Encrypted backup archive (.age)",
+            Self::KeePass => "KeePass database (.kdbx)",
+            Self::Json => "Plaintext Lilypad JSON",
+            Self::Csv => "Plaintext CSV",
+        }
+    }
+
+    fn hint(self) -> &'static str {
+        match self {
+            Self::Archive => "/home/me/Backups/lilypad-backup.age",
+            Self::KeePass => "/home/me/Documents/lilypad.kdbx",
+            Self::Json => "/home/me/Documents/lilypad-export.json",
+            Self::Csv => "/home/me/Documents/lilypad-export.csv",
+        }
+    }
+
+    fn is_encrypted(self) -> bool {
+        matches!(self, Self::Archive | Self::KeePass)
+    }
+}
+
+/// The file, format, and password of the next export.
+pub(crate) struct ExportForm {
+    format: ExportFormat,
+    path: String,
+    password: SecretBuffer,
+    confirm: SecretBuffer,
+    /// Ticked once the user accepts that a plaintext export exposes every
+    /// secret; reset after each export.
+    plaintext_accepted: bool,
+    error: Option<String>,
+}
+
+impl Default for ExportForm {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Archive,
+            path: String::new(),
+            password: secret::secret_buffer(),
+            confirm: secret::secret_buffer(),
+            plaintext_accepted: false,
+            error: None,
+        }
+    }
+}
+
+impl ExportForm {
+    /// A copy of the archive or database password for the worker thread.
+    pub(crate) fn password(&self) -> SecretBuffer {
+        secret::secret_buffer_from(&self.password)
+    }
+
+    /// Forgets the passwords and the plaintext acknowledgement, keeping the
+    /// chosen file and format.
+    pub(crate) fn clear(&mut self) {
+        self.password.zeroize();
+        self.confirm.zeroize();
+        self.plaintext_accepted = false;
+        self.error = None;
+    }
+}
+
+impl LilypadApp {
+    pub(crate) fn render_export_settings(&mut self, ui: &mut egui::Ui) {
+        let busy = self.key_task.is_some();
+        let encrypting = self
+            .key_task
+            .as_ref()
+            .map(|task| task.kind())
+            .filter(|kind| {
+                matches!(
+                    kind,
+                    KeyTaskKind::ExportKeePass | KeyTaskKind::ExportArchive
+                )
+            });
+        let entry_count = self.vault.as_ref().map_or(0, |vault| vault.entries().len());
+        let palette = self.palette;
+        let form = &mut self.export_form;
+        let mut export_requested = false;
+
+        ui.add_enabled_ui(!busy, |ui| {
+            egui::Grid::new("export_target")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Format");
+                    egui::ComboBox::from_id_salt("export_format")
+                        .selected_text(form.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in ExportFormat::ALL {
+                                if ui
+                                    .selectable_value(&mut form.format, format, format.label())
+                                    .changed()
+                                {
+                                    form.clear();
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("File");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut form.path)
+                            .hint_text(form.format.hint())
+                            .desired_width(360.0),
+                    );
+                    ui.end_row();
+
+                    let labels = match form.format {
+                        ExportFormat::Archive => Some(("Archive passphrase", "Confirm passphrase")),
+                        ExportFormat::KeePass => Some(("Database password", "Confirm password")),
+                        ExportFormat::Json | ExportFormat::Csv => None,
+                    };
+                    if let Some((password, confirm)) = labels {
+                        for (label, value) in
+                            [(password, &mut form.password), (confirm, &mut form.confirm)]
+                        {
+                            ui.label(label);
+                            ui.add(egui::TextEdit::singleline(&mut **value).password(true));
+                            ui.end_row();
+                        }
+                    }
+                });
+
+            if !form.format.is_encrypted() {
+                ui.colored_label(
+                    palette.danger,
+                    "This file is not encrypted. Anyone who can read it sees every password, \
+                     and copies may linger in backups or synced folders.",
+                );
+                ui.checkbox(
+                    &mut form.plaintext_accepted,
+                    "I understand and want an unencrypted file",
+                );
+            }
+
+            if let Some(kind) = encrypting {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new());
+                    ui.label(kind.label());
+                });
+            } else {
+                let ready = !form.path.trim().is_empty()
+                    && if form.format.is_encrypted() {
+                        !form.password.is_empty()
+                    } else {
+                        form.plaintext_accepted
+                    };
+                export_requested = ui
+                    .add_enabled(
+                        ready && entry_count > 0,
+                        egui::Button::new(format!("Export {entry_count} entries")),
+                    )
+                    .clicked();
+            }
+        });
+        if encrypting.is_some() && ui.button("Cancel").clicked() {
+            self.cancel_key_task();
+        }
+
+        if let Some(error) = &self.export_form.error {
+            ui.colored_label(palette.danger, error);
+        }
+        let note = match self.export_form.format {
+            ExportFormat::Archive => {
+                "The archive holds every entry with its attachments and history. Restore it \
+                 from the create-vault screen on a new machine, or open it with the age tool."
+            }
+            ExportFormat::KeePass => {
+                "The database uses this vault's Argon2id settings. Card, identity, and Wi-Fi \
+                 details become named fields in KeePassXC and come back intact when imported."
+            }
+            ExportFormat::Json => "Lilypad can import this file again without losing anything.",
+            ExportFormat::Csv => {
+                "One row per entry for spreadsheets and other managers. Attachments, history, \
+                 and custom fields are left out."
+            }
+        };
+        ui.label(RichText::new(note).small().weak());
+
+        if export_requested {
+            self.request_export();
+        }
+    }
+
+    fn request_export(&mut self) {
+        let form = &mut self.export_form;
+        form.error = None;
+        let path = PathBuf::from(form.path.trim());
+        if form.format.is_encrypted() && *form.password != *form.confirm {
+            form.error = Some("The passwords do not match.".to_string());
+            return;
+        }
+        if path.exists() {
+            form.error = Some(format!("{} already exists.", path.display()));
+            return;
+        }
+        match form.format {
+            ExportFormat::Archive => self.start_archive_export(path),
+            ExportFormat::KeePass => self.start_keepass_export(path),
+            ExportFormat::Json | ExportFormat::Csv => self.write_plaintext_export(&path),
+        }
+    }
+
+    /// Plaintext needs no key derivation, so it is written right away.
+    fn write_plaintext_export(&mut self, path: &Path) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let contents = match self.export_form.format {
+            ExportFormat::Csv => Ok(export::to_csv(vault.entries())),
+            _ => export::to_json(vault.entries()).map_err(|error| error.to_string()),
+        };
+        let result = contents
+            .map_err(|error| format!("Unable to export: {error}"))
+            .and_then(|text| {
+                vault::write_new(path, text.as_bytes())
+                    .map_err(|error| format!("Unable to write {}: {error}", path.display()))
+            })
+            .map(|()| vault.entries().len());
+        self.finish_export(path, result);
+    }
+
+    /// Reports a finished export.
+    pub(crate) fn finish_export(&mut self, path: &Path, result: Result<usize, String>) {
+        match result {
+            Ok(count) => {
+                self.export_form.clear();
+                self.status_message =
+                    Some(format!("Exported {count} entries to {}", path.display()));
+            }
+            Err(error) => self.export_form.error = Some(error),
+        }
+    }
+}
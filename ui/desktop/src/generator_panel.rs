@@ -0,0 +1,264 @@
+//! Generator panel: random character passwords or EFF-wordlist passphrases.
+
+use crate::strength_meter::{strength_color, strength_meter};
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::config::{LENGTH_RANGE, WORD_COUNT_RANGE};
+use lilypad_core::generator::AMBIGUOUS;
+use lilypad_core::{secret, strength, GeneratorMode, GeneratorSettings, PasswordRules};
+
+impl LilypadApp {
+    pub(crate) fn render_password_generator(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Mode").strong());
+                ui.radio_value(
+                    &mut self.generator.mode,
+                    GeneratorMode::Characters,
+                    "Random characters",
+                );
+                ui.radio_value(
+                    &mut self.generator.mode,
+                    GeneratorMode::Passphrase,
+                    "Passphrase",
+                );
+            });
+            ui.add_space(4.0);
+
+            match self.generator.mode {
+                GeneratorMode::Characters => self.render_character_options(ui),
+                GeneratorMode::Passphrase => self.render_passphrase_options(ui),
+            }
+
+            let generator = self.panel_generator();
+            let check = generator.check();
+            let generation_possible = check.is_ok();
+            if let Err(error) = check {
+                ui.add_space(4.0);
+                ui.colored_label(self.palette.danger, format!("Cannot generate: {error}"));
+            }
+            let entropy = generator.entropy_bits();
+            let strength = generator.strength();
+
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Strength").strong());
+                ui.colored_label(strength_color(&self.palette, strength), strength.label());
+                ui.separator();
+                ui.label(RichText::new("Entropy").strong());
+                ui.label(format!("{entropy:.1} bits"));
+            });
+
+            ui.add_space(6.0);
+            let generate_button = egui::Button::new(
+                RichText::new("Generate password")
+                    .strong()
+                    .color(self.palette.on_accent),
+            )
+            .fill(self.palette.accent)
+            .min_size(egui::vec2(200.0, 32.0));
+
+            if ui
+                .add_enabled(generation_possible, generate_button)
+                .clicked()
+            {
+                self.generate_password(ctx);
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Generated password").strong());
+                if ui.button("Copy").clicked() {
+                    let password = secret::secret_buffer_from(&self.generated_password);
+                    self.copy_secret(ctx, &password, "Password");
+                }
+            });
+
+            ui.add(
+                egui::TextEdit::singleline(&mut *self.generated_password)
+                    .password(true)
+                    .hint_text("Generate a password to display it here"),
+            );
+            if !self.generated_password.is_empty() {
+                ui.add_space(4.0);
+                strength_meter(
+                    ui,
+                    &self.palette,
+                    &strength::estimate(&self.generated_password, &[]),
+                );
+            }
+        });
+    }
+
+    /// The panel settings, narrowed by the applied site rules in character
+    /// mode.
+    fn panel_generator(&self) -> GeneratorSettings {
+        let mut generator = self.generator.clone();
+        if let Some(rules) = &self.site_rules {
+            if generator.mode == GeneratorMode::Characters {
+                generator.password = rules.configure(&generator.password);
+            }
+        }
+        generator
+    }
+
+    /// Generates a password with the panel settings and copies it.
+    pub(crate) fn generate_password(&mut self, ctx: &egui::Context) {
+        match self.panel_generator().generate() {
+            Ok(password) => {
+                self.copy_secret(ctx, &password, "New password");
+                self.generated_password = password;
+            }
+            Err(error) => {
+                self.status_message = Some(format!("Unable to generate password: {error}"));
+            }
+        }
+    }
+
+    fn render_character_options(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Site rules").strong());
+            ui.add(
+                egui::TextEdit::singleline(&mut self.generator_rules)
+                    .hint_text("required: upper; allowed: digit, [-_]; maxlength: 20"),
+            );
+            if ui
+                .add_enabled(
+                    !self.generator_rules.trim().is_empty(),
+                    egui::Button::new("Apply"),
+                )
+                .on_hover_text("Configure the options below to comply with a site's passwordrules")
+                .clicked()
+            {
+                match PasswordRules::parse(&self.generator_rules) {
+                    Ok(rules) => {
+                        self.site_rules = Some(rules);
+                        self.status_message =
+                            Some("Generated passwords follow the site rules".to_string());
+                    }
+                    Err(error) => {
+                        self.status_message = Some(format!("Unable to read site rules: {error}"));
+                    }
+                }
+            }
+        });
+        if self.site_rules.is_some() {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new("Site rules narrow the options below for each password.")
+                        .small()
+                        .weak(),
+                );
+                if ui.small_button("Clear site rules").clicked() {
+                    self.site_rules = None;
+                    self.status_message = Some("Site rules cleared".to_string());
+                }
+            });
+        }
+
+        let options = &mut self.generator.password;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Length").strong());
+            ui.add(
+                egui::Slider::new(&mut options.length, LENGTH_RANGE.0..=LENGTH_RANGE.1)
+                    .text("characters")
+                    .step_by(1.0),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut options.lowercase, "Lowercase (abc)");
+            ui.checkbox(&mut options.uppercase, "Uppercase (ABC)");
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut options.digits, "Digits (0-9)");
+            ui.checkbox(&mut options.symbols, "Symbols (!#$)");
+        });
+
+        egui::CollapsingHeader::new("Advanced constraints")
+            .id_salt("generator_constraints")
+            .show(ui, |ui| {
+                ui.checkbox(
+                    &mut options.exclude_ambiguous,
+                    format!("Exclude look-alikes ({})", AMBIGUOUS),
+                );
+                ui.horizontal(|ui| {
+                    let mut limit_runs = options.max_consecutive.is_some();
+                    if ui
+                        .checkbox(&mut limit_runs, "Limit repeated characters to")
+                        .changed()
+                    {
+                        options.max_consecutive = limit_runs.then_some(1);
+                    }
+                    if let Some(max_run) = &mut options.max_consecutive {
+                        ui.add(
+                            egui::DragValue::new(max_run)
+                                .range(1..=8)
+                                .suffix(" in a row"),
+                        );
+                    }
+                });
+                egui::Grid::new("generator_char_lists")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Also include");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut options.include_chars)
+                                .hint_text("e.g. -_"),
+                        );
+                        ui.end_row();
+                        ui.label("Never use");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut options.exclude_chars)
+                                .hint_text("e.g. {}[]"),
+                        );
+                        ui.end_row();
+                    });
+
+                ui.add_space(4.0);
+                ui.label(RichText::new("Minimum per class").strong());
+                ui.horizontal_wrapped(|ui| {
+                    for (enabled, minimum, label) in [
+                        (options.lowercase, &mut options.min_lowercase, "lowercase"),
+                        (options.uppercase, &mut options.min_uppercase, "uppercase"),
+                        (options.digits, &mut options.min_digits, "digits"),
+                        (options.symbols, &mut options.min_symbols, "symbols"),
+                    ] {
+                        ui.add_enabled(
+                            enabled,
+                            egui::DragValue::new(minimum)
+                                .range(0..=16)
+                                .prefix(format!("{label}: ")),
+                        );
+                    }
+                });
+            });
+    }
+
+    fn render_passphrase_options(&mut self, ui: &mut egui::Ui) {
+        let options = &mut self.generator.passphrase;
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Words").strong());
+            ui.add(
+                egui::Slider::new(
+                    &mut options.word_count,
+                    WORD_COUNT_RANGE.0..=WORD_COUNT_RANGE.1,
+                )
+                .text("words")
+                .step_by(1.0),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Separator").strong());
+            ui.add(
+                egui::TextEdit::singleline(&mut options.separator)
+                    .char_limit(3)
+                    .desired_width(40.0),
+            );
+            ui.checkbox(&mut options.capitalize, "Capitalize words");
+            ui.checkbox(&mut options.append_digit, "Append a digit");
+        });
+    }
+}
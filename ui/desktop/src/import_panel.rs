@@ -0,0 +1,584 @@
+//! Import section of the Settings view: reads another manager's export,
+//! lets the user say what each column of a CSV file holds, previews what
+//! the file holds and what is already in the vault, and only adds the
+//! entries once the user confirms.
+
+use crate::key_task::KeyTaskKind;
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::csv_import::{self, CsvTable, FailedRow, Field, Mapping, Preset};
+use lilypad_core::export::{self, ImportSummary};
+use lilypad_core::secret::{self, SecretBuffer, Zeroize, Zeroizing};
+use lilypad_core::text::capitalize;
+use lilypad_core::{
+    bitwarden, kdbx, BitwardenError, BitwardenImport, CsvImport, Entry, EntryKind, KdbxError,
+};
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+
+/// File formats the import reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ImportFormat {
+    Bitwarden,
+    Csv,
+    KeePass,
+    Lilypad,
+}
+
+impl ImportFormat {
+    const ALL: [Self; 4] = [Self::Bitwarden, Self::Csv, Self::KeePass, Self::Lilypad];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Bitwarden => "Bitwarden (.json)",
+            Self::Csv => "CSV (Chrome, Firefox, LastPass, other)",
+            Self::KeePass => "KeePass / KeePassXC (.kdbx)",
+            Self::Lilypad => "Lilypad export (.json)",
+        }
+    }
+}
+
+/// The file being imported and, once read, its preview.
+pub(crate) struct ImportForm {
+    format: ImportFormat,
+    path: String,
+    /// Password of a KeePass database or password-protected Bitwarden export.
+    password: SecretBuffer,
+    /// Set once the chosen file turned out to be password protected; always
+    /// for KeePass databases.
+    needs_password: bool,
+    error: Option<String>,
+    /// A CSV file read but not yet converted, while its columns are mapped.
+    csv: Option<CsvColumns>,
+    preview: Option<ImportPreview>,
+}
+
+impl Default for ImportForm {
+    fn default() -> Self {
+        Self {
+            format: ImportFormat::Bitwarden,
+            path: String::new(),
+            password: secret::secret_buffer(),
+            needs_password: false,
+            error: None,
+            csv: None,
+            preview: None,
+        }
+    }
+}
+
+impl ImportForm {
+    /// A copy of the file's password for the worker thread.
+    pub(crate) fn password(&self) -> SecretBuffer {
+        secret::secret_buffer_from(&self.password)
+    }
+
+    /// Forgets the password and any entries read, keeping the chosen file.
+    pub(crate) fn clear(&mut self) {
+        self.password.zeroize();
+        self.needs_password = self.format == ImportFormat::KeePass;
+        self.error = None;
+        self.csv = None;
+        self.preview = None;
+    }
+}
+
+/// A CSV file and the field chosen for each of its columns.
+struct CsvColumns {
+    table: CsvTable,
+    preset: Option<Preset>,
+    mapping: Mapping,
+}
+
+/// Entries read from a file, waiting for confirmation.
+struct ImportPreview {
+    entries: Vec<Entry>,
+    /// Names of items the source had but Lilypad cannot store.
+    unsupported: Vec<String>,
+    /// What importing would do to the vault as it was when read.
+    summary: ImportSummary,
+    /// CSV rows repeating a vault entry or an earlier row, which the
+    /// summary does not count since they have no entry id to compare.
+    duplicates: usize,
+    /// CSV rows that could not be turned into entries.
+    failed: Vec<FailedRow>,
+}
+
+impl Drop for ImportPreview {
+    fn drop(&mut self) {
+        self.entries.zeroize();
+    }
+}
+
+impl LilypadApp {
+    pub(crate) fn render_import_settings(&mut self, ui: &mut egui::Ui) {
+        let busy = self.key_task.is_some();
+        let decrypting = self.key_task.as_ref().map(|task| task.kind());
+        let decrypting = decrypting
+            .filter(|kind| matches!(kind, KeyTaskKind::DecryptImport | KeyTaskKind::OpenKeePass));
+        let form = &mut self.import_form;
+        let mut read_requested = false;
+
+        let reading = form.preview.is_none() && form.csv.is_none();
+        ui.add_enabled_ui(!busy && reading, |ui| {
+            egui::Grid::new("import_source")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("Format");
+                    egui::ComboBox::from_id_salt("import_format")
+                        .selected_text(form.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in ImportFormat::ALL {
+                                if ui
+                                    .selectable_value(&mut form.format, format, format.label())
+                                    .changed()
+                                {
+                                    form.needs_password = format == ImportFormat::KeePass;
+                                    form.error = None;
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("File");
+                    let path = ui.add(
+                        egui::TextEdit::singleline(&mut form.path)
+                            .hint_text("/home/me/Downloads/bitwarden_export.json")
+                            .desired_width(360.0),
+                    );
+                    if path.changed() {
+                        form.needs_password = form.format == ImportFormat::KeePass;
+                        form.password.zeroize();
+                        form.error = None;
+                    }
+                    ui.end_row();
+
+                    if form.needs_password {
+                        ui.label(if form.format == ImportFormat::KeePass {
+                            "Database password"
+                        } else {
+                            "Export password"
+                        });
+                        let password =
+                            ui.add(egui::TextEdit::singleline(&mut *form.password).password(true));
+                        read_requested |= password.lost_focus()
+                            && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                        ui.end_row();
+                    }
+                });
+
+            if let Some(kind) = decrypting {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Spinner::new());
+                    ui.label(kind.label());
+                });
+            } else if reading {
+                read_requested |= ui
+                    .add_enabled(!form.path.trim().is_empty(), egui::Button::new("Preview"))
+                    .clicked();
+            }
+        });
+        if decrypting.is_some() && ui.button("Cancel").clicked() {
+            self.cancel_key_task();
+        }
+
+        let read_requested = read_requested && !self.import_form.path.trim().is_empty();
+        if let Some(error) = &self.import_form.error {
+            ui.colored_label(self.palette.danger, error);
+        }
+        if self.import_form.preview.is_some() {
+            self.render_import_preview(ui);
+        } else if self.import_form.csv.is_some() {
+            self.render_csv_mapping(ui);
+        } else {
+            ui.label(
+                RichText::new(
+                    "Nothing is added until you confirm the preview. Entries already in the \
+                     vault are skipped, so importing the same file twice is harmless.",
+                )
+                .small()
+                .weak(),
+            );
+        }
+
+        if read_requested {
+            self.read_import_file();
+        }
+    }
+
+    /// One row per CSV column: its header, a sample value, and the field it
+    /// goes into.
+    fn render_csv_mapping(&mut self, ui: &mut egui::Ui) {
+        let Some(csv) = &mut self.import_form.csv else {
+            return;
+        };
+        let palette = self.palette;
+
+        ui.label(
+            RichText::new(format!(
+                "{} rows found in {}",
+                csv.table.row_count(),
+                self.import_form.path.trim()
+            ))
+            .strong(),
+        );
+        ui.label(match csv.preset {
+            Some(preset) => format!(
+                "This looks like a {} export; its columns have been matched below.",
+                preset.label()
+            ),
+            None => {
+                "Choose what each column holds. Columns set to Ignore are not imported.".to_string()
+            }
+        });
+
+        egui::Grid::new("csv_mapping")
+            .num_columns(3)
+            .spacing([12.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new("Column").weak());
+                ui.label(RichText::new("First value").weak());
+                ui.label(RichText::new("Import as").weak());
+                ui.end_row();
+
+                for (index, header) in csv.table.headers.iter().enumerate() {
+                    let field = &mut csv.mapping.columns[index];
+                    ui.label(header);
+                    let sample = csv
+                        .table
+                        .sample(index)
+                        .and_then(|value| value.lines().next())
+                        .unwrap_or_default();
+                    if matches!(field, Some(Field::Password | Field::Totp)) && !sample.is_empty() {
+                        ui.label(RichText::new("••••••••").weak());
+                    } else {
+                        ui.label(RichText::new(truncate(sample, 40)).weak());
+                    }
+                    egui::ComboBox::from_id_salt(("csv_field", index))
+                        .selected_text(field.map_or("Ignore", Field::label))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(field, None, "Ignore");
+                            for option in Field::ALL {
+                                ui.selectable_value(field, Some(option), option.label());
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+
+        let usable = csv.mapping.is_usable();
+        if !usable {
+            ui.colored_label(
+                palette.warning,
+                "Map at least one column to a title, website, username, password, or notes.",
+            );
+        }
+        let mut previewed = false;
+        let mut cancelled = false;
+        ui.horizontal(|ui| {
+            previewed = ui
+                .add_enabled(usable, egui::Button::new("Preview"))
+                .clicked();
+            cancelled = ui.button("Cancel").clicked();
+        });
+
+        if previewed {
+            self.preview_csv_import();
+        } else if cancelled {
+            self.import_form.clear();
+        }
+    }
+
+    fn render_import_preview(&mut self, ui: &mut egui::Ui) {
+        let Some(preview) = &self.import_form.preview else {
+            return;
+        };
+        let palette = self.palette;
+
+        ui.label(
+            RichText::new(format!(
+                "{} entries found in {}",
+                preview.entries.len(),
+                self.import_form.path.trim()
+            ))
+            .strong(),
+        );
+        egui::Grid::new("import_preview")
+            .num_columns(2)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for kind in EntryKind::templates() {
+                    let count = preview
+                        .entries
+                        .iter()
+                        .filter(|entry| entry.kind.label() == kind.label())
+                        .count();
+                    if count > 0 {
+                        ui.label(kind.label());
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                }
+                let folders: BTreeSet<&str> = preview
+                    .entries
+                    .iter()
+                    .map(|entry| entry.folder.as_str())
+                    .filter(|folder| !folder.is_empty())
+                    .collect();
+                if !folders.is_empty() {
+                    ui.label("Folders");
+                    ui.label(folders.len().to_string())
+                        .on_hover_text(folders.into_iter().collect::<Vec<_>>().join("\n"));
+                    ui.end_row();
+                }
+            });
+
+        if preview.summary.skipped > 0 {
+            ui.label(format!(
+                "{} are already in the vault and will be skipped.",
+                preview.summary.skipped
+            ));
+        }
+        if preview.duplicates > 0 {
+            ui.label(format!(
+                "{} rows repeat an entry in the vault or an earlier row and will be skipped.",
+                preview.duplicates
+            ));
+        }
+        if !preview.failed.is_empty() {
+            ui.colored_label(
+                palette.warning,
+                format!("{} rows could not be imported:", preview.failed.len()),
+            );
+            egui::ScrollArea::vertical()
+                .id_salt("import_failed")
+                .max_height(120.0)
+                .show(ui, |ui| {
+                    for row in &preview.failed {
+                        ui.label(
+                            RichText::new(format!("Line {}: {}", row.line, row.reason)).small(),
+                        );
+                    }
+                });
+        }
+        if !preview.unsupported.is_empty() {
+            ui.colored_label(
+                palette.warning,
+                format!(
+                    "Not imported, since Lilypad has no matching entry type: {}",
+                    preview.unsupported.join(", ")
+                ),
+            );
+        }
+
+        let added = preview.summary.added;
+        let mapped = self.import_form.csv.is_some();
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let mut remap = false;
+        ui.horizontal(|ui| {
+            confirmed = ui
+                .add_enabled(
+                    added > 0,
+                    egui::Button::new(format!("Import {added} entries")),
+                )
+                .clicked();
+            if mapped {
+                remap = ui.button("Change columns").clicked();
+            }
+            cancelled = ui.button("Cancel").clicked();
+        });
+        if !self.import_form.needs_password {
+            ui.label(
+                RichText::new(
+                    "The export file is not encrypted. Delete it once the import is done.",
+                )
+                .small()
+                .color(palette.warning),
+            );
+        }
+
+        if confirmed {
+            self.apply_import();
+        } else if remap {
+            self.import_form.preview = None;
+        } else if cancelled {
+            self.import_form.clear();
+        }
+    }
+
+    /// Reads the chosen file. KeePass databases and password-protected
+    /// Bitwarden exports are decrypted on the key-derivation worker, since
+    /// their KDF is slow.
+    fn read_import_file(&mut self) {
+        let form = &mut self.import_form;
+        form.error = None;
+        let path = form.path.trim().to_string();
+        if form.format == ImportFormat::KeePass {
+            match fs::read(&path) {
+                Ok(data) => self.read_keepass_file(Zeroizing::new(data)),
+                Err(error) => form.error = Some(format!("Unable to read {path}: {error}")),
+            }
+            return;
+        }
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => Zeroizing::new(text),
+            Err(error) => {
+                form.error = Some(format!("Unable to read {path}: {error}"));
+                return;
+            }
+        };
+
+        match form.format {
+            // Read as bytes above.
+            ImportFormat::KeePass => {}
+            ImportFormat::Csv => match csv_import::read(&text) {
+                Ok(table) => {
+                    let preset = Preset::detect(&table.headers);
+                    let mapping = Mapping::guess(&table);
+                    form.csv = Some(CsvColumns {
+                        table,
+                        preset,
+                        mapping,
+                    });
+                }
+                Err(error) => form.error = Some(capitalize(&error.to_string())),
+            },
+            ImportFormat::Lilypad => match export::from_json(&text) {
+                Ok(entries) => self.show_import_preview(entries, Vec::new()),
+                Err(error) => form.error = Some(capitalize(&error.to_string())),
+            },
+            ImportFormat::Bitwarden => match bitwarden::is_password_protected(&text) {
+                Ok(false) => self.finish_import_read(bitwarden::read(&text, None)),
+                Ok(true) if form.password.is_empty() => {
+                    form.needs_password = true;
+                    form.error = Some(
+                        "This export is password protected. Enter the password chosen when \
+                         exporting it."
+                            .to_string(),
+                    );
+                }
+                Ok(true) => self.start_import_decrypt(text),
+                Err(error) => form.error = Some(capitalize(&error.to_string())),
+            },
+        }
+    }
+
+    /// Shows the preview of a Bitwarden export, or why it could not be read.
+    pub(crate) fn finish_import_read(&mut self, result: Result<BitwardenImport, BitwardenError>) {
+        let form = &mut self.import_form;
+        match result {
+            Ok(import) => {
+                form.password.zeroize();
+                self.show_import_preview(import.entries, import.skipped);
+            }
+            Err(BitwardenError::PasswordRequired | BitwardenError::WrongPassword) => {
+                form.needs_password = true;
+                form.password.zeroize();
+                form.error = Some("The export password is incorrect.".to_string());
+            }
+            Err(error) => form.error = Some(capitalize(&error.to_string())),
+        }
+    }
+
+    fn read_keepass_file(&mut self, data: Zeroizing<Vec<u8>>) {
+        let form = &mut self.import_form;
+        match kdbx::check(&data) {
+            Ok(()) if form.password.is_empty() => {
+                form.needs_password = true;
+                form.error = Some("Enter the database password.".to_string());
+            }
+            Ok(()) => self.start_keepass_decrypt(data),
+            Err(error) => form.error = Some(capitalize(&error.to_string())),
+        }
+    }
+
+    /// Shows the preview of a KeePass database, or why it could not be
+    /// opened.
+    pub(crate) fn finish_keepass_read(&mut self, result: Result<Vec<Entry>, KdbxError>) {
+        let form = &mut self.import_form;
+        form.password.zeroize();
+        match result {
+            Ok(entries) => self.show_import_preview(entries, Vec::new()),
+            Err(error) => form.error = Some(capitalize(&error.to_string())),
+        }
+    }
+
+    fn show_import_preview(&mut self, entries: Vec<Entry>, unsupported: Vec<String>) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let summary = export::preview_import(vault, &entries);
+        self.import_form.preview = Some(ImportPreview {
+            entries,
+            unsupported,
+            summary,
+            duplicates: 0,
+            failed: Vec::new(),
+        });
+    }
+
+    /// Converts the CSV rows with the chosen columns and previews the result.
+    fn preview_csv_import(&mut self) {
+        let (Some(vault), Some(csv)) = (&self.vault, &self.import_form.csv) else {
+            return;
+        };
+        let CsvImport {
+            entries,
+            duplicates,
+            failed,
+        } = csv_import::convert(&csv.table, &csv.mapping, vault.entries());
+        self.show_import_preview(entries, Vec::new());
+        if let Some(preview) = &mut self.import_form.preview {
+            preview.duplicates = duplicates.len();
+            preview.failed = failed;
+        }
+    }
+
+    /// Adds the previewed entries and saves, or leaves the vault untouched if
+    /// saving fails.
+    fn apply_import(&mut self) {
+        let Some(mut preview) = self.import_form.preview.take() else {
+            return;
+        };
+        let Some(vault) = self.vault.as_mut() else {
+            return;
+        };
+        let entries = std::mem::take(&mut preview.entries);
+        let existing: HashSet<_> = vault.entries().iter().map(|entry| entry.id).collect();
+        let new_ids: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.id)
+            .filter(|id| !existing.contains(id))
+            .collect();
+
+        let summary = export::import_entries(vault, entries);
+        match vault.save() {
+            Ok(()) => {
+                self.import_form.clear();
+                self.status_message = Some(format!(
+                    "Imported {} entries ({} skipped)",
+                    summary.added, summary.skipped
+                ));
+                self.invalidate_search();
+            }
+            Err(error) => {
+                for id in new_ids {
+                    vault.remove_entry(id);
+                }
+                self.import_form.error = Some(format!("Unable to save the vault: {error}"));
+            }
+        }
+    }
+}
+
+/// The first `limit` characters of `text`, with an ellipsis if cut.
+fn truncate(text: &str, limit: usize) -> String {
+    match text.char_indices().nth(limit) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
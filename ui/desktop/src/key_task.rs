@@ -0,0 +1,413 @@
+//! Runs the Argon2id key derivation behind unlock, vault creation, and
+//! master-password changes on a worker thread, along with the decryption of
+//! password-protected imports, backup archives, and vault snapshots, and the
+//! encryption of archive and KeePass exports.
+//!
+//! A memory-hard KDF takes a second or more, which would freeze the egui
+//! `update` loop. Each job gets its own thread and reports back through an
+//! `mpsc` channel that `poll_key_task` checks every frame. Cancelling drops
+//! the receiver: the worker still finishes, but its result is discarded (and
+//! wiped, since vaults and keys zeroize on drop) instead of being applied.
+
+use crate::LilypadApp;
+use eframe::egui;
+use lilypad_core::secret::{self, Zeroize, Zeroizing};
+use lilypad_core::{
+    archive, bitwarden, kdbx, vault, Archive, ArchiveError, ArchiveMetadata, Backups,
+    BitwardenError, BitwardenImport, Entry, KdbxError, MasterKey, OpenedSnapshot, Vault,
+    VaultError,
+};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+/// What a worker sends back when it is done.
+enum KeyOutcome {
+    Unlocked(Result<Vault, VaultError>),
+    Created {
+        path: PathBuf,
+        key: Result<MasterKey, VaultError>,
+    },
+    PasswordChanged(Result<MasterKey, VaultError>),
+    ImportRead(Result<BitwardenImport, BitwardenError>),
+    KeePassRead(Result<Vec<Entry>, KdbxError>),
+    Exported {
+        path: PathBuf,
+        /// The number of entries written, or why nothing was.
+        result: Result<usize, String>,
+    },
+    ArchiveRead(Result<Archive, ArchiveError>),
+    Restored {
+        path: PathBuf,
+        key: Result<MasterKey, VaultError>,
+    },
+    SnapshotRestored {
+        taken_at: u64,
+        result: Result<OpenedSnapshot, VaultError>,
+    },
+}
+
+/// Which job is running, for the spinner label and for deciding which card
+/// shows it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyTaskKind {
+    Unlock,
+    Create,
+    ChangePassword,
+    DecryptImport,
+    OpenKeePass,
+    ExportKeePass,
+    ExportArchive,
+    OpenArchive,
+    Restore,
+    RestoreSnapshot,
+}
+
+impl KeyTaskKind {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Unlock => "Unlocking vault…",
+            Self::Create => "Creating vault…",
+            Self::ChangePassword => "Changing master password…",
+            Self::DecryptImport => "Decrypting export…",
+            Self::OpenKeePass => "Opening KeePass database…",
+            Self::ExportKeePass => "Encrypting KeePass database…",
+            Self::ExportArchive => "Encrypting backup archive…",
+            Self::OpenArchive => "Decrypting backup archive…",
+            Self::Restore => "Restoring vault…",
+            Self::RestoreSnapshot => "Restoring from backup…",
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            Self::Unlock => "Unlock",
+            Self::Create => "Vault creation",
+            Self::ChangePassword => "Password change",
+            Self::DecryptImport | Self::OpenKeePass => "Import",
+            Self::ExportKeePass | Self::ExportArchive => "Export",
+            Self::OpenArchive | Self::Restore | Self::RestoreSnapshot => "Restore",
+        }
+    }
+}
+
+/// A key derivation running on a worker thread.
+pub(crate) struct KeyTask {
+    kind: KeyTaskKind,
+    receiver: Receiver<KeyOutcome>,
+}
+
+impl KeyTask {
+    fn spawn(kind: KeyTaskKind, job: impl FnOnce() -> KeyOutcome + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // The receiver is gone if the task was cancelled; the outcome is
+            // simply dropped.
+            let _ = sender.send(job());
+        });
+        Self { kind, receiver }
+    }
+
+    pub(crate) fn kind(&self) -> KeyTaskKind {
+        self.kind
+    }
+}
+
+impl LilypadApp {
+    /// Opens the vault with the typed master password in the background.
+    pub(crate) fn start_unlock(&mut self, path: PathBuf) {
+        let password = secret::secret_buffer_from(&self.master_password);
+        self.master_password.zeroize();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::Unlock, move || {
+            KeyOutcome::Unlocked(Vault::open(&path, &password))
+        }));
+    }
+
+    /// Derives the key for a new vault in the background. Nothing is written
+    /// until the key arrives, so cancelling leaves no file behind.
+    pub(crate) fn start_create(&mut self, path: PathBuf) {
+        let password = secret::secret_buffer_from(&self.master_password);
+        let kdf = self.config.security.kdf.to_params();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::Create, move || {
+            let key = MasterKey::derive(&password, kdf);
+            KeyOutcome::Created { path, key }
+        }));
+    }
+
+    /// Checks the current password from the Security panel form against the
+    /// open vault and derives a key for the new one with the configured cost
+    /// parameters. The vault stays usable meanwhile.
+    pub(crate) fn start_password_change(&mut self) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let check = vault.password_check();
+        let kdf = self.config.security.kdf.to_params();
+        let (current, new) = self.password_change.passwords();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::ChangePassword, move || {
+            let key = check
+                .verify(&current)
+                .and_then(|()| MasterKey::derive(&new, kdf));
+            KeyOutcome::PasswordChanged(key)
+        }));
+    }
+
+    /// Decrypts a password-protected Bitwarden export with the password from
+    /// the import form.
+    pub(crate) fn start_import_decrypt(&mut self, text: Zeroizing<String>) {
+        let password = self.import_form.password();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::DecryptImport, move || {
+            KeyOutcome::ImportRead(bitwarden::read(&text, Some(&password)))
+        }));
+    }
+
+    /// Opens a KeePass database with the password from the import form.
+    pub(crate) fn start_keepass_decrypt(&mut self, data: Zeroizing<Vec<u8>>) {
+        let password = self.import_form.password();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::OpenKeePass, move || {
+            KeyOutcome::KeePassRead(kdbx::read(&data, &password))
+        }));
+    }
+
+    /// Writes every entry to a new KeePass database at `path`, stretching the
+    /// export password with the vault's own Argon2id settings.
+    pub(crate) fn start_keepass_export(&mut self, path: PathBuf) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let entries = Zeroizing::new(vault.entries().to_vec());
+        let kdf = vault.kdf_params();
+        let password = self.export_form.password();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::ExportKeePass, move || {
+            let result = kdbx::write(&entries, &password, kdf)
+                .map_err(|error| format!("Unable to export: {error}"))
+                .and_then(|data| {
+                    vault::write_new(&path, &data)
+                        .map_err(|error| format!("Unable to write {}: {error}", path.display()))
+                })
+                .map(|()| entries.len());
+            KeyOutcome::Exported { path, result }
+        }));
+    }
+
+    /// Writes every entry to a new backup archive at `path`, encrypted with
+    /// the passphrase from the export form.
+    pub(crate) fn start_archive_export(&mut self, path: PathBuf) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let metadata = ArchiveMetadata::of(vault);
+        let entries = Zeroizing::new(vault.entries().to_vec());
+        let passphrase = self.export_form.password();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::ExportArchive, move || {
+            let result = archive::write(&metadata, &entries, &passphrase)
+                .map_err(|error| format!("Unable to export: {error}"))
+                .and_then(|data| {
+                    vault::write_new(&path, &data)
+                        .map_err(|error| format!("Unable to write {}: {error}", path.display()))
+                })
+                .map(|()| entries.len());
+            KeyOutcome::Exported { path, result }
+        }));
+    }
+
+    /// Decrypts a backup archive with the passphrase from the restore form.
+    pub(crate) fn start_archive_open(&mut self, data: Vec<u8>) {
+        let passphrase = self.restore_form.passphrase();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::OpenArchive, move || {
+            KeyOutcome::ArchiveRead(archive::read(&data, &passphrase))
+        }));
+    }
+
+    /// Derives the key for the restored vault; the opened archive is written
+    /// into it once the key arrives.
+    pub(crate) fn start_restore(&mut self, path: PathBuf) {
+        let password = secret::secret_buffer_from(&self.master_password);
+        let kdf = self.config.security.kdf.to_params();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::Restore, move || {
+            let key = MasterKey::derive(&password, kdf);
+            KeyOutcome::Restored { path, key }
+        }));
+    }
+
+    /// Checks and decrypts `snapshot` with the password from the Backups
+    /// form. The vault file is only replaced once the result is applied, so
+    /// cancelling or locking meanwhile leaves it untouched.
+    pub(crate) fn start_snapshot_restore(
+        &mut self,
+        backups: Backups,
+        snapshot: PathBuf,
+        taken_at: u64,
+    ) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let vault_path = vault.path().to_path_buf();
+        let password = self.backup_form.password();
+        self.key_task = Some(KeyTask::spawn(KeyTaskKind::RestoreSnapshot, move || {
+            let result = backups.open(&snapshot, &vault_path, &password);
+            KeyOutcome::SnapshotRestored { taken_at, result }
+        }));
+    }
+
+    /// Discards the running job's result.
+    pub(crate) fn cancel_key_task(&mut self) {
+        if let Some(task) = self.key_task.take() {
+            self.status_message = Some(format!("{} cancelled", task.kind().noun()));
+        }
+    }
+
+    /// Applies a finished job, and keeps frames coming while one is running
+    /// so the result is picked up without waiting for input.
+    pub(crate) fn poll_key_task(&mut self, ctx: &egui::Context) {
+        let Some(task) = &self.key_task else {
+            return;
+        };
+        let outcome = match task.receiver.try_recv() {
+            Ok(outcome) => outcome,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(50));
+                return;
+            }
+            Err(TryRecvError::Disconnected) => {
+                self.status_message = Some(format!("{} failed unexpectedly", task.kind().noun()));
+                self.key_task = None;
+                return;
+            }
+        };
+        self.key_task = None;
+
+        match outcome {
+            KeyOutcome::Unlocked(result) => self.finish_unlock(result),
+            KeyOutcome::Created { path, key } => {
+                self.finish_create(key.and_then(|key| Vault::create_with_key(&path, key)));
+            }
+            KeyOutcome::PasswordChanged(key) => self.finish_password_change(key),
+            KeyOutcome::ImportRead(result) => self.finish_import_read(result),
+            KeyOutcome::KeePassRead(result) => self.finish_keepass_read(result),
+            KeyOutcome::Exported { path, result } => self.finish_export(&path, result),
+            KeyOutcome::ArchiveRead(result) => self.finish_archive_read(result),
+            KeyOutcome::Restored { path, key } => self.finish_restore(&path, key),
+            KeyOutcome::SnapshotRestored { taken_at, result } => {
+                self.finish_snapshot_restore(taken_at, result);
+            }
+        }
+    }
+
+    fn finish_password_change(&mut self, key: Result<MasterKey, VaultError>) {
+        let Some(vault) = self.vault.as_mut() else {
+            return;
+        };
+        match key.and_then(|key| vault.set_master_key(key)) {
+            Ok(()) => {
+                self.password_change.clear();
+                self.status_message = Some("Master password changed".to_string());
+            }
+            Err(VaultError::WrongPassword) => {
+                self.password_change.reject_current();
+                self.status_message = Some("Current master password is incorrect".to_string());
+            }
+            Err(error) => {
+                self.status_message = Some(format!("Unable to change master password: {error}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lilypad_core::crypto::KdfParams;
+    use lilypad_core::entry::EntryKind;
+    use lilypad_core::session::LockReason;
+    use lilypad_core::{AppDirs, Config};
+    use std::fs;
+    use std::path::Path;
+
+    const PASSWORD: &str = "pond lily frog";
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 16 * 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+    const TAKEN_AT: u64 = 1_700_000_000;
+
+    /// An app with a vault open in `dir` holding one entry more than its
+    /// snapshot from `TAKEN_AT`, which is returned with its backups.
+    fn app_with_snapshot(dir: &Path) -> (LilypadApp, Backups, PathBuf) {
+        let backups = Backups::new(dir.join("backups"), 5);
+        let mut vault =
+            Vault::create_with_params(&dir.join("vault.lilypad"), PASSWORD, TEST_KDF).unwrap();
+        vault.set_backups(Some(backups.clone()));
+        vault.add_entry(Entry::new("Mail", EntryKind::SecureNote));
+        vault.save().unwrap();
+        let contents = fs::read(vault.path()).unwrap();
+        let snapshot = backups.snapshot(vault.path(), &contents, TAKEN_AT).unwrap();
+        vault.add_entry(Entry::new("Bank", EntryKind::SecureNote));
+        vault.save().unwrap();
+
+        let dirs = AppDirs {
+            config_dir: dir.join("config"),
+            data_dir: dir.to_path_buf(),
+        };
+        let mut app = LilypadApp::with_config(Config::default(), Some(dirs));
+        assert_eq!(app.backup_dir.as_deref(), Some(backups.dir()));
+        app.vault = Some(vault);
+        app.backup_form = crate::BackupForm::with_password(PASSWORD);
+        (app, backups, snapshot)
+    }
+
+    #[test]
+    fn locking_mid_restore_leaves_the_vault_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, backups, snapshot) = app_with_snapshot(dir.path());
+        let vault_path = dir.path().join("vault.lilypad");
+        let before = fs::read(&vault_path).unwrap();
+        let listed = backups.list(&vault_path).unwrap().len();
+
+        app.start_snapshot_restore(backups.clone(), snapshot, TAKEN_AT);
+        // Wait for the worker to finish everything it does, then lock
+        // before the result is applied.
+        let outcome = app.key_task.as_ref().unwrap().receiver.recv().unwrap();
+        assert!(matches!(
+            outcome,
+            KeyOutcome::SnapshotRestored { result: Ok(_), .. }
+        ));
+        app.lock_vault(LockReason::Idle);
+        drop(outcome);
+
+        assert!(app.vault.is_none());
+        assert!(app.key_task.is_none());
+        assert_eq!(fs::read(&vault_path).unwrap(), before);
+        assert_eq!(backups.list(&vault_path).unwrap().len(), listed);
+    }
+
+    #[test]
+    fn applying_a_restore_replaces_the_vault_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut app, backups, snapshot) = app_with_snapshot(dir.path());
+        let vault_path = dir.path().join("vault.lilypad");
+        let before = fs::read(&vault_path).unwrap();
+
+        app.start_snapshot_restore(backups.clone(), snapshot, TAKEN_AT);
+        let ctx = egui::Context::default();
+        while app.key_task.is_some() {
+            thread::sleep(Duration::from_millis(10));
+            app.poll_key_task(&ctx);
+        }
+
+        assert_eq!(app.vault.as_ref().unwrap().entries().len(), 1);
+        assert_eq!(
+            Vault::open(&vault_path, PASSWORD).unwrap().entries().len(),
+            1
+        );
+        // The replaced file was kept as a snapshot of its own.
+        let kept = backups
+            .list(&vault_path)
+            .unwrap()
+            .iter()
+            .any(|snapshot| fs::read(&snapshot.path).unwrap() == before);
+        assert!(kept);
+    }
+}
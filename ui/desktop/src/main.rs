@@ -1,32 +1,108 @@
-use directories::ProjectDirs;
+mod auto_lock;
+mod backup_panel;
+mod clipboard;
+mod command_palette;
+mod editor;
+mod entry_list;
+mod export_panel;
+mod generator_panel;
+mod import_panel;
+mod key_task;
+mod restore;
+mod security_panel;
+mod settings_panel;
+mod shortcuts;
+mod strength_meter;
+mod theme;
+mod unlock;
+
+use backup_panel::BackupForm;
+use command_palette::CommandPalette;
+use editor::EntryEditor;
 use eframe::{egui, App};
-use egui::{Align2, Color32, CornerRadius, Margin, OutputCommand, RichText};
-use rand::Rng;
-use std::fs;
+use egui::{Align2, Color32, Margin, RichText};
+use export_panel::ExportForm;
+use import_panel::ImportForm;
+use key_task::KeyTask;
+use lilypad_core::secret::{self, SecretBuffer};
+use lilypad_core::session::{AutoLock, LockReason};
+use lilypad_core::{
+    Action, AppDirs, ClipboardClear, Config, GeneratorSettings, Keymap, PasswordRules, SearchHit,
+    SearchIndex, Theme, Vault,
+};
+use restore::RestoreForm;
+use security_panel::PasswordChange;
+use settings_panel::SettingsForm;
+use std::path::PathBuf;
+use std::time::Instant;
+use theme::Palette;
+
+/// `selected_category` of the Settings view, which has no navigation button.
+const SETTINGS_CATEGORY: usize = 5;
 
 fn main() -> eframe::Result<()> {
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Lilypad Desktop",
         native_options,
-        Box::new(|_cc| Ok(Box::<LilypadApp>::default())),
+        Box::new(|cc| {
+            let mut app = LilypadApp::new();
+            app.apply_appearance(&cc.egui_ctx);
+            Ok(Box::new(app))
+        }),
     )
 }
 
 struct LilypadApp {
     show_welcome: bool,
-    vault_unlocked: bool,
+    vault: Option<Vault>,
+    editor: Option<EntryEditor>,
     search_query: String,
+    search_index: Option<SearchIndex>,
+    search_results: Vec<SearchHit>,
+    /// Query `search_results` was computed for; `None` when stale.
+    search_results_query: Option<String>,
+    selected_result: usize,
+    scroll_to_selection: bool,
     selected_category: usize,
     status_message: Option<String>,
-    welcome_ack_path: Option<std::path::PathBuf>,
-    master_password: String,
-    generated_password: String,
-    generator_length: usize,
-    generator_lowercase: bool,
-    generator_uppercase: bool,
-    generator_digits: bool,
-    generator_symbols: bool,
+    config: Config,
+    /// Platform config directory holding `config.toml`; `None` if unknown.
+    config_dir: Option<PathBuf>,
+    vault_path: Option<PathBuf>,
+    /// Platform data directory location, used when the config sets none.
+    default_vault_path: Option<PathBuf>,
+    /// Platform folder where saves keep vault snapshots.
+    backup_dir: Option<PathBuf>,
+    master_password: SecretBuffer,
+    confirm_password: SecretBuffer,
+    unlock_retry_at: Option<Instant>,
+    generated_password: SecretBuffer,
+    generator: GeneratorSettings,
+    /// Site rules typed into the Generator panel, applied on request.
+    generator_rules: String,
+    /// The applied site rules. They narrow `generator` for each password
+    /// the panel makes, leaving the settings themselves as the user set them.
+    site_rules: Option<PasswordRules>,
+    clipboard_clear: ClipboardClear,
+    /// Opened on first copy; `None` if the platform clipboard is unavailable.
+    system_clipboard: Option<arboard::Clipboard>,
+    auto_lock: AutoLock,
+    /// Key derivation running on a worker thread, if any.
+    key_task: Option<KeyTask>,
+    password_change: PasswordChange,
+    settings_form: SettingsForm,
+    import_form: ImportForm,
+    export_form: ExportForm,
+    restore_form: RestoreForm,
+    backup_form: BackupForm,
+    /// Themes found in the user's `themes` folder.
+    user_themes: Vec<Theme>,
+    /// The theme in use; follow-system mode swaps it at runtime.
+    theme: Theme,
+    palette: Palette,
+    keymap: Keymap,
+    command_palette: Option<CommandPalette>,
 }
 
 impl Default for LilypadApp {
@@ -37,51 +113,110 @@ impl Default for LilypadApp {
 
 impl App for LilypadApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.follow_system_theme(ctx);
+        self.poll_clipboard(ctx);
+        self.poll_key_task(ctx);
+
         if self.show_welcome {
             self.render_welcome_modal(ctx);
             return;
         }
 
-        if !self.vault_unlocked {
+        if self.vault.is_some() {
+            self.poll_auto_lock(ctx);
+        }
+        if self.vault.is_some() {
+            self.handle_shortcuts(ctx);
+        }
+        if self.vault.is_none() {
             self.render_unlock_screen(ctx);
             return;
         }
 
+        // Before the panels, so its keys are consumed before they see them.
+        self.render_command_palette(ctx);
         self.render_header(ctx);
+        self.render_entry_editor(ctx);
         self.render_main_panel(ctx);
         self.render_navigation_bar(ctx);
         self.render_status_bar(ctx);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.clear_clipboard_now();
+    }
 }
 
 impl LilypadApp {
     fn new() -> Self {
+        let dirs = AppDirs::platform();
+        let mut load_error = None;
+        let config = match &dirs {
+            Some(dirs) => Config::load(&dirs.config_dir).unwrap_or_else(|error| {
+                load_error = Some(format!("{error}; using default settings"));
+                Config::default()
+            }),
+            None => Config::default(),
+        };
+        let mut app = Self::with_config(config, dirs);
+        if load_error.is_some() {
+            app.status_message = load_error;
+        }
+        app
+    }
+
+    /// An app on `config` that keeps its settings, themes, and snapshots in
+    /// `dirs`, or nowhere when `None`; tests pass a temporary folder.
+    fn with_config(config: Config, dirs: Option<AppDirs>) -> Self {
         let mut app = Self {
             show_welcome: true,
-            vault_unlocked: false,
+            vault: None,
+            editor: None,
             search_query: String::new(),
+            search_index: None,
+            search_results: Vec::new(),
+            search_results_query: None,
+            selected_result: 0,
+            scroll_to_selection: false,
             selected_category: 0,
             status_message: None,
-            welcome_ack_path: None,
-            master_password: String::new(),
-            generated_password: String::new(),
-            generator_length: 16,
-            generator_lowercase: true,
-            generator_uppercase: true,
-            generator_digits: true,
-            generator_symbols: true,
+            config,
+            config_dir: None,
+            vault_path: None,
+            default_vault_path: None,
+            backup_dir: None,
+            master_password: secret::secret_buffer(),
+            confirm_password: secret::secret_buffer(),
+            unlock_retry_at: None,
+            generated_password: secret::secret_buffer(),
+            generator: GeneratorSettings::default(),
+            generator_rules: String::new(),
+            site_rules: None,
+            clipboard_clear: ClipboardClear::default(),
+            system_clipboard: None,
+            auto_lock: AutoLock::default(),
+            key_task: None,
+            password_change: PasswordChange::default(),
+            settings_form: SettingsForm::default(),
+            import_form: ImportForm::default(),
+            export_form: ExportForm::default(),
+            restore_form: RestoreForm::default(),
+            backup_form: BackupForm::default(),
+            user_themes: Vec::new(),
+            theme: Theme::dark(),
+            palette: Palette::of(&Theme::dark()),
+            keymap: Keymap::default(),
+            command_palette: None,
         };
 
-        if let Some(project_dirs) = ProjectDirs::from("", "", "Lilypad") {
-            let welcome_ack_path = project_dirs.config_dir().join("welcome_ack");
-            app.welcome_ack_path = Some(welcome_ack_path.clone());
-
-            if let Ok(contents) = fs::read_to_string(&welcome_ack_path) {
-                if contents.trim() == "acknowledged=true" {
-                    app.show_welcome = false;
-                }
-            }
+        if let Some(dirs) = dirs {
+            app.default_vault_path = Some(dirs.default_vault_path());
+            app.backup_dir = Some(dirs.backup_dir());
+            app.config_dir = Some(dirs.config_dir);
+            app.load_user_themes();
         }
+        app.apply_config();
+        app.resume_unlock_backoff();
 
         app
     }
@@ -118,185 +253,74 @@ impl LilypadApp {
             });
     }
 
-    fn render_unlock_screen(&mut self, ctx: &egui::Context) {
-        let background = Color32::from_rgb(14, 22, 33);
-        egui::CentralPanel::default()
-            .frame(egui::Frame::new().fill(background))
-            .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(40.0);
-                    let card_frame = egui::Frame::new()
-                        .fill(Color32::from_rgb(24, 36, 54))
-                        .stroke(egui::Stroke::new(1.0, Color32::from_rgb(45, 78, 120)))
-                        .corner_radius(CornerRadius::from(12.0))
-                        .inner_margin(egui::Margin::same(24));
-
-                    ui.allocate_ui_with_layout(
-                        ui.available_size(),
-                        egui::Layout::top_down(egui::Align::Center),
-                        |ui| {
-                            ui.add_space(32.0);
-                            ui.label(
-                                RichText::new("Unlock Lilypad Vault")
-                                    .size(28.0)
-                                    .strong()
-                                    .color(Color32::from_rgb(205, 225, 255)),
-                            );
-                            ui.add_space(8.0);
-                            ui.label(
-                                RichText::new(
-                                    "Secure your workspace with a strong master password before accessing your vault.",
-                                )
-                                .color(Color32::from_gray(200)),
-                            );
-                            ui.add_space(24.0);
-
-                            card_frame.show(ui, |ui| {
-                                ui.vertical(|ui| {
-                                    ui.label(
-                                        RichText::new("Master Password")
-                                            .size(16.0)
-                                            .color(Color32::from_rgb(185, 210, 240)),
-                                    );
-                                    ui.add_space(6.0);
-                                    ui.add(
-                                        egui::TextEdit::singleline(&mut self.master_password)
-                                            .password(true)
-                                            .hint_text("Enter your master password"),
-                                    );
-                                    ui.add_space(12.0);
-
-                                    let requirements = self.password_requirements();
-                                    let all_met = self.password_meets_requirements();
-
-                                    ui.label(RichText::new("Password requirements").strong());
-                                    ui.add_space(4.0);
-                                    for (label, satisfied) in requirements {
-                                        let color = if satisfied {
-                                            Color32::from_rgb(111, 207, 151)
-                                        } else {
-                                            Color32::from_rgb(240, 105, 105)
-                                        };
-                                        ui.horizontal(|ui| {
-                                            ui.colored_label(color, if satisfied { "✔" } else { "○" });
-                                            ui.label(RichText::new(label).color(Color32::from_gray(220)));
-                                        });
-                                    }
-
-                                    ui.add_space(16.0);
-                                    let button = egui::Button::new(
-                                        RichText::new("Unlock Vault")
-                                            .strong()
-                                            .color(Color32::from_rgb(16, 22, 32)),
-                                    )
-                                    .fill(if all_met {
-                                        Color32::from_rgb(111, 207, 151)
-                                    } else {
-                                        Color32::from_rgb(70, 94, 124)
-                                    })
-                                    .min_size(egui::vec2(240.0, 36.0))
-                                    .corner_radius(8.0);
-
-                                    if ui.add_enabled(all_met, button).clicked() {
-                                        self.vault_unlocked = true;
-                                        self.status_message = Some("Vault unlocked".to_string());
-                                    }
-
-                                    ui.add_space(8.0);
-                                    ui.label(
-                                        RichText::new(
-                                            "Use a password manager-friendly secret to keep your vault secure.",
-                                        )
-                                        .color(Color32::from_gray(180))
-                                        .italics(),
-                                    );
-                                });
-                            });
-                            ui.add_space(40.0);
-                        },
-                    );
-                });
-            });
-    }
-
-    fn password_requirements(&self) -> [(&'static str, bool); 4] {
-        [
-            (
-                "At least 12 characters",
-                self.master_password.chars().count() >= 12,
-            ),
-            (
-                "Contains a lowercase letter",
-                self.master_password.chars().any(|c| c.is_ascii_lowercase()),
-            ),
-            (
-                "Contains an uppercase letter",
-                self.master_password.chars().any(|c| c.is_ascii_uppercase()),
-            ),
-            (
-                "Contains a special character",
-                self.master_password
-                    .chars()
-                    .any(|c| !c.is_ascii_alphanumeric() && !c.is_whitespace()),
-            ),
-        ]
-    }
-
-    fn password_meets_requirements(&self) -> bool {
-        self.password_requirements()
-            .iter()
-            .all(|(_, satisfied)| *satisfied)
-    }
-
     fn render_header(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("header").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("Lilypad Vault");
                 ui.separator();
                 ui.label("Search");
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.search_query).hint_text("Search entries"),
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query)
+                        .id(egui::Id::new(entry_list::SEARCH_BOX_ID))
+                        .hint_text("Search entries"),
                 );
+                if search.changed() {
+                    self.selected_category = 0;
+                }
                 ui.separator();
-                if ui.button("Add Entry").clicked() {
-                    self.status_message = Some("Entry creation coming soon".to_string());
+                if self.header_button(ui, "Add Entry", Action::NewEntry) {
+                    self.open_new_entry_editor();
                 }
-                if ui.button("Settings").clicked() {
-                    self.status_message = Some("Settings placeholder".to_string());
+                if self.header_button(ui, "Commands", Action::CommandPalette) {
+                    self.toggle_command_palette();
+                }
+                if self.header_button(ui, "Settings", Action::OpenSettings) {
+                    self.selected_category = SETTINGS_CATEGORY;
+                }
+                if self.header_button(ui, "🔒 Lock now", Action::Lock) {
+                    self.lock_vault(LockReason::Manual);
                 }
             });
         });
     }
 
+    /// A header button whose hover text names its shortcut.
+    fn header_button(&self, ui: &mut egui::Ui, label: &str, action: Action) -> bool {
+        let response = ui.button(label);
+        match self.shortcut_label(action) {
+            Some(shortcut) => response.on_hover_text(shortcut).clicked(),
+            None => response.clicked(),
+        }
+    }
+
     fn render_navigation_bar(&mut self, ctx: &egui::Context) {
         let nav_items = [
-            ("Vault", "🗄️"),
-            ("Generator", "⚙️"),
-            ("Alerts", "🔔"),
-            ("Account", "👤"),
-            ("Security", "🛡️"),
+            ("Vault", "🗄️", Action::ShowVault),
+            ("Generator", "⚙️", Action::ShowGenerator),
+            ("Alerts", "🔔", Action::ShowAlerts),
+            ("Account", "👤", Action::ShowAccount),
+            ("Security", "🛡️", Action::ShowSecurity),
         ];
 
-        let background = Color32::from_rgb(245, 247, 250);
-        let accent = Color32::from_rgb(70, 118, 190);
+        let palette = self.palette;
 
         egui::TopBottomPanel::bottom("navigation_bar")
             .frame(
                 egui::Frame::NONE
-                    .fill(background)
-                    .stroke(egui::Stroke::new(1.0, Color32::from_gray(210)))
+                    .fill(palette.surface)
+                    .stroke(egui::Stroke::new(1.0, palette.border))
                     .inner_margin(Margin::symmetric(12, 8)),
             )
             .show(ctx, |ui| {
                 ui.set_height(96.0);
                 ui.horizontal_centered(|ui| {
-                    for (index, (label, icon)) in nav_items.iter().enumerate() {
+                    for (index, (label, icon, action)) in nav_items.iter().enumerate() {
                         let selected = self.selected_category == index;
                         let text = format!("{icon}\n{label}");
                         let text_color = if selected {
-                            Color32::from_rgb(16, 28, 46)
+                            palette.on_accent
                         } else {
-                            Color32::from_gray(60)
+                            palette.text_muted
                         };
 
                         let button = egui::Button::new(
@@ -304,13 +328,17 @@ impl LilypadApp {
                         )
                         .min_size(egui::vec2(108.0, 70.0))
                         .fill(if selected {
-                            accent
+                            palette.accent
                         } else {
-                            Color32::from_white_alpha(0)
+                            Color32::TRANSPARENT
                         })
                         .corner_radius(12.0);
 
-                        if ui.add(button).clicked() {
+                        let mut response = ui.add(button);
+                        if let Some(shortcut) = self.shortcut_label(*action) {
+                            response = response.on_hover_text(shortcut);
+                        }
+                        if response.clicked() {
                             self.selected_category = index;
                         }
                     }
@@ -348,6 +376,13 @@ impl LilypadApp {
                     ui.add_space(8.0);
                     ui.label("Account controls are coming soon.");
                 }
+                SETTINGS_CATEGORY => {
+                    ui.heading("Settings");
+                    ui.separator();
+                    ui.label("Preferences are saved automatically and apply right away.");
+                    ui.add_space(8.0);
+                    this.render_settings_panel(ui);
+                }
                 4 => {
                     ui.heading("Security");
                     ui.separator();
@@ -355,23 +390,16 @@ impl LilypadApp {
                         "Centralize security options such as session locks and recovery methods.",
                     );
                     ui.add_space(8.0);
-                    ui.label("Security controls will appear here when available.");
+                    this.render_security_panel(ui);
                 }
                 _ => {
                     ui.heading("Credentials");
                     ui.separator();
                     ui.label(
-                        "This area will list stored items. Use the Add Entry action to populate the vault.",
+                        "Logins, secure notes, payment cards, identities, and Wi-Fi credentials stored in your vault. Use the Add Entry action to populate it.",
                     );
                     ui.add_space(8.0);
-                    ui.group(|ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(RichText::new("Example: Email Account").strong());
-                            ui.separator();
-                            ui.label("user@example.com");
-                        });
-                        ui.label("Last updated: pending");
-                    });
+                    this.render_entry_list(ui);
                 }
             });
         });
@@ -396,159 +424,53 @@ impl LilypadApp {
         .inner
     }
 
-    fn render_password_generator(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        ui.vertical(|ui| {
-            ui.horizontal(|ui| {
-                ui.label(RichText::new("Length").strong());
-                ui.add(
-                    egui::Slider::new(&mut self.generator_length, 8..=64)
-                        .text("characters")
-                        .step_by(1.0),
-                );
-            });
-
-            ui.horizontal(|ui| {
-                ui.checkbox(&mut self.generator_lowercase, "Lowercase (abc)");
-                ui.checkbox(&mut self.generator_uppercase, "Uppercase (ABC)");
-            });
-            ui.horizontal(|ui| {
-                ui.checkbox(&mut self.generator_digits, "Digits (0-9)");
-                ui.checkbox(&mut self.generator_symbols, "Symbols (!#$)");
-            });
-
-            let generation_possible = self.generator_lowercase
-                || self.generator_uppercase
-                || self.generator_digits
-                || self.generator_symbols;
-
-            let (strength_label, strength_color) = self.generator_strength();
-
-            ui.add_space(6.0);
-            ui.horizontal(|ui| {
-                ui.label(RichText::new("Strength").strong());
-                ui.colored_label(strength_color, strength_label);
-            });
-
-            ui.add_space(6.0);
-            let generate_button = egui::Button::new(
-                RichText::new("Generate password")
-                    .strong()
-                    .color(Color32::from_rgb(16, 22, 32)),
-            )
-            .fill(Color32::from_rgb(111, 207, 151))
-            .min_size(egui::vec2(200.0, 32.0));
-
-            if ui
-                .add_enabled(generation_possible, generate_button)
-                .clicked()
-            {
-                if let Some(password) = self.generate_password() {
-                    self.generated_password = password.clone();
-                    self.status_message = Some("New password generated".to_string());
-                    ctx.send_cmd(OutputCommand::CopyText(password.clone()));
-                }
-            }
-
-            ui.add_space(10.0);
-            ui.horizontal(|ui| {
-                ui.label(RichText::new("Generated password").strong());
-                if ui.button("Copy").clicked() {
-                    let password = self.generated_password.clone();
-                    ctx.send_cmd(OutputCommand::CopyText(password));
-                    self.status_message = Some("Password copied to clipboard".to_string());
-                }
-            });
-
-            ui.add(
-                egui::TextEdit::singleline(&mut self.generated_password)
-                    .password(true)
-                    .hint_text("Generate a password to display it here"),
-            );
-        });
-    }
-
-    fn generate_password(&self) -> Option<String> {
-        let mut charset = String::new();
-        if self.generator_lowercase {
-            charset.push_str("abcdefghijklmnopqrstuvwxyz");
-        }
-        if self.generator_uppercase {
-            charset.push_str("ABCDEFGHIJKLMNOPQRSTUVWXYZ");
-        }
-        if self.generator_digits {
-            charset.push_str("0123456789");
-        }
-        if self.generator_symbols {
-            charset.push_str("!#$%&()*+,-./:;<=>?@[]^_{|}~");
-        }
-
-        if charset.is_empty() {
-            return None;
-        }
-
-        let mut rng = rand::thread_rng();
-        let generated: String = (0..self.generator_length)
-            .map(|_| {
-                let idx = rng.gen_range(0..charset.len());
-                charset.chars().nth(idx).unwrap_or('A')
-            })
-            .collect();
-
-        Some(generated)
-    }
-
-    fn generator_strength(&self) -> (&'static str, Color32) {
-        let mut score = 0;
-        let length = self.generator_length as u32;
-
-        if length >= 12 {
-            score += 1;
-        }
-        if length >= 20 {
-            score += 1;
-        }
-        if self.generator_lowercase && self.generator_uppercase {
-            score += 1;
-        }
-        if self.generator_digits {
-            score += 1;
-        }
-        if self.generator_symbols {
-            score += 1;
-        }
-
-        match score {
-            0 | 1 => ("Weak", Color32::from_rgb(240, 105, 105)),
-            2 | 3 => ("Moderate", Color32::from_rgb(255, 193, 107)),
-            4 => ("Strong", Color32::from_rgb(111, 207, 151)),
-            _ => ("Very strong", Color32::from_rgb(76, 175, 80)),
-        }
-    }
-
     fn render_status_bar(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.horizontal_centered(|ui| {
                 if let Some(message) = &self.status_message {
-                    ui.label(RichText::new(message).color(Color32::from_rgb(40, 120, 40)));
+                    ui.label(RichText::new(message).color(self.palette.success));
                 } else {
                     ui.label("Ready");
                 }
+                if let Some(seconds) = self.clipboard_countdown() {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(format!("Clipboard clears in {seconds} s"));
+                    });
+                }
             });
         });
     }
 
     fn persist_welcome_acknowledgement(&mut self) {
-        if let Some(path) = &self.welcome_ack_path {
-            if let Some(parent) = path.parent() {
-                if let Err(error) = fs::create_dir_all(parent) {
-                    self.status_message = Some(format!("Unable to prepare config folder: {error}"));
-                    return;
-                }
-            }
+        self.config.welcome_acknowledged = true;
+        self.save_config();
+    }
+
+    /// Applies loaded settings to the running app.
+    fn apply_config(&mut self) {
+        let config = &self.config;
+        self.show_welcome = !config.welcome_acknowledged;
+        self.generator = config.generator.to_settings();
+        self.auto_lock.idle_timeout = config.security.idle_timeout();
+        self.auto_lock.lock_on_minimize = config.security.lock_on_minimize;
+        self.auto_lock.lock_on_focus_loss = config.security.lock_on_focus_loss;
+        self.clipboard_clear
+            .set_clear_after(config.security.clipboard_clear_after());
+        self.keymap = config.keymap();
+        self.vault_path = config
+            .vault
+            .path
+            .clone()
+            .or_else(|| self.default_vault_path.clone());
+    }
 
-            if let Err(error) = fs::write(path, "acknowledged=true") {
-                self.status_message = Some(format!("Unable to save welcome state: {error}"));
-            }
+    /// Writes the current settings to `config.toml`.
+    pub(crate) fn save_config(&mut self) {
+        let Some(config_dir) = &self.config_dir else {
+            return;
+        };
+        if let Err(error) = self.config.save(config_dir) {
+            self.status_message = Some(format!("Unable to save settings: {error}"));
         }
     }
 }
@@ -0,0 +1,230 @@
+//! Restore card of the lock screen: opens a backup archive written by the
+//! Export section, shows what it holds, and creates the vault from it under
+//! a newly chosen master password.
+
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::secret::{self, SecretBuffer, Zeroize};
+use lilypad_core::text::capitalize;
+use lilypad_core::{archive, clock, Archive, ArchiveError, MasterKey, VaultError};
+use std::fs;
+use std::path::Path;
+
+/// The archive being restored and, once decrypted, its contents.
+pub(crate) struct RestoreForm {
+    open: bool,
+    path: String,
+    passphrase: SecretBuffer,
+    error: Option<String>,
+    archive: Option<Archive>,
+}
+
+impl Default for RestoreForm {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: String::new(),
+            passphrase: secret::secret_buffer(),
+            error: None,
+            archive: None,
+        }
+    }
+}
+
+impl RestoreForm {
+    pub(crate) fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub(crate) fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// A copy of the archive passphrase for the worker thread.
+    pub(crate) fn passphrase(&self) -> SecretBuffer {
+        secret::secret_buffer_from(&self.passphrase)
+    }
+
+    /// Closes the card and forgets the passphrase and the decrypted entries.
+    pub(crate) fn clear(&mut self) {
+        self.open = false;
+        self.passphrase.zeroize();
+        self.error = None;
+        self.archive = None;
+    }
+}
+
+impl LilypadApp {
+    pub(crate) fn render_restore_card(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        if self.restore_form.archive.is_some() {
+            self.render_restored_archive(ui);
+        } else {
+            let busy = self.key_task.is_some();
+            let form = &mut self.restore_form;
+            let mut open_requested = false;
+            ui.label(
+                RichText::new("Backup Archive")
+                    .size(16.0)
+                    .color(palette.text),
+            );
+            ui.add_space(6.0);
+            ui.add_enabled(
+                !busy,
+                egui::TextEdit::singleline(&mut form.path)
+                    .hint_text("/home/me/Backups/lilypad-backup.age"),
+            );
+            ui.add_space(8.0);
+            ui.label(
+                RichText::new("Archive Passphrase")
+                    .size(16.0)
+                    .color(palette.text),
+            );
+            ui.add_space(6.0);
+            let passphrase = ui.add_enabled(
+                !busy,
+                egui::TextEdit::singleline(&mut *form.passphrase)
+                    .password(true)
+                    .hint_text("The passphrase chosen when exporting"),
+            );
+            open_requested |=
+                passphrase.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            if let Some(error) = &form.error {
+                ui.add_space(8.0);
+                ui.colored_label(palette.danger, error);
+            }
+
+            ui.add_space(16.0);
+            if self.render_key_progress(ui) {
+                return;
+            }
+            let form = &self.restore_form;
+            let ready = !form.path.trim().is_empty() && !form.passphrase.is_empty();
+            let button = egui::Button::new(
+                RichText::new("Open Archive")
+                    .strong()
+                    .color(palette.on_accent),
+            )
+            .fill(palette.action_fill(ready))
+            .min_size(egui::vec2(240.0, 36.0))
+            .corner_radius(8.0);
+            open_requested |= ui.add_enabled(ready, button).clicked();
+            if open_requested && ready {
+                self.open_archive();
+            }
+        }
+
+        ui.add_space(8.0);
+        if self.key_task.is_none() && ui.link("Create an empty vault instead").clicked() {
+            self.restore_form.clear();
+            self.status_message = None;
+        }
+    }
+
+    /// What the opened archive holds, then the new master password.
+    fn render_restored_archive(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        if let Some(archive) = &self.restore_form.archive {
+            let metadata = &archive.metadata;
+            ui.label(
+                RichText::new(format!(
+                    "Backup of {}",
+                    clock::format_utc(metadata.archived_at)
+                ))
+                .strong()
+                .color(palette.text),
+            );
+            ui.label(
+                RichText::new(format!(
+                    "{} entries, {} attachments. Written by Lilypad {}.",
+                    archive.entries.len(),
+                    metadata.attachment_count,
+                    metadata.app_version
+                ))
+                .color(palette.text_muted),
+            );
+            ui.add_space(12.0);
+        }
+
+        let all_met = self.render_new_master_password(ui);
+        ui.add_space(16.0);
+        if self.render_key_progress(ui) {
+            return;
+        }
+        let button = egui::Button::new(
+            RichText::new("Restore Vault")
+                .strong()
+                .color(palette.on_accent),
+        )
+        .fill(palette.action_fill(all_met))
+        .min_size(egui::vec2(240.0, 36.0))
+        .corner_radius(8.0);
+        if ui.add_enabled(all_met, button).clicked() {
+            match self.vault_path.clone() {
+                Some(path) => {
+                    self.status_message = None;
+                    self.start_restore(path);
+                }
+                None => {
+                    self.status_message =
+                        Some("Unable to locate the Lilypad data folder".to_string());
+                }
+            }
+        }
+    }
+
+    /// Checks the chosen file before its passphrase is stretched on the
+    /// worker thread.
+    fn open_archive(&mut self) {
+        let form = &mut self.restore_form;
+        form.error = None;
+        let path = form.path.trim().to_string();
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(error) => {
+                form.error = Some(format!("Unable to read {path}: {error}"));
+                return;
+            }
+        };
+        match archive::check(&data) {
+            Ok(()) => self.start_archive_open(data),
+            Err(error) => form.error = Some(capitalize(&error.to_string())),
+        }
+    }
+
+    /// Keeps the decrypted archive for the second step, or says why it could
+    /// not be opened.
+    pub(crate) fn finish_archive_read(&mut self, result: Result<Archive, ArchiveError>) {
+        let form = &mut self.restore_form;
+        form.passphrase.zeroize();
+        match result {
+            Ok(archive) => form.archive = Some(archive),
+            Err(error) => form.error = Some(capitalize(&error.to_string())),
+        }
+    }
+
+    /// Writes the archive's entries into a new vault at `path` with the
+    /// derived key, then opens it.
+    pub(crate) fn finish_restore(&mut self, path: &Path, key: Result<MasterKey, VaultError>) {
+        let Some(archive) = self.restore_form.archive.take() else {
+            return;
+        };
+        let archived_at = archive.metadata.archived_at;
+        match key.and_then(|key| archive::restore(path, key, archive)) {
+            Ok(vault) => {
+                let count = vault.entries().len();
+                self.restore_form.clear();
+                self.finish_create(Ok(vault));
+                self.status_message = Some(format!(
+                    "Restored {count} entries from the backup of {}",
+                    clock::format_utc(archived_at)
+                ));
+            }
+            Err(error) => {
+                self.restore_form.clear();
+                self.status_message = Some(format!("Unable to restore vault: {error}"));
+            }
+        }
+    }
+}
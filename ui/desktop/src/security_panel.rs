@@ -0,0 +1,219 @@
+//! Security panel: auto-lock and clipboard protections, changing the master
+//! password, and vault backups.
+
+use crate::key_task::KeyTaskKind;
+use crate::strength_meter::strength_meter;
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::secret::{self, SecretBuffer, Zeroize};
+use lilypad_core::session::{timeout_label, LockReason, CLIPBOARD_TIMEOUTS, IDLE_TIMEOUTS};
+use lilypad_core::{strength, PasswordPolicy, Requirement};
+use std::time::Duration;
+
+/// The "Change master password" form.
+pub(crate) struct PasswordChange {
+    current: SecretBuffer,
+    new: SecretBuffer,
+    confirm: SecretBuffer,
+}
+
+impl Default for PasswordChange {
+    fn default() -> Self {
+        Self {
+            current: secret::secret_buffer(),
+            new: secret::secret_buffer(),
+            confirm: secret::secret_buffer(),
+        }
+    }
+}
+
+impl PasswordChange {
+    /// Empties the form, e.g. after a successful change or on lock.
+    pub(crate) fn clear(&mut self) {
+        self.current.zeroize();
+        self.new.zeroize();
+        self.confirm.zeroize();
+    }
+
+    /// Copies of the current and new passwords for the worker thread.
+    pub(crate) fn passwords(&self) -> (SecretBuffer, SecretBuffer) {
+        (
+            secret::secret_buffer_from(&self.current),
+            secret::secret_buffer_from(&self.new),
+        )
+    }
+
+    /// Empties only the current-password field after it was rejected.
+    pub(crate) fn reject_current(&mut self) {
+        self.current.zeroize();
+    }
+}
+
+impl LilypadApp {
+    pub(crate) fn render_security_panel(&mut self, ui: &mut egui::Ui) {
+        self.render_lock_settings(ui);
+        ui.add_space(8.0);
+        if ui.button("🔒 Lock now").clicked() {
+            self.lock_vault(LockReason::Manual);
+            return;
+        }
+
+        ui.add_space(12.0);
+        self.render_password_change(ui);
+
+        ui.add_space(12.0);
+        self.render_backup_settings(ui);
+    }
+
+    /// Auto-lock and clipboard controls, shared with the Settings view.
+    /// Changes apply immediately and are saved to the config.
+    pub(crate) fn render_lock_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.label(RichText::new("Session").strong());
+        ui.horizontal(|ui| {
+            ui.label("Lock after inactivity");
+            let current = self.auto_lock.idle_timeout.map(|timeout| timeout.as_secs());
+            egui::ComboBox::from_id_salt("idle_timeout")
+                .selected_text(timeout_label(&IDLE_TIMEOUTS, current))
+                .show_ui(ui, |ui| {
+                    for (seconds, label) in IDLE_TIMEOUTS {
+                        if ui.selectable_label(current == seconds, label).clicked() {
+                            self.auto_lock.idle_timeout = seconds.map(Duration::from_secs);
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        changed |= ui
+            .checkbox(
+                &mut self.auto_lock.lock_on_minimize,
+                "Lock when the window is minimized",
+            )
+            .changed();
+        changed |= ui
+            .checkbox(
+                &mut self.auto_lock.lock_on_focus_loss,
+                "Lock when the window loses focus",
+            )
+            .changed();
+        ui.label(
+            RichText::new("The vault also locks when the computer resumes from sleep.")
+                .small()
+                .weak(),
+        );
+
+        ui.add_space(12.0);
+        ui.label(RichText::new("Clipboard").strong());
+        ui.horizontal(|ui| {
+            ui.label("Clear copied secrets after");
+            let current = self
+                .clipboard_clear
+                .clear_after()
+                .map(|after| after.as_secs());
+            egui::ComboBox::from_id_salt("clipboard_timeout")
+                .selected_text(timeout_label(&CLIPBOARD_TIMEOUTS, current))
+                .show_ui(ui, |ui| {
+                    for (seconds, label) in CLIPBOARD_TIMEOUTS {
+                        if ui.selectable_label(current == seconds, label).clicked() {
+                            self.clipboard_clear
+                                .set_clear_after(seconds.map(Duration::from_secs));
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        ui.label(
+            RichText::new(
+                "Lilypad only clears the clipboard if it still holds the secret it copied.",
+            )
+            .small()
+            .weak(),
+        );
+
+        if changed {
+            self.store_security_settings();
+        }
+    }
+
+    /// Copies the live auto-lock and clipboard settings into the config and
+    /// saves it, so they survive a restart.
+    fn store_security_settings(&mut self) {
+        let security = &mut self.config.security;
+        security.set_idle_timeout(self.auto_lock.idle_timeout);
+        security.lock_on_minimize = self.auto_lock.lock_on_minimize;
+        security.lock_on_focus_loss = self.auto_lock.lock_on_focus_loss;
+        security.set_clipboard_clear_after(self.clipboard_clear.clear_after());
+        self.save_config();
+    }
+
+    fn render_password_change(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Master password").strong());
+        let busy = self.key_task.is_some();
+        let form = &mut self.password_change;
+        egui::Grid::new("password_change")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                for (label, value) in [
+                    ("Current password", &mut form.current),
+                    ("New password", &mut form.new),
+                    ("Confirm new password", &mut form.confirm),
+                ] {
+                    ui.label(label);
+                    ui.add_enabled(
+                        !busy,
+                        egui::TextEdit::singleline(&mut **value).password(true),
+                    );
+                    ui.end_row();
+                }
+            });
+
+        if !form.new.is_empty() {
+            strength_meter(ui, &self.palette, &strength::estimate(&form.new, &[]));
+        }
+        let mut requirements = PasswordPolicy::default().requirements(&form.new);
+        requirements.push(Requirement {
+            label: "Both new entries match".to_string(),
+            satisfied: !form.confirm.is_empty() && form.new == form.confirm,
+        });
+        let ready = !form.current.is_empty()
+            && requirements.iter().all(|requirement| requirement.satisfied);
+        if !form.new.is_empty() {
+            for Requirement { label, satisfied } in requirements {
+                let color = self.palette.check_color(satisfied);
+                ui.horizontal(|ui| {
+                    ui.colored_label(color, if satisfied { "✔" } else { "○" });
+                    ui.label(label);
+                });
+            }
+        }
+
+        let changing = self
+            .key_task
+            .as_ref()
+            .is_some_and(|task| task.kind() == KeyTaskKind::ChangePassword);
+        if changing {
+            ui.horizontal(|ui| {
+                ui.add(egui::Spinner::new());
+                ui.label(KeyTaskKind::ChangePassword.label());
+                if ui.button("Cancel").clicked() {
+                    self.cancel_key_task();
+                }
+            });
+        } else if ui
+            .add_enabled(ready && !busy, egui::Button::new("Change master password"))
+            .clicked()
+        {
+            self.start_password_change();
+        }
+        ui.label(
+            RichText::new(
+                "The vault is re-encrypted with a key derived from the new password. \
+                 There is no way to recover it if forgotten.",
+            )
+            .small()
+            .weak(),
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Settings view opened from the header: security, generator defaults,
+//! appearance, keyboard shortcuts, vault location, import, and export. Every
+//! preference control edits `self.config`, is applied to the running app
+//! straight away, and is saved to `config.toml`.
+
+use crate::LilypadApp;
+use eframe::egui;
+use egui::RichText;
+use lilypad_core::config::{
+    KdfConfig, FONT_SCALE_RANGE, KDF_ITERATIONS_RANGE, KDF_MEMORY_MIB_RANGE, KDF_PARALLELISM_RANGE,
+    LENGTH_RANGE, WORD_COUNT_RANGE,
+};
+use lilypad_core::theme;
+use lilypad_core::{Action, GeneratorMode, Keymap, Shortcut};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Edits that are only applied on request.
+#[derive(Default)]
+pub(crate) struct SettingsForm {
+    /// Vault path being typed; `None` until the Settings view is first shown.
+    vault_location: Option<String>,
+    /// Shortcut text per `Action::ALL` entry; `None` until first shown.
+    shortcuts: Option<Vec<String>>,
+    shortcut_error: Option<String>,
+}
+
+impl LilypadApp {
+    pub(crate) fn render_settings_panel(&mut self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.heading("Security");
+            self.render_lock_settings(ui);
+            ui.add_space(8.0);
+            self.render_kdf_settings(ui);
+
+            ui.add_space(16.0);
+            ui.heading("Generator defaults");
+            self.render_generator_defaults(ui);
+
+            ui.add_space(16.0);
+            ui.heading("Appearance");
+            self.render_appearance_settings(ui);
+
+            ui.add_space(16.0);
+            ui.heading("Keyboard shortcuts");
+            self.render_shortcut_settings(ui);
+
+            ui.add_space(16.0);
+            ui.heading("Vault location");
+            self.render_vault_location(ui);
+
+            ui.add_space(16.0);
+            ui.heading("Import");
+            self.render_import_settings(ui);
+
+            ui.add_space(16.0);
+            ui.heading("Export");
+            self.render_export_settings(ui);
+        });
+    }
+
+    fn render_kdf_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Key derivation (Argon2id)").strong());
+        let kdf = &mut self.config.security.kdf;
+        let mut changed = false;
+        egui::Grid::new("kdf_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Memory");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut kdf.memory_mib)
+                            .range(KDF_MEMORY_MIB_RANGE.0..=KDF_MEMORY_MIB_RANGE.1)
+                            .suffix(" MiB"),
+                    )
+                    .changed();
+                ui.end_row();
+                ui.label("Iterations");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut kdf.iterations)
+                            .range(KDF_ITERATIONS_RANGE.0..=KDF_ITERATIONS_RANGE.1),
+                    )
+                    .changed();
+                ui.end_row();
+                ui.label("Parallelism");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut kdf.parallelism)
+                            .range(KDF_PARALLELISM_RANGE.0..=KDF_PARALLELISM_RANGE.1),
+                    )
+                    .changed();
+                ui.end_row();
+            });
+        if *kdf != KdfConfig::default() && ui.small_button("Reset to recommended").clicked() {
+            *kdf = KdfConfig::default();
+            changed = true;
+        }
+        if let Some(vault) = &self.vault {
+            let current = KdfConfig::from_params(vault.kdf_params());
+            ui.label(
+                RichText::new(format!(
+                    "This vault uses {} MiB, {} iterations, {} lanes. New settings apply the next \
+                     time you change the master password.",
+                    current.memory_mib, current.iterations, current.parallelism
+                ))
+                .small()
+                .weak(),
+            );
+        }
+        if changed {
+            self.save_config();
+        }
+    }
+
+    fn render_generator_defaults(&mut self, ui: &mut egui::Ui) {
+        let defaults = &mut self.config.generator;
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .radio_value(
+                    &mut defaults.mode,
+                    GeneratorMode::Characters,
+                    "Random characters",
+                )
+                .changed();
+            changed |= ui
+                .radio_value(&mut defaults.mode, GeneratorMode::Passphrase, "Passphrase")
+                .changed();
+        });
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut defaults.length, LENGTH_RANGE.0..=LENGTH_RANGE.1)
+                    .text("Password length"),
+            )
+            .changed();
+        ui.horizontal_wrapped(|ui| {
+            // Keep at least one class on, as the config requires.
+            let enabled = [
+                defaults.lowercase,
+                defaults.uppercase,
+                defaults.digits,
+                defaults.symbols,
+            ]
+            .into_iter()
+            .filter(|&on| on)
+            .count();
+            for (value, label) in [
+                (&mut defaults.lowercase, "a-z"),
+                (&mut defaults.uppercase, "A-Z"),
+                (&mut defaults.digits, "0-9"),
+                (&mut defaults.symbols, "!@#"),
+            ] {
+                let locked = *value && enabled == 1;
+                changed |= ui
+                    .add_enabled(!locked, egui::Checkbox::new(value, label))
+                    .changed();
+            }
+        });
+        changed |= ui
+            .checkbox(
+                &mut defaults.exclude_ambiguous,
+                "Exclude look-alike characters",
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(
+                    &mut defaults.word_count,
+                    WORD_COUNT_RANGE.0..=WORD_COUNT_RANGE.1,
+                )
+                .text("Passphrase words"),
+            )
+            .changed();
+        ui.horizontal(|ui| {
+            ui.label("Separator");
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut defaults.separator).desired_width(40.0))
+                .changed();
+            changed |= ui
+                .checkbox(&mut defaults.capitalize, "Capitalize")
+                .changed();
+            changed |= ui
+                .checkbox(&mut defaults.append_digit, "Append a digit")
+                .changed();
+        });
+        if changed {
+            defaults.apply_to(&mut self.generator);
+            self.save_config();
+        }
+    }
+
+    fn render_appearance_settings(&mut self, ui: &mut egui::Ui) {
+        let choices = self.theme_choices();
+        let appearance = &mut self.config.appearance;
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Theme");
+            let selected = choices
+                .iter()
+                .find(|(name, _)| *name == appearance.theme)
+                .map_or(appearance.theme.clone(), |(_, label)| label.clone());
+            egui::ComboBox::from_id_salt("theme")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for (name, label) in choices {
+                        if ui
+                            .selectable_label(appearance.theme == name, label)
+                            .clicked()
+                        {
+                            appearance.theme = name;
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        let scale = ui.add(
+            egui::Slider::new(
+                &mut appearance.font_scale,
+                FONT_SCALE_RANGE.0..=FONT_SCALE_RANGE.1,
+            )
+            .step_by(0.05)
+            .text("Font scale"),
+        );
+        // Rescaling while dragging would move the slider under the pointer.
+        changed |= scale.drag_stopped() || (scale.changed() && !scale.dragged());
+        ui.label(
+            RichText::new(format!(
+                "Add your own themes as TOML files in the \"{}\" folder next to config.toml.",
+                theme::THEMES_DIR
+            ))
+            .small()
+            .weak(),
+        );
+        if changed {
+            self.apply_appearance(ui.ctx());
+            self.save_config();
+        }
+    }
+
+    fn render_shortcut_settings(&mut self, ui: &mut egui::Ui) {
+        let keymap = &self.keymap;
+        let form = &mut self.settings_form;
+        let shortcuts = form.shortcuts.get_or_insert_with(|| shortcut_texts(keymap));
+        egui::Grid::new("shortcut_settings")
+            .num_columns(2)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for (action, text) in Action::ALL.iter().zip(shortcuts.iter_mut()) {
+                    ui.label(action.label());
+                    ui.add(
+                        egui::TextEdit::singleline(text)
+                            .hint_text("Unbound")
+                            .desired_width(140.0),
+                    );
+                    ui.end_row();
+                }
+            });
+        if let Some(error) = &form.shortcut_error {
+            ui.colored_label(self.palette.danger, error);
+        }
+
+        let mut apply = None;
+        ui.horizontal(|ui| {
+            if ui.button("Apply shortcuts").clicked() {
+                apply = Some(overrides_from(shortcuts));
+            }
+            if !self.config.keymap.is_empty() && ui.button("Restore defaults").clicked() {
+                apply = Some(BTreeMap::new());
+            }
+        });
+        ui.label(
+            RichText::new(
+                "Write shortcuts like Ctrl+Shift+K (Ctrl is Command on macOS); leave a field \
+                 empty to unbind it.",
+            )
+            .small()
+            .weak(),
+        );
+
+        if let Some(overrides) = apply {
+            self.set_keymap(overrides);
+        }
+    }
+
+    /// Validates and applies `[keymap]` overrides, keeping the old keymap on
+    /// error.
+    fn set_keymap(&mut self, overrides: BTreeMap<String, String>) {
+        match Keymap::from_overrides(&overrides) {
+            Ok(keymap) => {
+                self.settings_form.shortcuts = Some(shortcut_texts(&keymap));
+                self.settings_form.shortcut_error = None;
+                self.keymap = keymap;
+                self.config.keymap = overrides;
+                self.save_config();
+            }
+            Err(error) => self.settings_form.shortcut_error = Some(error.to_string()),
+        }
+    }
+
+    fn render_vault_location(&mut self, ui: &mut egui::Ui) {
+        let current = self
+            .vault
+            .as_ref()
+            .map(|vault| vault.path().to_path_buf())
+            .or_else(|| self.vault_path.clone());
+        let location = self.settings_form.vault_location.get_or_insert_with(|| {
+            current
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        });
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(location).desired_width(360.0));
+        });
+        let requested = PathBuf::from(location.trim());
+        let unchanged = current.as_ref() == Some(&requested);
+        let valid = requested.is_absolute();
+
+        let mut target = None;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(valid && !unchanged, egui::Button::new("Move vault here"))
+                .clicked()
+            {
+                target = Some(Some(requested.clone()));
+            }
+            if self.config.vault.path.is_some() && ui.button("Use default location").clicked() {
+                target = Some(None);
+            }
+        });
+        if !valid {
+            ui.label(
+                RichText::new("Enter an absolute path to a file.")
+                    .small()
+                    .weak(),
+            );
+        }
+        ui.label(
+            RichText::new(
+                "The vault file is moved to the new location; an existing file there is never \
+                 overwritten.",
+            )
+            .small()
+            .weak(),
+        );
+
+        if let Some(path) = target {
+            self.set_vault_location(path);
+        }
+    }
+
+    /// Moves the open vault to `path` (`None` for the platform default) and
+    /// remembers the choice.
+    fn set_vault_location(&mut self, path: Option<PathBuf>) {
+        let Some(destination) = path.clone().or_else(|| self.default_vault_path.clone()) else {
+            return;
+        };
+        if let Some(vault) = self.vault.as_mut() {
+            if vault.path() != destination {
+                if let Err(error) = vault.relocate(&destination) {
+                    self.status_message = Some(format!("Unable to move the vault: {error}"));
+                    return;
+                }
+            }
+        }
+        self.config.vault.path = path;
+        self.vault_path = Some(destination.clone());
+        self.settings_form.vault_location = Some(destination.display().to_string());
+        self.status_message = Some(format!("Vault stored at {}", destination.display()));
+        self.save_config();
+    }
+}
+
+fn shortcut_texts(keymap: &Keymap) -> Vec<String> {
+    Action::ALL
+        .iter()
+        .map(|action| {
+            keymap
+                .shortcut(*action)
+                .map(|shortcut| shortcut.to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Overrides for the shortcuts that differ from the defaults, so later
+/// default changes still reach users who never touched them.
+fn overrides_from(shortcuts: &[String]) -> BTreeMap<String, String> {
+    Action::ALL
+        .iter()
+        .zip(shortcuts)
+        .filter_map(|(action, text)| {
+            let text = text.trim();
+            // Store valid shortcuts in canonical spelling; leave the rest for
+            // `Keymap::from_overrides` to report.
+            let text = match text.parse::<Shortcut>() {
+                Ok(shortcut) if shortcut == action.default_shortcut() => return None,
+                Ok(shortcut) => shortcut.to_string(),
+                Err(_) => text.to_string(),
+            };
+            Some((action.name().to_string(), text))
+        })
+        .collect()
+}
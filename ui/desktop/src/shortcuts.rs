@@ -0,0 +1,114 @@
+//! Global keyboard shortcuts. The configured `Keymap` is translated into egui
+//! shortcuts each frame, and `run_action` carries out an `Action` whether it
+//! came from a shortcut or from the command palette.
+
+use crate::{entry_list, LilypadApp, SETTINGS_CATEGORY};
+use eframe::egui;
+use egui::{KeyboardShortcut, Modifiers};
+use lilypad_core::secret;
+use lilypad_core::session::LockReason;
+use lilypad_core::{Action, Shortcut};
+
+/// `selected_category` of the Generator panel.
+const GENERATOR_CATEGORY: usize = 1;
+
+impl LilypadApp {
+    /// Runs the action whose shortcut was pressed this frame, if any.
+    pub(crate) fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        let pressed = ctx.input_mut(|input| {
+            self.keymap
+                .bindings()
+                .iter()
+                .find(|(_, shortcut)| {
+                    keyboard_shortcut(*shortcut)
+                        .is_some_and(|shortcut| input.consume_shortcut(&shortcut))
+                })
+                .map(|(action, _)| *action)
+        });
+        if let Some(action) = pressed {
+            self.run_action(ctx, action);
+        }
+    }
+
+    pub(crate) fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        if action != Action::CommandPalette {
+            self.command_palette = None;
+        }
+        match action {
+            Action::CommandPalette => self.toggle_command_palette(),
+            Action::FocusSearch => {
+                self.selected_category = 0;
+                ctx.memory_mut(|memory| {
+                    memory.request_focus(egui::Id::new(entry_list::SEARCH_BOX_ID));
+                });
+            }
+            Action::NewEntry => self.open_new_entry_editor(),
+            Action::CopyUsername => self.copy_selected(ctx, false),
+            Action::CopyPassword => self.copy_selected(ctx, true),
+            Action::GeneratePassword => {
+                self.selected_category = GENERATOR_CATEGORY;
+                self.generate_password(ctx);
+            }
+            Action::Lock => self.lock_vault(LockReason::Manual),
+            Action::ShowVault => self.selected_category = 0,
+            Action::ShowGenerator => self.selected_category = GENERATOR_CATEGORY,
+            Action::ShowAlerts => self.selected_category = 2,
+            Action::ShowAccount => self.selected_category = 3,
+            Action::ShowSecurity => self.selected_category = 4,
+            Action::OpenSettings => self.selected_category = SETTINGS_CATEGORY,
+        }
+    }
+
+    /// The bound shortcut for hover text and the palette, if any.
+    pub(crate) fn shortcut_label(&self, action: Action) -> Option<String> {
+        self.keymap
+            .shortcut(action)
+            .map(|shortcut| shortcut.to_string())
+    }
+
+    /// Copies the username or main secret of the entry selected in the Vault
+    /// list.
+    fn copy_selected(&mut self, ctx: &egui::Context, password: bool) {
+        self.refresh_search();
+        let entry = self
+            .search_results
+            .get(self.selected_result)
+            .and_then(|hit| self.vault.as_ref()?.entries().get(hit.index));
+        let Some(entry) = entry else {
+            self.status_message = Some("Select an entry first".to_string());
+            return;
+        };
+
+        let (value, what) = if password {
+            (entry.primary_secret(), "Password")
+        } else {
+            (entry.username(), "Username")
+        };
+        match value.map(secret::secret_buffer_from) {
+            Some(value) => self.copy_secret(ctx, &value, what),
+            None => {
+                self.status_message = Some(format!(
+                    "\"{}\" has no {} to copy",
+                    entry.title,
+                    what.to_lowercase()
+                ));
+            }
+        }
+    }
+}
+
+/// `Ctrl` maps to Command on macOS, as the keymap documents.
+fn keyboard_shortcut(shortcut: Shortcut) -> Option<KeyboardShortcut> {
+    let key = egui::Key::from_name(shortcut.key)?;
+    let mut modifiers = Modifiers::NONE;
+    if shortcut.ctrl {
+        modifiers |= Modifiers::COMMAND;
+    }
+    if shortcut.alt {
+        modifiers |= Modifiers::ALT;
+    }
+    if shortcut.shift {
+        modifiers |= Modifiers::SHIFT;
+    }
+    Some(KeyboardShortcut::new(modifiers, key))
+}
@@ -0,0 +1,47 @@
+//! Strength readout shared by the Generator panel and the create-vault form.
+
+use crate::theme::Palette;
+use eframe::egui;
+use egui::{Color32, RichText};
+use lilypad_core::{Strength, StrengthEstimate};
+
+pub(crate) fn strength_color(palette: &Palette, strength: Strength) -> Color32 {
+    match strength {
+        Strength::Weak => palette.danger,
+        Strength::Moderate => palette.warning,
+        Strength::Strong | Strength::VeryStrong => palette.success,
+    }
+}
+
+/// Draws a four-step bar, the rating, the offline crack time, and the
+/// estimator's warning and first suggestion.
+pub(crate) fn strength_meter(ui: &mut egui::Ui, palette: &Palette, estimate: &StrengthEstimate) {
+    let color = strength_color(palette, estimate.strength);
+    ui.horizontal(|ui| {
+        let filled = estimate.strength as usize + 1;
+        for step in 0..4 {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(36.0, 6.0), egui::Sense::hover());
+            let fill = if step < filled {
+                color
+            } else {
+                ui.visuals().widgets.inactive.bg_fill
+            };
+            ui.painter().rect_filled(rect, 3.0, fill);
+        }
+        ui.colored_label(color, RichText::new(estimate.strength.label()).strong());
+    });
+    ui.label(
+        RichText::new(format!(
+            "About {:.0} bits · {} to crack offline",
+            estimate.entropy_bits(),
+            estimate.crack_time
+        ))
+        .small(),
+    );
+    if let Some(warning) = &estimate.warning {
+        ui.label(RichText::new(warning).small().color(color));
+    }
+    if let Some(suggestion) = estimate.suggestions.first() {
+        ui.label(RichText::new(suggestion).small().weak());
+    }
+}
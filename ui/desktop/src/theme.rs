@@ -0,0 +1,188 @@
+//! Maps the core `Theme` onto egui: the egui `Visuals` used by stock
+//! widgets, and a `Palette` of `Color32` tokens for the app's own drawing.
+//!
+//! Render code takes colors from `self.palette` only. Follow-system mode
+//! registers the dark and light themes with egui and swaps the palette when
+//! the OS switches.
+
+use crate::LilypadApp;
+use eframe::egui;
+use egui::{Color32, Stroke, ThemePreference, Visuals};
+use lilypad_core::theme::{self, Color, Theme};
+
+/// The semantic tokens of the active theme, converted for egui.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Palette {
+    pub(crate) background: Color32,
+    pub(crate) surface: Color32,
+    pub(crate) border: Color32,
+    pub(crate) text: Color32,
+    pub(crate) text_muted: Color32,
+    pub(crate) accent: Color32,
+    pub(crate) on_accent: Color32,
+    pub(crate) disabled: Color32,
+    pub(crate) success: Color32,
+    pub(crate) warning: Color32,
+    pub(crate) danger: Color32,
+    pub(crate) highlight: Color32,
+}
+
+impl Palette {
+    pub(crate) fn of(theme: &Theme) -> Self {
+        let colors = &theme.colors;
+        Self {
+            background: color32(colors.background),
+            surface: color32(colors.surface),
+            border: color32(colors.border),
+            text: color32(colors.text),
+            text_muted: color32(colors.text_muted),
+            accent: color32(colors.accent),
+            on_accent: color32(colors.on_accent),
+            disabled: color32(colors.disabled),
+            success: color32(colors.success),
+            warning: color32(colors.warning),
+            danger: color32(colors.danger),
+            highlight: color32(colors.highlight),
+        }
+    }
+
+    /// Fill for a primary button that may be disabled.
+    pub(crate) fn action_fill(&self, enabled: bool) -> Color32 {
+        if enabled {
+            self.accent
+        } else {
+            self.disabled
+        }
+    }
+
+    /// Color of a requirement check mark.
+    pub(crate) fn check_color(&self, satisfied: bool) -> Color32 {
+        if satisfied {
+            self.success
+        } else {
+            self.danger
+        }
+    }
+}
+
+fn color32(color: Color) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+}
+
+/// egui styling for stock widgets under `theme`.
+fn visuals(theme: &Theme) -> Visuals {
+    let palette = Palette::of(theme);
+    let mut visuals = if theme.dark {
+        Visuals::dark()
+    } else {
+        Visuals::light()
+    };
+    visuals.panel_fill = palette.background;
+    visuals.window_fill = palette.surface;
+    visuals.window_stroke = Stroke::new(1.0, palette.border);
+    visuals.faint_bg_color = palette.surface;
+    visuals.hyperlink_color = palette.accent;
+    visuals.warn_fg_color = palette.warning;
+    visuals.error_fg_color = palette.danger;
+    visuals.selection.bg_fill = palette.accent.gamma_multiply(0.5);
+    visuals.selection.stroke = Stroke::new(1.0, palette.text);
+    visuals.widgets.noninteractive.fg_stroke.color = palette.text;
+    visuals.widgets.noninteractive.bg_stroke.color = palette.border;
+    visuals.widgets.inactive.fg_stroke.color = palette.text;
+    visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, palette.border);
+    visuals
+}
+
+fn egui_theme(theme: &Theme) -> egui::Theme {
+    if theme.dark {
+        egui::Theme::Dark
+    } else {
+        egui::Theme::Light
+    }
+}
+
+impl LilypadApp {
+    /// Reads user themes from the `themes` folder next to `config.toml`.
+    pub(crate) fn load_user_themes(&mut self) {
+        let Some(config_dir) = &self.config_dir else {
+            return;
+        };
+        let (themes, errors) = Theme::load_dir(&config_dir.join(theme::THEMES_DIR));
+        self.user_themes = themes;
+        if let Some(error) = errors.first() {
+            self.status_message = Some(format!("Unable to load theme {error}"));
+        }
+    }
+
+    /// Names and labels for the theme picker: built-ins, follow-system, and
+    /// user themes.
+    pub(crate) fn theme_choices(&self) -> Vec<(String, String)> {
+        let builtin = [
+            (theme::DARK, "Dark"),
+            (theme::LIGHT, "Light"),
+            (theme::HIGH_CONTRAST, "High contrast"),
+            (theme::FOLLOW_SYSTEM, "Follow system"),
+        ]
+        .map(|(name, label)| (name.to_string(), label.to_string()));
+        builtin
+            .into_iter()
+            .chain(
+                self.user_themes
+                    .iter()
+                    .map(|theme| (theme.name.clone(), theme.name.clone())),
+            )
+            .collect()
+    }
+
+    /// Applies the theme and font scale from the config to `ctx`.
+    pub(crate) fn apply_appearance(&mut self, ctx: &egui::Context) {
+        let name = self.config.appearance.theme.clone();
+        if name == theme::FOLLOW_SYSTEM {
+            ctx.set_visuals_of(egui::Theme::Dark, visuals(&Theme::dark()));
+            ctx.set_visuals_of(egui::Theme::Light, visuals(&Theme::light()));
+            ctx.set_theme(ThemePreference::System);
+            self.set_active_theme(system_theme(ctx));
+        } else {
+            let theme = Theme::builtin(&name)
+                .or_else(|| {
+                    self.user_themes
+                        .iter()
+                        .find(|theme| theme.name == name)
+                        .cloned()
+                })
+                .unwrap_or_else(|| {
+                    self.status_message =
+                        Some(format!("Theme \"{name}\" not found; using the dark theme"));
+                    Theme::dark()
+                });
+            ctx.set_visuals_of(egui_theme(&theme), visuals(&theme));
+            ctx.set_theme(egui_theme(&theme));
+            self.set_active_theme(theme);
+        }
+        ctx.set_zoom_factor(self.config.appearance.font_scale);
+    }
+
+    /// In follow-system mode, switches the palette when the OS theme changes.
+    pub(crate) fn follow_system_theme(&mut self, ctx: &egui::Context) {
+        if self.config.appearance.theme != theme::FOLLOW_SYSTEM {
+            return;
+        }
+        let wanted = system_theme(ctx);
+        if wanted.dark != self.theme.dark {
+            self.set_active_theme(wanted);
+        }
+    }
+
+    fn set_active_theme(&mut self, theme: Theme) {
+        self.palette = Palette::of(&theme);
+        self.theme = theme;
+    }
+}
+
+/// The built-in theme matching the OS preference, dark when unknown.
+fn system_theme(ctx: &egui::Context) -> Theme {
+    match ctx.system_theme() {
+        Some(egui::Theme::Light) => Theme::light(),
+        _ => Theme::dark(),
+    }
+}
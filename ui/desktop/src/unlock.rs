@@ -0,0 +1,314 @@
+//! Lock screen: the create-vault wizard shown on first run and the unlock
+//! prompt shown once a vault file exists. The restore-from-archive card that
+//! can replace the wizard lives in `restore.rs`.
+
+use crate::strength_meter::strength_meter;
+use crate::LilypadApp;
+use eframe::egui;
+use egui::{CornerRadius, RichText};
+use lilypad_core::clock;
+use lilypad_core::config::UnlockState;
+use lilypad_core::secret::Zeroize;
+use lilypad_core::session::unlock_wait;
+use lilypad_core::{strength, PasswordPolicy, Requirement, Vault, VaultError};
+use std::time::{Duration, Instant};
+
+impl LilypadApp {
+    /// Picks up the back-off left from earlier failed unlocks, including
+    /// ones from before a restart.
+    pub(crate) fn resume_unlock_backoff(&mut self) {
+        let unlock = &self.config.unlock;
+        self.unlock_retry_at =
+            unlock_wait(unlock.failed_attempts, unlock.last_failure_at, clock::now())
+                .map(|wait| Instant::now() + wait);
+    }
+
+    pub(crate) fn render_unlock_screen(&mut self, ctx: &egui::Context) {
+        let vault_exists = self.vault_path.as_ref().is_some_and(|path| path.exists());
+        let restoring = !vault_exists && self.restore_form.is_open();
+        let (title, subtitle) = if vault_exists {
+            (
+                "Unlock Lilypad Vault",
+                "Enter your master password to access your vault.",
+            )
+        } else if restoring {
+            (
+                "Restore your Lilypad Vault",
+                "Open a backup archive, then choose the master password for the restored vault.",
+            )
+        } else {
+            (
+                "Create your Lilypad Vault",
+                "Choose a strong master password. It encrypts your vault and cannot be recovered if lost.",
+            )
+        };
+
+        let palette = self.palette;
+        egui::CentralPanel::default()
+            .frame(egui::Frame::new().fill(palette.background))
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(40.0);
+                    let card_frame = egui::Frame::new()
+                        .fill(palette.surface)
+                        .stroke(egui::Stroke::new(1.0, palette.border))
+                        .corner_radius(CornerRadius::from(12.0))
+                        .inner_margin(egui::Margin::same(24));
+
+                    ui.allocate_ui_with_layout(
+                        ui.available_size(),
+                        egui::Layout::top_down(egui::Align::Center),
+                        |ui| {
+                            ui.add_space(32.0);
+                            ui.label(RichText::new(title).size(28.0).strong().color(palette.text));
+                            ui.add_space(8.0);
+                            ui.label(RichText::new(subtitle).color(palette.text_muted));
+                            ui.add_space(24.0);
+
+                            card_frame.show(ui, |ui| {
+                                ui.vertical(|ui| {
+                                    if vault_exists {
+                                        self.render_unlock_card(ui);
+                                    } else if restoring {
+                                        self.render_restore_card(ui);
+                                    } else {
+                                        self.render_create_card(ui);
+                                    }
+
+                                    if let Some(message) = &self.status_message {
+                                        ui.add_space(8.0);
+                                        ui.colored_label(palette.danger, message);
+                                    }
+                                });
+                            });
+                            ui.add_space(40.0);
+                        },
+                    );
+                });
+            });
+    }
+
+    fn render_unlock_card(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        ui.label(
+            RichText::new("Master Password")
+                .size(16.0)
+                .color(palette.text),
+        );
+        ui.add_space(6.0);
+        let busy = self.key_task.is_some();
+        let response = ui.add_enabled(
+            !busy,
+            egui::TextEdit::singleline(&mut *self.master_password)
+                .password(true)
+                .hint_text("Enter your master password"),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+        let remaining_backoff = self
+            .unlock_retry_at
+            .map(|retry_at| retry_at.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero());
+        let can_submit = remaining_backoff.is_none() && !self.master_password.is_empty();
+
+        ui.add_space(16.0);
+        if self.render_key_progress(ui) {
+            return;
+        }
+        let button = egui::Button::new(
+            RichText::new("Unlock Vault")
+                .strong()
+                .color(palette.on_accent),
+        )
+        .fill(palette.action_fill(can_submit))
+        .min_size(egui::vec2(240.0, 36.0))
+        .corner_radius(8.0);
+
+        if (ui.add_enabled(can_submit, button).clicked() || submitted) && can_submit {
+            self.unlock_vault();
+        }
+
+        if let Some(remaining) = remaining_backoff {
+            ui.add_space(8.0);
+            ui.label(
+                RichText::new(format!(
+                    "Too many failed attempts. Try again in {} s.",
+                    remaining.as_secs() + 1
+                ))
+                .color(palette.warning),
+            );
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
+    }
+
+    fn render_create_card(&mut self, ui: &mut egui::Ui) {
+        let palette = self.palette;
+        let all_met = self.render_new_master_password(ui);
+
+        ui.add_space(16.0);
+        if self.render_key_progress(ui) {
+            return;
+        }
+        let button = egui::Button::new(
+            RichText::new("Create Vault")
+                .strong()
+                .color(palette.on_accent),
+        )
+        .fill(palette.action_fill(all_met))
+        .min_size(egui::vec2(240.0, 36.0))
+        .corner_radius(8.0);
+
+        if ui.add_enabled(all_met, button).clicked() {
+            self.create_vault();
+        }
+
+        ui.add_space(8.0);
+        ui.label(
+            RichText::new("Use a password manager-friendly secret to keep your vault secure.")
+                .color(palette.text_muted)
+                .italics(),
+        );
+        ui.add_space(8.0);
+        if ui.link("Restore from a backup archive instead").clicked() {
+            self.restore_form.open();
+            self.status_message = None;
+        }
+    }
+
+    /// The master password and confirmation fields of a new vault with the
+    /// policy checklist. Returns whether every requirement is met.
+    pub(crate) fn render_new_master_password(&mut self, ui: &mut egui::Ui) -> bool {
+        let palette = self.palette;
+        ui.label(
+            RichText::new("Master Password")
+                .size(16.0)
+                .color(palette.text),
+        );
+        ui.add_space(6.0);
+        let busy = self.key_task.is_some();
+        ui.add_enabled(
+            !busy,
+            egui::TextEdit::singleline(&mut *self.master_password)
+                .password(true)
+                .hint_text("Choose a master password"),
+        );
+        if !self.master_password.is_empty() {
+            ui.add_space(4.0);
+            strength_meter(
+                ui,
+                &palette,
+                &strength::estimate(&self.master_password, &[]),
+            );
+        }
+        ui.add_space(8.0);
+        ui.label(
+            RichText::new("Confirm Master Password")
+                .size(16.0)
+                .color(palette.text),
+        );
+        ui.add_space(6.0);
+        ui.add_enabled(
+            !busy,
+            egui::TextEdit::singleline(&mut *self.confirm_password)
+                .password(true)
+                .hint_text("Re-enter the master password"),
+        );
+        ui.add_space(12.0);
+
+        let passwords_match =
+            !self.confirm_password.is_empty() && self.master_password == self.confirm_password;
+        let mut requirements = PasswordPolicy::default().requirements(&self.master_password);
+        requirements.push(Requirement {
+            label: "Both entries match".to_string(),
+            satisfied: passwords_match,
+        });
+        let all_met = requirements.iter().all(|requirement| requirement.satisfied);
+
+        ui.label(RichText::new("Password requirements").strong());
+        ui.add_space(4.0);
+        for Requirement { label, satisfied } in requirements {
+            let color = palette.check_color(satisfied);
+            ui.horizontal(|ui| {
+                ui.colored_label(color, if satisfied { "✔" } else { "○" });
+                ui.label(RichText::new(label).color(palette.text));
+            });
+        }
+        all_met
+    }
+
+    fn unlock_vault(&mut self) {
+        let Some(path) = self.vault_path.clone() else {
+            self.status_message = Some("Unable to locate the Lilypad data folder".to_string());
+            return;
+        };
+        self.status_message = None;
+        self.start_unlock(path);
+    }
+
+    pub(crate) fn finish_unlock(&mut self, result: Result<Vault, VaultError>) {
+        match result {
+            Ok(vault) => {
+                self.vault = Some(vault);
+                self.attach_backups();
+                self.unlock_retry_at = None;
+                if self.config.unlock.failed_attempts > 0 {
+                    self.config.unlock = UnlockState::default();
+                    self.save_config();
+                }
+                self.status_message = Some("Vault unlocked".to_string());
+                self.start_session();
+            }
+            Err(error) => {
+                self.config.unlock.record_failure(clock::now());
+                self.save_config();
+                self.resume_unlock_backoff();
+                self.status_message = Some(format!(
+                    "Unable to unlock vault: {error} (attempt {})",
+                    self.config.unlock.failed_attempts
+                ));
+            }
+        }
+    }
+
+    fn create_vault(&mut self) {
+        let Some(path) = self.vault_path.clone() else {
+            self.status_message = Some("Unable to locate the Lilypad data folder".to_string());
+            return;
+        };
+        self.status_message = None;
+        self.start_create(path);
+    }
+
+    pub(crate) fn finish_create(&mut self, result: Result<Vault, VaultError>) {
+        match result {
+            Ok(vault) => {
+                self.vault = Some(vault);
+                self.attach_backups();
+                self.master_password.zeroize();
+                self.confirm_password.zeroize();
+                self.status_message = Some("New vault created".to_string());
+                self.start_session();
+            }
+            Err(error) => {
+                self.status_message = Some(format!("Unable to create vault: {error}"));
+            }
+        }
+    }
+
+    /// Spinner and Cancel button shown in place of the submit button while
+    /// the key is derived. Returns `true` while a task is running.
+    pub(crate) fn render_key_progress(&mut self, ui: &mut egui::Ui) -> bool {
+        let Some(task) = &self.key_task else {
+            return false;
+        };
+        let label = task.kind().label();
+        ui.horizontal(|ui| {
+            ui.add(egui::Spinner::new());
+            ui.label(RichText::new(label).color(self.palette.text));
+            if ui.button("Cancel").clicked() {
+                self.cancel_key_task();
+            }
+        });
+        true
+    }
+}
@@ -0,0 +1,107 @@
+//! Locking the vault: on demand, after inactivity, on focus loss (when the
+//! terminal reports focus), and after the computer resumes from sleep.
+
+use crate::{App, Section};
+use lilypad_core::secret::Zeroize;
+use lilypad_core::session::{LockReason, WindowState};
+use std::time::{Instant, SystemTime};
+
+impl App {
+    /// Checks the auto-lock rules and locks when one of them fires. Only
+    /// call while the vault is unlocked.
+    pub(crate) fn poll_auto_lock(&mut self) {
+        let window = WindowState {
+            minimized: false,
+            focused: self.terminal_focused,
+        };
+        if let Some(reason) = self
+            .auto_lock
+            .check(Instant::now(), SystemTime::now(), window)
+        {
+            self.lock_vault(reason);
+        }
+    }
+
+    /// Drops every piece of decrypted state and returns to the unlock screen.
+    pub(crate) fn lock_vault(&mut self, reason: LockReason) {
+        self.clear_clipboard_now();
+        self.vault = None;
+        self.detail = None;
+        self.search_index = None;
+        self.search_results.clear();
+        self.search_query.clear();
+        self.searching = false;
+        self.selected_result = 0;
+        self.generated_password.zeroize();
+        self.master_password.zeroize();
+        self.confirm_password.zeroize();
+        self.section = Section::Vault;
+        self.status_message = Some(format!("Vault {}", reason.label()));
+    }
+
+    /// Starts the idle timer afresh once the vault is open.
+    pub(crate) fn start_session(&mut self) {
+        self.auto_lock.reset(Instant::now(), SystemTime::now());
+    }
+
+    /// Time left before the idle timer locks, for the status bar; only shown
+    /// during the last minute.
+    pub(crate) fn auto_lock_countdown(&self) -> Option<String> {
+        self.vault.as_ref()?;
+        let remaining = self
+            .auto_lock
+            .remaining(Instant::now(), SystemTime::now())?;
+        (remaining.as_secs() < 60).then(|| format!("{} s", remaining.as_secs() + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::tests::FakeClipboard;
+    use crate::tests::{press, press_ctrl, unlocked_app};
+    use ratatui::crossterm::event::KeyCode;
+
+    #[test]
+    fn locking_drops_decrypted_state_and_the_copied_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let clipboard = FakeClipboard::default();
+        let mut app = unlocked_app(dir.path(), &clipboard);
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Char('m'));
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.detail.is_some());
+        press(&mut app, KeyCode::Char('p'));
+        app.generate_password();
+        app.master_password.push_str("typed");
+        clipboard.sent();
+
+        press_ctrl(&mut app, 'l');
+        assert!(app.vault.is_none());
+        assert!(app.detail.is_none() && app.search_index.is_none());
+        assert!(app.search_results.is_empty() && app.search_query.is_empty());
+        assert!(app.generated_password.is_empty() && app.master_password.is_empty());
+        assert_eq!(app.clipboard_countdown(), None);
+        assert_eq!(clipboard.sent(), "\x1b]52;c;\x07");
+        assert_eq!(app.section, Section::Vault);
+        assert_eq!(app.status_message.as_deref(), Some("Vault locked"));
+    }
+
+    #[test]
+    fn losing_focus_locks_only_when_asked_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = unlocked_app(dir.path(), &FakeClipboard::default());
+        app.handle_event(ratatui::crossterm::event::Event::FocusLost);
+        app.poll_auto_lock();
+        assert!(app.vault.is_some());
+
+        app.auto_lock.lock_on_focus_loss = true;
+        app.poll_auto_lock();
+        assert!(app.vault.is_none());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Vault locked when the window lost focus")
+        );
+    }
+}
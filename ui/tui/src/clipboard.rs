@@ -0,0 +1,286 @@
+//! Copying secrets to the clipboard and clearing them again after the
+//! configured timeout, on lock, and on exit.
+//!
+//! With a local display the system clipboard is used through `arboard`, and
+//! a secret is only cleared if the clipboard still holds it. Over SSH there
+//! is no such clipboard, so secrets are sent to the user's terminal with an
+//! OSC 52 escape sequence instead. The terminal clipboard cannot be read
+//! back, so it is cleared unconditionally when the timeout passes.
+
+use crate::App;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use lilypad_core::clipboard;
+use lilypad_core::secret::{SecretString, Zeroizing};
+use std::io::{self, Write};
+use std::time::Instant;
+
+/// Where the pending secret was copied to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Target {
+    System,
+    Terminal,
+}
+
+/// The clipboards a secret can be copied to: [`Platform`] in the app, a
+/// fake in tests.
+pub(crate) trait Clipboard {
+    /// Places `text` on the system clipboard; `false` when there is none or
+    /// it refused.
+    fn set_system(&mut self, text: &str) -> bool;
+    /// What the system clipboard holds, if it can be read.
+    fn system_text(&mut self) -> Option<Zeroizing<String>>;
+    fn clear_system(&mut self) -> bool;
+    /// Writes an escape sequence to the terminal the user is typing in.
+    fn write_terminal(&mut self, sequence: &str) -> io::Result<()>;
+}
+
+/// The system clipboard through `arboard`, opened on first copy, and the
+/// terminal on stdout.
+#[derive(Default)]
+pub(crate) struct Platform {
+    /// `None` until the first copy, and after it when there is no display.
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard for Platform {
+    fn set_system(&mut self, text: &str) -> bool {
+        if self.system.is_none() {
+            self.system = arboard::Clipboard::new().ok();
+        }
+        self.system
+            .as_mut()
+            .is_some_and(|system| system.set_text(text).is_ok())
+    }
+
+    fn system_text(&mut self) -> Option<Zeroizing<String>> {
+        self.system.as_mut()?.get_text().ok().map(Zeroizing::new)
+    }
+
+    fn clear_system(&mut self) -> bool {
+        self.system
+            .as_mut()
+            .is_some_and(|system| system.clear().is_ok())
+    }
+
+    fn write_terminal(&mut self, sequence: &str) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(sequence.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl App {
+    /// Copies `secret` and schedules it for clearing; `what` names it in the
+    /// status bar ("Password", "Card number", …).
+    pub(crate) fn copy_secret(&mut self, secret: &str, what: &str) {
+        if secret.is_empty() {
+            return;
+        }
+
+        let target = if self.clipboard.set_system(secret) {
+            Target::System
+        } else if self.write_terminal_clipboard(secret).is_ok() {
+            Target::Terminal
+        } else {
+            self.status_message = Some(format!("Unable to copy {}", what.to_lowercase()));
+            return;
+        };
+        self.clipboard_target = Some(target);
+        self.clipboard_clear.copied(secret, Instant::now());
+        self.status_message = Some(match target {
+            Target::System => format!("{what} copied to clipboard"),
+            Target::Terminal => format!("{what} sent to the terminal clipboard"),
+        });
+    }
+
+    /// Clears a copied secret whose timeout has passed.
+    pub(crate) fn poll_clipboard(&mut self) {
+        if let Some(secret) = self.clipboard_clear.take_due(Instant::now()) {
+            if self.clear_clipboard(&secret) {
+                self.status_message = Some("Clipboard cleared".to_string());
+            }
+        }
+    }
+
+    /// Clears a pending secret right away; used on lock and exit.
+    pub(crate) fn clear_clipboard_now(&mut self) {
+        if let Some(secret) = self.clipboard_clear.take() {
+            self.clear_clipboard(&secret);
+        }
+    }
+
+    /// Seconds until the pending secret is cleared, for the status bar.
+    pub(crate) fn clipboard_countdown(&self) -> Option<u64> {
+        self.clipboard_clear
+            .remaining(Instant::now())
+            .map(|remaining| remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0))
+    }
+
+    fn clear_clipboard(&mut self, secret: &SecretString) -> bool {
+        match self.clipboard_target.take() {
+            Some(Target::System) => {
+                let current = self.clipboard.system_text();
+                clipboard::still_holds(current.as_deref().map(String::as_str), secret)
+                    && self.clipboard.clear_system()
+            }
+            Some(Target::Terminal) => self.write_terminal_clipboard("").is_ok(),
+            None => false,
+        }
+    }
+
+    /// Sets the clipboard of the terminal the user is typing in (OSC 52),
+    /// which works across SSH in most modern terminal emulators.
+    fn write_terminal_clipboard(&mut self, text: &str) -> io::Result<()> {
+        let encoded = Zeroizing::new(STANDARD.encode(text));
+        let sequence = Zeroizing::new(format!("\x1b]52;c;{}\x07", encoded.as_str()));
+        self.clipboard.write_terminal(&sequence)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::tests::{press, unlocked_app};
+    use crate::Section;
+    use ratatui::crossterm::event::KeyCode;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    /// Clipboards for tests. Clones share their contents, so a test can look
+    /// inside the one it handed to the app.
+    #[derive(Clone, Default)]
+    pub(crate) struct FakeClipboard(Rc<RefCell<Contents>>);
+
+    #[derive(Default)]
+    struct Contents {
+        /// `None` when there is no system clipboard, as over SSH.
+        system: Option<String>,
+        terminal: String,
+    }
+
+    impl FakeClipboard {
+        /// A machine with a display, whose system clipboard starts empty.
+        pub(crate) fn with_system() -> Self {
+            let fake = Self::default();
+            fake.0.borrow_mut().system = Some(String::new());
+            fake
+        }
+
+        pub(crate) fn system(&self) -> Option<String> {
+            self.0.borrow().system.clone()
+        }
+
+        /// Something the user copied in another program.
+        pub(crate) fn copy_elsewhere(&self, text: &str) {
+            self.0.borrow_mut().system = Some(text.to_string());
+        }
+
+        /// The escape sequences written to the terminal since the last call.
+        pub(crate) fn sent(&self) -> String {
+            std::mem::take(&mut self.0.borrow_mut().terminal)
+        }
+    }
+
+    impl Clipboard for FakeClipboard {
+        fn set_system(&mut self, text: &str) -> bool {
+            match &mut self.0.borrow_mut().system {
+                Some(system) => {
+                    *system = text.to_string();
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn system_text(&mut self) -> Option<Zeroizing<String>> {
+            self.0.borrow().system.clone().map(Zeroizing::new)
+        }
+
+        fn clear_system(&mut self) -> bool {
+            match &mut self.0.borrow_mut().system {
+                Some(system) => {
+                    system.clear();
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn write_terminal(&mut self, sequence: &str) -> io::Result<()> {
+            self.0.borrow_mut().terminal.push_str(sequence);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_terminal_and_clears_it_when_due() {
+        let dir = tempfile::tempdir().unwrap();
+        let clipboard = FakeClipboard::default();
+        let mut app = unlocked_app(dir.path(), &clipboard);
+        app.show_section(Section::Vault);
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.clipboard_target, Some(Target::Terminal));
+        assert_eq!(clipboard.sent(), "\x1b]52;c;aHVudGVyMg==\x07");
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Password sent to the terminal clipboard")
+        );
+        let after = clipboard::DEFAULT_CLEAR_AFTER.as_secs();
+        assert_eq!(app.clipboard_countdown(), Some(after));
+
+        // Not due yet: nothing is cleared.
+        app.poll_clipboard();
+        assert_eq!(clipboard.sent(), "");
+        assert_eq!(app.clipboard_target, Some(Target::Terminal));
+
+        app.clipboard_clear.set_clear_after(Some(Duration::ZERO));
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(clipboard.sent(), "\x1b]52;c;ZnJvZw==\x07");
+        app.poll_clipboard();
+        assert_eq!(clipboard.sent(), "\x1b]52;c;\x07");
+        assert_eq!(app.clipboard_target, None);
+        assert_eq!(app.clipboard_countdown(), None);
+        assert_eq!(app.status_message.as_deref(), Some("Clipboard cleared"));
+    }
+
+    #[test]
+    fn system_clipboard_is_cleared_only_while_it_holds_the_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let clipboard = FakeClipboard::with_system();
+        let mut app = unlocked_app(dir.path(), &clipboard);
+        app.clipboard_clear.set_clear_after(Some(Duration::ZERO));
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.clipboard_target, Some(Target::System));
+        assert_eq!(clipboard.system().as_deref(), Some("hunter2"));
+        assert_eq!(clipboard.sent(), "");
+        app.poll_clipboard();
+        assert_eq!(clipboard.system().as_deref(), Some(""));
+
+        press(&mut app, KeyCode::Char('p'));
+        clipboard.copy_elsewhere("lunch order");
+        app.poll_clipboard();
+        assert_eq!(clipboard.system().as_deref(), Some("lunch order"));
+        assert_eq!(app.clipboard_target, None);
+    }
+
+    #[test]
+    fn without_a_timeout_secrets_stay_until_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let clipboard = FakeClipboard::default();
+        let mut app = unlocked_app(dir.path(), &clipboard);
+        app.clipboard_clear.set_clear_after(None);
+        app.copy_secret("hunter2", "Password");
+        clipboard.sent();
+        app.poll_clipboard();
+        assert_eq!(app.clipboard_countdown(), None);
+        assert_eq!(clipboard.sent(), "");
+
+        app.clear_clipboard_now();
+        assert_eq!(clipboard.sent(), "\x1b]52;c;\x07");
+        // Nothing is pending, so a second clear sends nothing.
+        app.clear_clipboard_now();
+        assert_eq!(clipboard.sent(), "");
+    }
+}
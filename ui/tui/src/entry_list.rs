@@ -0,0 +1,395 @@
+//! Vault section: the search box, the ranked entry list, and the detail view
+//! with copy actions.
+//!
+//! The TUI only reads entries; adding and editing stay in the desktop app and
+//! `lilypad-cli`, so the search index is built once per unlock.
+
+use crate::{masked, App};
+use lilypad_core::secret::Zeroizing;
+use lilypad_core::{clock, Entry, EntryKind, SearchIndex, Uuid};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use std::ops::Range;
+
+/// Rows moved by PageUp and PageDown.
+const PAGE: usize = 10;
+
+/// The entry open in the detail view.
+pub(crate) struct EntryDetail {
+    id: Uuid,
+    selected_field: usize,
+    reveal: bool,
+}
+
+/// One labelled value of the detail view.
+pub(crate) struct DetailField {
+    pub(crate) label: String,
+    pub(crate) value: Zeroizing<String>,
+    pub(crate) secret: bool,
+}
+
+impl App {
+    /// Builds the index on first use and re-runs the current query.
+    pub(crate) fn refresh_search(&mut self) {
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        let index = self
+            .search_index
+            .get_or_insert_with(|| SearchIndex::build(vault.entries()));
+        self.search_results = index.search(&self.search_query);
+        self.selected_result = self
+            .selected_result
+            .min(self.search_results.len().saturating_sub(1));
+    }
+
+    /// The entry open in the detail view, or else the selected row.
+    pub(crate) fn current_entry(&self) -> Option<&Entry> {
+        let vault = self.vault.as_ref()?;
+        match &self.detail {
+            Some(detail) => vault.entry(detail.id),
+            None => self
+                .search_results
+                .get(self.selected_result)
+                .and_then(|hit| vault.entries().get(hit.index)),
+        }
+    }
+
+    pub(crate) fn start_search(&mut self) {
+        self.detail = None;
+        self.searching = true;
+    }
+
+    pub(crate) fn render_entry_list(&mut self, frame: &mut Frame, area: Rect) {
+        if self.search_index.is_none() {
+            self.refresh_search();
+        }
+        let palette = self.palette;
+        let [search_area, list_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(area);
+
+        let cursor = if self.searching { "▏" } else { "" };
+        let search = if self.search_query.is_empty() && !self.searching {
+            Line::styled("Press / to search", palette.muted())
+        } else {
+            Line::from(vec![
+                Span::styled("Search: ", palette.muted()),
+                Span::raw(format!("{}{cursor}", self.search_query)),
+            ])
+        };
+        frame.render_widget(Paragraph::new(search), search_area);
+
+        let (list_area, detail_area) = if self.detail.is_some() {
+            let [list, detail] =
+                Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                    .areas(list_area);
+            (list, Some(detail))
+        } else {
+            (list_area, None)
+        };
+
+        let Some(vault) = &self.vault else {
+            return;
+        };
+        if vault.entries().is_empty() {
+            frame.render_widget(
+                Paragraph::new(
+                    "Your vault is empty. Add entries in the desktop app or with lilypad-cli.",
+                )
+                .style(palette.muted())
+                .wrap(Wrap { trim: true }),
+                list_area,
+            );
+            return;
+        }
+        if self.search_results.is_empty() {
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "No entries match \"{}\".",
+                    self.search_query.trim()
+                ))
+                .style(palette.muted()),
+                list_area,
+            );
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .search_results
+            .iter()
+            .filter_map(|hit| {
+                let entry = vault.entries().get(hit.index)?;
+                let mut spans = highlighted_title(
+                    &entry.title,
+                    &hit.title_matches,
+                    palette.base(),
+                    palette.matched(),
+                );
+                let subtitle = entry.subtitle();
+                if !subtitle.is_empty() {
+                    spans.push(Span::styled(format!("  {subtitle}"), palette.muted()));
+                }
+                Some(ListItem::new(Line::from(spans)))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(palette.selected())
+            .highlight_symbol("› ");
+        let mut state = ListState::default().with_selected(Some(self.selected_result));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        if let (Some(detail), Some(area)) = (&self.detail, detail_area) {
+            if let Some(entry) = vault.entry(detail.id) {
+                self.render_entry_detail(frame, area, entry, detail);
+            }
+        }
+    }
+
+    fn render_entry_detail(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        entry: &Entry,
+        detail: &EntryDetail,
+    ) {
+        let palette = self.palette;
+        let mut lines = vec![
+            Line::styled(entry.title.clone(), palette.accent_text()),
+            Line::styled(entry.kind.label(), palette.muted()),
+            Line::raw(""),
+        ];
+        for (index, field) in detail_fields(entry).iter().enumerate() {
+            let value = if field.secret && !detail.reveal {
+                masked(&field.value)
+            } else {
+                field.value.to_string()
+            };
+            let style = if index == detail.selected_field {
+                palette.selected()
+            } else {
+                palette.base()
+            };
+            let mut value_lines = value.lines();
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<16}", field.label), palette.muted()),
+                Span::styled(value_lines.next().unwrap_or_default().to_string(), style),
+            ]));
+            for line in value_lines {
+                lines.push(Line::from(vec![
+                    Span::raw(" ".repeat(16)),
+                    Span::styled(line.to_string(), style),
+                ]));
+            }
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            format!("Modified {}", clock::format_utc(entry.modified_at)),
+            palette.muted(),
+        ));
+
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(palette.panel().title(" Details ")),
+            area,
+        );
+    }
+
+    pub(crate) fn handle_vault_key(&mut self, key: KeyEvent) {
+        if self.searching {
+            self.handle_search_key(key);
+        } else if self.detail.is_some() {
+            self.handle_detail_key(key);
+        } else {
+            self.handle_list_key(key);
+        }
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_query.push(c);
+                self.selected_result = 0;
+                self.refresh_search();
+            }
+            KeyCode::Backspace => {
+                self.search_query.pop();
+                self.selected_result = 0;
+                self.refresh_search();
+            }
+            KeyCode::Enter | KeyCode::Down | KeyCode::Tab => self.searching = false,
+            KeyCode::Esc => {
+                self.search_query.clear();
+                self.searching = false;
+                self.refresh_search();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) {
+        let last = self.search_results.len().saturating_sub(1);
+        let selected = &mut self.selected_result;
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => *selected = (*selected + 1).min(last),
+            KeyCode::PageUp => *selected = selected.saturating_sub(PAGE),
+            KeyCode::PageDown => *selected = (*selected + PAGE).min(last),
+            KeyCode::Home => *selected = 0,
+            KeyCode::End => *selected = last,
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::Enter | KeyCode::Right => {
+                if let Some(entry) = self.current_entry() {
+                    self.detail = Some(EntryDetail {
+                        id: entry.id,
+                        selected_field: 0,
+                        reveal: false,
+                    });
+                }
+            }
+            KeyCode::Esc if !self.search_query.is_empty() => {
+                self.search_query.clear();
+                self.refresh_search();
+            }
+            KeyCode::Char('u') => self.copy_selected(false),
+            KeyCode::Char('p') => self.copy_selected(true),
+            _ => {}
+        }
+    }
+
+    fn handle_detail_key(&mut self, key: KeyEvent) {
+        let Some(fields) = self.current_entry().map(detail_fields) else {
+            self.detail = None;
+            return;
+        };
+        let Some(detail) = self.detail.as_mut() else {
+            return;
+        };
+        let last = fields.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                detail.selected_field = detail.selected_field.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                detail.selected_field = (detail.selected_field + 1).min(last);
+            }
+            KeyCode::Char('r') => detail.reveal = !detail.reveal,
+            KeyCode::Enter | KeyCode::Char('c') => {
+                if let Some(field) = fields.get(detail.selected_field) {
+                    self.copy_secret(&field.value, &field.label);
+                }
+            }
+            KeyCode::Esc | KeyCode::Left | KeyCode::Backspace => self.detail = None,
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::Char('u') => self.copy_selected(false),
+            KeyCode::Char('p') => self.copy_selected(true),
+            _ => {}
+        }
+    }
+}
+
+/// The non-empty fields of `entry` in display order.
+pub(crate) fn detail_fields(entry: &Entry) -> Vec<DetailField> {
+    let mut fields = Vec::new();
+    let mut push = |label: &str, value: &str, secret: bool| {
+        if !value.is_empty() {
+            fields.push(DetailField {
+                label: label.to_string(),
+                value: Zeroizing::new(value.to_string()),
+                secret,
+            });
+        }
+    };
+
+    match &entry.kind {
+        EntryKind::Login(login) => {
+            push("Username", &login.username, false);
+            push("Password", &login.password, true);
+            push("TOTP seed", &login.totp, true);
+            for url in &login.urls {
+                push("Website", url, false);
+            }
+        }
+        EntryKind::SecureNote => {}
+        EntryKind::Card(card) => {
+            push("Cardholder", &card.cardholder_name, false);
+            push("Brand", &card.brand, false);
+            push("Number", &card.number, true);
+            if !card.expiry_month.is_empty() || !card.expiry_year.is_empty() {
+                push(
+                    "Expires",
+                    &format!("{}/{}", card.expiry_month, card.expiry_year),
+                    false,
+                );
+            }
+            push("Security code", &card.security_code, true);
+        }
+        EntryKind::Identity(identity) => {
+            let name = format!("{} {}", identity.first_name, identity.last_name);
+            push("Name", name.trim(), false);
+            push("Email", &identity.email, false);
+            push("Phone", &identity.phone, false);
+            push("Company", &identity.company, false);
+            push("Address", &identity.address, false);
+            push("City", &identity.city, false);
+            push("State", &identity.state, false);
+            push("Postal code", &identity.postal_code, false);
+            push("Country", &identity.country, false);
+        }
+        EntryKind::Wifi(wifi) => {
+            push("Network", &wifi.ssid, false);
+            push("Password", &wifi.password, true);
+            push("Security", wifi.security.label(), false);
+        }
+    }
+    for field in &entry.custom_fields {
+        push(&field.name, &field.value, field.hidden);
+    }
+    let attachments: Vec<&str> = entry
+        .attachments
+        .iter()
+        .map(|attachment| attachment.name.as_str())
+        .collect();
+    push("Attachments", &attachments.join(", "), false);
+    push("Folder", &entry.folder, false);
+    push("Tags", &entry.tags.join(", "), false);
+    push("Notes", &entry.notes, false);
+    fields
+}
+
+/// Splits `title` into spans with the matched char ranges highlighted.
+fn highlighted_title(
+    title: &str,
+    matches: &[Range<usize>],
+    normal: ratatui::style::Style,
+    highlight: ratatui::style::Style,
+) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut segment = String::new();
+    let mut segment_highlighted = false;
+    for (position, c) in title.chars().enumerate() {
+        let highlighted = matches.iter().any(|range| range.contains(&position));
+        if highlighted != segment_highlighted && !segment.is_empty() {
+            let style = if segment_highlighted {
+                highlight
+            } else {
+                normal
+            };
+            spans.push(Span::styled(std::mem::take(&mut segment), style));
+        }
+        segment_highlighted = highlighted;
+        segment.push(c);
+    }
+    if !segment.is_empty() {
+        let style = if segment_highlighted {
+            highlight
+        } else {
+            normal
+        };
+        spans.push(Span::styled(segment, style));
+    }
+    spans
+}
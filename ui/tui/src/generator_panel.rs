@@ -0,0 +1,194 @@
+//! Generator section: random-character or passphrase mode, the main options
+//! for each, and the strength of the settings. Advanced constraints and
+//! site rules stay in the desktop app.
+
+use crate::App;
+use lilypad_core::config::{LENGTH_RANGE, WORD_COUNT_RANGE};
+use lilypad_core::secret::{self, Zeroize};
+use lilypad_core::{GeneratorMode, Strength};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+impl App {
+    /// Generates with the current settings and copies the result, like the
+    /// desktop Generate button.
+    pub(crate) fn generate_password(&mut self) {
+        match self.generator.generate() {
+            Ok(password) => {
+                self.copy_secret(&password, "New password");
+                self.generated_password = password;
+            }
+            Err(error) => {
+                self.status_message = Some(format!("Unable to generate password: {error}"));
+            }
+        }
+    }
+
+    pub(crate) fn render_generator_panel(&self, frame: &mut Frame, area: Rect) {
+        let palette = self.palette;
+        let generator = &self.generator;
+        let check = |on: bool| if on { "[x]" } else { "[ ]" };
+        let option = |key: &str, text: String| {
+            Line::from(vec![
+                Span::styled(format!("  {key}  "), palette.accent_text()),
+                Span::raw(text),
+            ])
+        };
+
+        let mut lines = Vec::new();
+        let output = if self.generated_password.is_empty() {
+            Span::styled("Press g to generate", palette.muted())
+        } else {
+            Span::styled(self.generated_password.as_str(), palette.base())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<12}", "Password"), palette.muted()),
+            output,
+        ]));
+        lines.push(Line::raw(""));
+
+        match generator.mode {
+            GeneratorMode::Characters => {
+                let password = &generator.password;
+                lines.push(option("m", "Mode: random characters".to_string()));
+                lines.push(option("←→", format!("Length: {}", password.length)));
+                lines.push(option(
+                    "l",
+                    format!("{} Lowercase", check(password.lowercase)),
+                ));
+                lines.push(option(
+                    "u",
+                    format!("{} Uppercase", check(password.uppercase)),
+                ));
+                lines.push(option("d", format!("{} Digits", check(password.digits))));
+                lines.push(option("s", format!("{} Symbols", check(password.symbols))));
+                lines.push(option(
+                    "a",
+                    format!(
+                        "{} Exclude look-alike characters",
+                        check(password.exclude_ambiguous)
+                    ),
+                ));
+            }
+            GeneratorMode::Passphrase => {
+                let passphrase = &generator.passphrase;
+                lines.push(option("m", "Mode: passphrase".to_string()));
+                lines.push(option("←→", format!("Words: {}", passphrase.word_count)));
+                lines.push(option(
+                    "u",
+                    format!("{} Capitalize words", check(passphrase.capitalize)),
+                ));
+                lines.push(option(
+                    "d",
+                    format!("{} Append a digit", check(passphrase.append_digit)),
+                ));
+            }
+        }
+
+        lines.push(Line::raw(""));
+        match generator.check() {
+            Ok(()) => {
+                let strength = generator.strength();
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<12}", "Strength"), palette.muted()),
+                    Span::styled(strength.label(), strength_style(&palette, strength)),
+                    Span::styled(
+                        format!("  {:.0} bits of entropy", generator.entropy_bits()),
+                        palette.muted(),
+                    ),
+                ]));
+            }
+            Err(error) => lines.push(Line::styled(
+                format!("Cannot generate: {error}"),
+                palette.danger_text(),
+            )),
+        }
+        lines.push(Line::styled(
+            format!(
+                "Copied passwords are cleared from the clipboard {}.",
+                self.clipboard_timeout_label()
+            ),
+            palette.muted(),
+        ));
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+
+    pub(crate) fn handle_generator_key(&mut self, key: KeyEvent) {
+        let generator = &mut self.generator;
+        let characters = generator.mode == GeneratorMode::Characters;
+        let password = &mut generator.password;
+        let passphrase = &mut generator.passphrase;
+        let changed = match key.code {
+            KeyCode::Char('g') | KeyCode::Char(' ') | KeyCode::Enter => {
+                self.generate_password();
+                return;
+            }
+            KeyCode::Char('c') => {
+                if self.generated_password.is_empty() {
+                    self.status_message = Some("Generate a password first".to_string());
+                } else {
+                    let generated = secret::secret_buffer_from(&self.generated_password);
+                    self.copy_secret(&generated, "Password");
+                }
+                return;
+            }
+            KeyCode::Char('m') => {
+                generator.mode = if characters {
+                    GeneratorMode::Passphrase
+                } else {
+                    GeneratorMode::Characters
+                };
+                true
+            }
+            KeyCode::Left | KeyCode::Char('-') if characters => {
+                password.length = password.length.saturating_sub(1).max(LENGTH_RANGE.0);
+                true
+            }
+            KeyCode::Right | KeyCode::Char('+') if characters => {
+                password.length = (password.length + 1).min(LENGTH_RANGE.1);
+                true
+            }
+            KeyCode::Left | KeyCode::Char('-') => {
+                passphrase.word_count = passphrase
+                    .word_count
+                    .saturating_sub(1)
+                    .max(WORD_COUNT_RANGE.0);
+                true
+            }
+            KeyCode::Right | KeyCode::Char('+') => {
+                passphrase.word_count = (passphrase.word_count + 1).min(WORD_COUNT_RANGE.1);
+                true
+            }
+            KeyCode::Char('l') if characters => toggle(&mut password.lowercase),
+            KeyCode::Char('u') if characters => toggle(&mut password.uppercase),
+            KeyCode::Char('d') if characters => toggle(&mut password.digits),
+            KeyCode::Char('s') if characters => toggle(&mut password.symbols),
+            KeyCode::Char('a') if characters => toggle(&mut password.exclude_ambiguous),
+            KeyCode::Char('u') => toggle(&mut passphrase.capitalize),
+            KeyCode::Char('d') => toggle(&mut passphrase.append_digit),
+            _ => false,
+        };
+        if changed {
+            // A password from the old settings no longer matches what is shown.
+            self.generated_password.zeroize();
+        }
+    }
+}
+
+fn toggle(flag: &mut bool) -> bool {
+    *flag = !*flag;
+    true
+}
+
+fn strength_style(palette: &crate::theme::Palette, strength: Strength) -> Style {
+    Style::new().fg(match strength {
+        Strength::Weak => palette.danger,
+        Strength::Moderate => palette.warning,
+        Strength::Strong | Strength::VeryStrong => palette.success,
+    })
+}
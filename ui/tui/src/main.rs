@@ -0,0 +1,559 @@
+//! `lilypad-tui`: a keyboard-first terminal frontend for the vault, usable
+//! over SSH on machines without a display.
+//!
+//! Like the desktop app it keeps all state in one `App`, with each screen in
+//! its own module adding an `impl App` block, and shares the config, vault
+//! file, auto-lock rules, and clipboard timing with it through
+//! `lilypad-core`.
+
+mod auto_lock;
+mod clipboard;
+mod entry_list;
+mod generator_panel;
+mod security_panel;
+mod shortcuts;
+mod theme;
+mod unlock;
+
+use lilypad_core::secret::{self, SecretBuffer};
+use lilypad_core::session::AutoLock;
+use lilypad_core::{
+    AppDirs, ClipboardClear, Config, GeneratorSettings, Keymap, SearchHit, SearchIndex, Theme,
+    Vault,
+};
+use ratatui::crossterm::event::{
+    self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Tabs, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the loop wakes without input, so timers fire on time.
+const TICK: Duration = Duration::from_millis(250);
+
+/// The sections of the navigation bar, in the desktop app's order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    Vault,
+    Generator,
+    Alerts,
+    Account,
+    Security,
+}
+
+impl Section {
+    const ALL: [Self; 5] = [
+        Self::Vault,
+        Self::Generator,
+        Self::Alerts,
+        Self::Account,
+        Self::Security,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Vault => "Vault",
+            Self::Generator => "Generator",
+            Self::Alerts => "Alerts",
+            Self::Account => "Account",
+            Self::Security => "Security",
+        }
+    }
+
+    fn position(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|&section| section == self)
+            .unwrap_or_default()
+    }
+}
+
+struct App {
+    config: Config,
+    config_dir: Option<PathBuf>,
+    vault_path: PathBuf,
+    vault: Option<Vault>,
+    user_themes: Vec<Theme>,
+    palette: theme::Palette,
+    keymap: Keymap,
+    section: Section,
+    // Lock screen
+    master_password: SecretBuffer,
+    confirm_password: SecretBuffer,
+    confirm_focused: bool,
+    unlock_retry_at: Option<Instant>,
+    /// Set when Enter is pressed so the "Unlocking…" frame is drawn before
+    /// the key derivation blocks the loop.
+    unlock_requested: bool,
+    // Vault section
+    search_index: Option<SearchIndex>,
+    search_query: String,
+    search_results: Vec<SearchHit>,
+    searching: bool,
+    selected_result: usize,
+    detail: Option<entry_list::EntryDetail>,
+    // Generator section
+    generator: GeneratorSettings,
+    generated_password: SecretBuffer,
+    // Security section
+    selected_setting: usize,
+    // Session
+    auto_lock: AutoLock,
+    terminal_focused: bool,
+    clipboard_clear: ClipboardClear,
+    clipboard: Box<dyn clipboard::Clipboard>,
+    clipboard_target: Option<clipboard::Target>,
+    status_message: Option<String>,
+    quit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        let dirs = AppDirs::platform();
+        let mut load_error = None;
+        let config = match &dirs {
+            Some(dirs) => Config::load(&dirs.config_dir).unwrap_or_else(|error| {
+                load_error = Some(format!("Unable to load settings: {error}"));
+                Config::default()
+            }),
+            None => Config::default(),
+        };
+        let mut app = Self::with_config(config, dirs, Box::<clipboard::Platform>::default());
+        if load_error.is_some() {
+            app.status_message = load_error;
+        }
+        app
+    }
+
+    /// An app on `config` that keeps its settings, themes, and vault in
+    /// `dirs`, or nowhere when `None`, and copies secrets to `clipboard`;
+    /// tests pass a temporary folder and a fake clipboard.
+    fn with_config(
+        config: Config,
+        dirs: Option<AppDirs>,
+        clipboard: Box<dyn clipboard::Clipboard>,
+    ) -> Self {
+        let vault_path = match &dirs {
+            Some(dirs) => dirs.vault_path(&config),
+            None => config
+                .vault
+                .path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(lilypad_core::paths::VAULT_FILE)),
+        };
+
+        let mut app = Self {
+            config,
+            config_dir: dirs.map(|dirs| dirs.config_dir),
+            vault_path,
+            vault: None,
+            user_themes: Vec::new(),
+            palette: theme::Palette::of(&Theme::dark()),
+            keymap: Keymap::default(),
+            section: Section::Vault,
+            master_password: secret::secret_buffer(),
+            confirm_password: secret::secret_buffer(),
+            confirm_focused: false,
+            unlock_retry_at: None,
+            unlock_requested: false,
+            search_index: None,
+            search_query: String::new(),
+            search_results: Vec::new(),
+            searching: false,
+            selected_result: 0,
+            detail: None,
+            generator: GeneratorSettings::default(),
+            generated_password: secret::secret_buffer(),
+            selected_setting: 0,
+            auto_lock: AutoLock::default(),
+            terminal_focused: true,
+            clipboard_clear: ClipboardClear::default(),
+            clipboard,
+            clipboard_target: None,
+            status_message: None,
+            quit: false,
+        };
+        app.load_user_themes();
+        app.apply_config();
+        app.resume_unlock_backoff();
+        app
+    }
+
+    fn apply_config(&mut self) {
+        let config = &self.config;
+        self.generator = config.generator.to_settings();
+        self.auto_lock.idle_timeout = config.security.idle_timeout();
+        // A terminal cannot be minimized; only focus reports are available.
+        self.auto_lock.lock_on_minimize = false;
+        self.auto_lock.lock_on_focus_loss = config.security.lock_on_focus_loss;
+        self.clipboard_clear
+            .set_clear_after(config.security.clipboard_clear_after());
+        self.keymap = config.keymap();
+        self.apply_theme();
+    }
+
+    /// Writes the current settings to `config.toml`.
+    fn save_config(&mut self) {
+        let Some(config_dir) = &self.config_dir else {
+            return;
+        };
+        if let Err(error) = self.config.save(config_dir) {
+            self.status_message = Some(format!("Unable to save settings: {error}"));
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.render(frame))?;
+            if self.unlock_requested {
+                self.unlock_requested = false;
+                self.submit_unlock();
+                continue;
+            }
+            if event::poll(TICK)? {
+                self.handle_event(event::read()?);
+            }
+            self.poll_clipboard();
+            if self.vault.is_some() {
+                self.poll_auto_lock();
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                self.auto_lock.activity(Instant::now(), SystemTime::now());
+                self.handle_key(key);
+            }
+            Event::FocusGained => self.terminal_focused = true,
+            Event::FocusLost => self.terminal_focused = false,
+            _ => {}
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        // Ctrl+C is reserved by the keymap, so it can always quit.
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        if self.vault.is_none() {
+            self.handle_unlock_key(key);
+            return;
+        }
+        if self.handle_shortcut(key) {
+            return;
+        }
+        let typing = self.section == Section::Vault && self.searching;
+        if !typing {
+            match key.code {
+                KeyCode::Tab => return self.show_section_offset(1),
+                KeyCode::BackTab => return self.show_section_offset(Section::ALL.len() - 1),
+                KeyCode::Char('q') => {
+                    self.quit = true;
+                    return;
+                }
+                KeyCode::Char(digit @ '1'..='5') => {
+                    let index = digit as usize - '1' as usize;
+                    return self.show_section(Section::ALL[index]);
+                }
+                _ => {}
+            }
+        }
+        match self.section {
+            Section::Vault => self.handle_vault_key(key),
+            Section::Generator => self.handle_generator_key(key),
+            Section::Security => self.handle_security_key(key),
+            Section::Alerts | Section::Account => {}
+        }
+    }
+
+    fn show_section(&mut self, section: Section) {
+        self.section = section;
+        self.searching = false;
+    }
+
+    fn show_section_offset(&mut self, offset: usize) {
+        let index = (self.section.position() + offset) % Section::ALL.len();
+        self.show_section(Section::ALL[index]);
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let palette = self.palette;
+        frame.render_widget(Block::new().style(palette.base()), frame.area());
+        let [header, body, status] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        if self.vault.is_none() {
+            self.render_title(frame, header);
+            self.render_unlock_screen(frame, body);
+        } else {
+            self.render_navigation_bar(frame, header);
+            let block = palette.panel().title(format!(" {} ", self.section.label()));
+            let inner = block.inner(body);
+            frame.render_widget(block, body);
+            match self.section {
+                Section::Vault => self.render_entry_list(frame, inner),
+                Section::Generator => self.render_generator_panel(frame, inner),
+                Section::Alerts => render_text(
+                    frame,
+                    inner,
+                    palette,
+                    &[
+                        "Stay ahead of security issues. Alerts will summarize important notices about your vault activity and account safety.",
+                        "",
+                        "No alerts to show yet. Check back soon.",
+                    ],
+                ),
+                Section::Account => render_text(
+                    frame,
+                    inner,
+                    palette,
+                    &[
+                        "Manage your profile, device approvals, and preferences in one place.",
+                        "",
+                        "Account controls are coming soon.",
+                    ],
+                ),
+                Section::Security => self.render_security_panel(frame, inner),
+            }
+        }
+        self.render_status_bar(frame, status);
+    }
+
+    fn render_title(&self, frame: &mut Frame, area: Rect) {
+        let title = Paragraph::new(Line::from(vec![
+            Span::styled(
+                "Lilypad",
+                self.palette.accent_text().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("  password manager", self.palette.muted()),
+        ]))
+        .block(self.palette.panel());
+        frame.render_widget(title, area);
+    }
+
+    fn render_navigation_bar(&self, frame: &mut Frame, area: Rect) {
+        let palette = self.palette;
+        let titles = Section::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, section)| format!(" {} {} ", index + 1, section.label()));
+        let tabs = Tabs::new(titles)
+            .select(self.section.position())
+            .style(palette.muted())
+            .highlight_style(palette.selected())
+            .divider("│")
+            .block(palette.panel().title(" Lilypad "));
+        frame.render_widget(tabs, area);
+    }
+
+    /// Key hints for the current screen on the left, and the latest message
+    /// and pending timers on the right.
+    fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
+        let palette = self.palette;
+        let hints = if self.vault.is_none() {
+            "Enter submit · Ctrl+C quit"
+        } else {
+            match self.section {
+                Section::Vault if self.detail.is_some() => {
+                    "↑↓ field · Enter copy · r reveal · Esc back"
+                }
+                Section::Vault if self.searching => "type to search · Enter/↓ list · Esc clear",
+                Section::Vault => {
+                    "/ search · ↑↓ select · Enter open · u/p copy · Tab section · q quit"
+                }
+                Section::Generator => "g generate · c copy · m mode · ←→ length · l/u/d/s classes",
+                Section::Security => "↑↓ select · ←→/Enter change · Tab section · q quit",
+                Section::Alerts | Section::Account => "Tab section · q quit",
+            }
+        };
+
+        let mut notes = Vec::new();
+        // The lock screen shows its messages in the card.
+        if let Some(message) = self
+            .status_message
+            .as_ref()
+            .filter(|_| self.vault.is_some())
+        {
+            notes.push(message.clone());
+        }
+        if let Some(seconds) = self.clipboard_countdown() {
+            notes.push(format!("clipboard clears in {seconds} s"));
+        }
+        if let Some(remaining) = self.auto_lock_countdown() {
+            notes.push(format!("locks in {remaining}"));
+        }
+
+        let hints_width = u16::try_from(hints.chars().count() + 2).unwrap_or(u16::MAX);
+        let [left, right] =
+            Layout::horizontal([Constraint::Length(hints_width), Constraint::Fill(1)]).areas(area);
+        frame.render_widget(Paragraph::new(hints).style(palette.muted()), left);
+        frame.render_widget(
+            Paragraph::new(notes.join(" · "))
+                .style(palette.base())
+                .right_aligned(),
+            right,
+        );
+    }
+}
+
+/// Paragraphs of plain text, wrapped to the area.
+fn render_text(frame: &mut Frame, area: Rect, palette: theme::Palette, lines: &[&str]) {
+    let text: Vec<Line> = lines.iter().map(|line| Line::from(*line)).collect();
+    frame.render_widget(
+        Paragraph::new(text)
+            .style(palette.base())
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+/// A masked stand-in for a secret, one bullet per character.
+fn masked(secret: &str) -> String {
+    "•".repeat(secret.chars().count())
+}
+
+fn main() -> ExitCode {
+    let mut app = App::new();
+    let result = ratatui::try_init().and_then(|mut terminal| {
+        // Focus reports feed the lock-on-focus-loss rule; terminals that do
+        // not send them simply stay "focused".
+        let _ = execute!(io::stdout(), EnableFocusChange);
+        let result = app.run(&mut terminal);
+        let _ = execute!(io::stdout(), DisableFocusChange);
+        result
+    });
+    app.clear_clipboard_now();
+    ratatui::restore();
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("lilypad-tui: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use clipboard::tests::FakeClipboard;
+    use lilypad_core::crypto::KdfParams;
+    use lilypad_core::entry::{Entry, EntryKind, Login};
+    use std::path::Path;
+
+    const PASSWORD: &str = "pond lily frog";
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 16 * 1024,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    /// An app on the default settings with a vault in `dir` holding one
+    /// login, unlocked and listed, copying to `clipboard`.
+    pub(crate) fn unlocked_app(dir: &Path, clipboard: &FakeClipboard) -> App {
+        let dirs = AppDirs {
+            config_dir: dir.join("config"),
+            data_dir: dir.to_path_buf(),
+        };
+        let mut app = App::with_config(Config::default(), Some(dirs), Box::new(clipboard.clone()));
+        assert_eq!(app.vault_path, dir.join("vault.lilypad"));
+        let mut vault = Vault::create_with_params(&app.vault_path, PASSWORD, TEST_KDF).unwrap();
+        vault.add_entry(Entry::new(
+            "Mail",
+            EntryKind::Login(Login {
+                username: "frog".to_string(),
+                password: "hunter2".to_string(),
+                ..Login::default()
+            }),
+        ));
+        vault.save().unwrap();
+        app.vault = Some(vault);
+        app.start_session();
+        app.refresh_search();
+        app
+    }
+
+    pub(crate) fn press(app: &mut App, code: KeyCode) {
+        app.handle_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    }
+
+    pub(crate) fn press_ctrl(app: &mut App, c: char) {
+        app.handle_event(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::CONTROL,
+        )));
+    }
+
+    #[test]
+    fn keys_switch_sections_unless_typing_a_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = unlocked_app(dir.path(), &FakeClipboard::default());
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.section, Section::Generator);
+        press(&mut app, KeyCode::BackTab);
+        press(&mut app, KeyCode::BackTab);
+        assert_eq!(app.section, Section::Security);
+        press(&mut app, KeyCode::Char('1'));
+        assert_eq!(app.section, Section::Vault);
+
+        // While searching, digits and `q` are part of the query.
+        press(&mut app, KeyCode::Char('/'));
+        for c in "q2".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(
+            (app.section, app.search_query.as_str()),
+            (Section::Vault, "q2")
+        );
+        assert!(!app.quit);
+        press(&mut app, KeyCode::Esc);
+        assert!(app.search_query.is_empty() && !app.searching);
+
+        press_ctrl(&mut app, 'g');
+        assert_eq!(app.section, Section::Generator);
+        assert!(!app.generated_password.is_empty());
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn locked_keys_only_reach_the_unlock_screen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = unlocked_app(dir.path(), &FakeClipboard::default());
+        press_ctrl(&mut app, 'l');
+        assert!(app.vault.is_none());
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.section, Section::Vault);
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.unlock_requested);
+        for c in PASSWORD.chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert!(!app.quit);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.unlock_requested);
+        app.submit_unlock();
+        assert!(app.vault.is_some());
+        assert!(app.master_password.is_empty());
+
+        press_ctrl(&mut app, 'c');
+        assert!(app.quit);
+    }
+}
@@ -0,0 +1,187 @@
+//! Security section: auto-lock and clipboard protections, and "Lock now".
+//! Changes apply immediately and are saved to the config shared with the
+//! desktop app.
+
+use crate::App;
+use lilypad_core::session::{timeout_label, LockReason, CLIPBOARD_TIMEOUTS, IDLE_TIMEOUTS};
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::time::Duration;
+
+/// Rows of the section, in order.
+const IDLE_ROW: usize = 0;
+const FOCUS_ROW: usize = 1;
+const CLIPBOARD_ROW: usize = 2;
+const LOCK_ROW: usize = 3;
+const ROWS: usize = 4;
+
+impl App {
+    pub(crate) fn render_security_panel(&self, frame: &mut Frame, area: Rect) {
+        let palette = self.palette;
+        let idle = self.auto_lock.idle_timeout.map(|timeout| timeout.as_secs());
+        let clipboard = self
+            .clipboard_clear
+            .clear_after()
+            .map(|after| after.as_secs());
+        let focus = if self.auto_lock.lock_on_focus_loss {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        let rows = [
+            (
+                "Lock after inactivity",
+                format!("‹ {} ›", timeout_label(&IDLE_TIMEOUTS, idle)),
+            ),
+            ("Lock when the terminal loses focus", focus.to_string()),
+            (
+                "Clear copied secrets after",
+                format!("‹ {} ›", timeout_label(&CLIPBOARD_TIMEOUTS, clipboard)),
+            ),
+            ("Lock now", String::new()),
+        ];
+
+        let mut lines: Vec<Line> = rows
+            .into_iter()
+            .enumerate()
+            .map(|(index, (label, value))| {
+                let style = if index == self.selected_setting {
+                    palette.selected()
+                } else {
+                    palette.base()
+                };
+                Line::from(vec![
+                    Span::styled(format!("{label:<38}"), style),
+                    Span::styled(value, palette.accent_text()),
+                ])
+            })
+            .collect();
+        lines.push(Line::raw(""));
+        for note in [
+            "The vault also locks when the computer resumes from sleep.",
+            "Focus loss is only noticed in terminals that report focus changes.",
+            "Lilypad only clears the system clipboard if it still holds the secret it copied; a terminal clipboard reached over SSH is always cleared.",
+            "Change the master password in the desktop app.",
+        ] {
+            lines.push(Line::styled(note, palette.muted()));
+        }
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+
+    pub(crate) fn handle_security_key(&mut self, key: KeyEvent) {
+        let step = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected_setting = self.selected_setting.saturating_sub(1);
+                return;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected_setting = (self.selected_setting + 1).min(ROWS - 1);
+                return;
+            }
+            KeyCode::Left => -1,
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => 1,
+            _ => return,
+        };
+
+        match self.selected_setting {
+            IDLE_ROW => {
+                let current = self.auto_lock.idle_timeout.map(|timeout| timeout.as_secs());
+                self.auto_lock.idle_timeout =
+                    cycle(&IDLE_TIMEOUTS, current, step).map(Duration::from_secs);
+            }
+            FOCUS_ROW => {
+                self.auto_lock.lock_on_focus_loss = !self.auto_lock.lock_on_focus_loss;
+            }
+            CLIPBOARD_ROW => {
+                let current = self
+                    .clipboard_clear
+                    .clear_after()
+                    .map(|after| after.as_secs());
+                self.clipboard_clear.set_clear_after(
+                    cycle(&CLIPBOARD_TIMEOUTS, current, step).map(Duration::from_secs),
+                );
+            }
+            LOCK_ROW => {
+                self.lock_vault(LockReason::Manual);
+                return;
+            }
+            _ => return,
+        }
+        self.store_security_settings();
+    }
+
+    /// How copied secrets are cleared, for the Generator section.
+    pub(crate) fn clipboard_timeout_label(&self) -> String {
+        match self.clipboard_clear.clear_after() {
+            Some(after) => format!(
+                "after {}",
+                timeout_label(&CLIPBOARD_TIMEOUTS, Some(after.as_secs()))
+            ),
+            None => "on lock or exit".to_string(),
+        }
+    }
+
+    /// Copies the live auto-lock and clipboard settings into the config and
+    /// saves it, so they survive a restart. The minimize rule is left as the
+    /// desktop app set it.
+    fn store_security_settings(&mut self) {
+        let security = &mut self.config.security;
+        security.set_idle_timeout(self.auto_lock.idle_timeout);
+        security.lock_on_focus_loss = self.auto_lock.lock_on_focus_loss;
+        security.set_clipboard_clear_after(self.clipboard_clear.clear_after());
+        self.save_config();
+    }
+}
+
+/// The preset `step` places after `current`, wrapping around. A value that
+/// is not a preset starts from the first one.
+fn cycle(presets: &[(Option<u64>, &str)], current: Option<u64>, step: isize) -> Option<u64> {
+    let position = presets
+        .iter()
+        .position(|(seconds, _)| *seconds == current)
+        .map_or(0, |position| {
+            (position as isize + step).rem_euclid(presets.len() as isize) as usize
+        });
+    presets[position].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::tests::FakeClipboard;
+    use crate::tests::{press, unlocked_app};
+
+    #[test]
+    fn keys_cycle_presets_and_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = unlocked_app(dir.path(), &FakeClipboard::default());
+        press(&mut app, KeyCode::Char('5'));
+        // Five minutes is the default; Left steps back, Right wraps to Never.
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.auto_lock.idle_timeout, Some(Duration::from_secs(120)));
+        for _ in 0..5 {
+            press(&mut app, KeyCode::Right);
+        }
+        assert_eq!(app.auto_lock.idle_timeout, None);
+        assert_eq!(app.config.security.idle_timeout(), None);
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        assert!(app.auto_lock.lock_on_focus_loss);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Left);
+        let clipboard = app.clipboard_clear.clear_after();
+        assert_eq!(clipboard, Some(Duration::from_secs(20)));
+        assert_eq!(app.config.security.clipboard_clear_after(), clipboard);
+        assert_eq!(app.clipboard_timeout_label(), "after 20 seconds");
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.vault.is_none());
+    }
+}
@@ -0,0 +1,116 @@
+//! Global keyboard shortcuts from the configured `Keymap`, and `run_action`,
+//! which carries out an `Action`.
+//!
+//! Terminals only report some combinations: most send nothing for Ctrl with
+//! a digit or punctuation, so sections are also reachable with Tab and the
+//! plain digit keys.
+
+use crate::{App, Section};
+use lilypad_core::secret;
+use lilypad_core::session::LockReason;
+use lilypad_core::{Action, Shortcut};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+impl App {
+    /// Runs the action bound to `key`, if any. Returns whether one ran.
+    pub(crate) fn handle_shortcut(&mut self, key: KeyEvent) -> bool {
+        let action = self
+            .keymap
+            .bindings()
+            .iter()
+            .find(|(_, shortcut)| matches(*shortcut, key))
+            .map(|(action, _)| *action);
+        match action {
+            Some(action) => {
+                self.run_action(action);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn run_action(&mut self, action: Action) {
+        match action {
+            Action::FocusSearch => {
+                self.show_section(Section::Vault);
+                self.start_search();
+            }
+            Action::CopyUsername => self.copy_selected(false),
+            Action::CopyPassword => self.copy_selected(true),
+            Action::GeneratePassword => {
+                self.show_section(Section::Generator);
+                self.generate_password();
+            }
+            Action::Lock => self.lock_vault(LockReason::Manual),
+            Action::ShowVault => self.show_section(Section::Vault),
+            Action::ShowGenerator => self.show_section(Section::Generator),
+            Action::ShowAlerts => self.show_section(Section::Alerts),
+            Action::ShowAccount => self.show_section(Section::Account),
+            // Lock and clipboard settings live in the Security section here.
+            Action::ShowSecurity | Action::OpenSettings => self.show_section(Section::Security),
+            Action::CommandPalette | Action::NewEntry => {
+                self.status_message = Some(format!(
+                    "{} is only available in the desktop app",
+                    action.label()
+                ));
+            }
+        }
+    }
+
+    /// Copies the username or main secret of the open or selected entry.
+    pub(crate) fn copy_selected(&mut self, password: bool) {
+        let Some(entry) = self.current_entry() else {
+            self.status_message = Some("Select an entry first".to_string());
+            return;
+        };
+
+        let (value, what) = if password {
+            (entry.primary_secret(), "Password")
+        } else {
+            (entry.username(), "Username")
+        };
+        match value.map(secret::secret_buffer_from) {
+            Some(value) => self.copy_secret(&value, what),
+            None => {
+                self.status_message = Some(format!(
+                    "\"{}\" has no {} to copy",
+                    entry.title,
+                    what.to_lowercase()
+                ));
+            }
+        }
+    }
+}
+
+/// Whether `key` is `shortcut`. Ctrl stands for Control in the terminal,
+/// also on macOS.
+fn matches(shortcut: Shortcut, key: KeyEvent) -> bool {
+    let modifiers = key.modifiers;
+    if shortcut.ctrl != modifiers.contains(KeyModifiers::CONTROL)
+        || shortcut.alt != modifiers.contains(KeyModifiers::ALT)
+    {
+        return false;
+    }
+    let (name, shifted) = match key.code {
+        KeyCode::Char(c) => match c {
+            ',' => ("Comma".to_string(), false),
+            '.' => ("Period".to_string(), false),
+            '/' => ("Slash".to_string(), false),
+            '-' => ("Minus".to_string(), false),
+            ' ' => ("Space".to_string(), false),
+            c => (c.to_ascii_uppercase().to_string(), c.is_ascii_uppercase()),
+        },
+        KeyCode::F(number) => (format!("F{number}"), false),
+        KeyCode::Esc => ("Escape".to_string(), false),
+        KeyCode::Enter => ("Enter".to_string(), false),
+        KeyCode::Tab => ("Tab".to_string(), false),
+        KeyCode::Delete => ("Delete".to_string(), false),
+        KeyCode::Home => ("Home".to_string(), false),
+        KeyCode::End => ("End".to_string(), false),
+        KeyCode::PageUp => ("PageUp".to_string(), false),
+        KeyCode::PageDown => ("PageDown".to_string(), false),
+        _ => return false,
+    };
+    let shift = shifted || modifiers.contains(KeyModifiers::SHIFT);
+    shortcut.shift == shift && shortcut.key.eq_ignore_ascii_case(&name)
+}
@@ -0,0 +1,130 @@
+//! Maps the core `Theme` onto ratatui styles.
+//!
+//! Render code takes colors from `self.palette` only. A terminal cannot tell
+//! us whether its own background is light or dark, so "Follow system" uses
+//! the dark theme.
+
+use crate::App;
+use lilypad_core::theme::{self, Color, Theme};
+use ratatui::style::{self, Modifier, Style};
+use ratatui::widgets::{Block, BorderType};
+
+/// The semantic tokens of the active theme, converted for ratatui.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Palette {
+    pub(crate) background: style::Color,
+    pub(crate) surface: style::Color,
+    pub(crate) border: style::Color,
+    pub(crate) text: style::Color,
+    pub(crate) text_muted: style::Color,
+    pub(crate) accent: style::Color,
+    pub(crate) on_accent: style::Color,
+    pub(crate) success: style::Color,
+    pub(crate) warning: style::Color,
+    pub(crate) danger: style::Color,
+    pub(crate) highlight: style::Color,
+}
+
+impl Palette {
+    pub(crate) fn of(theme: &Theme) -> Self {
+        let colors = &theme.colors;
+        Self {
+            background: rgb(colors.background),
+            surface: rgb(colors.surface),
+            border: rgb(colors.border),
+            text: rgb(colors.text),
+            text_muted: rgb(colors.text_muted),
+            accent: rgb(colors.accent),
+            on_accent: rgb(colors.on_accent),
+            success: rgb(colors.success),
+            warning: rgb(colors.warning),
+            danger: rgb(colors.danger),
+            highlight: rgb(colors.highlight),
+        }
+    }
+
+    /// Body text on the window background.
+    pub(crate) fn base(&self) -> Style {
+        Style::new().fg(self.text).bg(self.background)
+    }
+
+    pub(crate) fn muted(&self) -> Style {
+        Style::new().fg(self.text_muted)
+    }
+
+    pub(crate) fn accent_text(&self) -> Style {
+        Style::new().fg(self.accent)
+    }
+
+    /// The selected tab, list row, or setting.
+    pub(crate) fn selected(&self) -> Style {
+        Style::new()
+            .fg(self.on_accent)
+            .bg(self.accent)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    /// Search matches within a title.
+    pub(crate) fn matched(&self) -> Style {
+        Style::new().fg(self.text).bg(self.highlight)
+    }
+
+    pub(crate) fn danger_text(&self) -> Style {
+        Style::new().fg(self.danger)
+    }
+
+    /// Color of a requirement check mark.
+    pub(crate) fn check_style(&self, satisfied: bool) -> Style {
+        Style::new().fg(if satisfied { self.success } else { self.danger })
+    }
+
+    /// A bordered panel on the surface color.
+    pub(crate) fn panel(&self) -> Block<'static> {
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().fg(self.border))
+            .title_style(Style::new().fg(self.text).add_modifier(Modifier::BOLD))
+            .style(Style::new().fg(self.text).bg(self.surface))
+    }
+}
+
+/// Terminals ignore alpha, so translucent tokens are drawn opaque.
+fn rgb(color: Color) -> style::Color {
+    style::Color::Rgb(color.r, color.g, color.b)
+}
+
+impl App {
+    /// Reads user themes from the `themes` folder next to `config.toml`.
+    pub(crate) fn load_user_themes(&mut self) {
+        let Some(config_dir) = &self.config_dir else {
+            return;
+        };
+        let (themes, errors) = Theme::load_dir(&config_dir.join(theme::THEMES_DIR));
+        self.user_themes = themes;
+        if let Some(error) = errors.first() {
+            self.status_message = Some(format!("Unable to load theme {error}"));
+        }
+    }
+
+    /// Applies the theme named in the config.
+    pub(crate) fn apply_theme(&mut self) {
+        let name = &self.config.appearance.theme;
+        let theme = if name == theme::FOLLOW_SYSTEM {
+            Theme::dark()
+        } else {
+            Theme::builtin(name)
+                .or_else(|| {
+                    self.user_themes
+                        .iter()
+                        .find(|theme| &theme.name == name)
+                        .cloned()
+                })
+                .unwrap_or_else(|| {
+                    self.status_message =
+                        Some(format!("Theme \"{name}\" not found; using the dark theme"));
+                    Theme::dark()
+                })
+        };
+        self.palette = Palette::of(&theme);
+    }
+}
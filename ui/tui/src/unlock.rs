@@ -0,0 +1,228 @@
+//! Lock screen: the create-vault form shown when no vault file exists yet and
+//! the unlock prompt shown otherwise.
+//!
+//! Key derivation runs on the UI thread; `unlock_requested` lets the loop
+//! draw "Unlocking…" first, since the terminal cannot repaint meanwhile.
+
+use crate::{masked, App};
+use lilypad_core::clock;
+use lilypad_core::config::UnlockState;
+use lilypad_core::secret::Zeroize;
+use lilypad_core::session::unlock_wait;
+use lilypad_core::{PasswordPolicy, Requirement, Vault};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+use std::time::{Duration, Instant};
+
+impl App {
+    /// Picks up the back-off left from earlier failed unlocks, including
+    /// ones from before a restart.
+    pub(crate) fn resume_unlock_backoff(&mut self) {
+        let unlock = &self.config.unlock;
+        self.unlock_retry_at =
+            unlock_wait(unlock.failed_attempts, unlock.last_failure_at, clock::now())
+                .map(|wait| Instant::now() + wait);
+    }
+
+    fn vault_exists(&self) -> bool {
+        self.vault_path.exists()
+    }
+
+    pub(crate) fn render_unlock_screen(&self, frame: &mut Frame, area: Rect) {
+        let palette = self.palette;
+        let creating = !self.vault_exists();
+        let (title, subtitle) = if creating {
+            (
+                " Create your Lilypad Vault ",
+                "Choose a strong master password. It encrypts your vault and cannot be recovered if lost.",
+            )
+        } else {
+            (
+                " Unlock Lilypad Vault ",
+                "Enter your master password to access your vault.",
+            )
+        };
+
+        let field = |label: &str, value: &str, focused: bool| {
+            let cursor = if focused { "▏" } else { "" };
+            Line::from(vec![
+                Span::styled(format!("{label:<18}"), palette.muted()),
+                Span::raw(format!("{}{cursor}", masked(value))),
+            ])
+        };
+
+        let mut lines = vec![
+            Line::styled(subtitle, palette.muted()),
+            Line::raw(""),
+            field(
+                "Master password",
+                &self.master_password,
+                !self.confirm_focused,
+            ),
+        ];
+        if creating {
+            lines.push(field(
+                "Confirm password",
+                &self.confirm_password,
+                self.confirm_focused,
+            ));
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Password requirements", palette.accent_text()));
+            for Requirement { label, satisfied } in self.create_requirements() {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        if satisfied { "  ✔ " } else { "  ○ " },
+                        palette.check_style(satisfied),
+                    ),
+                    Span::raw(label),
+                ]));
+            }
+        }
+        lines.push(Line::raw(""));
+        if self.unlock_requested {
+            let label = if creating {
+                "Creating vault…"
+            } else {
+                "Unlocking…"
+            };
+            lines.push(Line::styled(label, palette.accent_text()));
+        } else if let Some(remaining) = self.remaining_backoff() {
+            lines.push(Line::styled(
+                format!(
+                    "Too many failed attempts. Try again in {} s.",
+                    remaining.as_secs() + 1
+                ),
+                palette.danger_text(),
+            ));
+        } else if let Some(message) = &self.status_message {
+            lines.push(Line::styled(message.clone(), palette.danger_text()));
+        }
+        lines.push(Line::styled(
+            format!("Vault: {}", self.vault_path.display()),
+            palette.muted(),
+        ));
+
+        // Room for the border and for the subtitle wrapping once.
+        let height = u16::try_from(lines.len() + 3).unwrap_or(u16::MAX);
+        let [card] = Layout::horizontal([Constraint::Max(72)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [card] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(card);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(palette.panel().title(title)),
+            card,
+        );
+    }
+
+    pub(crate) fn handle_unlock_key(&mut self, key: KeyEvent) {
+        let creating = !self.vault_exists();
+        let field = if creating && self.confirm_focused {
+            &mut self.confirm_password
+        } else {
+            &mut self.master_password
+        };
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => field.push(c),
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down if creating => {
+                self.confirm_focused = !self.confirm_focused;
+            }
+            KeyCode::Enter if creating && !self.confirm_focused => self.confirm_focused = true,
+            KeyCode::Enter => {
+                let ready = if creating {
+                    self.create_requirements()
+                        .iter()
+                        .all(|requirement| requirement.satisfied)
+                } else {
+                    !self.master_password.is_empty() && self.remaining_backoff().is_none()
+                };
+                self.unlock_requested = ready;
+            }
+            KeyCode::Esc => {
+                self.master_password.zeroize();
+                self.confirm_password.zeroize();
+                self.confirm_focused = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Unlocks or creates the vault; called by the loop after the
+    /// "Unlocking…" frame is on screen.
+    pub(crate) fn submit_unlock(&mut self) {
+        if self.vault_exists() {
+            self.unlock_vault();
+        } else {
+            self.create_vault();
+        }
+    }
+
+    fn unlock_vault(&mut self) {
+        let result = Vault::open(&self.vault_path, &self.master_password);
+        self.master_password.zeroize();
+        match result {
+            Ok(vault) => {
+                self.vault = Some(vault);
+                self.unlock_retry_at = None;
+                if self.config.unlock.failed_attempts > 0 {
+                    self.config.unlock = UnlockState::default();
+                    self.save_config();
+                }
+                self.status_message = Some("Vault unlocked".to_string());
+                self.start_session();
+            }
+            Err(error) => {
+                self.config.unlock.record_failure(clock::now());
+                self.save_config();
+                self.resume_unlock_backoff();
+                self.status_message = Some(format!(
+                    "Unable to unlock vault: {error} (attempt {})",
+                    self.config.unlock.failed_attempts
+                ));
+            }
+        }
+    }
+
+    fn create_vault(&mut self) {
+        let kdf = self.config.security.kdf.to_params();
+        match Vault::create_with_params(&self.vault_path, &self.master_password, kdf) {
+            Ok(vault) => {
+                self.vault = Some(vault);
+                self.master_password.zeroize();
+                self.confirm_password.zeroize();
+                self.confirm_focused = false;
+                self.status_message = Some("New vault created".to_string());
+                self.start_session();
+            }
+            Err(error) => {
+                self.status_message = Some(format!("Unable to create vault: {error}"));
+            }
+        }
+    }
+
+    /// The master-password policy plus the confirmation check.
+    fn create_requirements(&self) -> Vec<Requirement> {
+        let mut requirements = PasswordPolicy::default().requirements(&self.master_password);
+        requirements.push(Requirement {
+            label: "Both entries match".to_string(),
+            satisfied: !self.confirm_password.is_empty()
+                && self.master_password == self.confirm_password,
+        });
+        requirements
+    }
+
+    fn remaining_backoff(&self) -> Option<Duration> {
+        self.unlock_retry_at
+            .map(|retry_at| retry_at.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}